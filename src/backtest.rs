@@ -2,36 +2,456 @@
 //!
 //! Simulates trading strategies against historical data
 
+use crate::db::Database;
+use crate::error::{PipelineError, Result};
+use crate::indicators::calculate_all;
 use crate::models::{
-    BacktestResult, BacktestTrade, DailyPrice, PerformanceMetrics, Strategy, StrategyConditionType,
-    TechnicalIndicator, TradeDirection,
+    BacktestResult, BacktestTrade, DailyPrice, PerformanceMetrics, PositionSizing, RuleCombinator,
+    Strategy, StrategyConditionType, StrategyRule, StrategyRules, TechnicalIndicator,
+    TradeDirection,
 };
-use chrono::NaiveDate;
-use std::collections::HashMap;
+use crate::resample::{resample, ResamplePeriod};
+use chrono::{Datelike, NaiveDate};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// Results of one strategy backtested on many symbols
+#[derive(Debug, Clone, Default)]
+pub struct MultiSymbolBacktest {
+    /// One result per symbol, best total return first
+    pub results: Vec<BacktestResult>,
+    /// Symbols that couldn't be tested, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// What the engine saw and did on one bar, as reported by `BacktestEngine::replay`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarDecision {
+    pub date: NaiveDate,
+    pub price: f64,
+    /// Equity marked at this bar's close, before any action
+    pub equity: f64,
+    /// Indicator values available on this bar (empty if none were stored)
+    pub indicators: HashMap<String, f64>,
+    /// Exit check result; `None` when flat or without indicators
+    pub exit_signal: Option<bool>,
+    /// Entry check result; `None` when no entry is allowed (in a position
+    /// without pyramiding, or at `max_positions`) or without indicators
+    pub entry_signal: Option<bool>,
+    /// Exit reason when one or more lots were closed on this bar
+    pub exited: Option<String>,
+    /// Entry condition when a lot was opened on this bar
+    pub entered: Option<String>,
+}
+
+/// What `position_size_percent` is a percentage of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizingBase {
+    /// Percent of uninvested cash at entry time (default)
+    #[default]
+    CurrentCash,
+    /// Percent of total equity (cash plus open positions), capped at available cash
+    TotalEquity,
+}
+
+/// Broker fee charged on each fill (entry and exit are charged separately)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommissionModel {
+    /// Fixed amount per fill
+    Flat(f64),
+    /// Amount per share filled
+    PerShare(f64),
+    /// Percent of the fill's notional value (e.g. 0.1 = 0.1%)
+    Percent(f64),
+}
+
+impl Default for CommissionModel {
+    fn default() -> Self {
+        CommissionModel::Flat(0.0)
+    }
+}
+
+impl CommissionModel {
+    /// Fee for filling `shares` at `price`
+    pub fn cost(&self, shares: f64, price: f64) -> f64 {
+        match *self {
+            CommissionModel::Flat(fee) => fee,
+            CommissionModel::PerShare(fee) => fee * shares,
+            CommissionModel::Percent(pct) => shares * price * pct / 100.0,
+        }
+    }
+
+    /// Most shares that `budget` buys at `price` once the fee is included
+    fn affordable_shares(&self, budget: f64, price: f64) -> f64 {
+        match *self {
+            CommissionModel::Flat(fee) => (budget - fee) / price,
+            CommissionModel::PerShare(fee) => budget / (price + fee),
+            CommissionModel::Percent(pct) => budget / (price * (1.0 + pct / 100.0)),
+        }
+    }
+}
 
 /// Backtest configuration
 #[derive(Debug, Clone)]
 pub struct BacktestConfig {
     pub initial_capital: f64,
-    pub commission_per_trade: f64,
+    /// Fee charged on every entry and exit fill
+    pub commission: CommissionModel,
+    /// Slippage in basis points: buys fill this much above the bar price,
+    /// sells this much below
+    pub slippage_bps: f64,
+    /// Base for position sizing; defaults to `SizingBase::CurrentCash`
+    pub sizing_base: SizingBase,
+    /// Start of the requested `run` window. Earlier bars are ignored, and a
+    /// warning is attached when the data begins materially later (e.g. a
+    /// recent listing tested over "5y").
+    pub requested_start: Option<NaiveDate>,
+    /// Trade on split/dividend-adjusted prices where bars carry `adjusted_close`
+    pub use_adjusted_close: bool,
+    /// Bar size for every run (`run`, `replay`, `run_portfolio`,
+    /// `simulate_rebalance` and friends). Anything coarser than daily
+    /// resamples the prices and recomputes the standard indicators on the
+    /// resampled bars; the indicators passed in are then ignored. Sharpe,
+    /// Sortino and information ratio annualize to match.
+    pub timeframe: ResamplePeriod,
+    /// Most lots a single-symbol run may hold at once
+    pub max_positions: usize,
+    /// Let entry signals add lots while already in a position, up to
+    /// `max_positions`. Without it entries wait until the position closes.
+    pub allow_pyramiding: bool,
 }
 
 impl Default for BacktestConfig {
     fn default() -> Self {
         Self {
             initial_capital: 10000.0,
-            commission_per_trade: 0.0,
+            commission: CommissionModel::default(),
+            slippage_bps: 0.0,
+            sizing_base: SizingBase::CurrentCash,
+            requested_start: None,
+            use_adjusted_close: false,
+            timeframe: ResamplePeriod::Daily,
+            max_positions: 1,
+            allow_pyramiding: false,
+        }
+    }
+}
+
+/// Gap between requested and actual start beyond which a short-history warning is raised
+const SHORT_HISTORY_TOLERANCE_DAYS: i64 = 30;
+
+/// Pair each date with its marked equity, ending on the realized final capital
+fn equity_curve(
+    dates: impl Iterator<Item = NaiveDate>,
+    equity_history: &[f64],
+    final_capital: f64,
+) -> Vec<(NaiveDate, f64)> {
+    let mut curve: Vec<(NaiveDate, f64)> = dates.zip(equity_history.iter().copied()).collect();
+    if let Some(last) = curve.last_mut() {
+        last.1 = final_capital;
+    }
+    curve
+}
+
+/// Annualized information ratio of an equity curve versus a benchmark
+///
+/// Active return is the per-bar strategy return minus the benchmark's on dates
+/// both series share; the ratio is its mean over its standard deviation,
+/// scaled by sqrt(`bars_per_year`). Returns 0 when there is too little overlap
+/// or the strategy tracks the benchmark exactly.
+pub fn information_ratio(
+    equity_curve: &[(NaiveDate, f64)],
+    benchmark: &[DailyPrice],
+    bars_per_year: f64,
+) -> f64 {
+    let benchmark_closes: HashMap<NaiveDate, f64> = benchmark
+        .iter()
+        .filter(|p| p.close > 0.0)
+        .map(|p| (p.date, p.close))
+        .collect();
+
+    let aligned: Vec<(f64, f64)> = equity_curve
+        .iter()
+        .filter_map(|(date, equity)| benchmark_closes.get(date).map(|&close| (*equity, close)))
+        .collect();
+
+    let active: Vec<f64> = aligned
+        .windows(2)
+        .filter(|w| w[0].0 > 0.0)
+        .map(|w| (w[1].0 / w[0].0 - 1.0) - (w[1].1 / w[0].1 - 1.0))
+        .collect();
+    if active.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = active.iter().sum::<f64>() / active.len() as f64;
+    let variance = active.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / active.len() as f64;
+    let tracking_error = variance.sqrt();
+
+    if tracking_error > 1e-12 {
+        mean / tracking_error * bars_per_year.sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// Rebalancing interval for fixed-weight portfolio simulations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rebalance {
+    Monthly,
+    Quarterly,
+}
+
+impl Rebalance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rebalance::Monthly => "monthly",
+            Rebalance::Quarterly => "quarterly",
+        }
+    }
+
+    /// True when `date` starts a new rebalancing period relative to `prev`
+    fn is_boundary(&self, prev: NaiveDate, date: NaiveDate) -> bool {
+        match self {
+            Rebalance::Monthly => prev.year() != date.year() || prev.month() != date.month(),
+            Rebalance::Quarterly => {
+                prev.year() != date.year() || prev.month0() / 3 != date.month0() / 3
+            }
+        }
+    }
+}
+
+/// Strategy setting swept by `BacktestEngine::walk_forward`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyParam {
+    EntryThreshold,
+    ExitThreshold,
+    StopLossPercent,
+    TakeProfitPercent,
+    PositionSizePercent,
+}
+
+impl std::str::FromStr for StrategyParam {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "entry_threshold" => Ok(StrategyParam::EntryThreshold),
+            "exit_threshold" => Ok(StrategyParam::ExitThreshold),
+            "stop_loss_percent" => Ok(StrategyParam::StopLossPercent),
+            "take_profit_percent" => Ok(StrategyParam::TakeProfitPercent),
+            "position_size_percent" => Ok(StrategyParam::PositionSizePercent),
+            _ => Err(PipelineError::Config(format!(
+                "Unknown strategy parameter: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl StrategyParam {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StrategyParam::EntryThreshold => "entry_threshold",
+            StrategyParam::ExitThreshold => "exit_threshold",
+            StrategyParam::StopLossPercent => "stop_loss_percent",
+            StrategyParam::TakeProfitPercent => "take_profit_percent",
+            StrategyParam::PositionSizePercent => "position_size_percent",
+        }
+    }
+
+    /// Copy of `strategy` with this setting changed to `value`
+    ///
+    /// Thresholds also update any combined rules on the strategy's headline
+    /// condition, since those replace the single threshold when set.
+    pub fn apply(&self, strategy: &Strategy, value: f64) -> Strategy {
+        let mut tuned = strategy.clone();
+        let retune = |rules: &mut Option<StrategyRules>, condition| {
+            for rule in rules.iter_mut().flat_map(|r| r.rules.iter_mut()) {
+                if rule.condition == condition {
+                    rule.threshold = value;
+                }
+            }
+        };
+        match self {
+            StrategyParam::EntryThreshold => {
+                tuned.entry_threshold = value;
+                retune(&mut tuned.entry_rules, tuned.entry_condition);
+            }
+            StrategyParam::ExitThreshold => {
+                tuned.exit_threshold = value;
+                retune(&mut tuned.exit_rules, tuned.exit_condition);
+            }
+            StrategyParam::StopLossPercent => tuned.stop_loss_percent = Some(value),
+            StrategyParam::TakeProfitPercent => tuned.take_profit_percent = Some(value),
+            StrategyParam::PositionSizePercent => tuned.position_size_percent = value,
+        }
+        tuned
+    }
+}
+
+/// Metric a parameter sweep maximizes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OptimizeMetric {
+    #[default]
+    TotalReturn,
+    SharpeRatio,
+}
+
+impl std::str::FromStr for OptimizeMetric {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "total_return" => Ok(OptimizeMetric::TotalReturn),
+            "sharpe" | "sharpe_ratio" => Ok(OptimizeMetric::SharpeRatio),
+            _ => Err(PipelineError::Config(format!(
+                "Unknown optimization metric: {} (expected total_return or sharpe_ratio)",
+                s
+            ))),
+        }
+    }
+}
+
+impl OptimizeMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptimizeMetric::TotalReturn => "total_return",
+            OptimizeMetric::SharpeRatio => "sharpe_ratio",
+        }
+    }
+
+    /// Score of a run; NaN scores rank below everything
+    pub fn score(&self, metrics: &PerformanceMetrics) -> f64 {
+        let score = match self {
+            OptimizeMetric::TotalReturn => metrics.total_return,
+            OptimizeMetric::SharpeRatio => metrics.sharpe_ratio,
+        };
+        if score.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            score
         }
     }
 }
 
+/// Values to try for one strategy setting, and how to rank them
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamGrid {
+    pub param: StrategyParam,
+    pub values: Vec<f64>,
+    pub objective: OptimizeMetric,
+}
+
+/// Outcome of `BacktestEngine::walk_forward`
+#[derive(Debug, Clone)]
+pub struct WalkForwardResult {
+    pub param: StrategyParam,
+    pub objective: OptimizeMetric,
+    /// Grid value with the best in-sample score (the first, on ties)
+    pub best_value: f64,
+    /// The base strategy with `best_value` applied
+    pub strategy: Strategy,
+    /// In-sample metrics for every grid value, in grid order
+    pub sweep: Vec<(f64, PerformanceMetrics)>,
+    /// First bar of the out-of-sample window
+    pub split_date: NaiveDate,
+    pub in_sample: PerformanceMetrics,
+    pub out_of_sample: PerformanceMetrics,
+}
+
+/// Fixed seed so repeated Monte Carlo runs of a backtest agree
+const MONTE_CARLO_SEED: u64 = 0x5EED_CAFE_F00D_BEEF;
+
+/// Distribution of outcomes from `BacktestEngine::monte_carlo`
+///
+/// Returns and drawdowns are percentages like `PerformanceMetrics`;
+/// drawdowns are positive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonteCarloStats {
+    pub iterations: usize,
+    /// Closed trades resampled in each path
+    pub trades: usize,
+    /// Total return of the original run
+    pub observed_return: f64,
+    pub return_p5: f64,
+    pub return_p50: f64,
+    pub return_p95: f64,
+    pub drawdown_p5: f64,
+    pub drawdown_p50: f64,
+    pub drawdown_p95: f64,
+}
+
+/// SplitMix64, enough randomness for bootstrap resampling without a dependency
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index in `0..n`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Distance from entry to the stop `PositionSizing::RiskPercent` sizes for;
+/// `None` under other sizing or without a positive `ATR_14` on the bar
+fn atr_stop_distance(strategy: &Strategy, today: &HashMap<String, f64>) -> Option<f64> {
+    match strategy.sizing() {
+        PositionSizing::RiskPercent { atr_mult, .. } => today
+            .get("ATR_14")
+            .map(|&atr| atr * atr_mult)
+            .filter(|&distance| distance > 0.0),
+        PositionSizing::PercentOfEquity(_) => None,
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let idx = ((pct / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[idx]
+}
+
 /// Open position during backtest
 #[derive(Debug, Clone)]
 struct OpenPosition {
     entry_date: NaiveDate,
     entry_price: f64,
     shares: f64,
+    direction: TradeDirection,
+    /// Fee paid on entry, charged against the trade's P&L at exit
+    entry_commission: f64,
     entry_reason: String,
+    /// Most favourable close since entry: the peak for longs, trough for shorts
+    best_price: f64,
+    /// Stop placed by `PositionSizing::RiskPercent` sizing
+    stop_price: Option<f64>,
+}
+
+impl OpenPosition {
+    /// Ratchet the high-water (or, for shorts, low-water) mark
+    fn track(&mut self, price: f64) {
+        self.best_price = match self.direction {
+            TradeDirection::Long => self.best_price.max(price),
+            TradeDirection::Short => self.best_price.min(price),
+        };
+    }
+}
+
+/// Long lots' value minus short lots' liability at `price`
+///
+/// Short proceeds sit in cash; the shares owed are a liability.
+fn net_open_value(positions: &[OpenPosition], price: f64) -> f64 {
+    positions.iter().fold(0.0, |net, pos| match pos.direction {
+        TradeDirection::Long => net + pos.shares * price,
+        TradeDirection::Short => net - pos.shares * price,
+    })
 }
 
 /// Main backtesting engine
@@ -66,20 +486,182 @@ impl BacktestEngine {
         map
     }
 
-    /// Check if entry condition is met
+    /// Shares to buy for a new entry given current cash and total equity
+    fn entry_shares(&self, cash: f64, equity: f64, size_percent: f64, price: f64) -> f64 {
+        let base = match self.config.sizing_base {
+            SizingBase::CurrentCash => cash,
+            SizingBase::TotalEquity => equity,
+        };
+        let position_value = (base * (size_percent / 100.0)).min(cash);
+        self.config.commission.affordable_shares(position_value, price)
+    }
+
+    /// Shares for a new entry under the strategy's sizing mode
+    ///
+    /// ATR-based sizing needs `ATR_14` on the entry bar and takes no position
+    /// without it.
+    fn position_shares(
+        &self,
+        strategy: &Strategy,
+        cash: f64,
+        equity: f64,
+        price: f64,
+        today: &HashMap<String, f64>,
+    ) -> f64 {
+        match strategy.sizing() {
+            PositionSizing::PercentOfEquity(percent) => {
+                self.entry_shares(cash, equity, percent, price)
+            }
+            PositionSizing::RiskPercent { risk, .. } => {
+                let Some(stop_distance) = atr_stop_distance(strategy, today) else {
+                    return 0.0;
+                };
+                let shares = equity * (risk / 100.0) / stop_distance;
+                shares.min(self.config.commission.affordable_shares(cash, price))
+            }
+        }
+    }
+
+    /// Stop for a new entry filled at `fill`: `atr_mult` ATR_14s below it
+    /// for longs (above for shorts) under ATR-based sizing, otherwise none
+    fn entry_stop(
+        &self,
+        strategy: &Strategy,
+        fill: f64,
+        today: &HashMap<String, f64>,
+    ) -> Option<f64> {
+        atr_stop_distance(strategy, today).map(|distance| match strategy.direction {
+            TradeDirection::Long => fill - distance,
+            TradeDirection::Short => fill + distance,
+        })
+    }
+
+    /// Fill price after slippage: buys fill higher, sells lower
+    fn fill_price(&self, price: f64, is_buy: bool) -> f64 {
+        let slippage = self.config.slippage_bps / 10_000.0;
+        if is_buy {
+            price * (1.0 + slippage)
+        } else {
+            price * (1.0 - slippage)
+        }
+    }
+
+    /// Close a position at `price`, returning the change in cash and the trade
+    ///
+    /// Longs sell (cash comes in); shorts buy to cover (cash goes out).
+    fn close_position(
+        &self,
+        symbol: &str,
+        pos: OpenPosition,
+        date: NaiveDate,
+        price: f64,
+        exit_reason: String,
+    ) -> (f64, BacktestTrade) {
+        let is_short = pos.direction == TradeDirection::Short;
+        let exit_price = self.fill_price(price, is_short);
+        let exit_commission = self.config.commission.cost(pos.shares, exit_price);
+        let price_change = if is_short {
+            pos.entry_price - exit_price
+        } else {
+            exit_price - pos.entry_price
+        };
+        let profit_loss = price_change * pos.shares - pos.entry_commission - exit_commission;
+        let profit_loss_percent = price_change / pos.entry_price * 100.0;
+        let cash_change = if is_short {
+            -(pos.shares * exit_price + exit_commission)
+        } else {
+            pos.shares * exit_price - exit_commission
+        };
+
+        let trade = BacktestTrade {
+            id: 0,
+            backtest_id: 0,
+            symbol: symbol.to_string(),
+            direction: pos.direction,
+            entry_date: pos.entry_date,
+            entry_price: pos.entry_price,
+            exit_date: Some(date),
+            exit_price: Some(exit_price),
+            shares: pos.shares,
+            entry_reason: pos.entry_reason,
+            exit_reason: Some(exit_reason),
+            profit_loss: Some(profit_loss),
+            profit_loss_percent: Some(profit_loss_percent),
+        };
+
+        (cash_change, trade)
+    }
+
+    /// Check if entry rules are met, returning the conditions that fired
     fn check_entry_condition(
         &self,
         strategy: &Strategy,
         price: f64,
         today: &HashMap<String, f64>,
         prev: Option<&HashMap<String, f64>>,
+    ) -> (bool, String) {
+        self.check_rules(
+            strategy.entry_rules.as_ref(),
+            StrategyRule {
+                condition: strategy.entry_condition,
+                threshold: strategy.entry_threshold,
+            },
+            price,
+            today,
+            prev,
+        )
+    }
+
+    /// Evaluate a rule set, or the strategy's single condition when it has none
+    ///
+    /// The reason is the fired conditions joined with `+`: every rule for
+    /// `All`, the matching ones for `Any`. An empty rule set never fires.
+    fn check_rules(
+        &self,
+        rules: Option<&StrategyRules>,
+        single: StrategyRule,
+        price: f64,
+        today: &HashMap<String, f64>,
+        prev: Option<&HashMap<String, f64>>,
+    ) -> (bool, String) {
+        let (combinator, rules) = match rules {
+            Some(set) => (set.combinator, set.rules.as_slice()),
+            None => (RuleCombinator::All, std::slice::from_ref(&single)),
+        };
+
+        let fired: Vec<&str> = rules
+            .iter()
+            .filter(|r| self.condition_met(r.condition, r.threshold, price, today, prev))
+            .map(|r| r.condition.as_str())
+            .collect();
+
+        let met = match combinator {
+            RuleCombinator::All => !rules.is_empty() && fired.len() == rules.len(),
+            RuleCombinator::Any => !fired.is_empty(),
+        };
+
+        if met {
+            (true, fired.join("+"))
+        } else {
+            (false, String::new())
+        }
+    }
+
+    /// Check one indicator condition against today's (and the previous bar's) values
+    fn condition_met(
+        &self,
+        condition: StrategyConditionType,
+        threshold: f64,
+        price: f64,
+        today: &HashMap<String, f64>,
+        prev: Option<&HashMap<String, f64>>,
     ) -> bool {
-        match strategy.entry_condition {
+        match condition {
             StrategyConditionType::RsiOversold => {
-                today.get("RSI_14").map_or(false, |&rsi| rsi < strategy.entry_threshold)
+                today.get("RSI_14").map_or(false, |&rsi| rsi < threshold)
             }
             StrategyConditionType::RsiOverbought => {
-                today.get("RSI_14").map_or(false, |&rsi| rsi > strategy.entry_threshold)
+                today.get("RSI_14").map_or(false, |&rsi| rsi > threshold)
             }
             StrategyConditionType::MacdCrossUp => {
                 if let (Some(prev_ind), Some(macd), Some(signal)) = (
@@ -153,7 +735,8 @@ impl BacktestEngine {
                     false
                 }
             }
-            // StopLoss and TakeProfit are exit-only conditions
+            // StopLoss and TakeProfit are exit-only conditions, checked from
+            // the strategy's percentages
             StrategyConditionType::StopLoss | StrategyConditionType::TakeProfit => false,
         }
     }
@@ -163,114 +746,69 @@ impl BacktestEngine {
         &self,
         strategy: &Strategy,
         price: f64,
-        entry_price: f64,
+        position: &OpenPosition,
         today: &HashMap<String, f64>,
         prev: Option<&HashMap<String, f64>>,
     ) -> (bool, String) {
+        // Shorts lose when price rises, so their stop sits above entry
+        let is_short = strategy.direction == TradeDirection::Short;
+        let entry_price = position.entry_price;
+
         // Check stop loss
         if let Some(stop_loss_pct) = strategy.stop_loss_percent {
-            let stop_price = entry_price * (1.0 - stop_loss_pct / 100.0);
-            if price <= stop_price {
+            let hit = if is_short {
+                price >= entry_price * (1.0 + stop_loss_pct / 100.0)
+            } else {
+                price <= entry_price * (1.0 - stop_loss_pct / 100.0)
+            };
+            if hit {
                 return (true, "stop_loss".to_string());
             }
         }
 
+        // Check the stop ATR-based sizing placed
+        if let Some(stop) = position.stop_price {
+            let hit = if is_short { price >= stop } else { price <= stop };
+            if hit {
+                return (true, "atr_stop".to_string());
+            }
+        }
+
         // Check take profit
         if let Some(take_profit_pct) = strategy.take_profit_percent {
-            let target_price = entry_price * (1.0 + take_profit_pct / 100.0);
-            if price >= target_price {
+            let hit = if is_short {
+                price <= entry_price * (1.0 - take_profit_pct / 100.0)
+            } else {
+                price >= entry_price * (1.0 + take_profit_pct / 100.0)
+            };
+            if hit {
                 return (true, "take_profit".to_string());
             }
         }
 
-        // Check strategy exit condition
-        let condition_met = match strategy.exit_condition {
-            StrategyConditionType::RsiOversold => {
-                today.get("RSI_14").map_or(false, |&rsi| rsi < strategy.exit_threshold)
-            }
-            StrategyConditionType::RsiOverbought => {
-                today.get("RSI_14").map_or(false, |&rsi| rsi > strategy.exit_threshold)
-            }
-            StrategyConditionType::MacdCrossUp => {
-                if let (Some(prev_ind), Some(macd), Some(signal)) = (
-                    prev,
-                    today.get("MACD_12_26"),
-                    today.get("MACD_SIGNAL_9"),
-                ) {
-                    if let (Some(&prev_macd), Some(&prev_signal)) = (
-                        prev_ind.get("MACD_12_26"),
-                        prev_ind.get("MACD_SIGNAL_9"),
-                    ) {
-                        prev_macd <= prev_signal && *macd > *signal
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            }
-            StrategyConditionType::MacdCrossDown => {
-                if let (Some(prev_ind), Some(macd), Some(signal)) = (
-                    prev,
-                    today.get("MACD_12_26"),
-                    today.get("MACD_SIGNAL_9"),
-                ) {
-                    if let (Some(&prev_macd), Some(&prev_signal)) = (
-                        prev_ind.get("MACD_12_26"),
-                        prev_ind.get("MACD_SIGNAL_9"),
-                    ) {
-                        prev_macd >= prev_signal && *macd < *signal
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            }
-            StrategyConditionType::PriceAboveSma => {
-                today.get("SMA_20").map_or(false, |&sma| price > sma)
-            }
-            StrategyConditionType::PriceBelowSma => {
-                today.get("SMA_20").map_or(false, |&sma| price < sma)
-            }
-            StrategyConditionType::SmaCrossUp => {
-                if let (Some(prev_ind), Some(&fast), Some(&slow)) =
-                    (prev, today.get("SMA_20"), today.get("SMA_50"))
-                {
-                    if let (Some(&prev_fast), Some(&prev_slow)) =
-                        (prev_ind.get("SMA_20"), prev_ind.get("SMA_50"))
-                    {
-                        prev_fast <= prev_slow && fast > slow
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            }
-            StrategyConditionType::SmaCrossDown => {
-                if let (Some(prev_ind), Some(&fast), Some(&slow)) =
-                    (prev, today.get("SMA_20"), today.get("SMA_50"))
-                {
-                    if let (Some(&prev_fast), Some(&prev_slow)) =
-                        (prev_ind.get("SMA_20"), prev_ind.get("SMA_50"))
-                    {
-                        prev_fast >= prev_slow && fast < slow
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
+        // Check trailing stop against the best price since entry
+        if let Some(trailing_pct) = strategy.trailing_stop_percent {
+            let hit = if is_short {
+                price >= position.best_price * (1.0 + trailing_pct / 100.0)
+            } else {
+                price <= position.best_price * (1.0 - trailing_pct / 100.0)
+            };
+            if hit {
+                return (true, "trailing_stop".to_string());
             }
-            StrategyConditionType::StopLoss | StrategyConditionType::TakeProfit => false,
-        };
-
-        if condition_met {
-            (true, strategy.exit_condition.as_str().to_string())
-        } else {
-            (false, String::new())
         }
+
+        // Check strategy exit rules
+        self.check_rules(
+            strategy.exit_rules.as_ref(),
+            StrategyRule {
+                condition: strategy.exit_condition,
+                threshold: strategy.exit_threshold,
+            },
+            price,
+            today,
+            prev,
+        )
     }
 
     /// Run a backtest
@@ -281,17 +819,115 @@ impl BacktestEngine {
         prices: &[DailyPrice],
         indicators: &[TechnicalIndicator],
     ) -> BacktestResult {
-        let indicator_map = self.build_indicator_map(indicators);
-
-        let mut cash = self.config.initial_capital;
-        let mut position: Option<OpenPosition> = None;
-        let mut trades: Vec<BacktestTrade> = Vec::new();
-        let mut equity_history: Vec<f64> = Vec::new();
+        self.run_against_benchmark(strategy, symbol, prices, indicators, &[])
+    }
 
-        // Sort prices by date
-        let mut sorted_prices = prices.to_vec();
+    /// Step through a backtest bar by bar
+    ///
+    /// Yields one `BarDecision` per priced bar with the same logic as `run`.
+    /// The end-of-data liquidation `run` performs is not a decision and is
+    /// not reported.
+    pub fn replay(
+        &self,
+        strategy: &Strategy,
+        symbol: &str,
+        prices: &[DailyPrice],
+        indicators: &[TechnicalIndicator],
+    ) -> impl Iterator<Item = BarDecision> {
+        let mut decisions = Vec::new();
+        self.simulate(strategy, symbol, prices, indicators, &[], Some(&mut decisions));
+        decisions.into_iter()
+    }
+
+    /// Run a backtest and measure it against a benchmark's price series
+    ///
+    /// Identical to `run`, plus `information_ratio` is computed from the
+    /// daily equity curve versus `benchmark`. An empty benchmark leaves it at 0.
+    pub fn run_against_benchmark(
+        &self,
+        strategy: &Strategy,
+        symbol: &str,
+        prices: &[DailyPrice],
+        indicators: &[TechnicalIndicator],
+        benchmark: &[DailyPrice],
+    ) -> BacktestResult {
+        self.simulate(strategy, symbol, prices, indicators, benchmark, None)
+    }
+
+    /// Prices resampled to `config.timeframe`
+    fn resampled<'a>(&self, prices: &'a [DailyPrice]) -> Cow<'a, [DailyPrice]> {
+        match self.config.timeframe {
+            ResamplePeriod::Daily => Cow::Borrowed(prices),
+            timeframe => Cow::Owned(resample(prices, timeframe)),
+        }
+    }
+
+    /// Prices and indicators at `config.timeframe`; see its docs
+    fn at_timeframe<'a>(
+        &self,
+        prices: &'a [DailyPrice],
+        indicators: &'a [TechnicalIndicator],
+    ) -> (Cow<'a, [DailyPrice]>, Cow<'a, [TechnicalIndicator]>) {
+        match self.resampled(prices) {
+            Cow::Borrowed(prices) => (Cow::Borrowed(prices), Cow::Borrowed(indicators)),
+            Cow::Owned(bars) => {
+                let computed = calculate_all(&bars);
+                (Cow::Owned(bars), Cow::Owned(computed))
+            }
+        }
+    }
+
+    /// Shared loop behind `run_against_benchmark` and `replay`
+    fn simulate(
+        &self,
+        strategy: &Strategy,
+        symbol: &str,
+        prices: &[DailyPrice],
+        indicators: &[TechnicalIndicator],
+        benchmark: &[DailyPrice],
+        mut decisions: Option<&mut Vec<BarDecision>>,
+    ) -> BacktestResult {
+        let (prices, indicators) = self.at_timeframe(prices, indicators);
+        let indicator_map = self.build_indicator_map(&indicators);
+
+        let mut cash = self.config.initial_capital;
+        let mut positions: Vec<OpenPosition> = Vec::new();
+        let mut trades: Vec<BacktestTrade> = Vec::new();
+        let mut equity_history: Vec<f64> = Vec::new();
+        let mut bars_in_market = 0usize;
+        let max_positions = self.config.max_positions.max(1);
+
+        // Sort prices by date, dropping bars that can't be traded
+        let window_start = self.config.requested_start.unwrap_or(NaiveDate::MIN);
+        let mut sorted_prices: Vec<DailyPrice> = prices
+            .iter()
+            .filter(|p| p.date >= window_start)
+            .map(|p| {
+                if self.config.use_adjusted_close {
+                    p.to_adjusted()
+                } else {
+                    p.clone()
+                }
+            })
+            .collect();
+        let in_window = sorted_prices.len();
+        sorted_prices.retain(|p| p.close > 0.0);
         sorted_prices.sort_by_key(|p| p.date);
 
+        let skipped_bars = in_window - sorted_prices.len();
+
+        let mut warnings = Vec::new();
+        if let (Some(requested), Some(first)) = (self.config.requested_start, sorted_prices.first()) {
+            let gap = (first.date - requested).num_days();
+            if gap > SHORT_HISTORY_TOLERANCE_DAYS {
+                let warning = format!(
+                    "Short history: {} data starts {} ({} days after requested start {})",
+                    symbol, first.date, gap, requested
+                );
+                warnings.push(warning);
+            }
+        }
+
         // Walk through each day
         for (i, price_data) in sorted_prices.iter().enumerate() {
             let date = price_data.date;
@@ -304,103 +940,115 @@ impl BacktestEngine {
                 None
             };
 
+            for pos in positions.iter_mut() {
+                pos.track(price);
+            }
+
             // Calculate current equity
-            let current_equity = if let Some(ref pos) = position {
-                cash + pos.shares * price
-            } else {
-                cash
-            };
+            if !positions.is_empty() {
+                bars_in_market += 1;
+            }
+            let current_equity = cash + net_open_value(&positions, price);
             equity_history.push(current_equity);
 
+            let mut decision = BarDecision {
+                date,
+                price,
+                equity: current_equity,
+                indicators: HashMap::new(),
+                exit_signal: None,
+                entry_signal: None,
+                exited: None,
+                entered: None,
+            };
+
             // Skip if no indicators for today
             let Some(today) = today_indicators else {
+                if let Some(log) = decisions.as_mut() {
+                    log.push(decision);
+                }
                 continue;
             };
 
-            // If we have a position, check exit conditions
-            if let Some(ref pos) = position {
-                let (should_exit, exit_reason) =
-                    self.check_exit_condition(strategy, price, pos.entry_price, today, prev_indicators);
-
-                if should_exit {
-                    // Close position
-                    let profit_loss = (price - pos.entry_price) * pos.shares - self.config.commission_per_trade;
-                    let profit_loss_percent = (price - pos.entry_price) / pos.entry_price * 100.0;
-
-                    cash += pos.shares * price - self.config.commission_per_trade;
-
-                    trades.push(BacktestTrade {
-                        id: 0,
-                        backtest_id: 0,
-                        symbol: symbol.to_string(),
-                        direction: TradeDirection::Long,
-                        entry_date: pos.entry_date,
-                        entry_price: pos.entry_price,
-                        exit_date: Some(date),
-                        exit_price: Some(price),
-                        shares: pos.shares,
-                        entry_reason: pos.entry_reason.clone(),
-                        exit_reason: Some(exit_reason),
-                        profit_loss: Some(profit_loss),
-                        profit_loss_percent: Some(profit_loss_percent),
-                    });
-
-                    position = None;
+            // Check each open lot's exit conditions; stops are relative to
+            // the lot's own entry
+            if !positions.is_empty() {
+                let mut any_exit = false;
+                for pos in std::mem::take(&mut positions) {
+                    let (should_exit, exit_reason) =
+                        self.check_exit_condition(strategy, price, &pos, today, prev_indicators);
+                    if !should_exit {
+                        positions.push(pos);
+                        continue;
+                    }
+                    any_exit = true;
+                    decision.exited.get_or_insert_with(|| exit_reason.clone());
+                    let (proceeds, trade) =
+                        self.close_position(symbol, pos, date, price, exit_reason);
+                    cash += proceeds;
+                    trades.push(trade);
                 }
+                decision.exit_signal = Some(any_exit);
             }
 
-            // If no position, check entry conditions
-            if position.is_none() {
-                if self.check_entry_condition(strategy, price, today, prev_indicators) {
-                    // Open position
-                    let position_value = cash * (strategy.position_size_percent / 100.0);
-                    let shares = (position_value - self.config.commission_per_trade) / price;
+            // Check entry conditions while another lot is allowed
+            let may_enter = positions.len() < max_positions
+                && (positions.is_empty() || self.config.allow_pyramiding);
+            if may_enter {
+                let (entry_met, entry_reason) =
+                    self.check_entry_condition(strategy, price, today, prev_indicators);
+                decision.entry_signal = Some(entry_met);
+                if entry_met {
+                    // Open a lot, sized against equity including the lots held
+                    let open_value = net_open_value(&positions, price);
+                    let direction = strategy.direction;
+                    let fill = self.fill_price(price, direction == TradeDirection::Long);
+                    let shares =
+                        self.position_shares(strategy, cash, cash + open_value, fill, today);
 
                     if shares > 0.0 {
-                        cash -= shares * price + self.config.commission_per_trade;
+                        let entry_commission = self.config.commission.cost(shares, fill);
+                        match direction {
+                            TradeDirection::Long => cash -= shares * fill + entry_commission,
+                            TradeDirection::Short => cash += shares * fill - entry_commission,
+                        }
 
-                        position = Some(OpenPosition {
+                        positions.push(OpenPosition {
                             entry_date: date,
-                            entry_price: price,
+                            entry_price: fill,
                             shares,
-                            entry_reason: strategy.entry_condition.as_str().to_string(),
+                            direction,
+                            entry_commission,
+                            entry_reason: entry_reason.clone(),
+                            best_price: price,
+                            stop_price: self.entry_stop(strategy, fill, today),
                         });
+                        decision.entered = Some(entry_reason);
                     }
                 }
             }
+
+            if let Some(log) = decisions.as_mut() {
+                decision.indicators = today.clone();
+                log.push(decision);
+            }
         }
 
-        // Close any remaining position at end
-        if let Some(pos) = position {
+        // Close any remaining lots at end
+        for pos in positions {
             if let Some(last_price) = sorted_prices.last() {
-                let profit_loss =
-                    (last_price.close - pos.entry_price) * pos.shares - self.config.commission_per_trade;
-                let profit_loss_percent =
-                    (last_price.close - pos.entry_price) / pos.entry_price * 100.0;
-
-                cash += pos.shares * last_price.close;
-
-                trades.push(BacktestTrade {
-                    id: 0,
-                    backtest_id: 0,
-                    symbol: symbol.to_string(),
-                    direction: TradeDirection::Long,
-                    entry_date: pos.entry_date,
-                    entry_price: pos.entry_price,
-                    exit_date: Some(last_price.date),
-                    exit_price: Some(last_price.close),
-                    shares: pos.shares,
-                    entry_reason: pos.entry_reason,
-                    exit_reason: Some("end_of_data".to_string()),
-                    profit_loss: Some(profit_loss),
-                    profit_loss_percent: Some(profit_loss_percent),
-                });
+                let (proceeds, trade) = self.close_position(
+                    symbol,
+                    pos,
+                    last_price.date,
+                    last_price.close,
+                    "end_of_data".to_string(),
+                );
+                cash += proceeds;
+                trades.push(trade);
             }
         }
 
-        // Calculate metrics
-        let metrics = self.calculate_metrics(&trades, &equity_history);
-
         let start_date = sorted_prices.first().map(|p| p.date).unwrap_or_else(|| {
             NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
         });
@@ -408,6 +1056,21 @@ impl BacktestEngine {
             NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
         });
 
+        // Calculate metrics
+        let span_days = (end_date - start_date).num_days();
+        let mut metrics = self.calculate_metrics(&trades, &equity_history, span_days);
+        if !sorted_prices.is_empty() {
+            metrics.time_in_market_percent =
+                bars_in_market as f64 / sorted_prices.len() as f64 * 100.0;
+        }
+        let marked: Vec<(NaiveDate, f64)> = sorted_prices
+            .iter()
+            .map(|p| p.date)
+            .zip(equity_history.iter().copied())
+            .collect();
+        metrics.information_ratio =
+            information_ratio(&marked, benchmark, self.config.timeframe.bars_per_year());
+
         BacktestResult {
             id: 0,
             strategy_id: strategy.id,
@@ -420,11 +1083,610 @@ impl BacktestEngine {
             metrics,
             trades,
             created_at: String::new(),
+            skipped_bars,
+            warnings,
+            equity_curve: equity_curve(sorted_prices.iter().map(|p| p.date), &equity_history, cash),
+        }
+    }
+
+    /// Backtest a strategy on every symbol that has price data
+    ///
+    /// Symbols missing an indicator the strategy's conditions need are skipped
+    /// with a note instead of producing an empty run. Results are not saved.
+    pub fn run_across_symbols(&self, db: &Database, strategy: &Strategy) -> Result<MultiSymbolBacktest> {
+        let required = strategy.required_indicators();
+
+        let benchmark = db.get_prices(&db.benchmark_symbol()?)?;
+        let mut symbols = db.get_symbols_with_data()?;
+        symbols.sort();
+
+        let mut outcome = MultiSymbolBacktest::default();
+        for symbol in symbols {
+            let indicators = db.get_all_indicators(&symbol)?;
+            let available: HashSet<&str> =
+                indicators.iter().map(|i| i.indicator_name.as_str()).collect();
+
+            let missing: Vec<&str> = required
+                .iter()
+                .copied()
+                .filter(|name| !available.contains(name))
+                .collect();
+            if indicators.is_empty() || !missing.is_empty() {
+                let reason = if indicators.is_empty() {
+                    "no indicator data".to_string()
+                } else {
+                    format!("missing {}", missing.join(", "))
+                };
+                outcome.skipped.push((symbol, reason));
+                continue;
+            }
+
+            let prices = db.get_prices(&symbol)?;
+            outcome.results.push(self.run_against_benchmark(
+                strategy,
+                &symbol,
+                &prices,
+                &indicators,
+                &benchmark,
+            ));
+        }
+
+        outcome.results.sort_by(|a, b| {
+            b.metrics
+                .total_return
+                .partial_cmp(&a.metrics.total_return)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(outcome)
+    }
+
+    /// Tune one strategy setting in-sample and check it out-of-sample
+    ///
+    /// `prices` (in date order) are split after the first `train_frac` of the
+    /// bars. Every value in `grid` is backtested on the in-sample window and
+    /// the best by `grid.objective` is then run once, untouched, on the
+    /// out-of-sample window, so its result is an honest estimate of how the
+    /// tuned strategy would have done on data it never saw. Indicators are
+    /// shared; each window only reads those on its own dates.
+    pub fn walk_forward(
+        &self,
+        base_strategy: &Strategy,
+        grid: &ParamGrid,
+        prices: &[DailyPrice],
+        indicators: &[TechnicalIndicator],
+        train_frac: f64,
+    ) -> Result<WalkForwardResult> {
+        if grid.values.is_empty() {
+            return Err(PipelineError::Config(
+                "Parameter grid has no values".to_string(),
+            ));
+        }
+        if !(train_frac > 0.0 && train_frac < 1.0) {
+            return Err(PipelineError::Config(format!(
+                "Training fraction must be between 0 and 1, got {}",
+                train_frac
+            )));
+        }
+
+        let split = (prices.len() as f64 * train_frac).round() as usize;
+        if split < 2 || prices.len() - split < 2 {
+            return Err(PipelineError::Config(format!(
+                "{} bars is too few to split {:.0}% in-sample",
+                prices.len(),
+                train_frac * 100.0
+            )));
+        }
+        let (train, test) = prices.split_at(split);
+        let symbol = &prices[0].symbol;
+
+        let sweep: Vec<(f64, PerformanceMetrics)> = grid
+            .values
+            .iter()
+            .map(|&value| {
+                let strategy = grid.param.apply(base_strategy, value);
+                let result = self.run(&strategy, symbol, train, indicators);
+                (value, result.metrics)
+            })
+            .collect();
+
+        let mut best = &sweep[0];
+        for candidate in &sweep[1..] {
+            if grid.objective.score(&candidate.1) > grid.objective.score(&best.1) {
+                best = candidate;
+            }
+        }
+        let (best_value, in_sample) = best.clone();
+
+        let strategy = grid.param.apply(base_strategy, best_value);
+        let out_of_sample = self.run(&strategy, symbol, test, indicators).metrics;
+
+        Ok(WalkForwardResult {
+            param: grid.param,
+            objective: grid.objective,
+            best_value,
+            strategy,
+            sweep,
+            split_date: test[0].date,
+            in_sample,
+            out_of_sample,
+        })
+    }
+
+    /// Backtest several strategies on the same bars, best total return first
+    ///
+    /// Every strategy runs against the same `prices`, `indicators` and
+    /// `benchmark` (see `run_against_benchmark`). Ties keep the order of
+    /// `strategies`. Nothing is saved.
+    pub fn compare_strategies(
+        &self,
+        strategies: &[Strategy],
+        symbol: &str,
+        prices: &[DailyPrice],
+        indicators: &[TechnicalIndicator],
+        benchmark: &[DailyPrice],
+    ) -> Vec<BacktestResult> {
+        let mut results: Vec<BacktestResult> = strategies
+            .iter()
+            .map(|s| self.run_against_benchmark(s, symbol, prices, indicators, benchmark))
+            .collect();
+        results.sort_by(|a, b| b.metrics.total_return.total_cmp(&a.metrics.total_return));
+        results
+    }
+
+    /// Bootstrap a completed run's trades to see how much of its result is luck
+    ///
+    /// Each closed trade becomes a return on the equity it was taken with,
+    /// replaying the trades in exit order from `initial_capital`. Every
+    /// iteration draws that many returns with replacement and compounds them
+    /// into an equity path, recording its total return and max drawdown.
+    /// Results are reproducible: the generator uses a fixed seed. Without
+    /// closed trades or iterations every statistic is 0.
+    pub fn monte_carlo(&self, result: &BacktestResult, iterations: usize) -> MonteCarloStats {
+        let mut closed: Vec<&BacktestTrade> = result
+            .trades
+            .iter()
+            .filter(|t| t.exit_date.is_some())
+            .collect();
+        closed.sort_by_key(|t| (t.exit_date, t.entry_date));
+
+        let mut equity = result.initial_capital;
+        let mut returns = Vec::with_capacity(closed.len());
+        for trade in closed {
+            let profit_loss = trade.profit_loss.unwrap_or(0.0);
+            if equity > 0.0 {
+                returns.push(profit_loss / equity);
+            }
+            equity += profit_loss;
+        }
+
+        let mut stats = MonteCarloStats {
+            iterations,
+            trades: returns.len(),
+            observed_return: result.metrics.total_return,
+            ..MonteCarloStats::default()
+        };
+        if returns.is_empty() || iterations == 0 {
+            return stats;
+        }
+
+        let mut rng = SplitMix64(MONTE_CARLO_SEED);
+        let mut final_returns = Vec::with_capacity(iterations);
+        let mut drawdowns = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let mut equity = 1.0;
+            let mut peak = 1.0;
+            let mut max_drawdown: f64 = 0.0;
+            for _ in 0..returns.len() {
+                equity *= 1.0 + returns[rng.below(returns.len())];
+                peak = f64::max(peak, equity);
+                max_drawdown = max_drawdown.max((peak - equity) / peak * 100.0);
+            }
+            final_returns.push((equity - 1.0) * 100.0);
+            drawdowns.push(max_drawdown);
+        }
+
+        final_returns.sort_by(|a, b| a.total_cmp(b));
+        drawdowns.sort_by(|a, b| a.total_cmp(b));
+        stats.return_p5 = percentile(&final_returns, 5.0);
+        stats.return_p50 = percentile(&final_returns, 50.0);
+        stats.return_p95 = percentile(&final_returns, 95.0);
+        stats.drawdown_p5 = percentile(&drawdowns, 5.0);
+        stats.drawdown_p50 = percentile(&drawdowns, 50.0);
+        stats.drawdown_p95 = percentile(&drawdowns, 95.0);
+        stats
+    }
+
+    /// Backtest one strategy across a basket of symbols sharing one capital pool
+    ///
+    /// `symbols` holds each symbol's prices and indicators. The engine walks
+    /// the union of all their dates; on each date exits are checked for every
+    /// symbol with a bar before any entries, so freed cash is available the
+    /// same day. Each entry is sized as `position_size_percent` of available
+    /// cash (or equity, per `sizing_base`) and is cut down so gross exposure
+    /// across open positions never exceeds current equity. Positions are
+    /// marked at each symbol's latest close, and any still open are closed at
+    /// that symbol's last bar. Trades from all symbols go into one result.
+    /// Each symbol is resampled to `config.timeframe` first.
+    pub fn run_portfolio(
+        &self,
+        strategy: &Strategy,
+        symbols: &[(String, Vec<DailyPrice>, Vec<TechnicalIndicator>)],
+    ) -> BacktestResult {
+        struct Leg<'a> {
+            symbol: &'a str,
+            prices: HashMap<NaiveDate, f64>,
+            indicators: HashMap<NaiveDate, HashMap<String, f64>>,
+            prev_date: Option<NaiveDate>,
+            last: Option<(NaiveDate, f64)>,
+            position: Option<OpenPosition>,
+        }
+
+        let window_start = self.config.requested_start.unwrap_or(NaiveDate::MIN);
+        let mut skipped_bars = 0;
+        let mut warnings = Vec::new();
+        let mut legs: Vec<Leg> = Vec::with_capacity(symbols.len());
+        for (symbol, prices, indicators) in symbols {
+            let (prices, indicators) = self.at_timeframe(prices, indicators);
+            let in_window: Vec<DailyPrice> = prices
+                .iter()
+                .filter(|p| p.date >= window_start)
+                .map(|p| {
+                    if self.config.use_adjusted_close {
+                        p.to_adjusted()
+                    } else {
+                        p.clone()
+                    }
+                })
+                .collect();
+            let priced: HashMap<NaiveDate, f64> = in_window
+                .iter()
+                .filter(|p| p.close > 0.0)
+                .map(|p| (p.date, p.close))
+                .collect();
+            skipped_bars += in_window.len() - priced.len();
+            if priced.is_empty() {
+                warnings.push(format!("No price data for {}", symbol));
+            }
+            legs.push(Leg {
+                symbol,
+                prices: priced,
+                indicators: self.build_indicator_map(&indicators),
+                prev_date: None,
+                last: None,
+                position: None,
+            });
+        }
+
+        let mut dates: Vec<NaiveDate> = legs
+            .iter()
+            .flat_map(|leg| leg.prices.keys().copied())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        dates.sort();
+
+        // Long value minus short liability, and gross exposure, at latest marks
+        let mark = |legs: &[Leg]| -> (f64, f64) {
+            legs.iter().fold((0.0, 0.0), |(net, gross), leg| {
+                match (&leg.position, leg.last) {
+                    (Some(pos), Some((_, price))) => {
+                        let value = pos.shares * price;
+                        match pos.direction {
+                            TradeDirection::Long => (net + value, gross + value),
+                            TradeDirection::Short => (net - value, gross + value),
+                        }
+                    }
+                    _ => (net, gross),
+                }
+            })
+        };
+
+        let mut cash = self.config.initial_capital;
+        let mut trades: Vec<BacktestTrade> = Vec::new();
+        let mut equity_history: Vec<f64> = Vec::with_capacity(dates.len());
+        let mut bars_in_market = 0usize;
+
+        for &date in &dates {
+            for leg in legs.iter_mut() {
+                if let Some(&price) = leg.prices.get(&date) {
+                    leg.last = Some((date, price));
+                    if let Some(pos) = leg.position.as_mut() {
+                        pos.track(price);
+                    }
+                }
+            }
+            let (net_value, _) = mark(&legs);
+            let equity = cash + net_value;
+            equity_history.push(equity);
+            if legs.iter().any(|leg| leg.position.is_some()) {
+                bars_in_market += 1;
+            }
+
+            // Exits first, so freed capital can fund today's entries
+            for leg in legs.iter_mut() {
+                let (Some(price), Some(today)) = (leg.prices.get(&date), leg.indicators.get(&date))
+                else {
+                    continue;
+                };
+                let Some(pos) = leg.position.as_ref() else {
+                    continue;
+                };
+                let prev = leg.prev_date.and_then(|d| leg.indicators.get(&d));
+                let (should_exit, exit_reason) =
+                    self.check_exit_condition(strategy, *price, pos, today, prev);
+                if should_exit {
+                    if let Some(pos) = leg.position.take() {
+                        let (proceeds, trade) =
+                            self.close_position(leg.symbol, pos, date, *price, exit_reason);
+                        cash += proceeds;
+                        trades.push(trade);
+                    }
+                }
+            }
+
+            for i in 0..legs.len() {
+                let leg = &legs[i];
+                let (Some(&price), Some(today)) = (leg.prices.get(&date), leg.indicators.get(&date))
+                else {
+                    continue;
+                };
+                if leg.position.is_some() {
+                    continue;
+                }
+                let prev = leg.prev_date.and_then(|d| leg.indicators.get(&d));
+                let (entry_met, entry_reason) =
+                    self.check_entry_condition(strategy, price, today, prev);
+                if !entry_met {
+                    continue;
+                }
+
+                let (net_value, gross) = mark(&legs);
+                let headroom = (cash + net_value - gross).max(0.0);
+                let direction = strategy.direction;
+                let fill = self.fill_price(price, direction == TradeDirection::Long);
+                let sized = self.position_shares(strategy, cash, cash + net_value, fill, today);
+                let capped = self.config.commission.affordable_shares(headroom, fill);
+                let shares = sized.min(capped);
+                if shares <= 0.0 {
+                    continue;
+                }
+
+                let entry_commission = self.config.commission.cost(shares, fill);
+                match direction {
+                    TradeDirection::Long => cash -= shares * fill + entry_commission,
+                    TradeDirection::Short => cash += shares * fill - entry_commission,
+                }
+                legs[i].position = Some(OpenPosition {
+                    entry_date: date,
+                    entry_price: fill,
+                    shares,
+                    direction,
+                    entry_commission,
+                    entry_reason,
+                    best_price: price,
+                    stop_price: self.entry_stop(strategy, fill, today),
+                });
+            }
+
+            for leg in legs.iter_mut() {
+                if leg.prices.contains_key(&date) {
+                    leg.prev_date = Some(date);
+                }
+            }
+        }
+
+        // Close what's still open at each symbol's last bar
+        for leg in legs.iter_mut() {
+            if let (Some(pos), Some((last_date, last_price))) = (leg.position.take(), leg.last) {
+                let (proceeds, trade) = self.close_position(
+                    leg.symbol,
+                    pos,
+                    last_date,
+                    last_price,
+                    "end_of_data".to_string(),
+                );
+                cash += proceeds;
+                trades.push(trade);
+            }
+        }
+        trades.sort_by_key(|t| (t.entry_date, t.exit_date));
+
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let start_date = dates.first().copied().unwrap_or(epoch);
+        let end_date = dates.last().copied().unwrap_or(epoch);
+        let mut metrics =
+            self.calculate_metrics(&trades, &equity_history, (end_date - start_date).num_days());
+        if !dates.is_empty() {
+            metrics.time_in_market_percent = bars_in_market as f64 / dates.len() as f64 * 100.0;
+        }
+
+        BacktestResult {
+            id: 0,
+            strategy_id: strategy.id,
+            strategy_name: strategy.name.clone(),
+            symbol: legs.iter().map(|leg| leg.symbol).collect::<Vec<_>>().join(","),
+            start_date,
+            end_date,
+            initial_capital: self.config.initial_capital,
+            final_capital: cash,
+            metrics,
+            trades,
+            created_at: String::new(),
+            skipped_bars,
+            warnings,
+            equity_curve: equity_curve(dates.iter().copied(), &equity_history, cash),
+        }
+    }
+
+    /// Simulate a fixed-weight portfolio that is periodically rebalanced
+    ///
+    /// Capital is allocated by `weights` (symbol -> fraction of equity) on the
+    /// first date all symbols have a price, then reset to those targets on the
+    /// first trading day of each new month/quarter. Weights summing to less
+    /// than 1.0 leave the remainder in cash. Only dates on which every symbol
+    /// has a price are used.
+    ///
+    /// Each holding period is recorded as one trade per symbol, closed at the
+    /// next rebalance (exit reason "rebalance") or at the end of data. Both
+    /// sides of every rebalance fill with `config.slippage_bps` and pay
+    /// `config.commission`, so costs come out of the cash being reallocated.
+    /// Prices are resampled to `config.timeframe` first.
+    pub fn simulate_rebalance(
+        &self,
+        weights: &HashMap<String, f64>,
+        prices_by_symbol: &HashMap<String, Vec<DailyPrice>>,
+        rebalance: Rebalance,
+        initial_capital: f64,
+    ) -> BacktestResult {
+        let mut symbols: Vec<String> = weights.keys().cloned().collect();
+        symbols.sort();
+
+        // Price lookup per symbol (positive closes only), and the dates shared by every symbol
+        let mut skipped_bars = 0;
+        let mut price_maps: HashMap<&str, HashMap<NaiveDate, f64>> = HashMap::new();
+        for symbol in &symbols {
+            let prices = prices_by_symbol
+                .get(symbol)
+                .map(|prices| self.resampled(prices))
+                .unwrap_or_default();
+            let map: HashMap<NaiveDate, f64> = prices
+                .iter()
+                .filter(|p| p.close > 0.0)
+                .map(|p| (p.date, p.close))
+                .collect();
+            skipped_bars += prices.len() - map.len();
+            price_maps.insert(symbol.as_str(), map);
+        }
+
+        let mut dates: Vec<NaiveDate> = price_maps
+            .values()
+            .next()
+            .map(|m| m.keys().copied().collect())
+            .unwrap_or_default();
+        dates.retain(|d| price_maps.values().all(|m| m.contains_key(d)));
+        dates.sort();
+
+        let mut cash = initial_capital;
+        let mut lots: Vec<OpenPosition> = Vec::new();
+        let mut lot_symbols: Vec<&str> = Vec::new();
+        let mut trades: Vec<BacktestTrade> = Vec::new();
+        let mut equity_history: Vec<f64> = Vec::new();
+
+        for (i, &date) in dates.iter().enumerate() {
+            let price_of = |symbol: &str| price_maps[symbol][&date];
+
+            let equity = cash
+                + lots
+                    .iter()
+                    .zip(&lot_symbols)
+                    .map(|(lot, symbol)| lot.shares * price_of(symbol))
+                    .sum::<f64>();
+
+            let should_rebalance = i == 0 || rebalance.is_boundary(dates[i - 1], date);
+
+            if should_rebalance {
+                // Close the current lots at today's prices
+                for (lot, symbol) in lots.drain(..).zip(lot_symbols.drain(..)) {
+                    let (cash_change, trade) = self.close_position(
+                        symbol,
+                        lot,
+                        date,
+                        price_of(symbol),
+                        "rebalance".to_string(),
+                    );
+                    cash += cash_change;
+                    trades.push(trade);
+                }
+
+                // Reopen at target weights of what the sale left
+                let investable = cash;
+                for symbol in &symbols {
+                    let price = price_of(symbol);
+                    if price <= 0.0 {
+                        continue;
+                    }
+                    let fill = self.fill_price(price, true);
+                    let shares = self
+                        .config
+                        .commission
+                        .affordable_shares(investable * weights[symbol], fill);
+                    if shares <= 0.0 {
+                        continue;
+                    }
+                    let entry_commission = self.config.commission.cost(shares, fill);
+                    cash -= shares * fill + entry_commission;
+                    lots.push(OpenPosition {
+                        entry_date: date,
+                        entry_price: fill,
+                        shares,
+                        direction: TradeDirection::Long,
+                        entry_commission,
+                        entry_reason: if i == 0 {
+                            "initial_allocation".to_string()
+                        } else {
+                            "rebalance".to_string()
+                        },
+                        best_price: price,
+                        stop_price: None,
+                    });
+                    lot_symbols.push(symbol.as_str());
+                }
+            }
+
+            equity_history.push(equity);
+        }
+
+        // Close remaining lots at the last shared date
+        if let Some(&last_date) = dates.last() {
+            for (lot, symbol) in lots.into_iter().zip(lot_symbols) {
+                let price = price_maps[symbol][&last_date];
+                let (cash_change, trade) =
+                    self.close_position(symbol, lot, last_date, price, "end_of_data".to_string());
+                cash += cash_change;
+                trades.push(trade);
+            }
+        }
+
+        let engine = BacktestEngine::new(BacktestConfig {
+            initial_capital,
+            ..self.config.clone()
+        });
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let start_date = dates.first().copied().unwrap_or(epoch);
+        let end_date = dates.last().copied().unwrap_or(epoch);
+        let mut metrics =
+            engine.calculate_metrics(&trades, &equity_history, (end_date - start_date).num_days());
+        // Holdings are reset to the target weights every period, never flat
+        if !dates.is_empty() && weights.values().any(|&w| w > 0.0) {
+            metrics.time_in_market_percent = 100.0;
+        }
+
+        BacktestResult {
+            id: 0,
+            strategy_id: 0,
+            strategy_name: format!("Rebalance ({})", rebalance.as_str()),
+            symbol: symbols.join(","),
+            start_date,
+            end_date,
+            initial_capital,
+            final_capital: cash,
+            metrics,
+            trades,
+            created_at: String::new(),
+            skipped_bars,
+            warnings: Vec::new(),
+            equity_curve: equity_curve(dates.iter().copied(), &equity_history, cash),
         }
     }
 
     /// Calculate performance metrics
-    fn calculate_metrics(&self, trades: &[BacktestTrade], equity_history: &[f64]) -> PerformanceMetrics {
+    fn calculate_metrics(
+        &self,
+        trades: &[BacktestTrade],
+        equity_history: &[f64],
+        span_days: i64,
+    ) -> PerformanceMetrics {
         let initial = self.config.initial_capital;
         let final_equity = *equity_history.last().unwrap_or(&initial);
 
@@ -516,7 +1778,15 @@ impl BacktestEngine {
             0.0
         };
 
-        // Simple Sharpe ratio approximation (assuming 252 trading days)
+        // Trade frequency over the calendar span
+        let trades_per_year = if span_days > 0 {
+            total_trades as f64 / (span_days as f64 / 365.25)
+        } else {
+            0.0
+        };
+
+        // Simple Sharpe ratio approximation, annualized by bars per year
+        let bars_per_year = self.config.timeframe.bars_per_year();
         let daily_returns: Vec<f64> = equity_history
             .windows(2)
             .map(|w| (w[1] - w[0]) / w[0])
@@ -540,7 +1810,40 @@ impl BacktestEngine {
         };
 
         let sharpe_ratio = if std_dev > 0.0 {
-            (avg_return / std_dev) * (252.0_f64).sqrt()
+            (avg_return / std_dev) * bars_per_year.sqrt()
+        } else {
+            0.0
+        };
+
+        // Sortino: downside deviation counts only negative days (target 0)
+        let downside_dev = if !daily_returns.is_empty() {
+            (daily_returns
+                .iter()
+                .map(|r| r.min(0.0).powi(2))
+                .sum::<f64>()
+                / daily_returns.len() as f64)
+                .sqrt()
+        } else {
+            0.0
+        };
+        let sortino_ratio = if downside_dev > 0.0 {
+            (avg_return / downside_dev) * bars_per_year.sqrt()
+        } else if avg_return > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+
+        // Calmar: compound annual growth rate over max drawdown
+        let annualized_return = if span_days > 0 && initial > 0.0 && final_equity > 0.0 {
+            ((final_equity / initial).powf(365.25 / span_days as f64) - 1.0) * 100.0
+        } else {
+            0.0
+        };
+        let calmar_ratio = if max_drawdown > 0.0 {
+            annualized_return / max_drawdown
+        } else if annualized_return > 0.0 {
+            f64::INFINITY
         } else {
             0.0
         };
@@ -558,6 +1861,1165 @@ impl BacktestEngine {
             avg_loss_percent: avg_loss,
             profit_factor,
             avg_trade_duration_days: avg_duration,
+            trades_per_year,
+            time_in_market_percent: 0.0,
+            information_ratio: 0.0,
+            sortino_ratio,
+            calmar_ratio,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::BacktestExportFormat;
+
+    fn series(symbol: &str, start: NaiveDate, closes: &[f64]) -> Vec<DailyPrice> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| DailyPrice {
+                symbol: symbol.to_string(),
+                date: start + chrono::Duration::days(i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1000,
+                adjusted_close: None,
+                source: "test".to_string(),
+            })
+            .collect()
+    }
+
+    /// Buy when RSI_14 drops below 30, sell when it rises above 70, all in
+    fn rsi_strategy() -> Strategy {
+        Strategy {
+            id: 0,
+            name: "RSI dip".to_string(),
+            description: None,
+            entry_condition: StrategyConditionType::RsiOversold,
+            entry_threshold: 30.0,
+            exit_condition: StrategyConditionType::RsiOverbought,
+            exit_threshold: 70.0,
+            stop_loss_percent: None,
+            take_profit_percent: None,
+            trailing_stop_percent: None,
+            position_size_percent: 100.0,
+            direction: TradeDirection::Long,
+            entry_rules: None,
+            exit_rules: None,
+            position_sizing: None,
+            created_at: String::new(),
+        }
+    }
+
+    /// RSI_14 on every bar of `prices`, valued by the bar's index
+    fn rsi_indicators(
+        prices: &[DailyPrice],
+        rsi: impl Fn(usize) -> f64,
+    ) -> Vec<TechnicalIndicator> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| TechnicalIndicator {
+                symbol: p.symbol.clone(),
+                date: p.date,
+                indicator_name: "RSI_14".to_string(),
+                value: rsi(i),
+            })
+            .collect()
+    }
+
+    /// RSI dip strategy that also wants price above its SMA_20
+    fn two_rule_strategy(combinator: RuleCombinator) -> Strategy {
+        Strategy {
+            name: "Dip in uptrend".to_string(),
+            entry_rules: Some(StrategyRules {
+                combinator,
+                rules: vec![
+                    StrategyRule {
+                        condition: StrategyConditionType::RsiOversold,
+                        threshold: 30.0,
+                    },
+                    StrategyRule {
+                        condition: StrategyConditionType::PriceAboveSma,
+                        threshold: 0.0,
+                    },
+                ],
+            }),
+            ..rsi_strategy()
+        }
+    }
+
+    #[test]
+    fn test_all_rules_require_every_condition() {
+        let engine = BacktestEngine::default();
+        let strategy = two_rule_strategy(RuleCombinator::All);
+        let oversold_below_sma =
+            HashMap::from([("RSI_14".to_string(), 25.0), ("SMA_20".to_string(), 110.0)]);
+        let oversold_above_sma =
+            HashMap::from([("RSI_14".to_string(), 25.0), ("SMA_20".to_string(), 90.0)]);
+
+        let (met, _) = engine.check_entry_condition(&strategy, 100.0, &oversold_below_sma, None);
+        assert!(!met);
+
+        let (met, reason) =
+            engine.check_entry_condition(&strategy, 100.0, &oversold_above_sma, None);
+        assert!(met);
+        assert_eq!(reason, "rsi_oversold+price_above_sma");
+
+        // Without explicit rules the single entry condition is a one-rule All list
+        let single = Strategy {
+            entry_rules: None,
+            ..strategy.clone()
+        };
+        let (met, reason) = engine.check_entry_condition(&single, 100.0, &oversold_below_sma, None);
+        assert!(met);
+        assert_eq!(reason, "rsi_oversold");
+
+        // The rules survive a save/load round trip
+        let db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        db.save_strategy(&strategy).unwrap();
+        let loaded = db.get_strategy("Dip in uptrend").unwrap().unwrap();
+        assert_eq!(loaded.entry_rules, strategy.entry_rules);
+        assert_eq!(loaded.exit_rules, None);
+    }
+
+    #[test]
+    fn test_any_rule_requires_either_condition() {
+        let engine = BacktestEngine::default();
+        let strategy = two_rule_strategy(RuleCombinator::Any);
+        let oversold_below_sma =
+            HashMap::from([("RSI_14".to_string(), 25.0), ("SMA_20".to_string(), 110.0)]);
+        let neutral_above_sma =
+            HashMap::from([("RSI_14".to_string(), 50.0), ("SMA_20".to_string(), 90.0)]);
+        let neutral_below_sma =
+            HashMap::from([("RSI_14".to_string(), 50.0), ("SMA_20".to_string(), 110.0)]);
+
+        let (met, reason) =
+            engine.check_entry_condition(&strategy, 100.0, &oversold_below_sma, None);
+        assert!(met);
+        assert_eq!(reason, "rsi_oversold");
+
+        let (met, reason) =
+            engine.check_entry_condition(&strategy, 100.0, &neutral_above_sma, None);
+        assert!(met);
+        assert_eq!(reason, "price_above_sma");
+
+        let (met, _) = engine.check_entry_condition(&strategy, 100.0, &neutral_below_sma, None);
+        assert!(!met);
+    }
+
+    #[test]
+    fn test_run_across_symbols_ranks_by_return() {
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        // Both symbols dip on day 2 (entry) and then rally at different rates
+        for (symbol, closes) in [
+            ("SLOW", [100.0, 100.0, 100.0, 102.0, 104.0, 106.0]),
+            ("FAST", [100.0, 100.0, 100.0, 110.0, 120.0, 130.0]),
+        ] {
+            let prices = series(symbol, start, &closes);
+            db.upsert_daily_prices(&prices).unwrap();
+            let indicators = rsi_indicators(&prices, |i| if i == 2 { 20.0 } else { 50.0 });
+            db.upsert_indicators(&indicators).unwrap();
+        }
+        // Prices but no indicators
+        db.upsert_daily_prices(&series("BARE", start, &[10.0, 11.0])).unwrap();
+
+        let strategy = rsi_strategy();
+
+        let outcome = BacktestEngine::default().run_across_symbols(&db, &strategy).unwrap();
+
+        let ranked: Vec<&str> = outcome.results.iter().map(|r| r.symbol.as_str()).collect();
+        assert_eq!(ranked, vec!["FAST", "SLOW"]);
+        assert!(outcome.results[0].metrics.total_return > outcome.results[1].metrics.total_return);
+        assert_eq!(outcome.skipped.len(), 1);
+        assert_eq!(outcome.skipped[0].0, "BARE");
+    }
+
+    #[test]
+    fn test_portfolio_shares_cash_and_never_goes_negative() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        // RSI dips (entry) and spikes (exit) on different days per symbol;
+        // CCC starts a day late, so the timeline is the union of dates
+        let rsi = |dips: &[usize], spikes: &[usize], i: usize| {
+            if dips.contains(&i) {
+                20.0
+            } else if spikes.contains(&i) {
+                80.0
+            } else {
+                50.0
+            }
+        };
+        let basket: Vec<(String, Vec<DailyPrice>, Vec<TechnicalIndicator>)> = [
+            ("AAA", 0, vec![100.0, 100.0, 95.0, 105.0, 110.0, 90.0, 95.0], vec![1, 5], vec![3]),
+            ("BBB", 0, vec![50.0, 50.0, 52.0, 48.0, 60.0, 61.0, 62.0], vec![1], vec![4]),
+            ("CCC", 1, vec![20.0, 21.0, 19.0, 25.0, 18.0, 30.0], vec![1, 3], vec![2]),
+        ]
+        .into_iter()
+        .map(|(symbol, offset, closes, dips, spikes)| {
+            let prices = series(symbol, start + chrono::Duration::days(offset), &closes);
+            let indicators = rsi_indicators(&prices, |i| rsi(&dips, &spikes, i));
+            (symbol.to_string(), prices, indicators)
+        })
+        .collect();
+
+        let strategy = Strategy {
+            position_size_percent: 60.0,
+            ..rsi_strategy()
+        };
+        let engine = BacktestEngine::new(BacktestConfig {
+            commission: CommissionModel::Flat(5.0),
+            slippage_bps: 10.0,
+            ..BacktestConfig::default()
+        });
+        let result = engine.run_portfolio(&strategy, &basket);
+
+        assert_eq!(result.symbol, "AAA,BBB,CCC");
+        assert_eq!(result.start_date, start);
+        assert_eq!(result.trades.len(), 5);
+        for symbol in ["AAA", "BBB", "CCC"] {
+            assert!(result.trades.iter().any(|t| t.symbol == symbol));
+        }
+
+        // Replay the cash ledger from the trades: exits settle before entries
+        let mut events: Vec<(NaiveDate, u8, f64)> = Vec::new();
+        for t in &result.trades {
+            let exit_price = t.exit_price.unwrap();
+            events.push((t.entry_date, 1, -(t.shares * t.entry_price + 5.0)));
+            events.push((t.exit_date.unwrap(), 0, t.shares * exit_price - 5.0));
+        }
+        events.sort_by_key(|&(date, order, _)| (date, order));
+        let mut cash = result.initial_capital;
+        for (date, _, change) in events {
+            cash += change;
+            assert!(cash >= -1e-9, "cash went negative on {}: {}", date, cash);
+        }
+        assert!((cash - result.final_capital).abs() < 1e-6);
+
+        // AAA and BBB enter together: 60% of cash, then 60% of what's left
+        let day1: Vec<&BacktestTrade> = result
+            .trades
+            .iter()
+            .filter(|t| t.entry_date == start + chrono::Duration::days(1))
+            .collect();
+        assert_eq!(day1.len(), 2);
+        let cost = |t: &BacktestTrade| t.shares * t.entry_price + 5.0;
+        assert!((cost(day1[0]) - 6000.0).abs() < 1e-6);
+        assert!((cost(day1[1]) - 2400.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_equity_curve_aligned_and_persisted() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut prices = series("AAPL", start, &[100.0, 100.0, 98.0, 104.0, 0.0, 108.0, 111.0]);
+        prices.reverse();
+        let dip = start + chrono::Duration::days(2);
+        let indicators =
+            rsi_indicators(&prices, |i| if prices[i].date == dip { 20.0 } else { 50.0 });
+        let strategy = rsi_strategy();
+        let db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let strategy = Strategy {
+            id: db.save_strategy(&strategy).unwrap(),
+            ..strategy
+        };
+
+        let engine = BacktestEngine::new(BacktestConfig {
+            commission: CommissionModel::Flat(10.0),
+            ..BacktestConfig::default()
+        });
+        let result = engine.run(&strategy, "AAPL", &prices, &indicators);
+
+        // One point per tradable bar (the zero close is dropped), in date order
+        assert_eq!(result.equity_curve.len(), 6);
+        assert_eq!(result.equity_curve[0], (start, 10_000.0));
+        assert!(result.equity_curve.windows(2).all(|w| w[0].0 < w[1].0));
+        let (last_date, last_value) = *result.equity_curve.last().unwrap();
+        assert_eq!(last_date, result.end_date);
+        assert_eq!(last_value, result.final_capital);
+
+        let id = db.save_backtest_result(&result).unwrap();
+        let loaded = db.get_backtest_detail(id).unwrap().unwrap();
+        assert_eq!(loaded.equity_curve, result.equity_curve);
+    }
+
+    #[test]
+    fn test_export_backtest_json_round_trips_and_csv_lists_trades() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let closes = [100.0, 100.0, 95.0, 90.0, 90.0, 100.0, 110.0, 110.0];
+        let prices = series("AAPL", start, &closes);
+        // Enter on bars 1 and 4, exit on bars 3 and 6: one loss, one win
+        let rsi = [50.0, 20.0, 50.0, 80.0, 20.0, 50.0, 80.0, 50.0];
+        let indicators = rsi_indicators(&prices, |i| rsi[i]);
+        let db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let strategy = rsi_strategy();
+        let strategy = Strategy {
+            id: db.save_strategy(&strategy).unwrap(),
+            ..strategy
+        };
+        let result = BacktestEngine::new(BacktestConfig::default())
+            .run(&strategy, "AAPL", &prices, &indicators);
+        assert_eq!(result.trades.len(), 2);
+        let id = db.save_backtest_result(&result).unwrap();
+
+        let mut json = Vec::new();
+        let found = db.export_backtest(id, BacktestExportFormat::Json, &mut json);
+        assert!(found.unwrap());
+        let parsed: BacktestResult = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed.id, id);
+        assert_eq!(parsed.trades.len(), 2);
+        assert_eq!(parsed.equity_curve, result.equity_curve);
+        assert_eq!(parsed.metrics.total_return, result.metrics.total_return);
+        assert_eq!(parsed.trades[1].exit_price, Some(110.0));
+
+        let mut csv = Vec::new();
+        let found = db.export_backtest(id, BacktestExportFormat::Csv, &mut csv);
+        assert!(found.unwrap());
+        let csv = String::from_utf8(csv).unwrap();
+        let (summary, trades) = csv.split_once("\n\n").unwrap();
+        assert!(summary.starts_with("metric,value\nstrategy,RSI dip\n"));
+        assert!(summary.contains("\ntotal_trades,2\n"));
+        let rows: Vec<&str> = trades.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("direction,entry_date,"));
+        assert!(rows[2].starts_with("long,2024-01-05,90,"));
+
+        let found = db.export_backtest(id + 1, BacktestExportFormat::Json, Vec::new());
+        assert!(!found.unwrap());
+    }
+
+    #[test]
+    fn test_export_backtest_json_round_trips_infinite_ratios() {
+        // Only real dips are bought, so every trade wins and equity never falls
+        let (prices, indicators) = dip_cycles(3);
+        let db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let strategy = rsi_strategy();
+        let strategy = Strategy {
+            id: db.save_strategy(&strategy).unwrap(),
+            ..strategy
+        };
+        let result = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+        assert_eq!(result.metrics.losing_trades, 0);
+        assert_eq!(result.metrics.profit_factor, f64::INFINITY);
+        assert_eq!(result.metrics.calmar_ratio, f64::INFINITY);
+        let id = db.save_backtest_result(&result).unwrap();
+
+        let mut json = Vec::new();
+        assert!(db
+            .export_backtest(id, BacktestExportFormat::Json, &mut json)
+            .unwrap());
+        assert!(String::from_utf8_lossy(&json).contains("\"profit_factor\": \"inf\""));
+        let parsed: BacktestResult = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed.metrics.profit_factor, f64::INFINITY);
+        assert_eq!(parsed.metrics.calmar_ratio, f64::INFINITY);
+        assert_eq!(parsed.metrics.sortino_ratio, result.metrics.sortino_ratio);
+        assert_eq!(parsed.metrics.total_return, result.metrics.total_return);
+
+        // Older exports wrote null
+        let legacy = String::from_utf8(json)
+            .unwrap()
+            .replace("\"profit_factor\": \"inf\"", "\"profit_factor\": null");
+        let parsed: BacktestResult = serde_json::from_str(&legacy).unwrap();
+        assert!(parsed.metrics.profit_factor.is_nan());
+    }
+
+    #[test]
+    fn test_atr_risk_sizing_buys_fewer_shares_when_volatile() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let strategy = Strategy {
+            name: "RSI dip, 1% risk".to_string(),
+            position_sizing: Some(PositionSizing::RiskPercent {
+                risk: 1.0,
+                atr_mult: 2.0,
+            }),
+            ..rsi_strategy()
+        };
+
+        let entry_shares = |symbol: &str, atr: f64| {
+            let prices = series(symbol, start, &[100.0, 100.0, 100.0]);
+            let indicators: Vec<TechnicalIndicator> = prices
+                .iter()
+                .flat_map(|p| {
+                    [("RSI_14", 20.0), ("ATR_14", atr)].map(|(name, value)| TechnicalIndicator {
+                        symbol: symbol.to_string(),
+                        date: p.date,
+                        indicator_name: name.to_string(),
+                        value,
+                    })
+                })
+                .collect();
+            let result = BacktestEngine::default().run(&strategy, symbol, &prices, &indicators);
+            result.trades[0].shares
+        };
+
+        // $100 risk budget (1% of $10,000) over a stop 2 ATRs away
+        let calm = entry_shares("CALM", 2.0);
+        let wild = entry_shares("WILD", 5.0);
+        assert!((calm - 25.0).abs() < 1e-9);
+        assert!((wild - 10.0).abs() < 1e-9);
+        assert!(wild < calm);
+        assert!(strategy.required_indicators().contains(&"ATR_14"));
+    }
+
+    #[test]
+    fn test_atr_risk_sizing_exits_at_its_stop() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0, 100.0, 97.0, 95.0, 94.0]);
+        // Oversold on the first bar only; ATR 2 puts a 2-ATR stop at $96
+        let indicators: Vec<TechnicalIndicator> = prices
+            .iter()
+            .enumerate()
+            .flat_map(|(i, p)| {
+                let rsi = if i == 0 { 20.0 } else { 50.0 };
+                [("RSI_14", rsi), ("ATR_14", 2.0)].map(|(name, value)| TechnicalIndicator {
+                    symbol: "TEST".to_string(),
+                    date: p.date,
+                    indicator_name: name.to_string(),
+                    value,
+                })
+            })
+            .collect();
+        let strategy = Strategy {
+            position_sizing: Some(PositionSizing::RiskPercent {
+                risk: 1.0,
+                atr_mult: 2.0,
+            }),
+            ..rsi_strategy()
+        };
+
+        let result = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+
+        assert_eq!(result.trades.len(), 1);
+        let trade = &result.trades[0];
+        assert_eq!(trade.exit_reason.as_deref(), Some("atr_stop"));
+        assert_eq!(trade.exit_date, Some(start + chrono::Duration::days(3)));
+        assert_eq!(trade.exit_price, Some(95.0));
+
+        // Percent-of-equity sizing places no stop and rides to the end
+        let by_percent = BacktestEngine::default().run(
+            &Strategy {
+                position_sizing: None,
+                ..strategy
+            },
+            "TEST",
+            &prices,
+            &indicators,
+        );
+        assert_eq!(by_percent.trades[0].exit_reason.as_deref(), Some("end_of_data"));
+    }
+
+    #[test]
+    fn test_trailing_stop_exits_near_peak() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let closes = [100.0, 100.0, 110.0, 130.0, 150.0, 140.0, 134.0, 120.0, 100.0, 85.0];
+        let prices = series("AAPL", start, &closes);
+        let indicators = rsi_indicators(&prices, |i| if i == 1 { 20.0 } else { 50.0 });
+        let strategy = Strategy {
+            stop_loss_percent: Some(20.0),
+            trailing_stop_percent: Some(10.0),
+            ..rsi_strategy()
+        };
+
+        let result = BacktestEngine::default().run(&strategy, "AAPL", &prices, &indicators);
+        assert_eq!(result.trades.len(), 1);
+        let trade = &result.trades[0];
+        // Peak 150, so the first close at or below 135 exits; the fixed stop sits at 80
+        assert_eq!(trade.exit_reason.as_deref(), Some("trailing_stop"));
+        assert_eq!(trade.exit_date, Some(prices[6].date));
+        assert_eq!(trade.exit_price, Some(134.0));
+
+        // Without the trailing stop the position rides the decline to the end
+        let fixed = Strategy {
+            trailing_stop_percent: None,
+            ..strategy
+        };
+        let result = BacktestEngine::default().run(&fixed, "AAPL", &prices, &indicators);
+        assert_eq!(result.trades[0].exit_reason.as_deref(), Some("end_of_data"));
+        assert_eq!(result.trades[0].exit_price, Some(85.0));
+    }
+
+    #[test]
+    fn test_sortino_exceeds_sharpe_on_asymmetric_returns() {
+        let engine = BacktestEngine::default();
+
+        // Large occasional gains, small frequent losses
+        let mut equity = vec![10_000.0];
+        for i in 0..60 {
+            let r = if i % 4 == 0 { 0.03 } else { -0.005 };
+            equity.push(equity.last().unwrap() * (1.0 + r));
+        }
+        let m = engine.calculate_metrics(&[], &equity, 90);
+        assert!(m.sharpe_ratio > 0.0);
+        assert!(m.sortino_ratio > m.sharpe_ratio, "{} <= {}", m.sortino_ratio, m.sharpe_ratio);
+
+        let cagr = ((equity[60] / 10_000.0).powf(365.25 / 90.0) - 1.0) * 100.0;
+        assert!((m.calmar_ratio - cagr / m.max_drawdown).abs() < 1e-9);
+
+        // Never a losing day or a drawdown: both ratios are infinite
+        let rising: Vec<f64> = (0..10).map(|i| 10_000.0 + 100.0 * i as f64).collect();
+        let m = engine.calculate_metrics(&[], &rising, 9);
+        assert_eq!(m.sortino_ratio, f64::INFINITY);
+        assert_eq!(m.calmar_ratio, f64::INFINITY);
+
+        // Flat equity: no return, no risk, both 0
+        let m = engine.calculate_metrics(&[], &[10_000.0; 10], 9);
+        assert_eq!(m.sortino_ratio, 0.0);
+        assert_eq!(m.calmar_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_short_history_reports_true_start() {
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+
+        // 200 bars of a recent listing
+        let listed = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        db.upsert_daily_prices(&series("NEW", listed, &[50.0; 200])).unwrap();
+        let prices = db.get_prices("NEW").unwrap();
+        let first = db.get_first_price_date("NEW").unwrap().unwrap();
+        assert_eq!(first, listed);
+
+        let strategy = Strategy {
+            name: "SMA".to_string(),
+            entry_condition: StrategyConditionType::PriceAboveSma,
+            entry_threshold: 0.0,
+            exit_condition: StrategyConditionType::PriceBelowSma,
+            exit_threshold: 0.0,
+            ..rsi_strategy()
+        };
+
+        // Five years requested
+        let engine = BacktestEngine::new(BacktestConfig {
+            requested_start: NaiveDate::from_ymd_opt(2019, 7, 1),
+            ..BacktestConfig::default()
+        });
+        let result = engine.run(&strategy, "NEW", &prices, &[]);
+
+        assert_eq!(result.start_date, first);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("Short history"));
+        assert!(result.warnings[0].contains("2024-01-01"));
+
+        // A requested start inside the data trims the window without warning
+        let engine = BacktestEngine::new(BacktestConfig {
+            requested_start: NaiveDate::from_ymd_opt(2024, 3, 1),
+            ..BacktestConfig::default()
+        });
+        let result = engine.run(&strategy, "NEW", &prices, &[]);
+        assert_eq!(result.start_date, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_zero_close_bar_is_skipped() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut prices = series("TEST", start, &[100.0; 10]);
+        prices[5].close = 0.0;
+
+        // RSI only signals an entry on the bad bar
+        let indicators = rsi_indicators(&prices, |i| if i == 5 { 20.0 } else { 50.0 });
+
+        let strategy = rsi_strategy();
+
+        let result = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+
+        assert_eq!(result.skipped_bars, 1);
+        assert!(result.trades.is_empty());
+        assert!(result.final_capital.is_finite());
+        assert_eq!(result.final_capital, 10000.0);
+        assert!(result.metrics.total_return.is_finite());
+    }
+
+    #[test]
+    fn test_adjusted_close_removes_split_drop() {
+        // 4:1 split halfway through: raw closes fall from 400 to 100
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut prices = series("TEST", start, &[400.0, 400.0, 400.0, 100.0, 100.0, 100.0]);
+        for p in prices.iter_mut() {
+            p.adjusted_close = Some(100.0);
+        }
+
+        let indicators = rsi_indicators(&prices, |i| if i == 0 { 20.0 } else { 50.0 });
+
+        let strategy = rsi_strategy();
+
+        let raw = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+        assert!((raw.metrics.total_return + 75.0).abs() < 1e-9);
+
+        let adjusted = BacktestEngine::new(BacktestConfig {
+            use_adjusted_close: true,
+            ..BacktestConfig::default()
+        })
+        .run(&strategy, "TEST", &prices, &indicators);
+        assert!(adjusted.metrics.total_return.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_in_market_half_the_bars() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0; 20]);
+
+        // Enter on bar 4, exit on bar 14: held through 10 of 20 bars
+        let indicators = rsi_indicators(&prices, |i| match i {
+            4 => 20.0,
+            14 => 80.0,
+            _ => 50.0,
+        });
+
+        let strategy = Strategy {
+            name: "RSI swing".to_string(),
+            ..rsi_strategy()
+        };
+
+        let result = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+
+        assert_eq!(result.trades.len(), 1);
+        assert!((result.metrics.time_in_market_percent - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pyramiding_adds_lots_up_to_position_cap() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0; 20]);
+
+        // Oversold for five straight bars, then overbought on bar 15
+        let indicators = rsi_indicators(&prices, |i| match i {
+            2..=6 => 20.0,
+            15 => 80.0,
+            _ => 50.0,
+        });
+
+        let strategy = Strategy {
+            position_size_percent: 25.0,
+            ..rsi_strategy()
+        };
+        let run = |max_positions, allow_pyramiding| {
+            BacktestEngine::new(BacktestConfig {
+                max_positions,
+                allow_pyramiding,
+                ..BacktestConfig::default()
+            })
+            .run(&strategy, "TEST", &prices, &indicators)
+        };
+
+        // Without pyramiding the extra oversold bars are ignored
+        let single = run(3, false);
+        assert_eq!(single.trades.len(), 1);
+        assert_eq!(single.trades[0].shares, 25.0);
+
+        // Pyramiding stops adding lots at the cap; every lot closes on the exit
+        let pyramided = run(3, true);
+        assert_eq!(pyramided.trades.len(), 3);
+        let entry_days: Vec<i64> = pyramided
+            .trades
+            .iter()
+            .map(|t| (t.entry_date - start).num_days())
+            .collect();
+        assert_eq!(entry_days, vec![2, 3, 4]);
+        assert!(pyramided
+            .trades
+            .iter()
+            .all(|t| t.exit_date == Some(start + chrono::Duration::days(15))));
+        let total_shares: f64 = pyramided.trades.iter().map(|t| t.shares).sum();
+        assert!(total_shares > single.trades[0].shares);
+    }
+
+    #[test]
+    fn test_trades_per_year_scales_with_span() {
+        let start = NaiveDate::from_ymd_opt(2022, 1, 3).unwrap();
+        let trades: Vec<BacktestTrade> = (0..6)
+            .map(|i| BacktestTrade {
+                id: 0,
+                backtest_id: 0,
+                symbol: "TEST".to_string(),
+                direction: TradeDirection::Long,
+                entry_date: start + chrono::Duration::days(i * 30),
+                entry_price: 100.0,
+                exit_date: Some(start + chrono::Duration::days(i * 30 + 10)),
+                exit_price: Some(105.0),
+                shares: 10.0,
+                entry_reason: "test".to_string(),
+                exit_reason: Some("test".to_string()),
+                profit_loss: Some(50.0),
+                profit_loss_percent: Some(5.0),
+            })
+            .collect();
+        let equity = vec![10000.0, 10300.0];
+
+        let engine = BacktestEngine::default();
+        let two_years = engine.calculate_metrics(&trades, &equity, 730);
+        let one_year = engine.calculate_metrics(&trades, &equity, 365);
+
+        assert_eq!(two_years.total_trades, one_year.total_trades);
+        assert!((one_year.trades_per_year - 2.0 * two_years.trades_per_year).abs() < 1e-9);
+        assert!((one_year.trades_per_year - 6.0 * 365.25 / 365.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_commission_models_and_slippage() {
+        // Enter at 100 on day 0, exit at 110 on day 3
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0, 104.0, 107.0, 110.0]);
+        let rsi = [20.0, 50.0, 50.0, 80.0];
+        let indicators = rsi_indicators(&prices, |i| rsi[i]);
+
+        let strategy = Strategy {
+            name: "RSI swing".to_string(),
+            ..rsi_strategy()
+        };
+
+        let net_pnl = |commission: CommissionModel, slippage_bps: f64| {
+            let result = BacktestEngine::new(BacktestConfig {
+                commission,
+                slippage_bps,
+                ..BacktestConfig::default()
+            })
+            .run(&strategy, "TEST", &prices, &indicators);
+            assert_eq!(result.trades.len(), 1);
+            let pnl = result.trades[0].profit_loss.unwrap();
+            // Cash accounting agrees with the trade's net P&L
+            assert!((result.final_capital - 10000.0 - pnl).abs() < 1e-6);
+            pnl
+        };
+
+        let free = net_pnl(CommissionModel::default(), 0.0);
+        assert!((free - 1000.0).abs() < 1e-9);
+
+        // $5 per fill: 99.95 shares, two fees
+        let flat = net_pnl(CommissionModel::Flat(5.0), 0.0);
+        assert!((flat - (99.95 * 10.0 - 10.0)).abs() < 1e-6);
+
+        // 1 cent per share on each side
+        let shares = 10000.0 / 100.01;
+        let per_share = net_pnl(CommissionModel::PerShare(0.01), 0.0);
+        assert!((per_share - shares * (10.0 - 0.02)).abs() < 1e-6);
+
+        // 0.1% of notional on each side
+        let shares = 10000.0 / 100.1;
+        let percent = net_pnl(CommissionModel::Percent(0.1), 0.0);
+        assert!((percent - shares * (10.0 - 0.1 - 0.11)).abs() < 1e-6);
+
+        // 50 bps: buy at 100.5, sell at 109.45
+        let shares = 10000.0 / 100.5;
+        let slipped = net_pnl(CommissionModel::default(), 50.0);
+        assert!((slipped - shares * (109.45 - 100.5)).abs() < 1e-6);
+
+        assert!(free > per_share && per_share > flat && flat > percent && percent > slipped);
+    }
+
+    #[test]
+    fn test_information_ratio_zero_when_tracking_benchmark() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let closes = [100.0, 102.0, 99.0, 103.0, 105.0, 101.0, 108.0, 110.0];
+        let prices = series("TEST", start, &closes);
+        let benchmark = series("SPY", start, &closes);
+
+        // Buy on the first bar and hold: equity moves exactly with the benchmark
+        let indicators = rsi_indicators(&prices, |i| if i == 0 { 20.0 } else { 50.0 });
+
+        let strategy = Strategy {
+            name: "Buy and hold".to_string(),
+            ..rsi_strategy()
+        };
+
+        let engine = BacktestEngine::default();
+        let tracking =
+            engine.run_against_benchmark(&strategy, "TEST", &prices, &indicators, &benchmark);
+        assert!(tracking.metrics.information_ratio.abs() < 1e-6);
+
+        // Sitting in cash while the benchmark rallies lags it
+        let idle = engine.run_against_benchmark(&strategy, "TEST", &prices, &[], &benchmark);
+        assert!(idle.metrics.information_ratio < 0.0);
+
+        // No benchmark, no ratio
+        let plain = engine.run(&strategy, "TEST", &prices, &indicators);
+        assert_eq!(plain.metrics.information_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_short_strategy_profits_from_decline() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0, 95.0, 90.0, 85.0, 80.0]);
+        let rsi = [80.0, 60.0, 50.0, 40.0, 20.0];
+        let indicators = rsi_indicators(&prices, |i| rsi[i]);
+
+        // Short when overbought, cover when oversold
+        let mut strategy = Strategy {
+            name: "RSI fade".to_string(),
+            entry_condition: StrategyConditionType::RsiOverbought,
+            entry_threshold: 70.0,
+            exit_condition: StrategyConditionType::RsiOversold,
+            exit_threshold: 30.0,
+            direction: TradeDirection::Short,
+            ..rsi_strategy()
+        };
+
+        let result = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+        assert_eq!(result.trades.len(), 1);
+        let trade = &result.trades[0];
+        assert_eq!(trade.direction, TradeDirection::Short);
+        assert_eq!(trade.exit_reason.as_deref(), Some("rsi_oversold"));
+        assert_eq!(trade.profit_loss, Some(2000.0));
+        assert_eq!(trade.profit_loss_percent, Some(20.0));
+        assert_eq!(result.final_capital, 12000.0);
+        assert!((result.metrics.total_return - 20.0).abs() < 1e-9);
+        assert_eq!(result.metrics.max_drawdown, 0.0);
+
+        // The same rules run long lose money on the decline
+        strategy.direction = TradeDirection::Long;
+        let long = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+        assert_eq!(long.trades[0].profit_loss, Some(-2000.0));
+    }
+
+    #[test]
+    fn test_short_stop_loss_triggers_on_rally() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0, 103.0, 111.0, 120.0]);
+        let indicators = rsi_indicators(&prices, |i| if i == 0 { 80.0 } else { 50.0 });
+
+        let strategy = Strategy {
+            name: "RSI fade".to_string(),
+            entry_condition: StrategyConditionType::RsiOverbought,
+            entry_threshold: 70.0,
+            exit_condition: StrategyConditionType::RsiOversold,
+            exit_threshold: 30.0,
+            stop_loss_percent: Some(10.0),
+            direction: TradeDirection::Short,
+            ..rsi_strategy()
+        };
+
+        let result = BacktestEngine::default().run(&strategy, "TEST", &prices, &indicators);
+        let trade = &result.trades[0];
+        assert_eq!(trade.exit_reason.as_deref(), Some("stop_loss"));
+        assert_eq!(trade.exit_price, Some(111.0));
+        assert_eq!(trade.profit_loss, Some(-1100.0));
+        assert_eq!(result.final_capital, 8900.0);
+    }
+
+    #[test]
+    fn test_replay_reports_each_bar_decision() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0, 104.0, 107.0, 110.0, 108.0]);
+        // No indicators on the last bar
+        let rsi = [20.0, 50.0, 50.0, 80.0];
+        let indicators = rsi_indicators(&prices[..4], |i| rsi[i]);
+
+        let strategy = Strategy {
+            name: "RSI swing".to_string(),
+            ..rsi_strategy()
+        };
+
+        let engine = BacktestEngine::default();
+        let decisions: Vec<BarDecision> =
+            engine.replay(&strategy, "TEST", &prices, &indicators).collect();
+        assert_eq!(decisions.len(), prices.len());
+
+        let entry = &decisions[0];
+        assert_eq!(entry.entry_signal, Some(true));
+        assert_eq!(entry.entered.as_deref(), Some("rsi_oversold"));
+        assert_eq!(entry.indicators["RSI_14"], 20.0);
+        assert_eq!(entry.exit_signal, None);
+
+        let holding = &decisions[1];
+        assert_eq!(holding.exit_signal, Some(false));
+        assert_eq!(holding.entry_signal, None);
+        assert!(holding.entered.is_none() && holding.exited.is_none());
+        assert_eq!(holding.equity, 10400.0);
+
+        assert_eq!(decisions[3].exited.as_deref(), Some("rsi_overbought"));
+        assert_eq!(decisions[3].entry_signal, Some(false));
+
+        let no_data = &decisions[4];
+        assert!(no_data.indicators.is_empty());
+        assert_eq!((no_data.entry_signal, no_data.exit_signal), (None, None));
+
+        // Replay makes the same trades as run
+        let result = engine.run(&strategy, "TEST", &prices, &indicators);
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.trades[0].exit_date, Some(decisions[3].date));
+    }
+
+    #[test]
+    fn test_sizing_base_with_open_position() {
+        // $5,000 cash plus a $5,000 open position, sizing 40% at $10/share
+        let cash = 5000.0;
+        let equity = 10000.0;
+
+        let by_cash = BacktestEngine::new(BacktestConfig {
+            sizing_base: SizingBase::CurrentCash,
+            ..BacktestConfig::default()
+        });
+        let by_equity = BacktestEngine::new(BacktestConfig {
+            sizing_base: SizingBase::TotalEquity,
+            ..BacktestConfig::default()
+        });
+
+        assert_eq!(by_cash.entry_shares(cash, equity, 40.0, 10.0), 200.0);
+        assert_eq!(by_equity.entry_shares(cash, equity, 40.0, 10.0), 400.0);
+
+        // Equity-based sizing never spends more than the available cash
+        assert_eq!(by_equity.entry_shares(cash, equity, 80.0, 10.0), 500.0);
+    }
+
+    #[test]
+    fn test_sizing_base_sizes_pyramided_entry() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &[100.0; 10]);
+
+        // Oversold on bars 2 and 3, overbought on bar 8
+        let indicators = rsi_indicators(&prices, |i| match i {
+            2 | 3 => 20.0,
+            8 => 80.0,
+            _ => 50.0,
+        });
+
+        let strategy = Strategy {
+            position_size_percent: 40.0,
+            ..rsi_strategy()
+        };
+        let lots = |sizing_base| -> Vec<f64> {
+            BacktestEngine::new(BacktestConfig {
+                sizing_base,
+                max_positions: 2,
+                allow_pyramiding: true,
+                ..BacktestConfig::default()
+            })
+            .run(&strategy, "TEST", &prices, &indicators)
+            .trades
+            .iter()
+            .map(|t| t.shares)
+            .collect()
+        };
+
+        // The first lot leaves $6,000 cash of $10,000 equity, so the second
+        // lot is 40% of whichever base is configured
+        assert_eq!(lots(SizingBase::CurrentCash), vec![40.0, 24.0]);
+        assert_eq!(lots(SizingBase::TotalEquity), vec![40.0, 40.0]);
+    }
+
+    #[test]
+    fn test_portfolio_and_rebalance_walk_resampled_bars() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let closes: Vec<f64> = (0..90).map(|i| 100.0 + (i % 7) as f64).collect();
+        let prices = series("SPY", start, &closes);
+        let weekly_dates: Vec<NaiveDate> = resample(&prices, ResamplePeriod::Weekly)
+            .iter()
+            .map(|bar| bar.date)
+            .collect();
+        let engine = BacktestEngine::new(BacktestConfig {
+            timeframe: ResamplePeriod::Weekly,
+            ..BacktestConfig::default()
+        });
+        let curve_dates = |result: &BacktestResult| -> Vec<NaiveDate> {
+            result.equity_curve.iter().map(|(date, _)| *date).collect()
+        };
+
+        let basket = vec![("SPY".to_string(), prices.clone(), Vec::new())];
+        let portfolio = engine.run_portfolio(&two_rule_strategy(RuleCombinator::All), &basket);
+        assert_eq!(curve_dates(&portfolio), weekly_dates);
+
+        let weights = HashMap::from([("SPY".to_string(), 1.0)]);
+        let by_symbol = HashMap::from([("SPY".to_string(), prices)]);
+        let rebalanced =
+            engine.simulate_rebalance(&weights, &by_symbol, Rebalance::Monthly, 10000.0);
+        assert_eq!(curve_dates(&rebalanced), weekly_dates);
+    }
+
+    #[test]
+    fn test_monthly_rebalance_resets_weights() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        // 91 days: Jan, Feb, Mar. SPY rises steadily, AGG is flat.
+        let spy: Vec<f64> = (0..91).map(|i| 100.0 + i as f64).collect();
+        let agg: Vec<f64> = vec![50.0; 91];
+
+        let mut prices = HashMap::new();
+        prices.insert("SPY".to_string(), series("SPY", start, &spy));
+        prices.insert("AGG".to_string(), series("AGG", start, &agg));
+
+        let mut weights = HashMap::new();
+        weights.insert("SPY".to_string(), 0.6);
+        weights.insert("AGG".to_string(), 0.4);
+
+        let engine = BacktestEngine::default();
+        let result = engine.simulate_rebalance(&weights, &prices, Rebalance::Monthly, 10000.0);
+
+        let feb_1 = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+
+        // Before the boundary the portfolio drifted above 60% SPY
+        let closed: Vec<_> = result
+            .trades
+            .iter()
+            .filter(|t| t.exit_date == Some(feb_1))
+            .collect();
+        let drift_spy = closed.iter().find(|t| t.symbol == "SPY").unwrap();
+        let drift_total: f64 = closed.iter().map(|t| t.shares * t.exit_price.unwrap()).sum();
+        assert!(drift_spy.shares * drift_spy.exit_price.unwrap() / drift_total > 0.6);
+
+        // At the boundary the new lots are back at target weights
+        let opened: Vec<_> = result
+            .trades
+            .iter()
+            .filter(|t| t.entry_date == feb_1)
+            .collect();
+        assert_eq!(opened.len(), 2);
+        let total: f64 = opened.iter().map(|t| t.shares * t.entry_price).sum();
+        for t in opened {
+            let weight = t.shares * t.entry_price / total;
+            let target = weights[&t.symbol];
+            assert!((weight - target).abs() < 1e-9, "{} weight {}", t.symbol, weight);
+        }
+
+        assert!(result.final_capital > 10000.0);
+        assert_eq!(result.symbol, "AGG,SPY");
+    }
+
+    #[test]
+    fn test_rebalance_pays_commission_and_slippage() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut prices = HashMap::new();
+        prices.insert("SPY".to_string(), series("SPY", start, &[100.0; 91]));
+        prices.insert("AGG".to_string(), series("AGG", start, &[50.0; 91]));
+
+        let mut weights = HashMap::new();
+        weights.insert("SPY".to_string(), 0.5);
+        weights.insert("AGG".to_string(), 0.5);
+
+        let engine = BacktestEngine::new(BacktestConfig {
+            commission: CommissionModel::Flat(5.0),
+            slippage_bps: 10.0,
+            ..BacktestConfig::default()
+        });
+        let result = engine.simulate_rebalance(&weights, &prices, Rebalance::Monthly, 10000.0);
+
+        // Three allocations (Jan, Feb, Mar) of two lots each, all losing to costs
+        assert_eq!(result.trades.len(), 6);
+        for t in &result.trades {
+            let close = if t.symbol == "SPY" { 100.0 } else { 50.0 };
+            assert!((t.entry_price - close * 1.001).abs() < 1e-9);
+            assert!((t.exit_price.unwrap() - close * 0.999).abs() < 1e-9);
+            assert!(t.profit_loss.unwrap() < -10.0);
+        }
+
+        // Costs leave the portfolio rather than being reinvested
+        let pnl: f64 = result.trades.iter().map(|t| t.profit_loss.unwrap()).sum();
+        assert!(result.final_capital < 10000.0);
+        assert!((result.final_capital - 10000.0 - pnl).abs() < 1e-6);
+    }
+
+    /// `cycles` 8-bar cycles, each with a real dip (RSI 25, price rallies
+    /// 10%) and a shallower false one (RSI 35, price drops 9%); RSI 75
+    /// marks the top after both
+    fn dip_cycles(cycles: usize) -> (Vec<DailyPrice>, Vec<TechnicalIndicator>) {
+        let cycle_closes = [100.0, 105.0, 110.0, 110.0, 110.0, 100.0, 100.0, 100.0];
+        let cycle_rsi = [25.0, 50.0, 75.0, 50.0, 35.0, 50.0, 75.0, 50.0];
+        let closes: Vec<f64> = cycle_closes
+            .iter()
+            .copied()
+            .cycle()
+            .take(8 * cycles)
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices = series("TEST", start, &closes);
+        let indicators = rsi_indicators(&prices, |i| cycle_rsi[i % cycle_rsi.len()]);
+        (prices, indicators)
+    }
+
+    #[test]
+    fn test_walk_forward_picks_threshold_that_skips_false_dips() {
+        let (prices, indicators) = dip_cycles(6);
+
+        let base = rsi_strategy();
+        let grid = ParamGrid {
+            param: StrategyParam::EntryThreshold,
+            values: vec![40.0, 20.0, 30.0],
+            objective: OptimizeMetric::TotalReturn,
+        };
+
+        let engine = BacktestEngine::default();
+        let wf = engine
+            .walk_forward(&base, &grid, &prices, &indicators, 0.5)
+            .unwrap();
+
+        // 40 also takes the false dips and 20 never trades
+        assert_eq!(wf.best_value, 30.0);
+        assert_eq!(wf.strategy.entry_threshold, 30.0);
+        assert_eq!(wf.sweep.len(), 3);
+        assert_eq!(wf.sweep[1].1.total_trades, 0);
+        assert!(wf.sweep[0].1.total_return < wf.in_sample.total_return);
+        assert_eq!(wf.split_date, prices[24].date);
+
+        // Three real dips in each half, compounding 10% apiece
+        let expected = (1.1f64.powi(3) - 1.0) * 100.0;
+        assert!((wf.in_sample.total_return - expected).abs() < 1e-6);
+        assert!((wf.out_of_sample.total_return - expected).abs() < 1e-6);
+
+        let bad_split = engine.walk_forward(&base, &grid, &prices, &indicators, 1.0);
+        assert!(matches!(bad_split, Err(PipelineError::Config(_))));
+    }
+
+    #[test]
+    fn test_compare_strategies_ranks_higher_return_first() {
+        let (prices, indicators) = dip_cycles(6);
+        let every_dip = Strategy {
+            name: "Every dip".to_string(),
+            entry_threshold: 40.0,
+            ..rsi_strategy()
+        };
+        let real_dips = Strategy {
+            name: "Real dips".to_string(),
+            entry_threshold: 30.0,
+            ..every_dip.clone()
+        };
+
+        let engine = BacktestEngine::default();
+        let ranked =
+            engine.compare_strategies(&[every_dip, real_dips], "TEST", &prices, &indicators, &[]);
+
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].strategy_name, "Real dips");
+        assert_eq!(ranked[1].strategy_name, "Every dip");
+        assert!(ranked[0].metrics.total_return > ranked[1].metrics.total_return);
+        assert!(ranked.iter().all(|r| r.symbol == "TEST" && r.id == 0));
+    }
+
+    #[test]
+    fn test_monte_carlo_percentiles_ordered_around_observed_return() {
+        // Entering on every dip alternates +10% and -9.09% trades, which
+        // net out to roughly flat
+        let (prices, indicators) = dip_cycles(6);
+        let strategy = Strategy {
+            entry_threshold: 40.0,
+            ..rsi_strategy()
+        };
+        let engine = BacktestEngine::default();
+        let result = engine.run(&strategy, "TEST", &prices, &indicators);
+        assert_eq!(result.trades.len(), 12);
+
+        let stats = engine.monte_carlo(&result, 2000);
+        assert_eq!(stats.iterations, 2000);
+        assert_eq!(stats.trades, 12);
+        assert!(stats.return_p5 < stats.return_p50 && stats.return_p50 < stats.return_p95);
+        assert!(
+            stats.drawdown_p5 <= stats.drawdown_p50 && stats.drawdown_p50 <= stats.drawdown_p95
+        );
+        assert!(stats.drawdown_p5 >= 0.0);
+        assert!((stats.return_p50 - stats.observed_return).abs() < 1.0);
+
+        // Same seed, same answer; nothing to resample without trades
+        assert_eq!(engine.monte_carlo(&result, 2000), stats);
+        let flat = BacktestResult {
+            trades: Vec::new(),
+            ..result
+        };
+        assert_eq!(engine.monte_carlo(&flat, 100).return_p95, 0.0);
+    }
+}
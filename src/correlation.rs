@@ -0,0 +1,104 @@
+//! Return correlation between symbols
+//!
+//! Pearson correlation of daily close-to-close returns, measured only across
+//! dates both symbols traded.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::models::DailyPrice;
+
+/// Fewest overlapping daily returns a correlation is reported for
+pub const MIN_OVERLAPPING_RETURNS: usize = 5;
+
+/// Pearson correlation of two series' daily returns on their common dates
+///
+/// Returns are taken between consecutive dates present in both series, so a
+/// day missing from either one is skipped rather than misaligning the rest.
+/// Returns NaN when there are fewer than `MIN_OVERLAPPING_RETURNS` returns or
+/// either series doesn't move.
+pub fn return_correlation(a: &[DailyPrice], b: &[DailyPrice]) -> f64 {
+    let b_closes: HashMap<NaiveDate, f64> = b.iter().map(|p| (p.date, p.close)).collect();
+
+    let mut common: Vec<(NaiveDate, f64, f64)> = a
+        .iter()
+        .filter_map(|p| {
+            b_closes
+                .get(&p.date)
+                .map(|&b_close| (p.date, p.close, b_close))
+        })
+        .collect();
+    common.sort_by_key(|(date, _, _)| *date);
+    common.dedup_by_key(|(date, _, _)| *date);
+
+    let returns: Vec<(f64, f64)> = common
+        .windows(2)
+        .filter(|w| w[0].1 > 0.0 && w[0].2 > 0.0)
+        .map(|w| ((w[1].1 - w[0].1) / w[0].1, (w[1].2 - w[0].2) / w[0].2))
+        .collect();
+
+    if returns.len() < MIN_OVERLAPPING_RETURNS {
+        return f64::NAN;
+    }
+
+    let n = returns.len() as f64;
+    let mean_a = returns.iter().map(|r| r.0).sum::<f64>() / n;
+    let mean_b = returns.iter().map(|r| r.1).sum::<f64>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+    for (ra, rb) in &returns {
+        covariance += (ra - mean_a) * (rb - mean_b);
+        variance_a += (ra - mean_a).powi(2);
+        variance_b += (rb - mean_b).powi(2);
+    }
+
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return f64::NAN;
+    }
+
+    covariance / (variance_a * variance_b).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(symbol: &str, closes: &[f64]) -> Vec<DailyPrice> {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| DailyPrice {
+                symbol: symbol.to_string(),
+                date: start + chrono::Duration::days(i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1000,
+                adjusted_close: None,
+                source: "test".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scaled_series_correlate_perfectly() {
+        let closes: Vec<f64> = (0..30)
+            .map(|i| 100.0 + (i as f64 * 0.7).sin() * 5.0)
+            .collect();
+        let doubled: Vec<f64> = closes.iter().map(|c| c * 2.0).collect();
+        let mirrored: Vec<f64> = closes.iter().map(|c| 200.0 - c).collect();
+
+        let a = series("AAA", &closes);
+        let b = series("BBB", &doubled);
+        assert!((return_correlation(&a, &b) - 1.0).abs() < 1e-9);
+        assert!(return_correlation(&a, &series("CCC", &mirrored)) < -0.9);
+
+        // Too little overlap
+        assert!(return_correlation(&a[..4], &b).is_nan());
+    }
+}
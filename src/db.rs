@@ -1,18 +1,306 @@
 //! SQLite database layer for Financial Pipeline
 
-use chrono::{NaiveDate, Utc};
-use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result as SqliteResult};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{Read, Write};
 use std::path::Path;
 
-use crate::error::Result;
+use crate::correlation::return_correlation;
+use crate::error::{PipelineError, Result};
+use crate::indicators::{calculate_incremental, calculate_rolling_beta, INCREMENTAL_WARMUP_BARS};
 use crate::models::{
-    AlertCondition, BacktestResult, BacktestTrade, DailyPrice, IndicatorAlert,
-    IndicatorAlertCondition, IndicatorAlertType, MacroData, PerformanceMetrics, Position,
-    PositionType, PriceAlert, Signal, SignalDirection, SignalType, Strategy,
+    percent_target, AlertCondition, BacktestResult, BacktestTrade, DailyPrice, Dividend,
+    IndicatorAlert, IndicatorAlertCondition, IndicatorAlertType, IntradayPrice, MacroData,
+    MacroFrequency, MacroFreshness, OptionLeg, OptionStrategy, PerformanceMetrics, Position,
+    PositionType, PriceAlert, Signal, SignalDirection, SignalType, Split, Strategy,
     StrategyConditionType, Symbol, TechnicalIndicator, TradeDirection,
 };
+use crate::notify::{deliver_in_background, sink_for, AlertNotification, NotificationSink};
+use crate::portfolio::{match_lots_fifo, FifoPosition, PortfolioExposure};
+use crate::signals::SignalConfig;
+use crate::trading_calendar::is_trading_day;
 use crate::trends::TrendData;
 
+/// Settings key for the benchmark used by beta/alpha/relative-strength features
+pub const BENCHMARK_SYMBOL_KEY: &str = "benchmark_symbol";
+
+/// Benchmark used when `benchmark_symbol` has not been set
+pub const DEFAULT_BENCHMARK_SYMBOL: &str = "SPY";
+
+/// Settings key for the comma-separated price sources to prefer, highest first
+pub const PRICE_SOURCE_PRIORITY_KEY: &str = "price_source_priority";
+
+/// Daily returns behind the betas in `Database::portfolio_exposure`
+pub const EXPOSURE_BETA_WINDOW: usize = 60;
+
+/// Storage format for `intraday_prices.timestamp`
+const INTRADAY_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Rows written per transaction by `Database::import_prices_reader`
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// Columns a price CSV must have, as written by `export_prices_csv`
+const CSV_PRICE_COLUMNS: [&str; 6] = ["date", "open", "high", "low", "close", "volume"];
+
+/// One `date,open,high,low,close,volume` row, as written by `export_prices_csv`
+#[derive(Debug, serde::Deserialize)]
+struct CsvPriceRow {
+    date: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl CsvPriceRow {
+    /// Validate the row into a bar; `None` if the date doesn't parse, a value
+    /// isn't a finite non-negative number, or high/low don't bound open/close
+    fn into_price(self, symbol: &str, source: &str) -> Option<DailyPrice> {
+        let date = NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()?;
+        let values = [self.open, self.high, self.low, self.close, self.volume];
+        if values.iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return None;
+        }
+        if self.high < self.open.max(self.close) || self.low > self.open.min(self.close) {
+            return None;
+        }
+
+        Some(DailyPrice {
+            symbol: symbol.to_string(),
+            date,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume.round() as i64,
+            adjusted_close: None,
+            source: source.to_string(),
+        })
+    }
+}
+
+/// Row counts from `Database::import_prices_csv`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Malformed or invalid rows left out
+    pub skipped: usize,
+}
+
+/// Row counts from `Database::merge_from`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub symbols: usize,
+    pub prices: usize,
+    pub indicators: usize,
+    pub strategies: usize,
+    pub positions: usize,
+    /// Rows skipped because the target already had them, plus merged
+    /// indicators dropped because their symbol's prices changed
+    pub conflicts: usize,
+}
+
+/// Position of `source` in a priority list; unlisted sources rank last
+fn source_rank(priority: &[String], source: &str) -> usize {
+    priority
+        .iter()
+        .position(|p| p.eq_ignore_ascii_case(source))
+        .unwrap_or(priority.len())
+}
+
+/// Those of `wanted` that `table` in the attached `merge_src` database has
+///
+/// Empty when it has no such table. Lets a merge read a database written
+/// before later migrations added columns; the rest take their defaults.
+fn merge_source_columns<'a>(
+    conn: &Connection,
+    table: &str,
+    wanted: &[&'a str],
+) -> Result<Vec<&'a str>> {
+    let present: Vec<String> = conn
+        .prepare(&format!("PRAGMA merge_src.table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<SqliteResult<Vec<_>>>()?;
+    Ok(wanted
+        .iter()
+        .copied()
+        .filter(|column| present.iter().any(|p| p == column))
+        .collect())
+}
+
+/// Copy `columns` of `table` from the attached `merge_src` database, keeping
+/// rows already here and skipping rows for `skip_symbols`
+///
+/// Returns the rows copied and the rows the source has.
+fn merge_table(
+    conn: &Connection,
+    table: &str,
+    columns: &[&str],
+    skip_symbols: &[String],
+) -> Result<(usize, usize)> {
+    let columns = merge_source_columns(conn, table, columns)?;
+    if columns.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM merge_src.{}", table),
+        [],
+        |row| row.get(0),
+    )?;
+    let mut sql = format!(
+        "INSERT OR IGNORE INTO main.{table} ({columns}) SELECT {columns} FROM merge_src.{table}",
+        columns = columns.join(", ")
+    );
+    if !skip_symbols.is_empty() {
+        sql.push_str(&format!(
+            " WHERE symbol NOT IN ({})",
+            vec!["?"; skip_symbols.len()].join(", ")
+        ));
+    }
+    let copied = conn.execute(&sql, params_from_iter(skip_symbols))?;
+
+    Ok((copied, total as usize))
+}
+
+/// Bars stored for a symbol from one data source, from `Database::list_sources`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceSource {
+    pub source: String,
+    pub bars: usize,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+}
+
+/// Decimal places used by the CSV exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportPrecision {
+    pub price_decimals: usize,
+    pub indicator_decimals: usize,
+}
+
+impl Default for ExportPrecision {
+    fn default() -> Self {
+        Self {
+            price_decimals: 2,
+            indicator_decimals: 4,
+        }
+    }
+}
+
+/// Output format for `Database::export_backtest`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BacktestExportFormat {
+    /// The whole `BacktestResult`, trades and equity curve included
+    Json,
+    /// `metric,value` rows, a blank line, then one row per trade
+    Csv,
+}
+
+impl std::str::FromStr for BacktestExportFormat {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(PipelineError::Config(format!(
+                "Unknown export format: {} (expected json or csv)",
+                s
+            ))),
+        }
+    }
+}
+
+impl BacktestExportFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// CSV body of `Database::export_backtest`
+fn write_backtest_csv<W: Write>(result: &BacktestResult, mut writer: W) -> Result<()> {
+    let m = &result.metrics;
+    let summary = [
+        ("strategy", result.strategy_name.clone()),
+        ("symbol", result.symbol.clone()),
+        ("start_date", result.start_date.to_string()),
+        ("end_date", result.end_date.to_string()),
+        ("initial_capital", result.initial_capital.to_string()),
+        ("final_capital", result.final_capital.to_string()),
+        ("total_return", m.total_return.to_string()),
+        ("total_return_dollars", m.total_return_dollars.to_string()),
+        ("max_drawdown", m.max_drawdown.to_string()),
+        ("sharpe_ratio", m.sharpe_ratio.to_string()),
+        ("sortino_ratio", m.sortino_ratio.to_string()),
+        ("calmar_ratio", m.calmar_ratio.to_string()),
+        ("information_ratio", m.information_ratio.to_string()),
+        ("win_rate", m.win_rate.to_string()),
+        ("total_trades", m.total_trades.to_string()),
+        ("winning_trades", m.winning_trades.to_string()),
+        ("losing_trades", m.losing_trades.to_string()),
+        ("avg_win_percent", m.avg_win_percent.to_string()),
+        ("avg_loss_percent", m.avg_loss_percent.to_string()),
+        ("profit_factor", m.profit_factor.to_string()),
+        (
+            "avg_trade_duration_days",
+            m.avg_trade_duration_days.to_string(),
+        ),
+        ("trades_per_year", m.trades_per_year.to_string()),
+        (
+            "time_in_market_percent",
+            m.time_in_market_percent.to_string(),
+        ),
+    ];
+
+    let mut csv = csv::Writer::from_writer(&mut writer);
+    csv.write_record(["metric", "value"])?;
+    for (metric, value) in &summary {
+        csv.write_record([*metric, value.as_str()])?;
+    }
+    csv.flush()?;
+    drop(csv);
+    writeln!(writer)?;
+
+    let mut csv = csv::Writer::from_writer(&mut writer);
+    csv.write_record([
+        "direction",
+        "entry_date",
+        "entry_price",
+        "exit_date",
+        "exit_price",
+        "shares",
+        "entry_reason",
+        "exit_reason",
+        "profit_loss",
+        "profit_loss_percent",
+    ])?;
+    let opt = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    for t in &result.trades {
+        csv.write_record([
+            t.direction.as_str().to_string(),
+            t.entry_date.to_string(),
+            t.entry_price.to_string(),
+            t.exit_date.map(|d| d.to_string()).unwrap_or_default(),
+            opt(t.exit_price),
+            t.shares.to_string(),
+            t.entry_reason.clone(),
+            t.exit_reason.clone().unwrap_or_default(),
+            opt(t.profit_loss),
+            opt(t.profit_loss_percent),
+        ])?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
 /// Database wrapper for financial data storage
 pub struct Database {
     conn: Connection,
@@ -37,41 +325,152 @@ impl Database {
     }
 
     /// Initialize database schema
+    ///
+    /// Existing tables are brought up to date with `migrate` first, then any
+    /// missing tables and indexes are created at the current schema.
     pub fn init_schema(&self) -> Result<()> {
+        self.migrate()?;
         self.conn.execute_batch(SCHEMA_SQL)?;
-        // Run migrations for existing databases
-        self.run_migrations()?;
         println!("[OK] Database schema initialized");
         Ok(())
     }
 
-    /// Run database migrations for existing tables
-    fn run_migrations(&self) -> Result<()> {
-        // Add favorited column to symbols table if it doesn't exist
+    /// Version of the newest migration applied to this database (0 if none)
+    pub fn schema_version(&self) -> Result<u32> {
+        self.conn.execute_batch(SCHEMA_VERSION_SQL)?;
+        let version: Option<u32> =
+            self.conn
+                .query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+                    row.get(0)
+                })?;
+        Ok(version.unwrap_or(0))
+    }
+
+    /// Apply every migration newer than the stored schema version, in order
+    ///
+    /// Each migration runs in its own transaction together with its
+    /// `schema_version` row. Steps are idempotent and skip tables that don't
+    /// exist yet (a fresh database gets them from `SCHEMA_SQL` at the current
+    /// shape), so this is safe on new, old and partially migrated databases.
+    /// Returns the resulting schema version.
+    pub fn migrate(&self) -> Result<u32> {
+        let mut version = self.schema_version()?;
+        let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > version).collect();
+
+        for migration in pending {
+            let tx = self.conn.unchecked_transaction()?;
+            for &(table, column, definition) in migration.add_columns {
+                self.add_column_if_missing(table, column, definition)?;
+            }
+            for &table in migration.rebuild_tables {
+                self.rebuild_table(table)?;
+            }
+            self.conn.execute(
+                "INSERT OR REPLACE INTO schema_version (version, description) VALUES (?1, ?2)",
+                params![migration.version, migration.description],
+            )?;
+            tx.commit()?;
+
+            version = migration.version;
+            println!("[MIGRATION] Schema v{}: {}", version, migration.description);
+        }
+
+        Ok(version)
+    }
+
+    /// Recreate an existing table from its definition in `SCHEMA_SQL`
+    ///
+    /// Rows are copied over for the columns both versions share. Indexes go
+    /// with the old table; `SCHEMA_SQL` recreates them. Does nothing when the
+    /// table doesn't exist yet.
+    fn rebuild_table(&self, table: &str) -> Result<()> {
+        let columns: Vec<String> = self
+            .conn
+            .prepare(&format!("PRAGMA table_info({})", table))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        if columns.is_empty() {
+            return Ok(());
+        }
+
+        let create = format!("CREATE TABLE IF NOT EXISTS {} (", table);
+        let definition = SCHEMA_SQL
+            .find(&create)
+            .and_then(|start| {
+                let len = SCHEMA_SQL[start..].find("\n);")? + 3;
+                Some(&SCHEMA_SQL[start..start + len])
+            })
+            .ok_or_else(|| {
+                PipelineError::Config(format!("No {} table in the schema to rebuild from", table))
+            })?;
+
+        let rebuilt = format!("{}_rebuild", table);
+        self.conn.execute_batch(&definition.replacen(
+            &create,
+            &format!("CREATE TABLE {} (", rebuilt),
+            1,
+        ))?;
+
+        let new_columns: Vec<String> = self
+            .conn
+            .prepare(&format!("PRAGMA table_info({})", rebuilt))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let shared = columns
+            .into_iter()
+            .filter(|c| new_columns.contains(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.conn.execute_batch(&format!(
+            "INSERT INTO {rebuilt} ({shared}) SELECT {shared} FROM {table};
+             DROP TABLE {table};
+             ALTER TABLE {rebuilt} RENAME TO {table};"
+        ))?;
+        println!("[MIGRATION] Rebuilt {} table", table);
+
+        Ok(())
+    }
+
+    /// Add a column to an existing table unless it is already present
+    ///
+    /// Does nothing when the table itself doesn't exist yet.
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
         let columns: Vec<String> = self
             .conn
-            .prepare("PRAGMA table_info(symbols)")?
+            .prepare(&format!("PRAGMA table_info({})", table))?
             .query_map([], |row| row.get::<_, String>(1))?
             .collect::<SqliteResult<Vec<_>>>()?;
 
-        if !columns.contains(&"favorited".to_string()) {
+        if !columns.is_empty() && !columns.iter().any(|c| c == column) {
             self.conn.execute(
-                "ALTER TABLE symbols ADD COLUMN favorited INTEGER DEFAULT 0",
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
                 [],
             )?;
-            println!("[MIGRATION] Added favorited column to symbols table");
+            println!("[MIGRATION] Added {} column to {} table", column, table);
         }
 
         Ok(())
     }
 
-    /// Insert or update a symbol
+    /// Insert or update a symbol's metadata, keeping its favorite flag
     pub fn upsert_symbol(&self, symbol: &Symbol) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT OR REPLACE INTO symbols
+            INSERT INTO symbols
             (symbol, name, sector, industry, market_cap, country, exchange, currency, isin, asset_class)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            ON CONFLICT(symbol) DO UPDATE SET
+                name = excluded.name,
+                sector = excluded.sector,
+                industry = excluded.industry,
+                market_cap = excluded.market_cap,
+                country = excluded.country,
+                exchange = excluded.exchange,
+                currency = excluded.currency,
+                isin = excluded.isin,
+                asset_class = excluded.asset_class,
+                updated_at = CURRENT_TIMESTAMP
             "#,
             params![
                 symbol.symbol,
@@ -89,46 +488,140 @@ impl Database {
         Ok(())
     }
 
+    /// Stored metadata for a symbol, if any has been recorded
+    pub fn get_symbol(&self, symbol: &str) -> Result<Option<Symbol>> {
+        let symbol = self
+            .conn
+            .query_row(
+                r#"
+                SELECT symbol, name, sector, industry, market_cap, country, exchange,
+                       currency, isin, asset_class
+                FROM symbols WHERE symbol = ?1
+                "#,
+                params![symbol],
+                |row| {
+                    Ok(Symbol {
+                        symbol: row.get(0)?,
+                        name: row.get(1)?,
+                        sector: row.get(2)?,
+                        industry: row.get(3)?,
+                        market_cap: row.get(4)?,
+                        country: row.get(5)?,
+                        exchange: row.get(6)?,
+                        currency: row.get(7)?,
+                        isin: row.get(8)?,
+                        asset_class: row.get(9)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(symbol)
+    }
+
     /// Insert or update daily price data
+    ///
+    /// See `upsert_daily_prices` for how sources are kept apart.
     pub fn upsert_daily_price(&self, price: &DailyPrice) -> Result<()> {
-        self.conn.execute(
+        let priority = self.price_source_priority()?;
+        Self::store_price(&self.conn, price, &priority)?;
+        Ok(())
+    }
+
+    /// Batch insert daily prices (more efficient)
+    ///
+    /// Every bar is kept under its own source in `source_prices`, so
+    /// `get_prices_by_source` can replay each feed as it arrived. The single
+    /// series in `daily_prices` that everything else reads holds one bar per
+    /// date: a bar replaces the stored one unless that came from a source
+    /// ranked higher in `price_source_priority`. Listed sources outrank
+    /// unlisted ones, and between equals the latest write wins. Returns the
+    /// number of bars stored.
+    pub fn upsert_daily_prices(&mut self, prices: &[DailyPrice]) -> Result<usize> {
+        let priority = self.price_source_priority()?;
+        let tx = self.conn.transaction()?;
+
+        for price in prices {
+            Self::store_price(&tx, price, &priority)?;
+        }
+
+        tx.commit()?;
+        Ok(prices.len())
+    }
+
+    /// Write one bar to `source_prices`, and to `daily_prices` unless a
+    /// higher-priority source already holds that date
+    fn store_price(conn: &Connection, price: &DailyPrice, priority: &[String]) -> Result<()> {
+        let date = price.date.to_string();
+        conn.prepare_cached(
+            r#"
+            INSERT OR REPLACE INTO source_prices
+            (symbol, timestamp, source, open, high, low, close, volume, adjusted_close)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )?
+        .execute(params![
+            price.symbol,
+            date,
+            price.source,
+            price.open,
+            price.high,
+            price.low,
+            price.close,
+            price.volume,
+            price.adjusted_close,
+        ])?;
+
+        let existing: Option<Option<String>> = conn
+            .prepare_cached("SELECT source FROM daily_prices WHERE symbol = ?1 AND timestamp = ?2")?
+            .query_row(params![price.symbol, date], |row| row.get(0))
+            .optional()?;
+        if let Some(existing) = existing {
+            let existing_rank = source_rank(priority, existing.as_deref().unwrap_or(""));
+            if source_rank(priority, &price.source) > existing_rank {
+                return Ok(());
+            }
+        }
+
+        conn.prepare_cached(
             r#"
             INSERT OR REPLACE INTO daily_prices
-            (symbol, timestamp, open, high, low, close, volume, source)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            (symbol, timestamp, open, high, low, close, volume, adjusted_close, source)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
             "#,
-            params![
-                price.symbol,
-                price.date.to_string(),
-                price.open,
-                price.high,
-                price.low,
-                price.close,
-                price.volume,
-                price.source,
-            ],
-        )?;
+        )?
+        .execute(params![
+            price.symbol,
+            date,
+            price.open,
+            price.high,
+            price.low,
+            price.close,
+            price.volume,
+            price.adjusted_close,
+            price.source,
+        ])?;
         Ok(())
     }
 
-    /// Batch insert daily prices (more efficient)
-    pub fn upsert_daily_prices(&mut self, prices: &[DailyPrice]) -> Result<usize> {
+    /// Insert or update intraday bars
+    pub fn upsert_intraday_prices(&mut self, prices: &[IntradayPrice]) -> Result<usize> {
         let tx = self.conn.transaction()?;
         let mut count = 0;
 
         {
             let mut stmt = tx.prepare(
                 r#"
-                INSERT OR REPLACE INTO daily_prices
-                (symbol, timestamp, open, high, low, close, volume, source)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                INSERT OR REPLACE INTO intraday_prices
+                (symbol, interval, timestamp, open, high, low, close, volume, source)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
                 "#,
             )?;
 
             for price in prices {
                 stmt.execute(params![
                     price.symbol,
-                    price.date.to_string(),
+                    price.interval,
+                    price.timestamp.format(INTRADAY_TIMESTAMP_FORMAT).to_string(),
                     price.open,
                     price.high,
                     price.low,
@@ -144,6 +637,200 @@ impl Database {
         Ok(count)
     }
 
+    /// Get intraday bars for a symbol at one interval, oldest first
+    pub fn get_intraday_prices(&self, symbol: &str, interval: &str) -> Result<Vec<IntradayPrice>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, interval, timestamp, open, high, low, close, volume, source
+            FROM intraday_prices
+            WHERE symbol = ?1 AND interval = ?2
+            ORDER BY timestamp ASC
+            "#,
+        )?;
+
+        let prices = stmt
+            .query_map(params![symbol, interval], |row| {
+                let ts: String = row.get(2)?;
+                Ok(IntradayPrice {
+                    symbol: row.get(0)?,
+                    interval: row.get(1)?,
+                    timestamp: NaiveDateTime::parse_from_str(&ts, INTRADAY_TIMESTAMP_FORMAT)
+                        .unwrap_or_default(),
+                    open: row.get(3)?,
+                    high: row.get(4)?,
+                    low: row.get(5)?,
+                    close: row.get(6)?,
+                    volume: row.get(7)?,
+                    source: row.get(8)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(prices)
+    }
+
+    /// Insert or update dividends (one per symbol and ex-date)
+    pub fn upsert_dividends(&mut self, dividends: &[Dividend]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT OR REPLACE INTO dividends (symbol, ex_date, amount, source)
+                VALUES (?1, ?2, ?3, ?4)
+                "#,
+            )?;
+
+            for dividend in dividends {
+                stmt.execute(params![
+                    dividend.symbol,
+                    dividend.ex_date.to_string(),
+                    dividend.amount,
+                    dividend.source,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(dividends.len())
+    }
+
+    /// Get a symbol's dividends, oldest first
+    pub fn get_dividends(&self, symbol: &str) -> Result<Vec<Dividend>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, ex_date, amount, source
+            FROM dividends
+            WHERE symbol = ?1
+            ORDER BY ex_date ASC
+            "#,
+        )?;
+
+        let dividends = stmt
+            .query_map(params![symbol], |row| {
+                let date_str: String = row.get(1)?;
+                Ok(Dividend {
+                    symbol: row.get(0)?,
+                    ex_date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                    amount: row.get(2)?,
+                    source: row.get(3)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(dividends)
+    }
+
+    /// Dividends earned on `quantity` shares of `symbol` held since `since_date`
+    ///
+    /// Only dividends whose ex-date falls after `since_date` count, since
+    /// shares bought on or after an ex-date don't receive that payment.
+    pub fn get_dividend_income(
+        &self,
+        symbol: &str,
+        since_date: NaiveDate,
+        quantity: f64,
+    ) -> Result<f64> {
+        let per_share: f64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM dividends WHERE symbol = ?1 AND ex_date > ?2",
+            params![symbol, since_date.to_string()],
+            |row| row.get(0),
+        )?;
+
+        Ok(per_share * quantity)
+    }
+
+    /// Dividend income for a portfolio position since it was opened
+    ///
+    /// Shorts owe the dividends instead, so theirs is negative. Positions
+    /// whose date can't be parsed earn nothing.
+    pub fn get_position_dividend_income(&self, position: &Position) -> Result<f64> {
+        let income = self.dividends_since(&position.symbol, &position.date, position.quantity)?;
+
+        Ok(match position.position_type {
+            PositionType::Buy => income,
+            PositionType::Sell => -income,
+        })
+    }
+
+    /// Dividend income on the open lots of a FIFO holding, each since its
+    /// own trade date (negative for shorts)
+    pub fn get_fifo_dividend_income(&self, holding: &FifoPosition) -> Result<f64> {
+        let mut income = 0.0;
+        for lot in &holding.lots {
+            income += self.dividends_since(&holding.symbol, &lot.date, lot.quantity)?;
+        }
+
+        Ok(match holding.position_type {
+            PositionType::Buy => income,
+            PositionType::Sell => -income,
+        })
+    }
+
+    /// `get_dividend_income` from a stored `YYYY-MM-DD` date; 0 if it doesn't parse
+    fn dividends_since(&self, symbol: &str, date: &str, quantity: f64) -> Result<f64> {
+        match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(since) => self.get_dividend_income(symbol, since, quantity),
+            Err(_) => Ok(0.0),
+        }
+    }
+
+    /// Insert or update splits (one per symbol and date)
+    pub fn upsert_splits(&mut self, splits: &[Split]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT OR REPLACE INTO splits (symbol, date, numerator, denominator, source)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )?;
+
+            for split in splits {
+                stmt.execute(params![
+                    split.symbol,
+                    split.date.to_string(),
+                    split.numerator,
+                    split.denominator,
+                    split.source,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(splits.len())
+    }
+
+    /// Get a symbol's splits, oldest first
+    pub fn get_splits(&self, symbol: &str) -> Result<Vec<Split>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, date, numerator, denominator, source
+            FROM splits
+            WHERE symbol = ?1
+            ORDER BY date ASC
+            "#,
+        )?;
+
+        let splits = stmt
+            .query_map(params![symbol], |row| {
+                let date_str: String = row.get(1)?;
+                Ok(Split {
+                    symbol: row.get(0)?,
+                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                    numerator: row.get(2)?,
+                    denominator: row.get(3)?,
+                    source: row.get(4)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(splits)
+    }
+
     /// Insert macro data
     pub fn upsert_macro_data(&self, data: &MacroData) -> Result<()> {
         self.conn.execute(
@@ -253,6 +940,44 @@ impl Database {
         Ok(data)
     }
 
+    /// Report each macro indicator's latest date, inferred frequency and staleness
+    pub fn macro_freshness(&self) -> Result<Vec<MacroFreshness>> {
+        let today = Utc::now().date_naive();
+        let mut report = Vec::new();
+
+        for indicator in self.get_macro_indicators()? {
+            // Newest first
+            let data = self.get_macro_data(&indicator)?;
+            let Some(latest) = data.first() else {
+                continue;
+            };
+
+            // Median spacing of recent observations
+            let mut gaps: Vec<i64> = data
+                .windows(2)
+                .take(12)
+                .map(|w| (w[0].date - w[1].date).num_days())
+                .collect();
+            gaps.sort_unstable();
+            let frequency = gaps
+                .get(gaps.len() / 2)
+                .map_or(MacroFrequency::Unknown, |&d| MacroFrequency::from_spacing_days(d));
+
+            let age_days = (today - latest.date).num_days();
+            let overdue = frequency.max_age_days().is_some_and(|max| age_days > max);
+
+            report.push(MacroFreshness {
+                indicator,
+                latest_date: latest.date,
+                frequency,
+                age_days,
+                overdue,
+            });
+        }
+
+        Ok(report)
+    }
+
     /// Log an API call
     pub fn log_api_call(&self, source: &str, endpoint: &str, symbol: &str) -> Result<()> {
         self.conn.execute(
@@ -265,6 +990,30 @@ impl Database {
         Ok(())
     }
 
+    /// Daily call counts for one API source since `since` (inclusive)
+    ///
+    /// Returns `(YYYY-MM-DD, calls)` per day with at least one call, oldest
+    /// first. Days are UTC, matching the `api_rate_limits` view.
+    pub fn get_api_usage(&self, source: &str, since: NaiveDate) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT DATE(timestamp) AS day, COUNT(*)
+            FROM api_calls
+            WHERE source = ?1 AND DATE(timestamp) >= ?2
+            GROUP BY day
+            ORDER BY day ASC
+            "#,
+        )?;
+
+        let usage = stmt
+            .query_map(params![source, since.to_string()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(usage)
+    }
+
     /// Get latest price for a symbol
     pub fn get_latest_price(&self, symbol: &str) -> Result<Option<f64>> {
         let mut stmt = self.conn.prepare(
@@ -285,37 +1034,329 @@ impl Database {
         }
     }
 
-    /// Get all prices for a symbol
-    pub fn get_prices(&self, symbol: &str) -> Result<Vec<DailyPrice>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT symbol, timestamp, open, high, low, close, volume, source
-            FROM daily_prices
-            WHERE symbol = ?1
-            ORDER BY timestamp ASC
-            "#,
+    /// Get the date of the earliest stored bar for a symbol
+    pub fn get_first_price_date(&self, symbol: &str) -> Result<Option<NaiveDate>> {
+        let first: Option<String> = self.conn.query_row(
+            "SELECT MIN(timestamp) FROM daily_prices WHERE symbol = ?1",
+            params![symbol],
+            |row| row.get(0),
         )?;
 
-        let prices = stmt
-            .query_map(params![symbol], |row| {
-                let date_str: String = row.get(1)?;
-                Ok(DailyPrice {
-                    symbol: row.get(0)?,
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    open: row.get(2)?,
-                    high: row.get(3)?,
-                    low: row.get(4)?,
-                    close: row.get(5)?,
-                    volume: row.get(6)?,
-                    source: row.get(7)?,
-                })
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
-
+        first
+            .map(|d| {
+                NaiveDate::parse_from_str(&d, "%Y-%m-%d").map_err(|_| PipelineError::DateParse(d))
+            })
+            .transpose()
+    }
+
+    /// Get the date of the latest stored bar for a symbol
+    pub fn get_last_price_date(&self, symbol: &str) -> Result<Option<NaiveDate>> {
+        let last: Option<String> = self.conn.query_row(
+            "SELECT MAX(timestamp) FROM daily_prices WHERE symbol = ?1",
+            params![symbol],
+            |row| row.get(0),
+        )?;
+
+        last.map(|d| {
+            NaiveDate::parse_from_str(&d, "%Y-%m-%d").map_err(|_| PipelineError::DateParse(d))
+        })
+        .transpose()
+    }
+
+    /// Symbols whose latest daily bar is more than `max_age_days` trading days old
+    ///
+    /// Age counts the trading days after the last bar up to today, so a
+    /// Friday close is 0 days old over the weekend (or a holiday Monday) and
+    /// 1 day old on the next session. Sorted by symbol.
+    pub fn get_stale_symbols(&self, max_age_days: i64) -> Result<Vec<String>> {
+        let today = Utc::now().date_naive();
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, MAX(timestamp) FROM daily_prices GROUP BY symbol ORDER BY symbol",
+        )?;
+        let latest = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stale = Vec::new();
+        for (symbol, date) in latest {
+            let last = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|_| PipelineError::DateParse(date))?;
+            let age = last
+                .iter_days()
+                .skip(1)
+                .take_while(|d| *d <= today)
+                .filter(|d| is_trading_day(*d))
+                .count() as i64;
+            if age > max_age_days {
+                stale.push(symbol);
+            }
+        }
+
+        Ok(stale)
+    }
+
+    /// Get all prices for a symbol
+    pub fn get_prices(&self, symbol: &str) -> Result<Vec<DailyPrice>> {
+        self.get_prices_window(symbol, None, 0)
+    }
+
+    /// Get the `n` most recent bars for a symbol, in ascending date order
+    pub fn get_recent_prices(&self, symbol: &str, n: usize) -> Result<Vec<DailyPrice>> {
+        self.get_prices_window(symbol, Some(n), 0)
+    }
+
+    /// Get a window of bars counted back from the most recent one
+    ///
+    /// `offset` skips that many of the newest bars and `limit` caps the window
+    /// size (`None` for everything older). The window is returned in
+    /// ascending date order, so page 2 of a chart is `offset = limit`.
+    pub fn get_prices_window(
+        &self,
+        symbol: &str,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<Vec<DailyPrice>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, timestamp, open, high, low, close, volume, source, adjusted_close
+            FROM daily_prices
+            WHERE symbol = ?1
+            ORDER BY timestamp DESC
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )?;
+
+        let mut prices = stmt
+            .query_map(
+                params![symbol, sql_limit(limit), offset as i64],
+                Self::map_price_row,
+            )?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        prices.reverse();
+
+        Ok(prices)
+    }
+
+    /// Every bar stored for a symbol from one source, in ascending date order
+    ///
+    /// Unlike `get_prices` this ignores `price_source_priority`: the feed is
+    /// returned as that source delivered it, including dates another source won.
+    pub fn get_prices_by_source(&self, symbol: &str, source: &str) -> Result<Vec<DailyPrice>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, timestamp, open, high, low, close, volume, source, adjusted_close
+            FROM source_prices
+            WHERE symbol = ?1 AND source = ?2
+            ORDER BY timestamp ASC
+            "#,
+        )?;
+
+        let prices = stmt
+            .query_map(params![symbol, source], Self::map_price_row)?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
         Ok(prices)
     }
 
+    /// Latest close a symbol has from one source; see `get_prices_by_source`
+    pub fn get_latest_price_by_source(&self, symbol: &str, source: &str) -> Result<Option<f64>> {
+        let price = self
+            .conn
+            .query_row(
+                r#"
+                SELECT close FROM source_prices
+                WHERE symbol = ?1 AND source = ?2
+                ORDER BY timestamp DESC
+                LIMIT 1
+                "#,
+                params![symbol, source],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(price)
+    }
+
+    /// Sources with bars for a symbol, most preferred first
+    ///
+    /// Ordered by `price_source_priority`, then by name for unlisted sources.
+    pub fn list_sources(&self, symbol: &str) -> Result<Vec<PriceSource>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT source, COUNT(*), MIN(timestamp), MAX(timestamp)
+            FROM source_prices
+            WHERE symbol = ?1
+            GROUP BY source
+            ORDER BY source
+            "#,
+        )?;
+
+        let parse_date = |s: String| {
+            NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+                .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())
+        };
+        let mut sources = stmt
+            .query_map(params![symbol], |row| {
+                Ok(PriceSource {
+                    source: row.get(0)?,
+                    bars: row.get::<_, i64>(1)? as usize,
+                    first_date: parse_date(row.get(2)?),
+                    last_date: parse_date(row.get(3)?),
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let priority = self.price_source_priority()?;
+        sources.sort_by_key(|s| source_rank(&priority, &s.source));
+        Ok(sources)
+    }
+
+    /// Visit every daily bar for a symbol in date order without loading them all
+    ///
+    /// Rows are read from SQLite one at a time and handed to `f`, so a long
+    /// history never has to fit in memory at once. An error from `f` stops
+    /// the scan and is returned. Returns the number of bars visited.
+    pub fn for_each_price<F>(&self, symbol: &str, mut f: F) -> Result<usize>
+    where
+        F: FnMut(DailyPrice) -> Result<()>,
+    {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, timestamp, open, high, low, close, volume, source, adjusted_close
+            FROM daily_prices
+            WHERE symbol = ?1
+            ORDER BY timestamp ASC
+            "#,
+        )?;
+
+        let mut rows = stmt.query(params![symbol])?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            f(Self::map_price_row(row)?)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Pairwise correlation of daily returns over each symbol's last `days` bars
+    ///
+    /// Returns one `(symbol_a, symbol_b, correlation)` entry per pair, in the
+    /// order the symbols are given. Only dates both symbols have bars for are
+    /// compared; a pair with too little overlapping history (see
+    /// `correlation::return_correlation`) gets NaN.
+    pub fn correlation_matrix(
+        &self,
+        symbols: &[String],
+        days: usize,
+    ) -> Result<Vec<(String, String, f64)>> {
+        let histories = symbols
+            .iter()
+            .map(|symbol| self.get_recent_prices(&symbol.to_uppercase(), days + 1))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut pairs = Vec::new();
+        for i in 0..symbols.len() {
+            for j in (i + 1)..symbols.len() {
+                pairs.push((
+                    symbols[i].to_uppercase(),
+                    symbols[j].to_uppercase(),
+                    return_correlation(&histories[i], &histories[j]),
+                ));
+            }
+        }
+
+        Ok(pairs)
+    }
+
+    /// Rank symbols by relative strength against a benchmark
+    ///
+    /// Each symbol's growth over its last `period_days` bars is divided by
+    /// the benchmark's over the same number of bars, so above 1.0 means it
+    /// outperformed (and a flat benchmark doesn't divide by zero). Symbols
+    /// with fewer than two bars are left out. Sorted strongest first.
+    pub fn relative_strength(
+        &self,
+        symbols: &[String],
+        benchmark: &str,
+        period_days: usize,
+    ) -> Result<Vec<(String, f64)>> {
+        let growth = |prices: &[DailyPrice]| match (prices.first(), prices.last()) {
+            (Some(first), Some(last)) if prices.len() >= 2 && first.close > 0.0 => {
+                Some(last.close / first.close)
+            }
+            _ => None,
+        };
+
+        let benchmark = benchmark.to_uppercase();
+        let benchmark_growth = growth(&self.get_recent_prices(&benchmark, period_days + 1)?)
+            .ok_or_else(|| {
+                PipelineError::NoData(format!(
+                    "Benchmark {} needs at least two price bars for relative strength",
+                    benchmark
+                ))
+            })?;
+
+        let mut ranking = Vec::new();
+        for symbol in symbols {
+            let symbol = symbol.to_uppercase();
+            if let Some(g) = growth(&self.get_recent_prices(&symbol, period_days + 1)?) {
+                ranking.push((symbol, g / benchmark_growth));
+            }
+        }
+        ranking.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(ranking)
+    }
+
+    /// Runs of missing trading days between a symbol's first and last stored bar
+    ///
+    /// Each gap is returned as (first missing day, last missing day).
+    /// Weekends and US market holidays (see `trading_calendar`) are skipped,
+    /// so a gap may span them.
+    pub fn find_price_gaps(&self, symbol: &str) -> Result<Vec<(NaiveDate, NaiveDate)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT timestamp FROM daily_prices WHERE symbol = ?1 ORDER BY timestamp ASC",
+        )?;
+        let dates: Vec<NaiveDate> = stmt
+            .query_map(params![symbol], |row| row.get::<_, String>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?
+            .iter()
+            .filter_map(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+            .collect();
+
+        let mut gaps = Vec::new();
+        for pair in dates.windows(2) {
+            let missing: Vec<NaiveDate> = pair[0]
+                .iter_days()
+                .skip(1)
+                .take_while(|d| *d < pair[1])
+                .filter(|d| is_trading_day(*d))
+                .collect();
+            if let (Some(&first), Some(&last)) = (missing.first(), missing.last()) {
+                gaps.push((first, last));
+            }
+        }
+
+        Ok(gaps)
+    }
+
+    /// Map a `symbol, timestamp, open, high, low, close, volume, source` row
+    fn map_price_row(row: &rusqlite::Row) -> SqliteResult<DailyPrice> {
+        let date_str: String = row.get(1)?;
+        Ok(DailyPrice {
+            symbol: row.get(0)?,
+            date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+            open: row.get(2)?,
+            high: row.get(3)?,
+            low: row.get(4)?,
+            close: row.get(5)?,
+            volume: row.get(6)?,
+            adjusted_close: row.get(8)?,
+            source: row.get(7)?,
+        })
+    }
+
     /// Get all symbols with price data
     pub fn get_symbols_with_data(&self) -> Result<Vec<String>> {
         let mut stmt = self
@@ -327,12 +1368,46 @@ impl Database {
         Ok(symbols)
     }
 
-    /// Clear price data for a symbol
+    /// Delete a symbol's bars dated before `cutoff`, along with the
+    /// indicators and signals derived from them
+    ///
+    /// Returns the number of price bars removed.
+    pub fn prune_history_before(&mut self, symbol: &str, cutoff: NaiveDate) -> Result<usize> {
+        let cutoff = cutoff.to_string();
+        let tx = self.conn.transaction()?;
+
+        let removed = tx.execute(
+            "DELETE FROM daily_prices WHERE symbol = ?1 AND timestamp < ?2",
+            params![symbol, cutoff],
+        )?;
+        tx.execute(
+            "DELETE FROM source_prices WHERE symbol = ?1 AND timestamp < ?2",
+            params![symbol, cutoff],
+        )?;
+        tx.execute(
+            "DELETE FROM technical_indicators WHERE symbol = ?1 AND timestamp < ?2",
+            params![symbol, cutoff],
+        )?;
+        tx.execute(
+            "DELETE FROM signals WHERE symbol = ?1 AND timestamp < ?2",
+            params![symbol, cutoff],
+        )?;
+
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Clear price data for a symbol, along with the indicators computed from it
     pub fn clear_symbol_prices(&self, symbol: &str) -> Result<()> {
         self.conn.execute(
             "DELETE FROM daily_prices WHERE symbol = ?1",
             params![symbol],
         )?;
+        self.conn.execute(
+            "DELETE FROM source_prices WHERE symbol = ?1",
+            params![symbol],
+        )?;
+        self.invalidate_indicators(symbol)?;
         println!("[OK] Cleared price data for {}", symbol);
         Ok(())
     }
@@ -600,284 +1675,487 @@ impl Database {
         Ok(())
     }
 
-    /// Store a technical indicator value
-    pub fn upsert_indicator(&self, ind: &TechnicalIndicator) -> Result<()> {
+    // ========================================================================
+    // Settings Methods
+    // ========================================================================
+
+    /// Get a setting value, or `None` if it has never been set
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let value = self
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value)
+    }
+
+    /// Insert or update a setting
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT OR REPLACE INTO technical_indicators
-            (symbol, timestamp, indicator_name, value)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT INTO settings (key, value, updated_at)
+            VALUES (?1, ?2, CURRENT_TIMESTAMP)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
             "#,
-            params![ind.symbol, ind.date.to_string(), ind.indicator_name, ind.value],
+            params![key, value],
         )?;
         Ok(())
     }
 
-    /// Batch store indicators
-    pub fn upsert_indicators(&mut self, indicators: &[TechnicalIndicator]) -> Result<usize> {
-        let tx = self.conn.transaction()?;
-        let mut count = 0;
-
-        {
-            let mut stmt = tx.prepare(
+    /// Point a notification sink (`webhook`, `log_file`) at a target, or
+    /// remove it with `None`
+    pub fn set_notification_target(&self, sink: &str, target: Option<&str>) -> Result<()> {
+        match target {
+            Some(target) => self.conn.execute(
                 r#"
-                INSERT OR REPLACE INTO technical_indicators
-                (symbol, timestamp, indicator_name, value)
-                VALUES (?1, ?2, ?3, ?4)
+                INSERT INTO notification_config (sink, target, updated_at)
+                VALUES (?1, ?2, CURRENT_TIMESTAMP)
+                ON CONFLICT(sink) DO UPDATE
+                SET target = excluded.target, updated_at = CURRENT_TIMESTAMP
                 "#,
-            )?;
-
-            for ind in indicators {
-                stmt.execute(params![
-                    ind.symbol,
-                    ind.date.to_string(),
-                    ind.indicator_name,
-                    ind.value
-                ])?;
-                count += 1;
-            }
-        }
+                params![sink, target],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM notification_config WHERE sink = ?1", params![sink])?,
+        };
+        Ok(())
+    }
 
-        tx.commit()?;
-        Ok(count)
+    /// Configured notification sinks as (sink, target) pairs, sorted by sink
+    pub fn get_notification_config(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT sink, target FROM notification_config ORDER BY sink")?;
+        let config = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(config)
     }
 
-    /// Get latest indicators for a symbol
-    pub fn get_latest_indicators(&self, symbol: &str) -> Result<Vec<TechnicalIndicator>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT t.symbol, t.timestamp, t.indicator_name, t.value
-            FROM technical_indicators t
-            INNER JOIN (
-                SELECT symbol, indicator_name, MAX(timestamp) as max_date
-                FROM technical_indicators
-                WHERE symbol = ?1
-                GROUP BY symbol, indicator_name
-            ) latest ON t.symbol = latest.symbol
-                AND t.indicator_name = latest.indicator_name
-                AND t.timestamp = latest.max_date
-            "#,
-        )?;
+    /// Send triggered alerts to every configured sink
+    ///
+    /// Delivery happens in the background (see `deliver_in_background`), so
+    /// the alert check returns without waiting on the network. A failing sink
+    /// is reported but doesn't fail the check, since the alerts have already
+    /// been marked triggered.
+    fn notify_triggered(
+        &self,
+        notifications: impl IntoIterator<Item = AlertNotification>,
+    ) -> Result<()> {
+        let notifications: Vec<AlertNotification> = notifications.into_iter().collect();
+        if notifications.is_empty() {
+            return Ok(());
+        }
+        let sinks: Vec<Box<dyn NotificationSink>> = self
+            .get_notification_config()?
+            .iter()
+            .filter_map(|(sink, target)| sink_for(sink, target))
+            .collect();
+        if !sinks.is_empty() {
+            deliver_in_background(sinks, notifications);
+        }
+        Ok(())
+    }
 
-        let indicators = stmt
-            .query_map(params![symbol], |row| {
-                let date_str: String = row.get(1)?;
-                Ok(TechnicalIndicator {
-                    symbol: row.get(0)?,
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    indicator_name: row.get(2)?,
-                    value: row.get(3)?,
-                })
-            })?
+    /// All stored settings as (key, value) pairs, sorted by key
+    pub fn get_all_settings(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
             .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(settings)
+    }
 
-        Ok(indicators)
+    /// Get a boolean setting ("true"/"false" or "1"/"0")
+    pub fn get_setting_bool(&self, key: &str) -> Result<Option<bool>> {
+        self.get_setting(key)?
+            .map(|value| match value.trim().to_lowercase().as_str() {
+                "true" | "1" => Ok(true),
+                "false" | "0" => Ok(false),
+                _ => Err(PipelineError::Config(format!(
+                    "Setting '{}' is not a boolean: {}",
+                    key, value
+                ))),
+            })
+            .transpose()
     }
 
-    /// Get indicator history for a symbol
-    pub fn get_indicator_history(
-        &self,
-        symbol: &str,
-        indicator_name: &str,
-    ) -> Result<Vec<TechnicalIndicator>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT symbol, timestamp, indicator_name, value
-            FROM technical_indicators
-            WHERE symbol = ?1 AND indicator_name = ?2
-            ORDER BY timestamp ASC
-            "#,
-        )?;
+    /// Store a boolean setting
+    pub fn set_setting_bool(&self, key: &str, value: bool) -> Result<()> {
+        self.set_setting(key, if value { "true" } else { "false" })
+    }
 
-        let indicators = stmt
-            .query_map(params![symbol, indicator_name], |row| {
-                let date_str: String = row.get(1)?;
-                Ok(TechnicalIndicator {
-                    symbol: row.get(0)?,
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    indicator_name: row.get(2)?,
-                    value: row.get(3)?,
+    /// Get a numeric setting
+    pub fn get_setting_f64(&self, key: &str) -> Result<Option<f64>> {
+        self.get_setting(key)?
+            .map(|value| {
+                value.trim().parse::<f64>().map_err(|_| {
+                    PipelineError::Config(format!("Setting '{}' is not a number: {}", key, value))
                 })
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
+            })
+            .transpose()
+    }
 
-        Ok(indicators)
+    /// Store a numeric setting
+    pub fn set_setting_f64(&self, key: &str, value: f64) -> Result<()> {
+        self.set_setting(key, &value.to_string())
     }
 
-    /// Add a price alert
-    pub fn add_alert(&self, symbol: &str, target_price: f64, condition: AlertCondition) -> Result<i64> {
-        let condition_str = match condition {
-            AlertCondition::Above => "above",
-            AlertCondition::Below => "below",
-        };
+    /// Get a setting stored as JSON and deserialize it
+    pub fn get_setting_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.get_setting(key)?
+            .map(|value| serde_json::from_str(&value).map_err(PipelineError::from))
+            .transpose()
+    }
+
+    /// Serialize a value to JSON and store it as a setting
+    pub fn set_setting_json<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        self.set_setting(key, &serde_json::to_string(value)?)
+    }
 
+    /// Store the signal thresholds used by `generate_signals`
+    pub fn save_signal_config(&self, config: &SignalConfig) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT INTO price_alerts (symbol, target_price, condition)
-            VALUES (?1, ?2, ?3)
+            INSERT INTO signal_config (id, config_json, updated_at)
+            VALUES (1, ?1, CURRENT_TIMESTAMP)
+            ON CONFLICT(id) DO UPDATE SET config_json = excluded.config_json, updated_at = CURRENT_TIMESTAMP
             "#,
-            params![symbol, target_price, condition_str],
+            params![serde_json::to_string(config)?],
         )?;
+        Ok(())
+    }
 
-        Ok(self.conn.last_insert_rowid())
-    }
-
-    /// Get all alerts (optionally filter by triggered status)
-    pub fn get_alerts(&self, only_active: bool) -> Result<Vec<PriceAlert>> {
-        let sql = if only_active {
-            "SELECT id, symbol, target_price, condition, triggered, created_at FROM price_alerts WHERE triggered = 0 ORDER BY created_at DESC"
-        } else {
-            "SELECT id, symbol, target_price, condition, triggered, created_at FROM price_alerts ORDER BY created_at DESC"
-        };
-
-        let mut stmt = self.conn.prepare(sql)?;
-
-        let alerts = stmt
-            .query_map([], |row| {
-                let condition_str: String = row.get(3)?;
-                let condition = if condition_str == "above" {
-                    AlertCondition::Above
-                } else {
-                    AlertCondition::Below
-                };
-
-                Ok(PriceAlert {
-                    id: row.get(0)?,
-                    symbol: row.get(1)?,
-                    target_price: row.get(2)?,
-                    condition,
-                    triggered: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
-
-        Ok(alerts)
+    /// Stored signal thresholds, or `None` if none have been saved
+    pub fn get_signal_config(&self) -> Result<Option<SignalConfig>> {
+        let json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT config_json FROM signal_config WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
     }
 
-    /// Delete an alert
-    pub fn delete_alert(&self, alert_id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM price_alerts WHERE id = ?1", params![alert_id])?;
-        Ok(())
+    /// Benchmark symbol for beta/alpha/relative-strength, defaulting to SPY
+    pub fn benchmark_symbol(&self) -> Result<String> {
+        Ok(self
+            .get_setting(BENCHMARK_SYMBOL_KEY)?
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| DEFAULT_BENCHMARK_SYMBOL.to_string()))
     }
 
-    /// Mark an alert as triggered
-    pub fn trigger_alert(&self, alert_id: i64) -> Result<()> {
-        self.conn.execute("UPDATE price_alerts SET triggered = 1 WHERE id = ?1", params![alert_id])?;
-        Ok(())
+    /// Price sources to prefer when two have a bar for the same date,
+    /// highest first (empty when `price_source_priority` is unset)
+    pub fn price_source_priority(&self) -> Result<Vec<String>> {
+        Ok(self
+            .get_setting(PRICE_SOURCE_PRIORITY_KEY)?
+            .map(|s| {
+                s.split(',')
+                    .map(|source| source.trim().to_string())
+                    .filter(|source| !source.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default())
     }
 
-    /// Check alerts against current prices, returns triggered alerts
-    pub fn check_alerts(&self) -> Result<Vec<PriceAlert>> {
-        let alerts = self.get_alerts(true)?;
-        let mut triggered = Vec::new();
-
-        for alert in alerts {
-            if let Ok(Some(current_price)) = self.get_latest_price(&alert.symbol) {
-                let should_trigger = match alert.condition {
-                    AlertCondition::Above => current_price >= alert.target_price,
-                    AlertCondition::Below => current_price <= alert.target_price,
-                };
-
-                if should_trigger {
-                    self.trigger_alert(alert.id)?;
-                    triggered.push(PriceAlert {
-                        triggered: true,
-                        ..alert
-                    });
-                }
-            }
+    /// Write a compacted, standalone copy of the database to `dest`
+    ///
+    /// Uses `VACUUM INTO`; this database is left untouched. Fails if `dest`
+    /// already exists.
+    pub fn vacuum_into(&self, dest: &Path) -> Result<()> {
+        if dest.exists() {
+            return Err(PipelineError::Config(format!(
+                "Destination already exists: {}",
+                dest.display()
+            )));
         }
 
-        Ok(triggered)
+        let dest_str = dest.to_string_lossy();
+        self.conn.execute("VACUUM INTO ?1", params![dest_str.as_ref()])?;
+        println!("[OK] Compacted database written to {}", dest.display());
+        Ok(())
     }
 
-    /// Add a portfolio position
-    pub fn add_position(
-        &self,
-        symbol: &str,
-        quantity: f64,
-        price: f64,
-        position_type: PositionType,
-        date: &str,
-        notes: Option<&str>,
-    ) -> Result<i64> {
-        let type_str = match position_type {
-            PositionType::Buy => "buy",
-            PositionType::Sell => "sell",
-        };
+    /// Merge prices, indicators, strategies and positions from another database file
+    ///
+    /// Rows already present here win: symbols and indicators already stored,
+    /// strategies with the same name and identical positions are skipped and
+    /// counted as conflicts. Prices are stored like fetched bars: a bar
+    /// already held from the same source is a conflict, and otherwise
+    /// `price_source_priority` decides which bar `daily_prices` keeps. A
+    /// symbol that already had prices and gains merged bars has its
+    /// indicators invalidated (merged ones included) so they are recomputed
+    /// from the combined series. Databases from older builds merge as they
+    /// are; columns they predate take their defaults. Runs in a single
+    /// transaction, so a failure leaves this database unchanged.
+    pub fn merge_from(&mut self, other_path: &Path) -> Result<MergeSummary> {
+        if !other_path.exists() {
+            return Err(PipelineError::Config(format!(
+                "Database not found: {}",
+                other_path.display()
+            )));
+        }
 
         self.conn.execute(
-            r#"
-            INSERT INTO portfolio_positions (symbol, quantity, price, position_type, date, notes)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-            "#,
-            params![symbol, quantity, price, type_str, date, notes],
+            "ATTACH DATABASE ?1 AS merge_src",
+            params![other_path.to_string_lossy()],
         )?;
 
-        Ok(self.conn.last_insert_rowid())
+        let result = self.merge_attached();
+
+        self.conn.execute("DETACH DATABASE merge_src", [])?;
+
+        let summary = result?;
+        println!(
+            "[OK] Merged {} prices, {} indicators, {} strategies, {} positions ({} conflicts)",
+            summary.prices,
+            summary.indicators,
+            summary.strategies,
+            summary.positions,
+            summary.conflicts
+        );
+        Ok(summary)
     }
 
-    /// Get all portfolio positions
-    pub fn get_positions(&self) -> Result<Vec<Position>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT id, symbol, quantity, price, position_type, date, notes
-            FROM portfolio_positions
-            ORDER BY date DESC
-            "#,
+    fn merge_attached(&self) -> Result<MergeSummary> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut summary = MergeSummary::default();
+        let mut total = 0;
+
+        let (symbols, symbols_total) = merge_table(
+            &tx,
+            "symbols",
+            &[
+                "symbol",
+                "name",
+                "sector",
+                "industry",
+                "market_cap",
+                "country",
+                "exchange",
+                "currency",
+                "isin",
+                "asset_class",
+                "favorited",
+                "updated_at",
+            ],
+            &[],
         )?;
+        summary.symbols = symbols;
+        total += symbols_total;
+
+        // Bars go through `store_price` like fetched ones; symbols that
+        // already had prices get their indicators invalidated below
+        let price_columns =
+            merge_source_columns(&tx, "daily_prices", &["symbol", "adjusted_close", "source"])?;
+        let mut touched = Vec::new();
+        if price_columns.contains(&"symbol") {
+            let adjusted_close = if price_columns.contains(&"adjusted_close") {
+                "adjusted_close"
+            } else {
+                "NULL"
+            };
+            let source = if price_columns.contains(&"source") {
+                "COALESCE(source, 'unknown')"
+            } else {
+                "'unknown'"
+            };
+            let incoming = tx
+                .prepare(&format!(
+                    "SELECT symbol, timestamp, open, high, low, close, volume, {}, {}
+                     FROM merge_src.daily_prices",
+                    source, adjusted_close
+                ))?
+                .query_map([], Self::map_price_row)?
+                .collect::<SqliteResult<Vec<_>>>()?;
+
+            let priority = self.price_source_priority()?;
+            let had_prices = self.get_symbols_with_data()?;
+            for price in &incoming {
+                let date = price.date.to_string();
+                let known: bool = tx
+                    .prepare_cached(
+                        r#"
+                        SELECT EXISTS(SELECT 1 FROM source_prices
+                                      WHERE symbol = ?1 AND timestamp = ?2 AND source = ?3)
+                            OR EXISTS(SELECT 1 FROM daily_prices
+                                      WHERE symbol = ?1 AND timestamp = ?2 AND source = ?3)
+                        "#,
+                    )?
+                    .query_row(params![price.symbol, date, price.source], |row| row.get(0))?;
+                if known {
+                    continue;
+                }
 
-        let positions = stmt
-            .query_map([], |row| {
-                let type_str: String = row.get(4)?;
-                let position_type = if type_str == "buy" {
-                    PositionType::Buy
-                } else {
-                    PositionType::Sell
-                };
+                Self::store_price(&tx, price, &priority)?;
+                summary.prices += 1;
+                if had_prices.contains(&price.symbol) && !touched.contains(&price.symbol) {
+                    touched.push(price.symbol.clone());
+                }
+            }
+            total += incoming.len();
+        }
+        for symbol in &touched {
+            self.invalidate_indicators(symbol)?;
+        }
 
-                Ok(Position {
-                    id: row.get(0)?,
-                    symbol: row.get(1)?,
-                    quantity: row.get(2)?,
-                    price: row.get(3)?,
-                    position_type,
-                    date: row.get(5)?,
-                    notes: row.get(6)?,
-                })
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
+        let (indicators, indicators_total) = merge_table(
+            &tx,
+            "technical_indicators",
+            &[
+                "symbol",
+                "timestamp",
+                "indicator_name",
+                "value",
+                "params",
+                "created_at",
+            ],
+            &touched,
+        )?;
+        summary.indicators = indicators;
+        total += indicators_total;
+
+        let (strategies, strategies_total) = merge_table(
+            &tx,
+            "strategies",
+            &[
+                "name",
+                "description",
+                "entry_condition",
+                "entry_threshold",
+                "exit_condition",
+                "exit_threshold",
+                "stop_loss_percent",
+                "take_profit_percent",
+                "position_size_percent",
+                "trailing_stop_percent",
+                "direction",
+                "entry_rules",
+                "exit_rules",
+                "position_sizing",
+                "created_at",
+            ],
+            &[],
+        )?;
+        summary.strategies = strategies;
+        total += strategies_total;
+
+        let position_columns = merge_source_columns(
+            &tx,
+            "portfolio_positions",
+            &[
+                "symbol",
+                "quantity",
+                "price",
+                "position_type",
+                "date",
+                "notes",
+                "option_strategy",
+                "strike",
+                "expiration",
+                "created_at",
+            ],
+        )?;
+        if !position_columns.is_empty() {
+            let option_strategy = if position_columns.contains(&"option_strategy") {
+                "o.option_strategy"
+            } else {
+                "NULL"
+            };
+            // Positions have no natural key, so skip exact duplicates
+            summary.positions = tx.execute(
+                &format!(
+                    r#"
+                    INSERT INTO main.portfolio_positions ({})
+                    SELECT {}
+                    FROM merge_src.portfolio_positions o
+                    WHERE NOT EXISTS (
+                        SELECT 1 FROM main.portfolio_positions m
+                        WHERE m.symbol = o.symbol AND m.quantity = o.quantity
+                          AND m.price = o.price AND m.position_type = o.position_type
+                          AND m.date = o.date AND m.option_strategy IS {}
+                    )
+                    "#,
+                    position_columns.join(", "),
+                    position_columns
+                        .iter()
+                        .map(|c| format!("o.{}", c))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    option_strategy
+                ),
+                [],
+            )?;
+            let positions_total: i64 = tx.query_row(
+                "SELECT COUNT(*) FROM merge_src.portfolio_positions",
+                [],
+                |row| row.get(0),
+            )?;
+            total += positions_total as usize;
+        }
 
-        Ok(positions)
+        summary.conflicts = total
+            - summary.symbols
+            - summary.prices
+            - summary.indicators
+            - summary.strategies
+            - summary.positions;
+
+        tx.commit()?;
+        Ok(summary)
     }
 
-    /// Delete a portfolio position
-    pub fn delete_position(&self, position_id: i64) -> Result<()> {
+    /// Store a technical indicator value; NaN and infinite values are skipped
+    pub fn upsert_indicator(&self, ind: &TechnicalIndicator) -> Result<()> {
+        if !ind.value.is_finite() {
+            return Ok(());
+        }
         self.conn.execute(
-            "DELETE FROM portfolio_positions WHERE id = ?1",
-            params![position_id],
+            r#"
+            INSERT OR REPLACE INTO technical_indicators
+            (symbol, timestamp, indicator_name, value)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![ind.symbol, ind.date.to_string(), ind.indicator_name, ind.value],
         )?;
         Ok(())
     }
 
-    /// Store Google Trends data
-    pub fn upsert_trends(&mut self, data: &[TrendData]) -> Result<usize> {
+    /// Batch store indicators
+    ///
+    /// NaN and infinite values are skipped rather than stored: SQLite would
+    /// turn NaN into NULL and fail the batch, and neither is valid JSON. A
+    /// skipped value reads back as a missing row; the count excludes them.
+    pub fn upsert_indicators(&mut self, indicators: &[TechnicalIndicator]) -> Result<usize> {
         let tx = self.conn.transaction()?;
         let mut count = 0;
 
         {
             let mut stmt = tx.prepare(
                 r#"
-                INSERT OR REPLACE INTO trends_data (keyword, date, value)
-                VALUES (?1, ?2, ?3)
+                INSERT OR REPLACE INTO technical_indicators
+                (symbol, timestamp, indicator_name, value)
+                VALUES (?1, ?2, ?3, ?4)
                 "#,
             )?;
 
-            for point in data {
-                stmt.execute(params![point.keyword, point.date.to_string(), point.value])?;
+            for ind in indicators.iter().filter(|i| i.value.is_finite()) {
+                stmt.execute(params![
+                    ind.symbol,
+                    ind.date.to_string(),
+                    ind.indicator_name,
+                    ind.value
+                ])?;
                 count += 1;
             }
         }
@@ -886,279 +2164,448 @@ impl Database {
         Ok(count)
     }
 
-    /// Get trends data for a keyword
-    pub fn get_trends(&self, keyword: &str) -> Result<Vec<TrendData>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT keyword, date, value
-            FROM trends_data
-            WHERE keyword = ?1
-            ORDER BY date ASC
-            "#,
+    /// Delete a symbol's stored indicators so they are recomputed from scratch
+    ///
+    /// Returns the number of indicator values removed.
+    pub fn invalidate_indicators(&self, symbol: &str) -> Result<usize> {
+        let removed = self.conn.execute(
+            "DELETE FROM technical_indicators WHERE symbol = ?1",
+            params![symbol],
         )?;
+        self.conn.execute(
+            "DELETE FROM indicator_state WHERE symbol = ?1",
+            params![symbol],
+        )?;
+        Ok(removed)
+    }
 
-        let trends = stmt
-            .query_map(params![keyword], |row| {
-                let date_str: String = row.get(1)?;
-                Ok(TrendData {
-                    keyword: row.get(0)?,
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    value: row.get(2)?,
-                })
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
+    /// Whether a symbol's stored indicators may no longer match its prices
+    ///
+    /// Each `refresh_indicators` records a checksum of the bars it covered.
+    /// Indicators are dirty when there is no such record (they were stored by
+    /// other means) or those bars have since been rewritten, pruned or cleared.
+    pub fn indicators_dirty(&self, symbol: &str) -> Result<bool> {
+        let state: Option<(String, String)> = self
+            .conn
+            .query_row(
+                "SELECT through_date, price_checksum FROM indicator_state WHERE symbol = ?1",
+                params![symbol],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
 
-        Ok(trends)
+        match state {
+            Some((through, checksum)) => Ok(self.price_checksum(symbol, &through)? != checksum),
+            None => {
+                let stored: i64 = self.conn.query_row(
+                    "SELECT COUNT(*) FROM technical_indicators WHERE symbol = ?1",
+                    params![symbol],
+                    |row| row.get(0),
+                )?;
+                Ok(stored > 0)
+            }
+        }
     }
 
-    // ========================================================================
-    // Signal Methods
-    // ========================================================================
+    /// Bring a symbol's stored indicators in line with its prices
+    ///
+    /// Dirty indicators (see `indicators_dirty`) are purged and recomputed in
+    /// full; otherwise only bars newer than the latest stored indicator are
+    /// calculated, and nothing when they are current. Returns the number of
+    /// indicator values written.
+    pub fn refresh_indicators(&mut self, symbol: &str) -> Result<usize> {
+        if self.indicators_dirty(symbol)? {
+            self.invalidate_indicators(symbol)?;
+        }
+        let prices = self.incremental_prices(symbol)?;
+        if prices.is_empty() {
+            self.invalidate_indicators(symbol)?;
+        }
+        let Some(last) = prices.iter().map(|p| p.date).max() else {
+            return Ok(0);
+        };
 
-    /// Store a signal
-    pub fn upsert_signal(&self, signal: &Signal) -> Result<i64> {
+        let existing = self.get_all_indicators(symbol)?;
+        let count = self.upsert_indicators(&calculate_incremental(&existing, &prices))?;
+
+        let through = last.to_string();
         self.conn.execute(
             r#"
-            INSERT OR REPLACE INTO signals
-            (symbol, signal_type, direction, strength, price_at_signal,
-             triggered_by, trigger_value, timestamp, acknowledged)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            INSERT OR REPLACE INTO indicator_state (symbol, through_date, price_checksum)
+            VALUES (?1, ?2, ?3)
             "#,
-            params![
-                signal.symbol,
-                signal.signal_type.as_str(),
-                signal.direction.as_str(),
-                signal.strength,
-                signal.price_at_signal,
-                signal.triggered_by,
-                signal.trigger_value,
-                signal.timestamp.to_string(),
-                signal.acknowledged,
-            ],
+            params![symbol, through, self.price_checksum(symbol, &through)?],
         )?;
+        Ok(count)
+    }
 
-        Ok(self.conn.last_insert_rowid())
+    /// The bars `calculate_incremental` needs to bring a symbol's indicators
+    /// up to date
+    ///
+    /// That is the bars after the last calculated one plus
+    /// `INCREMENTAL_WARMUP_BARS` before them, reaching back to the start of the
+    /// first new bar's year for VWAP. With nothing calculated it is the full
+    /// history.
+    pub fn incremental_prices(&self, symbol: &str) -> Result<Vec<DailyPrice>> {
+        // Senkou spans are stored on projected dates, not calculated bars
+        let last: Option<String> = self.conn.query_row(
+            r#"
+            SELECT MAX(p.timestamp)
+            FROM daily_prices p
+            WHERE p.symbol = ?1 AND EXISTS (
+                SELECT 1 FROM technical_indicators t
+                WHERE t.symbol = p.symbol AND t.timestamp = p.timestamp
+                  AND t.indicator_name NOT LIKE 'ICHIMOKU_SENKOU%'
+            )
+            "#,
+            params![symbol],
+            |row| row.get(0),
+        )?;
+        let Some(last) = last else {
+            return self.get_prices(symbol);
+        };
+
+        let (new_bars, first_new): (i64, Option<String>) = self.conn.query_row(
+            r#"
+            SELECT COUNT(*), MIN(timestamp) FROM daily_prices
+            WHERE symbol = ?1 AND timestamp > ?2
+            "#,
+            params![symbol, last],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let since_year_start: i64 = match first_new {
+            Some(first) => self.conn.query_row(
+                "SELECT COUNT(*) FROM daily_prices WHERE symbol = ?1 AND timestamp >= ?2",
+                params![symbol, format!("{}-01-01", &first[..4])],
+                |row| row.get(0),
+            )?,
+            None => 0,
+        };
+        let bars = (INCREMENTAL_WARMUP_BARS + new_bars as usize).max(since_year_start as usize + 1);
+        self.get_recent_prices(symbol, bars)
     }
 
-    /// Batch store signals
-    pub fn upsert_signals(&mut self, signals: &[Signal]) -> Result<usize> {
-        let tx = self.conn.transaction()?;
-        let mut count = 0;
+    /// Fingerprint of a symbol's bars up to and including `through`
+    fn price_checksum(&self, symbol: &str, through: &str) -> Result<String> {
+        let (count, prices, volume): (i64, f64, f64) = self.conn.query_row(
+            r#"
+            SELECT COUNT(*), TOTAL(open + high + low + close + COALESCE(adjusted_close, 0)),
+                   TOTAL(volume)
+            FROM daily_prices
+            WHERE symbol = ?1 AND timestamp <= ?2
+            "#,
+            params![symbol, through],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok(format!("{}:{}:{}", count, prices, volume))
+    }
 
-        {
-            let mut stmt = tx.prepare(
-                r#"
-                INSERT OR REPLACE INTO signals
-                (symbol, signal_type, direction, strength, price_at_signal,
-                 triggered_by, trigger_value, timestamp, acknowledged)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-                "#,
-            )?;
+    /// Distinct indicator names stored for a symbol, sorted
+    pub fn get_indicator_names(&self, symbol: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT indicator_name FROM technical_indicators WHERE symbol = ?1 ORDER BY indicator_name",
+        )?;
+        let names = stmt
+            .query_map(params![symbol], |row| row.get(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(names)
+    }
 
-            for signal in signals {
-                stmt.execute(params![
-                    signal.symbol,
-                    signal.signal_type.as_str(),
-                    signal.direction.as_str(),
-                    signal.strength,
-                    signal.price_at_signal,
-                    signal.triggered_by,
-                    signal.trigger_value,
-                    signal.timestamp.to_string(),
-                    signal.acknowledged,
-                ])?;
-                count += 1;
-            }
-        }
-
-        tx.commit()?;
-        Ok(count)
-    }
-
-    /// Get signals for a symbol
-    pub fn get_signals(&self, symbol: &str, only_unacknowledged: bool) -> Result<Vec<Signal>> {
-        let sql = if only_unacknowledged {
-            r#"
-            SELECT id, symbol, signal_type, direction, strength, price_at_signal,
-                   triggered_by, trigger_value, timestamp, created_at, acknowledged
-            FROM signals
-            WHERE symbol = ?1 AND acknowledged = 0
-            ORDER BY timestamp DESC
-            "#
-        } else {
+    /// Get latest indicators for a symbol
+    pub fn get_latest_indicators(&self, symbol: &str) -> Result<Vec<TechnicalIndicator>> {
+        let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, symbol, signal_type, direction, strength, price_at_signal,
-                   triggered_by, trigger_value, timestamp, created_at, acknowledged
-            FROM signals
-            WHERE symbol = ?1
-            ORDER BY timestamp DESC
-            "#
-        };
-
-        let mut stmt = self.conn.prepare(sql)?;
+            SELECT t.symbol, t.timestamp, t.indicator_name, t.value
+            FROM technical_indicators t
+            INNER JOIN (
+                SELECT symbol, indicator_name, MAX(timestamp) as max_date
+                FROM technical_indicators
+                WHERE symbol = ?1
+                GROUP BY symbol, indicator_name
+            ) latest ON t.symbol = latest.symbol
+                AND t.indicator_name = latest.indicator_name
+                AND t.timestamp = latest.max_date
+            "#,
+        )?;
 
-        let signals = stmt
+        let indicators = stmt
             .query_map(params![symbol], |row| {
-                let signal_type_str: String = row.get(2)?;
-                let direction_str: String = row.get(3)?;
-                let date_str: String = row.get(8)?;
-
-                Ok(Signal {
-                    id: row.get(0)?,
-                    symbol: row.get(1)?,
-                    signal_type: SignalType::from_str(&signal_type_str)
-                        .unwrap_or(SignalType::RsiOversold),
-                    direction: SignalDirection::from_str(&direction_str),
-                    strength: row.get(4)?,
-                    price_at_signal: row.get(5)?,
-                    triggered_by: row.get(6)?,
-                    trigger_value: row.get(7)?,
-                    timestamp: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                let date_str: String = row.get(1)?;
+                Ok(TechnicalIndicator {
+                    symbol: row.get(0)?,
+                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
                         .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    created_at: row.get(9)?,
-                    acknowledged: row.get(10)?,
+                    indicator_name: row.get(2)?,
+                    value: row.get(3)?,
                 })
             })?
             .collect::<SqliteResult<Vec<_>>>()?;
 
-        Ok(signals)
+        Ok(indicators)
     }
 
-    /// Get recent signals across all symbols
-    pub fn get_recent_signals(&self, limit: usize) -> Result<Vec<Signal>> {
+    /// Get indicator history for a symbol
+    pub fn get_indicator_history(
+        &self,
+        symbol: &str,
+        indicator_name: &str,
+    ) -> Result<Vec<TechnicalIndicator>> {
+        self.get_indicator_history_window(symbol, indicator_name, None, 0)
+    }
+
+    /// Get a window of indicator values counted back from the most recent one
+    ///
+    /// Same paging rules as [`Database::get_prices_window`].
+    pub fn get_indicator_history_window(
+        &self,
+        symbol: &str,
+        indicator_name: &str,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<Vec<TechnicalIndicator>> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, symbol, signal_type, direction, strength, price_at_signal,
-                   triggered_by, trigger_value, timestamp, created_at, acknowledged
-            FROM signals
-            ORDER BY timestamp DESC, strength DESC
-            LIMIT ?1
+            SELECT symbol, timestamp, indicator_name, value
+            FROM technical_indicators
+            WHERE symbol = ?1 AND indicator_name = ?2
+            ORDER BY timestamp DESC
+            LIMIT ?3 OFFSET ?4
             "#,
         )?;
 
-        let signals = stmt
-            .query_map(params![limit as i64], |row| {
-                let signal_type_str: String = row.get(2)?;
-                let direction_str: String = row.get(3)?;
-                let date_str: String = row.get(8)?;
-
-                Ok(Signal {
-                    id: row.get(0)?,
-                    symbol: row.get(1)?,
-                    signal_type: SignalType::from_str(&signal_type_str)
-                        .unwrap_or(SignalType::RsiOversold),
-                    direction: SignalDirection::from_str(&direction_str),
-                    strength: row.get(4)?,
-                    price_at_signal: row.get(5)?,
-                    triggered_by: row.get(6)?,
-                    trigger_value: row.get(7)?,
-                    timestamp: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    created_at: row.get(9)?,
-                    acknowledged: row.get(10)?,
-                })
-            })?
+        let mut indicators = stmt
+            .query_map(
+                params![symbol, indicator_name, sql_limit(limit), offset as i64],
+                |row| {
+                    let date_str: String = row.get(1)?;
+                    Ok(TechnicalIndicator {
+                        symbol: row.get(0)?,
+                        date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                            .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                        indicator_name: row.get(2)?,
+                        value: row.get(3)?,
+                    })
+                },
+            )?
             .collect::<SqliteResult<Vec<_>>>()?;
+        indicators.reverse();
 
-        Ok(signals)
+        Ok(indicators)
     }
 
-    /// Acknowledge a signal
-    pub fn acknowledge_signal(&self, signal_id: i64) -> Result<()> {
-        self.conn.execute(
-            "UPDATE signals SET acknowledged = 1 WHERE id = ?1",
-            params![signal_id],
-        )?;
-        Ok(())
+    /// Export all daily bars for a symbol as CSV
+    ///
+    /// Columns: `date,open,high,low,close,volume`. Prices are rounded to
+    /// `precision.price_decimals`; volume is always an integer. Bars are
+    /// streamed with `for_each_price` rather than loaded up front.
+    /// Returns the number of data rows written (excluding the header).
+    pub fn export_prices_csv<W: Write>(
+        &self,
+        symbol: &str,
+        mut writer: W,
+        precision: &ExportPrecision,
+    ) -> Result<usize> {
+        let dp = precision.price_decimals;
+
+        writeln!(writer, "date,open,high,low,close,volume")?;
+        let count = self.for_each_price(symbol, |p| {
+            writeln!(
+                writer,
+                "{},{:.dp$},{:.dp$},{:.dp$},{:.dp$},{}",
+                p.date, p.open, p.high, p.low, p.close, p.volume
+            )?;
+            Ok(())
+        })?;
+        writer.flush()?;
+
+        Ok(count)
     }
 
-    /// Acknowledge all signals for a symbol
-    pub fn acknowledge_all_signals(&self, symbol: &str) -> Result<()> {
-        self.conn.execute(
-            "UPDATE signals SET acknowledged = 1 WHERE symbol = ?1",
-            params![symbol],
-        )?;
-        Ok(())
+    /// Import daily bars for a symbol from a `date,open,high,low,close,volume`
+    /// CSV file, such as one written by `export_prices_csv`
+    ///
+    /// Rows that fail to parse or validate are skipped and counted in the
+    /// summary rather than aborting the import.
+    pub fn import_prices_csv<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        symbol: &str,
+        source: &str,
+    ) -> Result<ImportSummary> {
+        let file = std::fs::File::open(path)?;
+        self.import_prices_reader(symbol, source, file, None)
     }
 
-    /// Delete old signals (cleanup)
-    pub fn cleanup_old_signals(&self, days: i64) -> Result<usize> {
-        let deleted = self.conn.execute(
-            "DELETE FROM signals WHERE timestamp < date('now', ?1)",
-            params![format!("-{} days", days)],
-        )?;
-        Ok(deleted)
+    /// Import daily bars from any CSV reader; see `import_prices_csv`
+    ///
+    /// Rows are streamed through a buffered reader and upserted in batches of
+    /// `IMPORT_BATCH_SIZE`, one transaction each, so memory stays bounded for
+    /// large files. `progress` is called with the running row count after each batch.
+    /// Fails only if the header lacks one of the expected columns.
+    pub fn import_prices_reader<R: Read>(
+        &mut self,
+        symbol: &str,
+        source: &str,
+        reader: R,
+        mut progress: Option<&mut dyn FnMut(usize)>,
+    ) -> Result<ImportSummary> {
+        let symbol = symbol.to_uppercase();
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(std::io::BufReader::new(reader));
+
+        let headers = rdr.headers()?;
+        if let Some(missing) = CSV_PRICE_COLUMNS
+            .iter()
+            .find(|column| !headers.iter().any(|h| h.eq_ignore_ascii_case(column)))
+        {
+            return Err(PipelineError::Config(format!(
+                "CSV is missing the {} column (expected {})",
+                missing,
+                CSV_PRICE_COLUMNS.join(",")
+            )));
+        }
+
+        let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+        let mut summary = ImportSummary::default();
+
+        for row in rdr.deserialize::<CsvPriceRow>() {
+            match row.ok().and_then(|row| row.into_price(&symbol, source)) {
+                Some(price) => batch.push(price),
+                None => {
+                    summary.skipped += 1;
+                    continue;
+                }
+            }
+
+            if batch.len() == IMPORT_BATCH_SIZE {
+                summary.imported += self.upsert_daily_prices(&batch)?;
+                batch.clear();
+                if let Some(cb) = progress.as_mut() {
+                    cb(summary.imported);
+                }
+            }
+        }
+
+        if !batch.is_empty() {
+            summary.imported += self.upsert_daily_prices(&batch)?;
+            if let Some(cb) = progress.as_mut() {
+                cb(summary.imported);
+            }
+        }
+
+        Ok(summary)
     }
 
-    /// Get all indicators for a symbol (for signal generation)
-    pub fn get_all_indicators(&self, symbol: &str) -> Result<Vec<TechnicalIndicator>> {
-        let mut stmt = self.conn.prepare(
+    /// Export the latest value of every indicator as `indicator,value,date` CSV rows
+    pub fn export_latest_indicators_csv<W: Write>(
+        &self,
+        symbol: &str,
+        mut writer: W,
+        precision: &ExportPrecision,
+    ) -> Result<usize> {
+        let indicators = self.get_latest_indicators(symbol)?;
+        let dp = precision.indicator_decimals;
+
+        writeln!(writer, "indicator,value,date")?;
+        for i in &indicators {
+            writeln!(writer, "{},{:.dp$},{}", i.indicator_name, i.value, i.date)?;
+        }
+        writer.flush()?;
+
+        Ok(indicators.len())
+    }
+
+    /// Export the full history of one indicator as `date,value` CSV rows
+    ///
+    /// Returns the number of data rows written (excluding the header).
+    pub fn export_indicator_history_csv<W: Write>(
+        &self,
+        symbol: &str,
+        indicator_name: &str,
+        mut writer: W,
+        precision: &ExportPrecision,
+    ) -> Result<usize> {
+        let history = self.get_indicator_history(symbol, indicator_name)?;
+        let dp = precision.indicator_decimals;
+
+        writeln!(writer, "date,value")?;
+        for ind in &history {
+            writeln!(writer, "{},{:.dp$}", ind.date, ind.value)?;
+        }
+        writer.flush()?;
+
+        Ok(history.len())
+    }
+
+    /// Add a price alert
+    pub fn add_alert(&self, symbol: &str, target_price: f64, condition: AlertCondition) -> Result<i64> {
+        let condition_str = match condition {
+            AlertCondition::Above => "above",
+            AlertCondition::Below => "below",
+        };
+
+        self.conn.execute(
             r#"
-            SELECT symbol, timestamp, indicator_name, value
-            FROM technical_indicators
-            WHERE symbol = ?1
-            ORDER BY timestamp ASC
+            INSERT INTO price_alerts (symbol, target_price, condition)
+            VALUES (?1, ?2, ?3)
             "#,
+            params![symbol, target_price, condition_str],
         )?;
 
-        let indicators = stmt
-            .query_map(params![symbol], |row| {
-                let date_str: String = row.get(1)?;
-                Ok(TechnicalIndicator {
-                    symbol: row.get(0)?,
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    indicator_name: row.get(2)?,
-                    value: row.get(3)?,
-                })
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
-
-        Ok(indicators)
+        Ok(self.conn.last_insert_rowid())
     }
 
-    // ========================================================================
-    // Indicator Alert Methods
-    // ========================================================================
+    /// Add an alert for a move of `percent` from `base_price` (e.g. -5.0 for a 5% drop)
+    ///
+    /// Without a base price the latest close is used. The implied target is
+    /// stored as the alert's target price, `below` for drops and `above` for rises.
+    pub fn add_percent_alert(
+        &self,
+        symbol: &str,
+        base_price: Option<f64>,
+        percent: f64,
+    ) -> Result<i64> {
+        let base_price = match base_price {
+            Some(price) => price,
+            None => self
+                .get_latest_price(symbol)?
+                .ok_or_else(|| PipelineError::NoData(symbol.to_string()))?,
+        };
+        let condition_str = if percent < 0.0 { "below" } else { "above" };
 
-    /// Add an indicator alert
-    pub fn add_indicator_alert(&self, alert: &IndicatorAlert) -> Result<i64> {
         self.conn.execute(
             r#"
-            INSERT INTO indicator_alerts
-            (symbol, alert_type, indicator_name, secondary_indicator, condition, threshold, message)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            INSERT INTO price_alerts (symbol, target_price, condition, base_price, percent_change)
+            VALUES (?1, ?2, ?3, ?4, ?5)
             "#,
             params![
-                alert.symbol,
-                alert.alert_type.as_str(),
-                alert.indicator_name,
-                alert.secondary_indicator,
-                alert.condition.as_str(),
-                alert.threshold,
-                alert.message,
+                symbol,
+                percent_target(base_price, percent),
+                condition_str,
+                base_price,
+                percent
             ],
         )?;
 
         Ok(self.conn.last_insert_rowid())
     }
 
-    /// Get all indicator alerts
-    pub fn get_indicator_alerts(&self, only_active: bool) -> Result<Vec<IndicatorAlert>> {
+    /// Get all alerts (optionally filter by triggered status)
+    pub fn get_alerts(&self, only_active: bool) -> Result<Vec<PriceAlert>> {
         let sql = if only_active {
             r#"
-            SELECT id, symbol, alert_type, indicator_name, secondary_indicator,
-                   condition, threshold, triggered, last_value, created_at, message
-            FROM indicator_alerts
+            SELECT id, symbol, target_price, condition, triggered, created_at,
+                   base_price, percent_change, recurring, last_triggered_at
+            FROM price_alerts
             WHERE triggered = 0
             ORDER BY created_at DESC
             "#
         } else {
             r#"
-            SELECT id, symbol, alert_type, indicator_name, secondary_indicator,
-                   condition, threshold, triggered, last_value, created_at, message
-            FROM indicator_alerts
+            SELECT id, symbol, target_price, condition, triggered, created_at,
+                   base_price, percent_change, recurring, last_triggered_at
+            FROM price_alerts
             ORDER BY created_at DESC
             "#
         };
@@ -1167,23 +2614,24 @@ impl Database {
 
         let alerts = stmt
             .query_map([], |row| {
-                let alert_type_str: String = row.get(2)?;
-                let condition_str: String = row.get(5)?;
+                let condition_str: String = row.get(3)?;
+                let condition = if condition_str == "above" {
+                    AlertCondition::Above
+                } else {
+                    AlertCondition::Below
+                };
 
-                Ok(IndicatorAlert {
+                Ok(PriceAlert {
                     id: row.get(0)?,
                     symbol: row.get(1)?,
-                    alert_type: IndicatorAlertType::from_str(&alert_type_str)
-                        .unwrap_or(IndicatorAlertType::Threshold),
-                    indicator_name: row.get(3)?,
-                    secondary_indicator: row.get(4)?,
-                    condition: IndicatorAlertCondition::from_str(&condition_str)
-                        .unwrap_or(IndicatorAlertCondition::CrossesAbove),
-                    threshold: row.get(6)?,
-                    triggered: row.get(7)?,
-                    last_value: row.get(8)?,
-                    created_at: row.get(9)?,
-                    message: row.get(10)?,
+                    target_price: row.get(2)?,
+                    condition,
+                    triggered: row.get(4)?,
+                    created_at: row.get(5)?,
+                    base_price: row.get(6)?,
+                    percent_change: row.get(7)?,
+                    recurring: row.get(8)?,
+                    last_triggered_at: row.get(9)?,
                 })
             })?
             .collect::<SqliteResult<Vec<_>>>()?;
@@ -1191,773 +2639,3619 @@ impl Database {
         Ok(alerts)
     }
 
-    /// Delete an indicator alert
-    pub fn delete_indicator_alert(&self, alert_id: i64) -> Result<()> {
-        self.conn.execute(
-            "DELETE FROM indicator_alerts WHERE id = ?1",
-            params![alert_id],
-        )?;
+    /// Delete an alert
+    pub fn delete_alert(&self, alert_id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM price_alerts WHERE id = ?1", params![alert_id])?;
         Ok(())
     }
 
-    /// Mark an indicator alert as triggered
-    pub fn trigger_indicator_alert(&self, alert_id: i64) -> Result<()> {
-        self.conn.execute(
-            "UPDATE indicator_alerts SET triggered = 1 WHERE id = ?1",
-            params![alert_id],
-        )?;
+    /// Mark an alert as triggered
+    pub fn trigger_alert(&self, alert_id: i64) -> Result<()> {
+        self.mark_triggered("price_alerts", alert_id)?;
         Ok(())
     }
 
-    /// Update last_value for an indicator alert
-    pub fn update_indicator_alert_state(&self, alert_id: i64, last_value: f64) -> Result<()> {
+    /// Make an alert fire once only, or re-arm after each trigger
+    pub fn set_alert_recurring(&self, alert_id: i64, recurring: bool) -> Result<()> {
         self.conn.execute(
-            "UPDATE indicator_alerts SET last_value = ?1 WHERE id = ?2",
-            params![last_value, alert_id],
+            "UPDATE price_alerts SET recurring = ?1 WHERE id = ?2",
+            params![recurring, alert_id],
         )?;
         Ok(())
     }
 
-    /// Get the latest value for a specific indicator
-    pub fn get_latest_indicator_value(&self, symbol: &str, indicator_name: &str) -> Result<Option<f64>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT value FROM technical_indicators
-            WHERE symbol = ?1 AND indicator_name = ?2
-            ORDER BY timestamp DESC
-            LIMIT 1
-            "#,
-        )?;
-
-        let result: SqliteResult<f64> = stmt.query_row(params![symbol, indicator_name], |row| row.get(0));
-
-        match result {
-            Ok(value) => Ok(Some(value)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e.into()),
-        }
-    }
-
-    /// Get the previous (second-to-last) indicator value
-    pub fn get_previous_indicator_value(&self, symbol: &str, indicator_name: &str) -> Result<Option<f64>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT value FROM technical_indicators
-            WHERE symbol = ?1 AND indicator_name = ?2
-            ORDER BY timestamp DESC
-            LIMIT 1 OFFSET 1
-            "#,
-        )?;
-
-        let result: SqliteResult<f64> = stmt.query_row(params![symbol, indicator_name], |row| row.get(0));
-
-        match result {
-            Ok(value) => Ok(Some(value)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e.into()),
-        }
+    /// Set `triggered` and stamp `last_triggered_at` on an alert row, returning the stamp
+    fn mark_triggered(&self, table: &str, alert_id: i64) -> Result<String> {
+        let sql = format!(
+            "UPDATE {} SET triggered = 1, last_triggered_at = CURRENT_TIMESTAMP \
+             WHERE id = ?1 RETURNING last_triggered_at",
+            table
+        );
+        let timestamp = self.conn.query_row(&sql, params![alert_id], |row| row.get(0))?;
+        Ok(timestamp)
     }
 
-    /// Check all indicator alerts, returns triggered alerts
-    pub fn check_indicator_alerts(&self) -> Result<Vec<IndicatorAlert>> {
-        let alerts = self.get_indicator_alerts(true)?;
-        let mut triggered_alerts = Vec::new();
-
-        for alert in alerts {
-            let current = self.get_latest_indicator_value(&alert.symbol, &alert.indicator_name)?;
-            let previous = alert.last_value.or_else(|| {
-                self.get_previous_indicator_value(&alert.symbol, &alert.indicator_name).ok().flatten()
-            });
-
-            let Some(current_val) = current else {
-                continue;
-            };
-
-            let should_trigger = match alert.condition {
-                IndicatorAlertCondition::CrossesAbove => {
-                    if let (Some(prev), Some(threshold)) = (previous, alert.threshold) {
-                        prev < threshold && current_val >= threshold
-                    } else {
-                        false
-                    }
-                }
-                IndicatorAlertCondition::CrossesBelow => {
-                    if let (Some(prev), Some(threshold)) = (previous, alert.threshold) {
-                        prev > threshold && current_val <= threshold
-                    } else {
-                        false
-                    }
-                }
-                IndicatorAlertCondition::BullishCrossover => {
-                    if let Some(secondary) = &alert.secondary_indicator {
-                        let secondary_current = self.get_latest_indicator_value(&alert.symbol, secondary)?;
-                        let secondary_prev = self.get_previous_indicator_value(&alert.symbol, secondary)?;
+    /// Check alerts against current prices, returns triggered alerts
+    ///
+    /// One-shot alerts fire once. A recurring alert that has fired is re-armed
+    /// when the price is back on the other side of its threshold, so it fires
+    /// again the next time the level is crossed.
+    pub fn check_alerts(&self) -> Result<Vec<PriceAlert>> {
+        let alerts = self.get_alerts(false)?;
+        let mut triggered = Vec::new();
 
-                        match (previous, secondary_current, secondary_prev) {
-                            (Some(prev_primary), Some(curr_sec), Some(prev_sec)) => {
-                                prev_primary <= prev_sec && current_val > curr_sec
-                            }
-                            _ => false,
-                        }
-                    } else {
-                        false
-                    }
-                }
-                IndicatorAlertCondition::BearishCrossover => {
-                    if let Some(secondary) = &alert.secondary_indicator {
-                        let secondary_current = self.get_latest_indicator_value(&alert.symbol, secondary)?;
-                        let secondary_prev = self.get_previous_indicator_value(&alert.symbol, secondary)?;
+        for alert in alerts.into_iter().filter(|a| !a.triggered || a.recurring) {
+            if let Ok(Some(current_price)) = self.get_latest_price(&alert.symbol) {
+                let should_trigger = match alert.condition {
+                    AlertCondition::Above => current_price >= alert.threshold(),
+                    AlertCondition::Below => current_price <= alert.threshold(),
+                };
 
-                        match (previous, secondary_current, secondary_prev) {
-                            (Some(prev_primary), Some(curr_sec), Some(prev_sec)) => {
-                                prev_primary >= prev_sec && current_val < curr_sec
-                            }
-                            _ => false,
-                        }
-                    } else {
-                        false
-                    }
+                if should_trigger && !alert.triggered {
+                    let last_triggered_at = self.mark_triggered("price_alerts", alert.id)?;
+                    triggered.push(PriceAlert {
+                        triggered: true,
+                        last_triggered_at: Some(last_triggered_at),
+                        ..alert
+                    });
+                } else if !should_trigger && alert.triggered {
+                    self.conn.execute(
+                        "UPDATE price_alerts SET triggered = 0 WHERE id = ?1",
+                        params![alert.id],
+                    )?;
                 }
-            };
-
-            if should_trigger {
-                self.trigger_indicator_alert(alert.id)?;
-                triggered_alerts.push(IndicatorAlert {
-                    triggered: true,
-                    ..alert
-                });
-            } else {
-                // Update last_value for next check
-                self.update_indicator_alert_state(alert.id, current_val)?;
             }
         }
 
-        Ok(triggered_alerts)
+        self.notify_triggered(triggered.iter().cloned().map(AlertNotification::Price))?;
+        Ok(triggered)
     }
 
-    // ========================================================================
-    // Backtest Methods
-    // ========================================================================
+    /// Add a portfolio position
+    pub fn add_position(
+        &self,
+        symbol: &str,
+        quantity: f64,
+        price: f64,
+        position_type: PositionType,
+        date: &str,
+        notes: Option<&str>,
+    ) -> Result<i64> {
+        let type_str = match position_type {
+            PositionType::Buy => "buy",
+            PositionType::Sell => "sell",
+        };
 
-    /// Save a strategy
-    pub fn save_strategy(&self, strategy: &Strategy) -> Result<i64> {
         self.conn.execute(
             r#"
-            INSERT OR REPLACE INTO strategies
-            (name, description, entry_condition, entry_threshold,
-             exit_condition, exit_threshold,
-             stop_loss_percent, take_profit_percent, position_size_percent)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            INSERT INTO portfolio_positions (symbol, quantity, price, position_type, date, notes)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            params![symbol, quantity, price, type_str, date, notes],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Add a covered call or cash-secured put
+    ///
+    /// The leg is stored as a sale of `quantity` shares' worth of contracts
+    /// (100 per contract) at `premium` per share.
+    pub fn add_option_position(
+        &self,
+        symbol: &str,
+        quantity: f64,
+        premium: f64,
+        leg: &OptionLeg,
+        date: &str,
+        notes: Option<&str>,
+    ) -> Result<i64> {
+        self.conn.execute(
+            r#"
+            INSERT INTO portfolio_positions
+            (symbol, quantity, price, position_type, date, notes,
+             option_strategy, strike, expiration)
+            VALUES (?1, ?2, ?3, 'sell', ?4, ?5, ?6, ?7, ?8)
             "#,
             params![
-                strategy.name,
-                strategy.description,
-                strategy.entry_condition.as_str(),
-                strategy.entry_threshold,
-                strategy.exit_condition.as_str(),
-                strategy.exit_threshold,
-                strategy.stop_loss_percent,
-                strategy.take_profit_percent,
-                strategy.position_size_percent,
+                symbol,
+                quantity,
+                premium,
+                date,
+                notes,
+                leg.strategy.as_str(),
+                leg.strike,
+                leg.expiration
             ],
         )?;
 
         Ok(self.conn.last_insert_rowid())
     }
 
-    /// Get all strategies
-    pub fn get_strategies(&self) -> Result<Vec<Strategy>> {
+    /// Get all portfolio positions
+    pub fn get_positions(&self) -> Result<Vec<Position>> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, name, description, entry_condition, entry_threshold,
-                   exit_condition, exit_threshold,
-                   stop_loss_percent, take_profit_percent, position_size_percent, created_at
-            FROM strategies
-            ORDER BY name ASC
+            SELECT id, symbol, quantity, price, position_type, date, notes,
+                   option_strategy, strike, expiration
+            FROM portfolio_positions
+            ORDER BY date DESC
             "#,
         )?;
 
-        let strategies = stmt
+        let positions = stmt
             .query_map([], |row| {
-                let entry_cond_str: String = row.get(3)?;
-                let exit_cond_str: String = row.get(5)?;
+                let type_str: String = row.get(4)?;
+                let position_type = if type_str == "buy" {
+                    PositionType::Buy
+                } else {
+                    PositionType::Sell
+                };
+                let strategy: Option<String> = row.get(7)?;
+                let option = strategy
+                    .and_then(|s| s.parse::<OptionStrategy>().ok())
+                    .map(|strategy| -> SqliteResult<OptionLeg> {
+                        Ok(OptionLeg {
+                            strategy,
+                            strike: row.get(8)?,
+                            expiration: row.get(9)?,
+                        })
+                    })
+                    .transpose()?;
 
-                Ok(Strategy {
+                Ok(Position {
                     id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    entry_condition: StrategyConditionType::from_str(&entry_cond_str)
-                        .unwrap_or(StrategyConditionType::RsiOversold),
-                    entry_threshold: row.get(4)?,
-                    exit_condition: StrategyConditionType::from_str(&exit_cond_str)
-                        .unwrap_or(StrategyConditionType::RsiOverbought),
-                    exit_threshold: row.get(6)?,
-                    stop_loss_percent: row.get(7)?,
-                    take_profit_percent: row.get(8)?,
-                    position_size_percent: row.get(9)?,
-                    created_at: row.get(10)?,
+                    symbol: row.get(1)?,
+                    quantity: row.get(2)?,
+                    price: row.get(3)?,
+                    position_type,
+                    date: row.get(5)?,
+                    notes: row.get(6)?,
+                    option,
                 })
             })?
             .collect::<SqliteResult<Vec<_>>>()?;
 
-        Ok(strategies)
+        Ok(positions)
     }
 
-    /// Get a strategy by name
-    pub fn get_strategy(&self, name: &str) -> Result<Option<Strategy>> {
-        let mut stmt = self.conn.prepare(
-            r#"
-            SELECT id, name, description, entry_condition, entry_threshold,
-                   exit_condition, exit_threshold,
-                   stop_loss_percent, take_profit_percent, position_size_percent, created_at
-            FROM strategies
-            WHERE name = ?1
-            "#,
-        )?;
+    /// Replay every portfolio trade with FIFO lot matching
+    ///
+    /// Returns one holding per symbol with its open lots, net side and
+    /// realized P&L (see `portfolio::match_lots_fifo`).
+    pub fn compute_positions_fifo(&self) -> Result<Vec<FifoPosition>> {
+        Ok(match_lots_fifo(&self.get_positions()?))
+    }
 
-        let result = stmt.query_row(params![name], |row| {
-            let entry_cond_str: String = row.get(3)?;
-            let exit_cond_str: String = row.get(5)?;
+    /// Long, short, net and beta-weighted exposure of the open holdings
+    ///
+    /// Holdings are FIFO-matched and marked at their latest close (or their
+    /// average entry price without one). Betas are against the configured
+    /// benchmark over the last `EXPOSURE_BETA_WINDOW` daily returns; see
+    /// `holding_beta` for how they are cached.
+    pub fn portfolio_exposure(&mut self) -> Result<PortfolioExposure> {
+        let benchmark = self.benchmark_symbol()?;
+        let mut exposure = PortfolioExposure {
+            benchmark: benchmark.clone(),
+            ..PortfolioExposure::default()
+        };
 
-            Ok(Strategy {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                entry_condition: StrategyConditionType::from_str(&entry_cond_str)
-                    .unwrap_or(StrategyConditionType::RsiOversold),
-                entry_threshold: row.get(4)?,
-                exit_condition: StrategyConditionType::from_str(&exit_cond_str)
-                    .unwrap_or(StrategyConditionType::RsiOverbought),
-                exit_threshold: row.get(6)?,
-                stop_loss_percent: row.get(7)?,
-                take_profit_percent: row.get(8)?,
-                position_size_percent: row.get(9)?,
-                created_at: row.get(10)?,
-            })
-        });
-
-        match result {
-            Ok(strategy) => Ok(Some(strategy)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e.into()),
+        for holding in self.compute_positions_fifo()? {
+            if holding.lots.is_empty() {
+                continue;
+            }
+            let price = self
+                .get_latest_price(&holding.symbol)?
+                .unwrap_or(holding.average_price());
+            let beta = self.holding_beta(&holding.symbol, &benchmark)?;
+            exposure.add(
+                &holding.symbol,
+                holding.position_type,
+                holding.quantity() * price,
+                beta,
+            );
         }
-    }
 
-    /// Delete a strategy
-    pub fn delete_strategy(&self, name: &str) -> Result<()> {
-        self.conn
-            .execute("DELETE FROM strategies WHERE name = ?1", params![name])?;
-        Ok(())
+        Ok(exposure)
     }
 
-    /// Save a backtest result
-    pub fn save_backtest_result(&self, result: &BacktestResult) -> Result<i64> {
-        let tx = self.conn.unchecked_transaction()?;
+    /// Beta of `symbol` against `benchmark` as of its latest bar
+    ///
+    /// The value is stored as the `BETA_{benchmark}_{EXPOSURE_BETA_WINDOW}`
+    /// indicator and reused until a newer bar arrives. `None` without enough
+    /// overlapping history; the benchmark itself is 1.0.
+    fn holding_beta(&mut self, symbol: &str, benchmark: &str) -> Result<Option<f64>> {
+        if symbol == benchmark {
+            return Ok(Some(1.0));
+        }
 
-        // Insert the backtest run
-        tx.execute(
-            r#"
-            INSERT INTO backtest_runs
-            (strategy_id, strategy_name, symbol, start_date, end_date,
-             initial_capital, final_capital, total_return, total_return_dollars,
-             max_drawdown, sharpe_ratio, win_rate, total_trades, winning_trades,
-             losing_trades, avg_win_percent, avg_loss_percent, profit_factor,
-             avg_trade_duration_days)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)
-            "#,
-            params![
-                result.strategy_id,
-                result.strategy_name,
-                result.symbol,
-                result.start_date.to_string(),
-                result.end_date.to_string(),
-                result.initial_capital,
-                result.final_capital,
-                result.metrics.total_return,
-                result.metrics.total_return_dollars,
-                result.metrics.max_drawdown,
-                result.metrics.sharpe_ratio,
-                result.metrics.win_rate,
-                result.metrics.total_trades as i64,
-                result.metrics.winning_trades as i64,
-                result.metrics.losing_trades as i64,
-                result.metrics.avg_win_percent,
-                result.metrics.avg_loss_percent,
-                result.metrics.profit_factor,
-                result.metrics.avg_trade_duration_days,
-            ],
+        let name = format!("BETA_{}_{}", benchmark, EXPOSURE_BETA_WINDOW);
+        let cached = self.get_indicator_history_window(symbol, &name, Some(1), 0)?;
+        if let (Some(beta), Some(last_bar)) = (cached.last(), self.get_last_price_date(symbol)?) {
+            if beta.date >= last_bar {
+                return Ok(Some(beta.value));
+            }
+        }
+
+        // Extra benchmark bars so its closes can be carried onto the first asset date
+        let prices = self.get_recent_prices(symbol, EXPOSURE_BETA_WINDOW + 1)?;
+        let benchmark_prices = self.get_recent_prices(benchmark, 2 * (EXPOSURE_BETA_WINDOW + 1))?;
+        let betas = calculate_rolling_beta(&prices, &benchmark_prices, EXPOSURE_BETA_WINDOW);
+        self.upsert_indicators(&betas)?;
+
+        Ok(betas.last().map(|b| b.value))
+    }
+
+    /// Delete a portfolio position
+    pub fn delete_position(&self, position_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM portfolio_positions WHERE id = ?1",
+            params![position_id],
         )?;
+        Ok(())
+    }
 
-        let backtest_id = tx.last_insert_rowid();
+    /// Store Google Trends data, one series per keyword and geo
+    pub fn upsert_trends(&mut self, data: &[TrendData]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
 
-        // Insert trades
         {
             let mut stmt = tx.prepare(
                 r#"
-                INSERT INTO backtest_trades
-                (backtest_id, symbol, direction, entry_date, entry_price, entry_reason,
-                 exit_date, exit_price, exit_reason, shares, profit_loss, profit_loss_percent)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                INSERT OR REPLACE INTO trends_data (keyword, geo, date, value)
+                VALUES (?1, ?2, ?3, ?4)
                 "#,
             )?;
 
-            for trade in &result.trades {
+            for point in data {
                 stmt.execute(params![
-                    backtest_id,
-                    trade.symbol,
-                    trade.direction.as_str(),
-                    trade.entry_date.to_string(),
-                    trade.entry_price,
-                    trade.entry_reason,
-                    trade.exit_date.map(|d| d.to_string()),
-                    trade.exit_price,
-                    trade.exit_reason,
-                    trade.shares,
-                    trade.profit_loss,
-                    trade.profit_loss_percent,
+                    point.keyword,
+                    point.geo,
+                    point.date.to_string(),
+                    point.value
                 ])?;
+                count += 1;
             }
         }
 
         tx.commit()?;
-        Ok(backtest_id)
+        Ok(count)
     }
 
-    /// Get backtest history
-    pub fn get_backtest_results(
-        &self,
-        strategy_name: Option<&str>,
-        symbol: Option<&str>,
-        limit: usize,
-    ) -> Result<Vec<BacktestResult>> {
-        let mut sql = String::from(
+    /// Get trends data for a keyword in one region (`""` for worldwide)
+    pub fn get_trends(&self, keyword: &str, geo: &str) -> Result<Vec<TrendData>> {
+        let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, strategy_id, strategy_name, symbol, start_date, end_date,
-                   initial_capital, final_capital, total_return, total_return_dollars,
-                   max_drawdown, sharpe_ratio, win_rate, total_trades, winning_trades,
-                   losing_trades, avg_win_percent, avg_loss_percent, profit_factor,
-                   avg_trade_duration_days, created_at
-            FROM backtest_runs
-            WHERE 1=1
+            SELECT keyword, geo, date, value
+            FROM trends_data
+            WHERE keyword = ?1 AND geo = ?2
+            ORDER BY date ASC
             "#,
-        );
-
-        if strategy_name.is_some() {
-            sql.push_str(" AND strategy_name = ?1");
-        }
-        if symbol.is_some() {
-            sql.push_str(if strategy_name.is_some() {
-                " AND symbol = ?2"
-            } else {
-                " AND symbol = ?1"
-            });
-        }
-
-        sql.push_str(" ORDER BY created_at DESC LIMIT ?");
-
-        let mut stmt = self.conn.prepare(&sql)?;
+        )?;
 
-        let results: Vec<BacktestResult> = match (strategy_name, symbol) {
-            (Some(strat), Some(sym)) => {
-                stmt.query_map(params![strat, sym, limit as i64], |row| self.map_backtest_row(row))?
-                    .collect::<SqliteResult<Vec<_>>>()?
-            }
-            (Some(strat), None) => {
-                stmt.query_map(params![strat, limit as i64], |row| self.map_backtest_row(row))?
-                    .collect::<SqliteResult<Vec<_>>>()?
-            }
-            (None, Some(sym)) => {
-                stmt.query_map(params![sym, limit as i64], |row| self.map_backtest_row(row))?
-                    .collect::<SqliteResult<Vec<_>>>()?
-            }
-            (None, None) => {
-                stmt.query_map(params![limit as i64], |row| self.map_backtest_row(row))?
-                    .collect::<SqliteResult<Vec<_>>>()?
-            }
-        };
+        let trends = stmt
+            .query_map(params![keyword, geo], |row| {
+                let date_str: String = row.get(2)?;
+                Ok(TrendData {
+                    keyword: row.get(0)?,
+                    geo: row.get(1)?,
+                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                    value: row.get(3)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
 
-        Ok(results)
+        Ok(trends)
     }
 
-    fn map_backtest_row(&self, row: &rusqlite::Row) -> SqliteResult<BacktestResult> {
-        let start_str: String = row.get(4)?;
-        let end_str: String = row.get(5)?;
-        let total_trades_i64: i64 = row.get(13)?;
-        let winning_trades_i64: i64 = row.get(14)?;
-        let losing_trades_i64: i64 = row.get(15)?;
-
-        Ok(BacktestResult {
-            id: row.get(0)?,
-            strategy_id: row.get(1)?,
-            strategy_name: row.get(2)?,
-            symbol: row.get(3)?,
-            start_date: NaiveDate::parse_from_str(&start_str, "%Y-%m-%d")
-                .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-            end_date: NaiveDate::parse_from_str(&end_str, "%Y-%m-%d")
-                .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-            initial_capital: row.get(6)?,
-            final_capital: row.get(7)?,
-            metrics: PerformanceMetrics {
-                total_return: row.get(8)?,
-                total_return_dollars: row.get(9)?,
-                max_drawdown: row.get(10)?,
-                sharpe_ratio: row.get(11)?,
-                win_rate: row.get(12)?,
-                total_trades: total_trades_i64 as usize,
-                winning_trades: winning_trades_i64 as usize,
-                losing_trades: losing_trades_i64 as usize,
-                avg_win_percent: row.get(16)?,
-                avg_loss_percent: row.get(17)?,
-                profit_factor: row.get(18)?,
-                avg_trade_duration_days: row.get(19)?,
-            },
-            trades: Vec::new(), // Trades loaded separately if needed
-            created_at: row.get(20)?,
-        })
-    }
+    // ========================================================================
+    // Signal Methods
+    // ========================================================================
 
-    /// Get backtest detail with trades
-    pub fn get_backtest_detail(&self, backtest_id: i64) -> Result<Option<BacktestResult>> {
-        let mut stmt = self.conn.prepare(
+    /// Store a signal
+    ///
+    /// A signal without a confirmation (`confirmed: None`) keeps the one
+    /// already stored for the same symbol, type and date.
+    pub fn upsert_signal(&self, signal: &Signal) -> Result<i64> {
+        self.conn.execute(
             r#"
-            SELECT id, strategy_id, strategy_name, symbol, start_date, end_date,
-                   initial_capital, final_capital, total_return, total_return_dollars,
-                   max_drawdown, sharpe_ratio, win_rate, total_trades, winning_trades,
-                   losing_trades, avg_win_percent, avg_loss_percent, profit_factor,
-                   avg_trade_duration_days, created_at
-            FROM backtest_runs
-            WHERE id = ?1
+            INSERT OR REPLACE INTO signals
+            (symbol, signal_type, direction, strength, price_at_signal,
+             triggered_by, trigger_value, timestamp, acknowledged, created_at, backfilled,
+             confirmed)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, COALESCE(NULLIF(?10, ''), CURRENT_TIMESTAMP), ?11,
+                    COALESCE(?12, (SELECT confirmed FROM signals
+                                   WHERE symbol = ?1 AND signal_type = ?2 AND timestamp = ?8)))
             "#,
+            params![
+                signal.symbol,
+                signal.signal_type.as_str(),
+                signal.direction.as_str(),
+                signal.strength,
+                signal.price_at_signal,
+                signal.triggered_by,
+                signal.trigger_value,
+                signal.timestamp.to_string(),
+                signal.acknowledged,
+                signal.created_at,
+                signal.backfilled,
+                signal.confirmed,
+            ],
         )?;
 
-        let result = stmt.query_row(params![backtest_id], |row| self.map_backtest_row(row));
-
-        let mut backtest = match result {
-            Ok(b) => b,
-            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
-            Err(e) => return Err(e.into()),
-        };
+        Ok(self.conn.last_insert_rowid())
+    }
 
-        // Load trades
-        let mut trade_stmt = self.conn.prepare(
-            r#"
-            SELECT id, backtest_id, symbol, direction, entry_date, entry_price, entry_reason,
-                   exit_date, exit_price, exit_reason, shares, profit_loss, profit_loss_percent
-            FROM backtest_trades
-            WHERE backtest_id = ?1
-            ORDER BY entry_date ASC
-            "#,
-        )?;
+    /// Batch store signals; see `upsert_signal` for how confirmation is kept
+    pub fn upsert_signals(&mut self, signals: &[Signal]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
 
-        let trades = trade_stmt
-            .query_map(params![backtest_id], |row| {
-                let dir_str: String = row.get(3)?;
-                let entry_str: String = row.get(4)?;
-                let exit_str: Option<String> = row.get(7)?;
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT OR REPLACE INTO signals
+                (symbol, signal_type, direction, strength, price_at_signal,
+                 triggered_by, trigger_value, timestamp, acknowledged, created_at, backfilled,
+                 confirmed)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, COALESCE(NULLIF(?10, ''), CURRENT_TIMESTAMP), ?11,
+                        COALESCE(?12, (SELECT confirmed FROM signals
+                                       WHERE symbol = ?1 AND signal_type = ?2 AND timestamp = ?8)))
+                "#,
+            )?;
 
-                Ok(BacktestTrade {
+            for signal in signals {
+                stmt.execute(params![
+                    signal.symbol,
+                    signal.signal_type.as_str(),
+                    signal.direction.as_str(),
+                    signal.strength,
+                    signal.price_at_signal,
+                    signal.triggered_by,
+                    signal.trigger_value,
+                    signal.timestamp.to_string(),
+                    signal.acknowledged,
+                    signal.created_at,
+                    signal.backfilled,
+                    signal.confirmed,
+                ])?;
+                count += 1;
+            }
+        }
+
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Get signals for a symbol
+    pub fn get_signals(&self, symbol: &str, only_unacknowledged: bool) -> Result<Vec<Signal>> {
+        let sql = if only_unacknowledged {
+            r#"
+            SELECT id, symbol, signal_type, direction, strength, price_at_signal,
+                   triggered_by, trigger_value, timestamp, created_at, acknowledged,
+                   backfilled, confirmed
+            FROM signals
+            WHERE symbol = ?1 AND acknowledged = 0
+            ORDER BY timestamp DESC
+            "#
+        } else {
+            r#"
+            SELECT id, symbol, signal_type, direction, strength, price_at_signal,
+                   triggered_by, trigger_value, timestamp, created_at, acknowledged,
+                   backfilled, confirmed
+            FROM signals
+            WHERE symbol = ?1
+            ORDER BY timestamp DESC
+            "#
+        };
+
+        let mut stmt = self.conn.prepare(sql)?;
+
+        let signals = stmt
+            .query_map(params![symbol], |row| {
+                let signal_type_str: String = row.get(2)?;
+                let direction_str: String = row.get(3)?;
+                let date_str: String = row.get(8)?;
+
+                Ok(Signal {
                     id: row.get(0)?,
-                    backtest_id: row.get(1)?,
-                    symbol: row.get(2)?,
-                    direction: TradeDirection::from_str(&dir_str),
-                    entry_date: NaiveDate::parse_from_str(&entry_str, "%Y-%m-%d")
+                    symbol: row.get(1)?,
+                    signal_type: SignalType::from_str(&signal_type_str)
+                        .unwrap_or(SignalType::RsiOversold),
+                    direction: SignalDirection::from_str(&direction_str),
+                    strength: row.get(4)?,
+                    price_at_signal: row.get(5)?,
+                    triggered_by: row.get(6)?,
+                    trigger_value: row.get(7)?,
+                    timestamp: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
                         .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
-                    entry_price: row.get(5)?,
-                    entry_reason: row.get(6)?,
-                    exit_date: exit_str.and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
-                    exit_price: row.get(8)?,
-                    exit_reason: row.get(9)?,
-                    shares: row.get(10)?,
-                    profit_loss: row.get(11)?,
-                    profit_loss_percent: row.get(12)?,
+                    created_at: row.get(9)?,
+                    acknowledged: row.get(10)?,
+                    backfilled: row.get(11)?,
+                    confirmed: row.get(12)?,
                 })
             })?
             .collect::<SqliteResult<Vec<_>>>()?;
 
-        backtest.trades = trades;
+        Ok(signals)
+    }
 
-        Ok(Some(backtest))
+    /// Get recent signals across all symbols
+    pub fn get_recent_signals(&self, limit: usize) -> Result<Vec<Signal>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, symbol, signal_type, direction, strength, price_at_signal,
+                   triggered_by, trigger_value, timestamp, created_at, acknowledged,
+                   backfilled, confirmed
+            FROM signals
+            ORDER BY timestamp DESC, strength DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let signals = stmt
+            .query_map(params![limit as i64], |row| {
+                let signal_type_str: String = row.get(2)?;
+                let direction_str: String = row.get(3)?;
+                let date_str: String = row.get(8)?;
+
+                Ok(Signal {
+                    id: row.get(0)?,
+                    symbol: row.get(1)?,
+                    signal_type: SignalType::from_str(&signal_type_str)
+                        .unwrap_or(SignalType::RsiOversold),
+                    direction: SignalDirection::from_str(&direction_str),
+                    strength: row.get(4)?,
+                    price_at_signal: row.get(5)?,
+                    triggered_by: row.get(6)?,
+                    trigger_value: row.get(7)?,
+                    timestamp: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                    created_at: row.get(9)?,
+                    acknowledged: row.get(10)?,
+                    backfilled: row.get(11)?,
+                    confirmed: row.get(12)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(signals)
     }
 
-    /// Delete a backtest result and its trades
-    pub fn delete_backtest(&self, backtest_id: i64) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
-        tx.execute(
-            "DELETE FROM backtest_trades WHERE backtest_id = ?1",
-            params![backtest_id],
+    /// Acknowledge a signal
+    pub fn acknowledge_signal(&self, signal_id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE signals SET acknowledged = 1 WHERE id = ?1",
+            params![signal_id],
         )?;
-        tx.execute(
-            "DELETE FROM backtest_runs WHERE id = ?1",
-            params![backtest_id],
+        Ok(())
+    }
+
+    /// Acknowledge all signals for a symbol
+    pub fn acknowledge_all_signals(&self, symbol: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE signals SET acknowledged = 1 WHERE symbol = ?1",
+            params![symbol],
         )?;
-        tx.commit()?;
         Ok(())
     }
-}
 
-/// Database schema SQL
-const SCHEMA_SQL: &str = r#"
--- Symbol master table
-CREATE TABLE IF NOT EXISTS symbols (
-    symbol TEXT PRIMARY KEY,
-    name TEXT,
-    sector TEXT,
-    industry TEXT,
-    market_cap REAL,
-    country TEXT,
-    exchange TEXT,
-    currency TEXT,
-    isin TEXT,
-    asset_class TEXT,
-    favorited INTEGER DEFAULT 0,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-);
+    /// Acknowledge open signals of one direction dated before `before`
+    pub fn acknowledge_signals_before(
+        &self,
+        symbol: &str,
+        direction: SignalDirection,
+        before: NaiveDate,
+    ) -> Result<usize> {
+        let updated = self.conn.execute(
+            r#"
+            UPDATE signals SET acknowledged = 1
+            WHERE symbol = ?1 AND direction = ?2 AND timestamp < ?3 AND acknowledged = 0
+            "#,
+            params![symbol, direction.as_str(), before.to_string()],
+        )?;
+        Ok(updated)
+    }
+
+    /// Delete old signals (cleanup)
+    pub fn cleanup_old_signals(&self, days: i64) -> Result<usize> {
+        let deleted = self.conn.execute(
+            "DELETE FROM signals WHERE timestamp < date('now', ?1)",
+            params![format!("-{} days", days)],
+        )?;
+        Ok(deleted)
+    }
+
+    /// Net signal score per symbol over recent unacknowledged signals
+    ///
+    /// Bullish signals add their strength, bearish signals subtract it and
+    /// neutral signals contribute zero. Ordered by score, highest first.
+    pub fn get_signal_scoreboard(&self, days: i64) -> Result<Vec<(String, f64)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol,
+                   SUM(CASE direction
+                           WHEN 'bullish' THEN strength
+                           WHEN 'bearish' THEN -strength
+                           ELSE 0
+                       END) AS score
+            FROM signals
+            WHERE acknowledged = 0 AND timestamp >= date('now', ?1)
+            GROUP BY symbol
+            ORDER BY score DESC, symbol ASC
+            "#,
+        )?;
+
+        let scores = stmt
+            .query_map(params![format!("-{} days", days)], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(scores)
+    }
+
+    /// Get all indicators for a symbol (for signal generation)
+    pub fn get_all_indicators(&self, symbol: &str) -> Result<Vec<TechnicalIndicator>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, timestamp, indicator_name, value
+            FROM technical_indicators
+            WHERE symbol = ?1
+            ORDER BY timestamp ASC
+            "#,
+        )?;
+
+        let indicators = stmt
+            .query_map(params![symbol], |row| {
+                let date_str: String = row.get(1)?;
+                Ok(TechnicalIndicator {
+                    symbol: row.get(0)?,
+                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                    indicator_name: row.get(2)?,
+                    value: row.get(3)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(indicators)
+    }
+
+    // ========================================================================
+    // Indicator Alert Methods
+    // ========================================================================
+
+    /// Add an indicator alert
+    pub fn add_indicator_alert(&self, alert: &IndicatorAlert) -> Result<i64> {
+        self.conn.execute(
+            r#"
+            INSERT INTO indicator_alerts
+            (symbol, alert_type, indicator_name, secondary_indicator, condition, threshold, message,
+             recurring)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+            params![
+                alert.symbol,
+                alert.alert_type.as_str(),
+                alert.indicator_name,
+                alert.secondary_indicator,
+                alert.condition.as_str(),
+                alert.threshold,
+                alert.message,
+                alert.recurring,
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get all indicator alerts
+    pub fn get_indicator_alerts(&self, only_active: bool) -> Result<Vec<IndicatorAlert>> {
+        let sql = if only_active {
+            r#"
+            SELECT id, symbol, alert_type, indicator_name, secondary_indicator,
+                   condition, threshold, triggered, last_value, created_at, message,
+                   recurring, last_triggered_at
+            FROM indicator_alerts
+            WHERE triggered = 0
+            ORDER BY created_at DESC
+            "#
+        } else {
+            r#"
+            SELECT id, symbol, alert_type, indicator_name, secondary_indicator,
+                   condition, threshold, triggered, last_value, created_at, message,
+                   recurring, last_triggered_at
+            FROM indicator_alerts
+            ORDER BY created_at DESC
+            "#
+        };
+
+        let mut stmt = self.conn.prepare(sql)?;
+
+        let alerts = stmt
+            .query_map([], |row| {
+                let alert_type_str: String = row.get(2)?;
+                let condition_str: String = row.get(5)?;
+
+                Ok(IndicatorAlert {
+                    id: row.get(0)?,
+                    symbol: row.get(1)?,
+                    alert_type: IndicatorAlertType::from_str(&alert_type_str)
+                        .unwrap_or(IndicatorAlertType::Threshold),
+                    indicator_name: row.get(3)?,
+                    secondary_indicator: row.get(4)?,
+                    condition: IndicatorAlertCondition::from_str(&condition_str)
+                        .unwrap_or(IndicatorAlertCondition::CrossesAbove),
+                    threshold: row.get(6)?,
+                    triggered: row.get(7)?,
+                    last_value: row.get(8)?,
+                    created_at: row.get(9)?,
+                    message: row.get(10)?,
+                    recurring: row.get(11)?,
+                    last_triggered_at: row.get(12)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(alerts)
+    }
+
+    /// Delete an indicator alert
+    pub fn delete_indicator_alert(&self, alert_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM indicator_alerts WHERE id = ?1",
+            params![alert_id],
+        )?;
+        Ok(())
+    }
+
+    /// Mark an indicator alert as triggered
+    pub fn trigger_indicator_alert(&self, alert_id: i64) -> Result<()> {
+        self.mark_triggered("indicator_alerts", alert_id)?;
+        Ok(())
+    }
+
+    /// Update last_value for an indicator alert
+    pub fn update_indicator_alert_state(&self, alert_id: i64, last_value: f64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE indicator_alerts SET last_value = ?1 WHERE id = ?2",
+            params![last_value, alert_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get the latest value for a specific indicator
+    pub fn get_latest_indicator_value(&self, symbol: &str, indicator_name: &str) -> Result<Option<f64>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT value FROM technical_indicators
+            WHERE symbol = ?1 AND indicator_name = ?2
+            ORDER BY timestamp DESC
+            LIMIT 1
+            "#,
+        )?;
+
+        let result: SqliteResult<f64> = stmt.query_row(params![symbol, indicator_name], |row| row.get(0));
+
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get the previous (second-to-last) indicator value
+    pub fn get_previous_indicator_value(&self, symbol: &str, indicator_name: &str) -> Result<Option<f64>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT value FROM technical_indicators
+            WHERE symbol = ?1 AND indicator_name = ?2
+            ORDER BY timestamp DESC
+            LIMIT 1 OFFSET 1
+            "#,
+        )?;
+
+        let result: SqliteResult<f64> = stmt.query_row(params![symbol, indicator_name], |row| row.get(0));
+
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Check all indicator alerts, returns triggered alerts
+    ///
+    /// A recurring alert that has fired is re-armed on the first check where
+    /// its crossing no longer holds, so it fires again on the next crossing.
+    /// `StaysAbove`/`StaysBelow` compare the current values on every check,
+    /// so they fire as soon as the state holds (even if it already did when
+    /// the alert was created) and, when recurring, once per stretch in which
+    /// it holds. Band-touch alerts compare the latest close with the band instead; see
+    /// `band_touched`.
+    pub fn check_indicator_alerts(&self) -> Result<Vec<IndicatorAlert>> {
+        let alerts = self.get_indicator_alerts(false)?;
+        let mut triggered_alerts = Vec::new();
+
+        for alert in alerts.into_iter().filter(|a| !a.triggered || a.recurring) {
+            let current = self.get_latest_indicator_value(&alert.symbol, &alert.indicator_name)?;
+            let previous = alert.last_value.or_else(|| {
+                self.get_previous_indicator_value(&alert.symbol, &alert.indicator_name).ok().flatten()
+            });
+
+            let Some(current_val) = current else {
+                continue;
+            };
+
+            let should_trigger = match alert.condition {
+                _ if alert.alert_type == IndicatorAlertType::BandTouch => {
+                    self.band_touched(&alert, current_val)?
+                }
+                IndicatorAlertCondition::CrossesAbove => {
+                    if let (Some(prev), Some(threshold)) = (previous, alert.threshold) {
+                        prev < threshold && current_val >= threshold
+                    } else {
+                        false
+                    }
+                }
+                IndicatorAlertCondition::CrossesBelow => {
+                    if let (Some(prev), Some(threshold)) = (previous, alert.threshold) {
+                        prev > threshold && current_val <= threshold
+                    } else {
+                        false
+                    }
+                }
+                IndicatorAlertCondition::BullishCrossover => {
+                    if let Some(secondary) = &alert.secondary_indicator {
+                        let secondary_current = self.get_latest_indicator_value(&alert.symbol, secondary)?;
+                        let secondary_prev = self.get_previous_indicator_value(&alert.symbol, secondary)?;
+
+                        match (previous, secondary_current, secondary_prev) {
+                            (Some(prev_primary), Some(curr_sec), Some(prev_sec)) => {
+                                prev_primary <= prev_sec && current_val > curr_sec
+                            }
+                            _ => false,
+                        }
+                    } else {
+                        false
+                    }
+                }
+                IndicatorAlertCondition::BearishCrossover => {
+                    if let Some(secondary) = &alert.secondary_indicator {
+                        let secondary_current = self.get_latest_indicator_value(&alert.symbol, secondary)?;
+                        let secondary_prev = self.get_previous_indicator_value(&alert.symbol, secondary)?;
+
+                        match (previous, secondary_current, secondary_prev) {
+                            (Some(prev_primary), Some(curr_sec), Some(prev_sec)) => {
+                                prev_primary >= prev_sec && current_val < curr_sec
+                            }
+                            _ => false,
+                        }
+                    } else {
+                        false
+                    }
+                }
+                IndicatorAlertCondition::StaysAbove | IndicatorAlertCondition::StaysBelow => {
+                    let reference = match &alert.secondary_indicator {
+                        Some(secondary) => {
+                            self.get_latest_indicator_value(&alert.symbol, secondary)?
+                        }
+                        None => alert.threshold,
+                    };
+                    match reference {
+                        Some(r) if alert.condition == IndicatorAlertCondition::StaysAbove => {
+                            current_val > r
+                        }
+                        Some(r) => current_val < r,
+                        None => false,
+                    }
+                }
+            };
+
+            // Update last_value for next check
+            self.update_indicator_alert_state(alert.id, current_val)?;
+
+            if should_trigger && !alert.triggered {
+                let last_triggered_at = self.mark_triggered("indicator_alerts", alert.id)?;
+                triggered_alerts.push(IndicatorAlert {
+                    triggered: true,
+                    last_value: Some(current_val),
+                    last_triggered_at: Some(last_triggered_at),
+                    ..alert
+                });
+            } else if !should_trigger && alert.triggered {
+                self.conn.execute(
+                    "UPDATE indicator_alerts SET triggered = 0 WHERE id = ?1",
+                    params![alert.id],
+                )?;
+            }
+        }
+
+        self.notify_triggered(
+            triggered_alerts
+                .iter()
+                .cloned()
+                .map(AlertNotification::Indicator),
+        )?;
+        Ok(triggered_alerts)
+    }
+
+    /// Whether the symbol's latest close touches or breaches a band alert's
+    /// band (`indicator_name`, e.g. `BB_UPPER_20`, valued at `band`)
+    ///
+    /// Upward conditions (`crosses_above`, `bullish_crossover`,
+    /// `stays_above`) fire on a close at or above the band, downward ones at
+    /// or below it. False without a stored close.
+    fn band_touched(&self, alert: &IndicatorAlert, band: f64) -> Result<bool> {
+        let Some(close) = self.get_latest_price(&alert.symbol)? else {
+            return Ok(false);
+        };
+        Ok(match alert.condition {
+            IndicatorAlertCondition::CrossesAbove
+            | IndicatorAlertCondition::BullishCrossover
+            | IndicatorAlertCondition::StaysAbove => close >= band,
+            IndicatorAlertCondition::CrossesBelow
+            | IndicatorAlertCondition::BearishCrossover
+            | IndicatorAlertCondition::StaysBelow => close <= band,
+        })
+    }
+
+    // ========================================================================
+    // Backtest Methods
+    // ========================================================================
+
+    /// Save a strategy
+    pub fn save_strategy(&self, strategy: &Strategy) -> Result<i64> {
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO strategies
+            (name, description, entry_condition, entry_threshold,
+             exit_condition, exit_threshold,
+             stop_loss_percent, take_profit_percent, position_size_percent, direction,
+             trailing_stop_percent, entry_rules, exit_rules, position_sizing)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            "#,
+            params![
+                strategy.name,
+                strategy.description,
+                strategy.entry_condition.as_str(),
+                strategy.entry_threshold,
+                strategy.exit_condition.as_str(),
+                strategy.exit_threshold,
+                strategy.stop_loss_percent,
+                strategy.take_profit_percent,
+                strategy.position_size_percent,
+                strategy.direction.as_str(),
+                strategy.trailing_stop_percent,
+                strategy.entry_rules.as_ref().map(serde_json::to_string).transpose()?,
+                strategy.exit_rules.as_ref().map(serde_json::to_string).transpose()?,
+                strategy.position_sizing.as_ref().map(serde_json::to_string).transpose()?,
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get all strategies
+    pub fn get_strategies(&self) -> Result<Vec<Strategy>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, name, description, entry_condition, entry_threshold,
+                   exit_condition, exit_threshold,
+                   stop_loss_percent, take_profit_percent, position_size_percent, created_at,
+                   direction, trailing_stop_percent, entry_rules, exit_rules, position_sizing
+            FROM strategies
+            ORDER BY name ASC
+            "#,
+        )?;
+
+        let strategies = stmt
+            .query_map([], |row| {
+                let entry_cond_str: String = row.get(3)?;
+                let exit_cond_str: String = row.get(5)?;
+
+                Ok(Strategy {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    entry_condition: StrategyConditionType::from_str(&entry_cond_str)
+                        .unwrap_or(StrategyConditionType::RsiOversold),
+                    entry_threshold: row.get(4)?,
+                    exit_condition: StrategyConditionType::from_str(&exit_cond_str)
+                        .unwrap_or(StrategyConditionType::RsiOverbought),
+                    exit_threshold: row.get(6)?,
+                    stop_loss_percent: row.get(7)?,
+                    take_profit_percent: row.get(8)?,
+                    trailing_stop_percent: row.get(12)?,
+                    position_size_percent: row.get(9)?,
+                    direction: TradeDirection::from_str(&row.get::<_, String>(11)?),
+                    entry_rules: json_column(row, 13)?,
+                    exit_rules: json_column(row, 14)?,
+                    position_sizing: json_column(row, 15)?,
+                    created_at: row.get(10)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(strategies)
+    }
+
+    /// Get a strategy by name
+    pub fn get_strategy(&self, name: &str) -> Result<Option<Strategy>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, name, description, entry_condition, entry_threshold,
+                   exit_condition, exit_threshold,
+                   stop_loss_percent, take_profit_percent, position_size_percent, created_at,
+                   direction, trailing_stop_percent, entry_rules, exit_rules, position_sizing
+            FROM strategies
+            WHERE name = ?1
+            "#,
+        )?;
+
+        let result = stmt.query_row(params![name], |row| {
+            let entry_cond_str: String = row.get(3)?;
+            let exit_cond_str: String = row.get(5)?;
+
+            Ok(Strategy {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                entry_condition: StrategyConditionType::from_str(&entry_cond_str)
+                    .unwrap_or(StrategyConditionType::RsiOversold),
+                entry_threshold: row.get(4)?,
+                exit_condition: StrategyConditionType::from_str(&exit_cond_str)
+                    .unwrap_or(StrategyConditionType::RsiOverbought),
+                exit_threshold: row.get(6)?,
+                stop_loss_percent: row.get(7)?,
+                take_profit_percent: row.get(8)?,
+                trailing_stop_percent: row.get(12)?,
+                position_size_percent: row.get(9)?,
+                direction: TradeDirection::from_str(&row.get::<_, String>(11)?),
+                entry_rules: json_column(row, 13)?,
+                exit_rules: json_column(row, 14)?,
+                position_sizing: json_column(row, 15)?,
+                created_at: row.get(10)?,
+            })
+        });
+
+        match result {
+            Ok(strategy) => Ok(Some(strategy)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete a strategy
+    pub fn delete_strategy(&self, name: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM strategies WHERE name = ?1", params![name])?;
+        Ok(())
+    }
+
+    /// Save a backtest result
+    pub fn save_backtest_result(&self, result: &BacktestResult) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        // Insert the backtest run
+        tx.execute(
+            r#"
+            INSERT INTO backtest_runs
+            (strategy_id, strategy_name, symbol, start_date, end_date,
+             initial_capital, final_capital, total_return, total_return_dollars,
+             max_drawdown, sharpe_ratio, win_rate, total_trades, winning_trades,
+             losing_trades, avg_win_percent, avg_loss_percent, profit_factor,
+             avg_trade_duration_days, trades_per_year, time_in_market_percent, information_ratio,
+             sortino_ratio, calmar_ratio)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)
+            "#,
+            params![
+                result.strategy_id,
+                result.strategy_name,
+                result.symbol,
+                result.start_date.to_string(),
+                result.end_date.to_string(),
+                result.initial_capital,
+                result.final_capital,
+                result.metrics.total_return,
+                result.metrics.total_return_dollars,
+                result.metrics.max_drawdown,
+                result.metrics.sharpe_ratio,
+                result.metrics.win_rate,
+                result.metrics.total_trades as i64,
+                result.metrics.winning_trades as i64,
+                result.metrics.losing_trades as i64,
+                result.metrics.avg_win_percent,
+                result.metrics.avg_loss_percent,
+                result.metrics.profit_factor,
+                result.metrics.avg_trade_duration_days,
+                result.metrics.trades_per_year,
+                result.metrics.time_in_market_percent,
+                result.metrics.information_ratio,
+                result.metrics.sortino_ratio,
+                result.metrics.calmar_ratio,
+            ],
+        )?;
+
+        let backtest_id = tx.last_insert_rowid();
+
+        // Insert trades
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT INTO backtest_trades
+                (backtest_id, symbol, direction, entry_date, entry_price, entry_reason,
+                 exit_date, exit_price, exit_reason, shares, profit_loss, profit_loss_percent)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                "#,
+            )?;
+
+            for trade in &result.trades {
+                stmt.execute(params![
+                    backtest_id,
+                    trade.symbol,
+                    trade.direction.as_str(),
+                    trade.entry_date.to_string(),
+                    trade.entry_price,
+                    trade.entry_reason,
+                    trade.exit_date.map(|d| d.to_string()),
+                    trade.exit_price,
+                    trade.exit_reason,
+                    trade.shares,
+                    trade.profit_loss,
+                    trade.profit_loss_percent,
+                ])?;
+            }
+        }
+
+        // Insert equity curve
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO backtest_equity (backtest_id, date, equity) VALUES (?1, ?2, ?3)",
+            )?;
+
+            for (date, equity) in &result.equity_curve {
+                stmt.execute(params![backtest_id, date.to_string(), equity])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(backtest_id)
+    }
+
+    /// Get backtest history
+    pub fn get_backtest_results(
+        &self,
+        strategy_name: Option<&str>,
+        symbol: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<BacktestResult>> {
+        let mut sql = String::from(
+            r#"
+            SELECT id, strategy_id, strategy_name, symbol, start_date, end_date,
+                   initial_capital, final_capital, total_return, total_return_dollars,
+                   max_drawdown, sharpe_ratio, win_rate, total_trades, winning_trades,
+                   losing_trades, avg_win_percent, avg_loss_percent, profit_factor,
+                   avg_trade_duration_days, created_at, trades_per_year, time_in_market_percent,
+                   information_ratio, sortino_ratio, calmar_ratio
+            FROM backtest_runs
+            WHERE 1=1
+            "#,
+        );
+
+        if strategy_name.is_some() {
+            sql.push_str(" AND strategy_name = ?1");
+        }
+        if symbol.is_some() {
+            sql.push_str(if strategy_name.is_some() {
+                " AND symbol = ?2"
+            } else {
+                " AND symbol = ?1"
+            });
+        }
+
+        sql.push_str(" ORDER BY created_at DESC LIMIT ?");
+
+        let mut stmt = self.conn.prepare(&sql)?;
+
+        let results: Vec<BacktestResult> = match (strategy_name, symbol) {
+            (Some(strat), Some(sym)) => {
+                stmt.query_map(params![strat, sym, limit as i64], |row| self.map_backtest_row(row))?
+                    .collect::<SqliteResult<Vec<_>>>()?
+            }
+            (Some(strat), None) => {
+                stmt.query_map(params![strat, limit as i64], |row| self.map_backtest_row(row))?
+                    .collect::<SqliteResult<Vec<_>>>()?
+            }
+            (None, Some(sym)) => {
+                stmt.query_map(params![sym, limit as i64], |row| self.map_backtest_row(row))?
+                    .collect::<SqliteResult<Vec<_>>>()?
+            }
+            (None, None) => {
+                stmt.query_map(params![limit as i64], |row| self.map_backtest_row(row))?
+                    .collect::<SqliteResult<Vec<_>>>()?
+            }
+        };
+
+        Ok(results)
+    }
+
+    fn map_backtest_row(&self, row: &rusqlite::Row) -> SqliteResult<BacktestResult> {
+        let start_str: String = row.get(4)?;
+        let end_str: String = row.get(5)?;
+        let total_trades_i64: i64 = row.get(13)?;
+        let winning_trades_i64: i64 = row.get(14)?;
+        let losing_trades_i64: i64 = row.get(15)?;
+
+        Ok(BacktestResult {
+            id: row.get(0)?,
+            strategy_id: row.get(1)?,
+            strategy_name: row.get(2)?,
+            symbol: row.get(3)?,
+            start_date: NaiveDate::parse_from_str(&start_str, "%Y-%m-%d")
+                .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+            end_date: NaiveDate::parse_from_str(&end_str, "%Y-%m-%d")
+                .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+            initial_capital: row.get(6)?,
+            final_capital: row.get(7)?,
+            metrics: PerformanceMetrics {
+                total_return: row.get(8)?,
+                total_return_dollars: row.get(9)?,
+                max_drawdown: row.get(10)?,
+                sharpe_ratio: row.get(11)?,
+                win_rate: row.get(12)?,
+                total_trades: total_trades_i64 as usize,
+                winning_trades: winning_trades_i64 as usize,
+                losing_trades: losing_trades_i64 as usize,
+                avg_win_percent: row.get(16)?,
+                avg_loss_percent: row.get(17)?,
+                profit_factor: row.get(18)?,
+                avg_trade_duration_days: row.get(19)?,
+                trades_per_year: row.get(21)?,
+                time_in_market_percent: row.get(22)?,
+                information_ratio: row.get(23)?,
+                sortino_ratio: row.get(24)?,
+                calmar_ratio: row.get(25)?,
+            },
+            trades: Vec::new(), // Trades loaded separately if needed
+            created_at: row.get(20)?,
+            skipped_bars: 0,
+            warnings: Vec::new(),
+            equity_curve: Vec::new(), // Loaded separately with the detail
+        })
+    }
+
+    /// Get backtest detail with trades
+    pub fn get_backtest_detail(&self, backtest_id: i64) -> Result<Option<BacktestResult>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, strategy_id, strategy_name, symbol, start_date, end_date,
+                   initial_capital, final_capital, total_return, total_return_dollars,
+                   max_drawdown, sharpe_ratio, win_rate, total_trades, winning_trades,
+                   losing_trades, avg_win_percent, avg_loss_percent, profit_factor,
+                   avg_trade_duration_days, created_at, trades_per_year, time_in_market_percent,
+                   information_ratio, sortino_ratio, calmar_ratio
+            FROM backtest_runs
+            WHERE id = ?1
+            "#,
+        )?;
+
+        let result = stmt.query_row(params![backtest_id], |row| self.map_backtest_row(row));
+
+        let mut backtest = match result {
+            Ok(b) => b,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        // Load trades
+        let mut trade_stmt = self.conn.prepare(
+            r#"
+            SELECT id, backtest_id, symbol, direction, entry_date, entry_price, entry_reason,
+                   exit_date, exit_price, exit_reason, shares, profit_loss, profit_loss_percent
+            FROM backtest_trades
+            WHERE backtest_id = ?1
+            ORDER BY entry_date ASC
+            "#,
+        )?;
+
+        let trades = trade_stmt
+            .query_map(params![backtest_id], |row| {
+                let dir_str: String = row.get(3)?;
+                let entry_str: String = row.get(4)?;
+                let exit_str: Option<String> = row.get(7)?;
+
+                Ok(BacktestTrade {
+                    id: row.get(0)?,
+                    backtest_id: row.get(1)?,
+                    symbol: row.get(2)?,
+                    direction: TradeDirection::from_str(&dir_str),
+                    entry_date: NaiveDate::parse_from_str(&entry_str, "%Y-%m-%d")
+                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                    entry_price: row.get(5)?,
+                    entry_reason: row.get(6)?,
+                    exit_date: exit_str.and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
+                    exit_price: row.get(8)?,
+                    exit_reason: row.get(9)?,
+                    shares: row.get(10)?,
+                    profit_loss: row.get(11)?,
+                    profit_loss_percent: row.get(12)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        backtest.trades = trades;
+
+        // Load equity curve
+        let mut equity_stmt = self.conn.prepare(
+            "SELECT date, equity FROM backtest_equity WHERE backtest_id = ?1 ORDER BY date ASC",
+        )?;
+        backtest.equity_curve = equity_stmt
+            .query_map(params![backtest_id], |row| {
+                let date_str: String = row.get(0)?;
+                Ok((
+                    NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+                    row.get(1)?,
+                ))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(Some(backtest))
+    }
+
+    /// Export a saved backtest with its metrics and trades
+    ///
+    /// JSON serializes the `BacktestResult` from `get_backtest_detail` as-is,
+    /// so it can be read back with `serde_json` (non-finite metrics, such as
+    /// the profit factor of a run with no losses, come out as `null`, which
+    /// won't parse back). CSV writes the run and its
+    /// metrics as `metric,value` rows, a blank line, then a flat trades table.
+    /// Returns false if there is no backtest with this id.
+    pub fn export_backtest<W: Write>(
+        &self,
+        backtest_id: i64,
+        format: BacktestExportFormat,
+        mut writer: W,
+    ) -> Result<bool> {
+        let Some(result) = self.get_backtest_detail(backtest_id)? else {
+            return Ok(false);
+        };
+
+        match format {
+            BacktestExportFormat::Json => {
+                serde_json::to_writer_pretty(&mut writer, &result)?;
+                writeln!(writer)?;
+            }
+            BacktestExportFormat::Csv => write_backtest_csv(&result, &mut writer)?,
+        }
+        writer.flush()?;
+
+        Ok(true)
+    }
+
+    /// Delete a backtest result with its trades and equity curve
+    pub fn delete_backtest(&self, backtest_id: i64) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM backtest_trades WHERE backtest_id = ?1",
+            params![backtest_id],
+        )?;
+        tx.execute(
+            "DELETE FROM backtest_equity WHERE backtest_id = ?1",
+            params![backtest_id],
+        )?;
+        tx.execute(
+            "DELETE FROM backtest_runs WHERE id = ?1",
+            params![backtest_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Read a nullable JSON column, such as a strategy's rules or sizing mode
+fn json_column<T: DeserializeOwned>(row: &rusqlite::Row, idx: usize) -> SqliteResult<Option<T>> {
+    let json: Option<String> = row.get(idx)?;
+    json.map(|j| {
+        serde_json::from_str(&j).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+    })
+    .transpose()
+}
+
+/// SQLite treats a negative LIMIT as "no limit"
+fn sql_limit(limit: Option<usize>) -> i64 {
+    limit.map_or(-1, |n| n as i64)
+}
+
+/// A numbered schema change, applied once in version order
+///
+/// Each step is idempotent: a column is only added when missing. New tables
+/// don't need a migration, since `SCHEMA_SQL` creates them with
+/// `IF NOT EXISTS` on every `init_schema`.
+struct Migration {
+    version: u32,
+    description: &'static str,
+    /// `(table, column, definition)` for each `ALTER TABLE ... ADD COLUMN`
+    add_columns: &'static [(&'static str, &'static str, &'static str)],
+    /// Tables recreated from their current `SCHEMA_SQL` definition, for
+    /// changes `ALTER TABLE` can't make such as a new CHECK constraint
+    rebuild_tables: &'static [&'static str],
+}
+
+/// Schema history; append new migrations with the next version number and
+/// also reflect the change in `SCHEMA_SQL` for fresh databases
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "favorite symbols",
+        add_columns: &[("symbols", "favorited", "INTEGER DEFAULT 0")],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 2,
+        description: "adjusted close on daily prices",
+        add_columns: &[("daily_prices", "adjusted_close", "REAL")],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 3,
+        description: "backfilled signals",
+        add_columns: &[("signals", "backfilled", "BOOLEAN DEFAULT 0")],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 4,
+        description: "trade frequency, exposure and information ratio metrics",
+        add_columns: &[
+            (
+                "backtest_runs",
+                "trades_per_year",
+                "REAL NOT NULL DEFAULT 0",
+            ),
+            (
+                "backtest_runs",
+                "time_in_market_percent",
+                "REAL NOT NULL DEFAULT 0",
+            ),
+            (
+                "backtest_runs",
+                "information_ratio",
+                "REAL NOT NULL DEFAULT 0",
+            ),
+        ],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 5,
+        description: "long or short strategies",
+        add_columns: &[("strategies", "direction", "TEXT NOT NULL DEFAULT 'long'")],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 6,
+        description: "trailing stops",
+        add_columns: &[("strategies", "trailing_stop_percent", "REAL")],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 7,
+        description: "Sortino and Calmar ratios",
+        add_columns: &[
+            ("backtest_runs", "sortino_ratio", "REAL NOT NULL DEFAULT 0"),
+            ("backtest_runs", "calmar_ratio", "REAL NOT NULL DEFAULT 0"),
+        ],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 8,
+        description: "multi-condition strategy rules",
+        add_columns: &[
+            ("strategies", "entry_rules", "TEXT"),
+            ("strategies", "exit_rules", "TEXT"),
+        ],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 9,
+        description: "strategy position sizing modes",
+        add_columns: &[("strategies", "position_sizing", "TEXT")],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 10,
+        description: "percent-change price alerts",
+        add_columns: &[
+            ("price_alerts", "base_price", "REAL"),
+            ("price_alerts", "percent_change", "REAL"),
+        ],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 11,
+        description: "recurring alerts",
+        add_columns: &[
+            ("price_alerts", "recurring", "BOOLEAN DEFAULT 0"),
+            ("price_alerts", "last_triggered_at", "TIMESTAMP"),
+            ("indicator_alerts", "recurring", "BOOLEAN DEFAULT 0"),
+            ("indicator_alerts", "last_triggered_at", "TIMESTAMP"),
+        ],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 12,
+        description: "covered call and cash-secured put positions",
+        add_columns: &[
+            ("portfolio_positions", "option_strategy", "TEXT"),
+            ("portfolio_positions", "strike", "REAL"),
+            ("portfolio_positions", "expiration", "TEXT"),
+        ],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 13,
+        description: "stays above/below indicator alert conditions",
+        add_columns: &[],
+        rebuild_tables: &["indicator_alerts"],
+    },
+    Migration {
+        version: 14,
+        description: "higher-timeframe confirmed signals",
+        add_columns: &[("signals", "confirmed", "BOOLEAN")],
+        rebuild_tables: &[],
+    },
+    Migration {
+        version: 15,
+        description: "regional Google Trends series",
+        add_columns: &[],
+        rebuild_tables: &["trends_data"],
+    },
+];
+
+/// Tracks which migrations have been applied
+const SCHEMA_VERSION_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    description TEXT,
+    applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
+/// Database schema SQL
+const SCHEMA_SQL: &str = r#"
+-- Symbol master table
+CREATE TABLE IF NOT EXISTS symbols (
+    symbol TEXT PRIMARY KEY,
+    name TEXT,
+    sector TEXT,
+    industry TEXT,
+    market_cap REAL,
+    country TEXT,
+    exchange TEXT,
+    currency TEXT,
+    isin TEXT,
+    asset_class TEXT,
+    favorited INTEGER DEFAULT 0,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+
+-- Daily price data
+CREATE TABLE IF NOT EXISTS daily_prices (
+    symbol TEXT,
+    timestamp DATE,
+    open REAL,
+    high REAL,
+    low REAL,
+    close REAL,
+    volume INTEGER,
+    adjusted_close REAL,
+    source TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (symbol, timestamp)
+);
+
+-- Every daily bar as each source delivered it; daily_prices holds the
+-- preferred one per date
+CREATE TABLE IF NOT EXISTS source_prices (
+    symbol TEXT NOT NULL,
+    timestamp DATE NOT NULL,
+    source TEXT NOT NULL,
+    open REAL,
+    high REAL,
+    low REAL,
+    close REAL,
+    volume INTEGER,
+    adjusted_close REAL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (symbol, timestamp, source)
+);
+
+-- Bars stored before per-source history existed (runs once, while empty)
+INSERT INTO source_prices
+(symbol, timestamp, source, open, high, low, close, volume, adjusted_close, created_at)
+SELECT symbol, timestamp, COALESCE(source, 'unknown'), open, high, low, close,
+       volume, adjusted_close, created_at
+FROM daily_prices
+WHERE NOT EXISTS (SELECT 1 FROM source_prices);
+
+-- Intraday price bars (UTC timestamps)
+CREATE TABLE IF NOT EXISTS intraday_prices (
+    symbol TEXT,
+    interval TEXT,
+    timestamp TIMESTAMP,
+    open REAL,
+    high REAL,
+    low REAL,
+    close REAL,
+    volume INTEGER,
+    source TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (symbol, interval, timestamp)
+);
+
+-- Cash dividends by ex-dividend date
+CREATE TABLE IF NOT EXISTS dividends (
+    symbol TEXT,
+    ex_date DATE,
+    amount REAL,
+    source TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (symbol, ex_date)
+);
+
+-- Stock splits (numerator new shares per denominator old)
+CREATE TABLE IF NOT EXISTS splits (
+    symbol TEXT,
+    date DATE,
+    numerator REAL,
+    denominator REAL,
+    source TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (symbol, date)
+);
+
+-- Macro economic indicators
+CREATE TABLE IF NOT EXISTS macro_data (
+    indicator TEXT,
+    date DATE,
+    value REAL,
+    frequency TEXT,
+    source TEXT DEFAULT 'FRED',
+    PRIMARY KEY (indicator, date)
+);
+
+-- Watchlists
+CREATE TABLE IF NOT EXISTS watchlists (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE,
+    description TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS watchlist_symbols (
+    watchlist_id INTEGER,
+    symbol TEXT,
+    added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    notes TEXT,
+    PRIMARY KEY (watchlist_id, symbol),
+    FOREIGN KEY (watchlist_id) REFERENCES watchlists(id)
+);
+
+-- API call tracking
+CREATE TABLE IF NOT EXISTS api_calls (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source TEXT,
+    endpoint TEXT,
+    symbol TEXT,
+    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+    success BOOLEAN,
+    error_message TEXT
+);
+
+-- Indexes
+CREATE INDEX IF NOT EXISTS idx_prices_symbol ON daily_prices(symbol);
+CREATE INDEX IF NOT EXISTS idx_prices_timestamp ON daily_prices(timestamp);
+CREATE INDEX IF NOT EXISTS idx_prices_source ON daily_prices(source);
+CREATE INDEX IF NOT EXISTS idx_symbols_sector ON symbols(sector);
+CREATE INDEX IF NOT EXISTS idx_macro_indicator ON macro_data(indicator);
+CREATE INDEX IF NOT EXISTS idx_macro_date ON macro_data(date);
+CREATE INDEX IF NOT EXISTS idx_api_calls_source ON api_calls(source);
+CREATE INDEX IF NOT EXISTS idx_api_calls_timestamp ON api_calls(timestamp);
+
+-- Views
+CREATE VIEW IF NOT EXISTS latest_prices AS
+SELECT p.*
+FROM daily_prices p
+INNER JOIN (
+    SELECT symbol, MAX(timestamp) as max_date
+    FROM daily_prices
+    GROUP BY symbol
+) latest ON p.symbol = latest.symbol AND p.timestamp = latest.max_date;
+
+CREATE VIEW IF NOT EXISTS api_rate_limits AS
+SELECT
+    source,
+    COUNT(*) as calls_today,
+    MAX(timestamp) as last_call
+FROM api_calls
+WHERE DATE(timestamp) = DATE('now')
+GROUP BY source;
+
+-- Technical indicators
+CREATE TABLE IF NOT EXISTS technical_indicators (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    timestamp DATE NOT NULL,
+    indicator_name TEXT NOT NULL,
+    value REAL NOT NULL,
+    params TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(symbol, timestamp, indicator_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ti_symbol_date ON technical_indicators(symbol, timestamp);
+CREATE INDEX IF NOT EXISTS idx_ti_indicator ON technical_indicators(indicator_name);
+
+-- Price alerts
+CREATE TABLE IF NOT EXISTS price_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    target_price REAL NOT NULL,
+    condition TEXT NOT NULL CHECK(condition IN ('above', 'below')),
+    triggered BOOLEAN DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    base_price REAL,
+    percent_change REAL,
+    recurring BOOLEAN DEFAULT 0,
+    last_triggered_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_symbol ON price_alerts(symbol);
+CREATE INDEX IF NOT EXISTS idx_alerts_triggered ON price_alerts(triggered);
+
+-- Portfolio positions
+CREATE TABLE IF NOT EXISTS portfolio_positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    price REAL NOT NULL,
+    position_type TEXT NOT NULL CHECK(position_type IN ('buy', 'sell')),
+    date TEXT NOT NULL,
+    notes TEXT,
+    option_strategy TEXT,
+    strike REAL,
+    expiration TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_positions_symbol ON portfolio_positions(symbol);
+
+-- Google Trends data
+CREATE TABLE IF NOT EXISTS trends_data (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    keyword TEXT NOT NULL,
+    geo TEXT NOT NULL DEFAULT '',
+    date DATE NOT NULL,
+    value INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(keyword, geo, date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trends_keyword ON trends_data(keyword);
+CREATE INDEX IF NOT EXISTS idx_trends_date ON trends_data(date);
+
+-- Trading signals
+CREATE TABLE IF NOT EXISTS signals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    signal_type TEXT NOT NULL,
+    direction TEXT NOT NULL CHECK(direction IN ('bullish', 'bearish', 'neutral')),
+    strength REAL NOT NULL CHECK(strength >= 0.0 AND strength <= 1.0),
+    price_at_signal REAL NOT NULL,
+    triggered_by TEXT NOT NULL,
+    trigger_value REAL NOT NULL,
+    timestamp DATE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    acknowledged BOOLEAN DEFAULT 0,
+    backfilled BOOLEAN DEFAULT 0,
+    confirmed BOOLEAN,
+    UNIQUE(symbol, signal_type, timestamp)
+);
+
+CREATE INDEX IF NOT EXISTS idx_signals_symbol ON signals(symbol);
+CREATE INDEX IF NOT EXISTS idx_signals_type ON signals(signal_type);
+CREATE INDEX IF NOT EXISTS idx_signals_timestamp ON signals(timestamp);
+CREATE INDEX IF NOT EXISTS idx_signals_direction ON signals(direction);
+CREATE INDEX IF NOT EXISTS idx_signals_acknowledged ON signals(acknowledged);
+
+-- Indicator-based alerts
+CREATE TABLE IF NOT EXISTS indicator_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    alert_type TEXT NOT NULL CHECK(alert_type IN ('threshold', 'crossover', 'band_touch')),
+    indicator_name TEXT NOT NULL,
+    secondary_indicator TEXT,
+    condition TEXT NOT NULL CHECK(condition IN (
+        'crosses_above', 'crosses_below', 'bullish_crossover', 'bearish_crossover',
+        'stays_above', 'stays_below'
+    )),
+    threshold REAL,
+    triggered BOOLEAN DEFAULT 0,
+    last_value REAL,
+    message TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    recurring BOOLEAN DEFAULT 0,
+    last_triggered_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_ind_alerts_symbol ON indicator_alerts(symbol);
+CREATE INDEX IF NOT EXISTS idx_ind_alerts_triggered ON indicator_alerts(triggered);
+
+-- Backtesting strategies
+CREATE TABLE IF NOT EXISTS strategies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL,
+    description TEXT,
+    entry_condition TEXT NOT NULL,
+    entry_threshold REAL NOT NULL,
+    exit_condition TEXT NOT NULL,
+    exit_threshold REAL NOT NULL,
+    stop_loss_percent REAL,
+    take_profit_percent REAL,
+    position_size_percent REAL NOT NULL DEFAULT 100.0,
+    direction TEXT NOT NULL DEFAULT 'long',
+    trailing_stop_percent REAL,
+    -- JSON StrategyRules; NULL uses entry_condition/exit_condition alone
+    entry_rules TEXT,
+    exit_rules TEXT,
+    -- JSON PositionSizing; NULL sizes by position_size_percent
+    position_sizing TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_strategies_name ON strategies(name);
+
+-- Backtest runs
+CREATE TABLE IF NOT EXISTS backtest_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    strategy_id INTEGER NOT NULL,
+    strategy_name TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    initial_capital REAL NOT NULL,
+    final_capital REAL NOT NULL,
+    total_return REAL NOT NULL,
+    total_return_dollars REAL NOT NULL,
+    max_drawdown REAL NOT NULL,
+    sharpe_ratio REAL NOT NULL,
+    win_rate REAL NOT NULL,
+    total_trades INTEGER NOT NULL,
+    winning_trades INTEGER NOT NULL,
+    losing_trades INTEGER NOT NULL,
+    avg_win_percent REAL NOT NULL,
+    avg_loss_percent REAL NOT NULL,
+    profit_factor REAL NOT NULL,
+    avg_trade_duration_days REAL NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    trades_per_year REAL NOT NULL DEFAULT 0,
+    time_in_market_percent REAL NOT NULL DEFAULT 0,
+    information_ratio REAL NOT NULL DEFAULT 0,
+    sortino_ratio REAL NOT NULL DEFAULT 0,
+    calmar_ratio REAL NOT NULL DEFAULT 0,
+    FOREIGN KEY (strategy_id) REFERENCES strategies(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_backtest_runs_strategy ON backtest_runs(strategy_id);
+CREATE INDEX IF NOT EXISTS idx_backtest_runs_symbol ON backtest_runs(symbol);
+CREATE INDEX IF NOT EXISTS idx_backtest_runs_date ON backtest_runs(created_at);
+
+-- Backtest trades
+CREATE TABLE IF NOT EXISTS backtest_trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    backtest_id INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    direction TEXT NOT NULL CHECK(direction IN ('long', 'short')),
+    entry_date DATE NOT NULL,
+    entry_price REAL NOT NULL,
+    entry_reason TEXT NOT NULL,
+    exit_date DATE,
+    exit_price REAL,
+    exit_reason TEXT,
+    shares REAL NOT NULL,
+    profit_loss REAL,
+    profit_loss_percent REAL,
+    FOREIGN KEY (backtest_id) REFERENCES backtest_runs(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_backtest_trades_run ON backtest_trades(backtest_id);
+CREATE INDEX IF NOT EXISTS idx_backtest_trades_symbol ON backtest_trades(symbol);
+
+-- Backtest equity curves (one point per traded date)
+CREATE TABLE IF NOT EXISTS backtest_equity (
+    backtest_id INTEGER NOT NULL,
+    date DATE NOT NULL,
+    equity REAL NOT NULL,
+    PRIMARY KEY (backtest_id, date),
+    FOREIGN KEY (backtest_id) REFERENCES backtest_runs(id)
+);
+
+-- Application settings (key/value)
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Where triggered alerts are delivered; sink is 'webhook' or 'log_file'
+CREATE TABLE IF NOT EXISTS notification_config (
+    sink TEXT PRIMARY KEY,
+    target TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Price bars covered by each symbol's last indicator refresh
+CREATE TABLE IF NOT EXISTS indicator_state (
+    symbol TEXT PRIMARY KEY,
+    through_date DATE NOT NULL,
+    price_checksum TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Single-row signal thresholds (SignalConfig as JSON)
+CREATE TABLE IF NOT EXISTS signal_config (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    config_json TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PositionSizing, RuleCombinator, StrategyRule, StrategyRules};
+    use chrono::Datelike;
+
+    fn test_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        db
+    }
+
+    /// Flat bar (open = high = low = close) with a volume of 1000
+    fn daily_bar(symbol: &str, date: NaiveDate, close: f64) -> DailyPrice {
+        DailyPrice {
+            symbol: symbol.to_string(),
+            date,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1000,
+            adjusted_close: None,
+            source: "test".to_string(),
+        }
+    }
+
+    fn signal(
+        symbol: &str,
+        signal_type: SignalType,
+        direction: SignalDirection,
+        strength: f64,
+        date: NaiveDate,
+    ) -> Signal {
+        Signal {
+            id: 0,
+            symbol: symbol.to_string(),
+            signal_type,
+            direction,
+            strength,
+            price_at_signal: 100.0,
+            triggered_by: "TEST".to_string(),
+            trigger_value: 0.0,
+            timestamp: date,
+            created_at: String::new(),
+            acknowledged: false,
+            backfilled: false,
+            confirmed: None,
+        }
+    }
+
+    #[test]
+    fn test_api_usage_counts_calls_per_day() {
+        let db = test_db();
+
+        for (source, timestamp) in [
+            ("yahoo_finance", "2024-03-01T09:15:00.123456789+00:00"),
+            ("yahoo_finance", "2024-03-01T23:59:59+00:00"),
+            ("yahoo_finance", "2024-03-02T00:00:01+00:00"),
+            ("FRED", "2024-03-02T12:00:00+00:00"),
+            ("yahoo_finance", "2024-02-28T12:00:00+00:00"),
+        ] {
+            db.conn
+                .execute(
+                    "INSERT INTO api_calls (source, endpoint, symbol, timestamp) \
+                     VALUES (?1, 'history', 'AAPL', ?2)",
+                    params![source, timestamp],
+                )
+                .unwrap();
+        }
+
+        let since = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let usage = db.get_api_usage("yahoo_finance", since).unwrap();
+        assert_eq!(
+            usage,
+            vec![("2024-03-01".to_string(), 2), ("2024-03-02".to_string(), 1)]
+        );
+        assert_eq!(db.get_api_usage("FRED", since).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_triggered_alert_posts_to_webhook() {
+        use crate::notify::WEBHOOK_SINK;
+        use crate::testutil::{MockResponse, MockServer};
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let (tx, rx) = mpsc::channel();
+        let base_url = MockServer::start_with_requests(move |request| {
+            tx.send((request.method.clone(), request.target.clone(), request.body.clone()))
+                .unwrap();
+            MockResponse::ok("{}")
+        });
+
+        let mut db = test_db();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        db.upsert_daily_prices(&[daily_bar("AAPL", date, 154.0)]).unwrap();
+        db.add_alert("AAPL", 150.0, AlertCondition::Above).unwrap();
+        db.add_alert("AAPL", 200.0, AlertCondition::Above).unwrap();
+        db.set_notification_target(WEBHOOK_SINK, Some(&format!("{}/hook", base_url)))
+            .unwrap();
+
+        assert_eq!(db.check_alerts().unwrap().len(), 1);
+
+        let (method, target, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(method, "POST");
+        assert_eq!(target, "/hook");
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["kind"], "price");
+        assert_eq!(payload["symbol"], "AAPL");
+        assert_eq!(payload["target_price"], 150.0);
+        assert_eq!(payload["triggered"], true);
+        // Only the triggered alert is sent
+        assert!(rx.try_recv().is_err());
+
+        // Removing the sink stops notifications
+        db.set_notification_target(WEBHOOK_SINK, None).unwrap();
+        assert!(db.get_notification_config().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_slow_webhook_does_not_block_alert_check() {
+        use crate::notify::WEBHOOK_SINK;
+        use crate::testutil::{MockResponse, MockServer};
+        use std::sync::mpsc;
+        use std::time::{Duration, Instant};
+
+        let (tx, rx) = mpsc::channel();
+        let base_url = MockServer::start(move |_| {
+            std::thread::sleep(Duration::from_secs(2));
+            tx.send(()).unwrap();
+            MockResponse::ok("{}")
+        });
+
+        let mut db = test_db();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        db.upsert_daily_prices(&[daily_bar("AAPL", date, 154.0)]).unwrap();
+        db.add_alert("AAPL", 150.0, AlertCondition::Above).unwrap();
+        db.set_notification_target(WEBHOOK_SINK, Some(&base_url))
+            .unwrap();
+
+        let started = Instant::now();
+        assert_eq!(db.check_alerts().unwrap().len(), 1);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // The webhook is still delivered afterwards
+        rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    }
+
+    #[test]
+    fn test_percent_alert_triggers_past_implied_target() {
+        let mut db = test_db();
+        let close = |day: u32, close: f64| {
+            daily_bar(
+                "AAPL",
+                NaiveDate::from_ymd_opt(2024, 3, day).unwrap(),
+                close,
+            )
+        };
+
+        db.upsert_daily_prices(&[close(1, 100.0)]).unwrap();
+        let drop_id = db.add_percent_alert("AAPL", None, -5.0).unwrap();
+        db.add_alert("AAPL", 110.0, AlertCondition::Above).unwrap();
+
+        let alerts = db.get_alerts(true).unwrap();
+        let drop = alerts.iter().find(|a| a.id == drop_id).unwrap();
+        assert_eq!(drop.condition, AlertCondition::Below);
+        assert_eq!(drop.base_price, Some(100.0));
+        assert!((drop.threshold() - 95.0).abs() < 1e-9);
+
+        // Down 4% isn't enough
+        db.upsert_daily_prices(&[close(4, 96.0)]).unwrap();
+        assert!(db.check_alerts().unwrap().is_empty());
+
+        // Down 6% passes the 95.00 target; the absolute alert stays untouched
+        db.upsert_daily_prices(&[close(5, 94.0)]).unwrap();
+        let triggered = db.check_alerts().unwrap();
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].id, drop_id);
+        assert_eq!(triggered[0].percent_change, Some(-5.0));
+        assert_eq!(db.get_alerts(true).unwrap().len(), 1);
+
+        assert!(db.add_percent_alert("MSFT", None, 5.0).is_err());
+    }
+
+    #[test]
+    fn test_recurring_alert_rearms_after_price_crosses_back() {
+        let mut db = test_db();
+        let one_shot = db.add_alert("AAPL", 100.0, AlertCondition::Above).unwrap();
+        let recurring = db.add_alert("AAPL", 100.0, AlertCondition::Above).unwrap();
+        db.set_alert_recurring(recurring, true).unwrap();
+
+        // Above, back below, above again
+        let mut fired = Vec::new();
+        for (day, close) in [(1, 95.0), (4, 102.0), (5, 102.5), (6, 97.0), (7, 104.0)] {
+            db.upsert_daily_prices(&[daily_bar(
+                "AAPL",
+                NaiveDate::from_ymd_opt(2024, 3, day).unwrap(),
+                close,
+            )])
+            .unwrap();
+            fired.extend(db.check_alerts().unwrap());
+        }
+
+        assert_eq!(fired.iter().filter(|a| a.id == one_shot).count(), 1);
+        assert_eq!(fired.iter().filter(|a| a.id == recurring).count(), 2);
+        assert!(fired.iter().all(|a| a.last_triggered_at.is_some()));
+
+        let alerts = db.get_alerts(false).unwrap();
+        let stored = alerts.iter().find(|a| a.id == recurring).unwrap();
+        assert!(stored.recurring && stored.triggered);
+        assert!(stored.last_triggered_at.is_some());
+    }
+
+    #[test]
+    fn test_band_touch_alert_fires_when_close_breaks_upper_band() {
+        let mut db = test_db();
+        let alert_id = db
+            .add_indicator_alert(&IndicatorAlert {
+                id: 0,
+                symbol: "AAPL".to_string(),
+                alert_type: IndicatorAlertType::BandTouch,
+                indicator_name: "BB_UPPER_20".to_string(),
+                secondary_indicator: None,
+                condition: IndicatorAlertCondition::CrossesAbove,
+                threshold: None,
+                triggered: false,
+                last_value: None,
+                created_at: String::new(),
+                message: None,
+                recurring: false,
+                last_triggered_at: None,
+            })
+            .unwrap();
+
+        // Close under the band, then above it while the band holds at 105
+        let mut fired = Vec::new();
+        for (day, close) in [(4, 101.0), (5, 106.0)] {
+            let date = NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+            db.upsert_daily_prices(&[daily_bar("AAPL", date, close)])
+            .unwrap();
+            db.upsert_indicators(&[TechnicalIndicator {
+                symbol: "AAPL".to_string(),
+                date,
+                indicator_name: "BB_UPPER_20".to_string(),
+                value: 105.0,
+            }])
+            .unwrap();
+            fired.push(db.check_indicator_alerts().unwrap());
+        }
+
+        assert!(fired[0].is_empty());
+        assert_eq!(fired[1].len(), 1);
+        assert_eq!(fired[1][0].id, alert_id);
+        assert_eq!(fired[1][0].last_value, Some(105.0));
+    }
+
+    #[test]
+    fn test_stays_below_alert_holds_across_checks() {
+        let mut db = test_db();
+        let alert = |condition, secondary: Option<&str>, threshold, recurring| IndicatorAlert {
+            id: 0,
+            symbol: "AAPL".to_string(),
+            alert_type: IndicatorAlertType::Crossover,
+            indicator_name: "RSI_14".to_string(),
+            secondary_indicator: secondary.map(str::to_string),
+            condition,
+            threshold,
+            triggered: false,
+            last_value: None,
+            created_at: String::new(),
+            message: None,
+            recurring,
+            last_triggered_at: None,
+        };
+        let below = IndicatorAlertCondition::StaysBelow;
+        let one_shot = db
+            .add_indicator_alert(&alert(below, Some("RSI_SMA_14"), None, false))
+            .unwrap();
+        let recurring = db
+            .add_indicator_alert(&alert(below, Some("RSI_SMA_14"), None, true))
+            .unwrap();
+        let above_50 = db
+            .add_indicator_alert(&alert(
+                IndicatorAlertCondition::StaysAbove,
+                None,
+                Some(50.0),
+                false,
+            ))
+            .unwrap();
+
+        // RSI under its average for three checks, above it, then under again
+        let mut fired = Vec::new();
+        for (day, rsi) in [(4, 40.0), (5, 42.0), (6, 45.0), (7, 55.0), (8, 41.0)] {
+            let date = NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+            let value = |name: &str, value| TechnicalIndicator {
+                symbol: "AAPL".to_string(),
+                date,
+                indicator_name: name.to_string(),
+                value,
+            };
+            db.upsert_indicators(&[value("RSI_14", rsi), value("RSI_SMA_14", 50.0)])
+                .unwrap();
+            let ids: Vec<i64> = db
+                .check_indicator_alerts()
+                .unwrap()
+                .iter()
+                .map(|a| a.id)
+                .collect();
+            fired.push(ids);
+        }
+
+        // Fires on the first check without needing a cross, then stays quiet
+        // while the state holds; only the recurring alert re-arms
+        assert_eq!(fired[0], vec![one_shot, recurring]);
+        assert!(fired[1].is_empty() && fired[2].is_empty());
+        assert_eq!(fired[3], vec![above_50]);
+        assert_eq!(fired[4], vec![recurring]);
+    }
+
+    #[test]
+    fn test_migration_rebuilds_indicator_alerts_for_stays_conditions() {
+        let db = Database::open_in_memory().unwrap();
+        db.conn
+            .execute_batch(
+                r#"
+                CREATE TABLE indicator_alerts (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    symbol TEXT NOT NULL,
+                    alert_type TEXT NOT NULL,
+                    indicator_name TEXT NOT NULL,
+                    secondary_indicator TEXT,
+                    condition TEXT NOT NULL CHECK(condition IN (
+                        'crosses_above', 'crosses_below', 'bullish_crossover', 'bearish_crossover'
+                    )),
+                    threshold REAL,
+                    triggered BOOLEAN DEFAULT 0,
+                    last_value REAL,
+                    message TEXT,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+                INSERT INTO indicator_alerts
+                (symbol, alert_type, indicator_name, condition, threshold)
+                VALUES ('AAPL', 'threshold', 'RSI_14', 'crosses_below', 30);
+                "#,
+            )
+            .unwrap();
+        db.init_schema().unwrap();
+
+        let alerts = db.get_indicator_alerts(false).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].condition, IndicatorAlertCondition::CrossesBelow);
+        assert_eq!(alerts[0].threshold, Some(30.0));
+
+        let id = db
+            .add_indicator_alert(&IndicatorAlert {
+                condition: IndicatorAlertCondition::StaysAbove,
+                ..alerts[0].clone()
+            })
+            .unwrap();
+        assert!(id > alerts[0].id);
+    }
+
+    #[test]
+    fn test_regional_trends_kept_apart_from_worldwide() {
+        let mut db = Database::open_in_memory().unwrap();
+        db.conn
+            .execute_batch(
+                r#"
+                CREATE TABLE trends_data (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    keyword TEXT NOT NULL,
+                    date DATE NOT NULL,
+                    value INTEGER NOT NULL,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(keyword, date)
+                );
+                INSERT INTO trends_data (keyword, date, value)
+                VALUES ('AAPL', '2024-01-07', 60);
+                "#,
+            )
+            .unwrap();
+        db.init_schema().unwrap();
+
+        // Rows from before the migration are the worldwide series
+        let worldwide = db.get_trends("AAPL", "").unwrap();
+        assert_eq!(worldwide.len(), 1);
+        assert_eq!(worldwide[0].value, 60);
+
+        let point = |geo: &str, value| TrendData {
+            keyword: "AAPL".to_string(),
+            geo: geo.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 1, 7).unwrap(),
+            value,
+        };
+        db.upsert_trends(&[point("US", 85)]).unwrap();
+        assert_eq!(db.get_trends("AAPL", "").unwrap()[0].value, 60);
+        assert_eq!(db.get_trends("AAPL", "US").unwrap()[0].value, 85);
+
+        db.upsert_trends(&[point("", 70)]).unwrap();
+        assert_eq!(db.get_trends("AAPL", "").unwrap()[0].value, 70);
+        assert_eq!(db.get_trends("AAPL", "US").unwrap()[0].value, 85);
+    }
+
+    #[test]
+    fn test_non_finite_indicator_values_never_reach_the_database() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        // Zero range and zero volume on every bar
+        let prices: Vec<DailyPrice> = (0..120)
+            .map(|i| DailyPrice {
+                volume: 0,
+                ..daily_bar("FLAT", start + chrono::Duration::days(i), 50.0)
+            })
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
+        assert!(db.refresh_indicators("FLAT").unwrap() > 0);
+
+        // Values from elsewhere are filtered the same way
+        let value = |value| TechnicalIndicator {
+            symbol: "FLAT".to_string(),
+            date: start,
+            indicator_name: "CUSTOM".to_string(),
+            value,
+        };
+        let batch = [
+            value(f64::NAN),
+            value(f64::INFINITY),
+            value(f64::NEG_INFINITY),
+        ];
+        assert_eq!(db.upsert_indicators(&batch).unwrap(), 0);
+        db.upsert_indicator(&value(f64::NAN)).unwrap();
+
+        let stored = db.get_all_indicators("FLAT").unwrap();
+        assert!(!stored.is_empty());
+        assert!(stored.iter().all(|i| i.value.is_finite()));
+        assert!(stored.iter().all(|i| i.indicator_name != "CUSTOM"));
+    }
+
+    #[test]
+    fn test_refresh_loads_only_the_incremental_window() {
+        let mut db = test_db();
+        // 400 weekday bars, so the history is longer than the warm-up window
+        let mut date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        let mut prices = Vec::new();
+        while prices.len() < 400 {
+            if date.weekday().number_from_monday() <= 5 {
+                let close = 100.0 + (prices.len() as f64 * 0.1).sin() * 10.0;
+                prices.push(DailyPrice {
+                    high: close + 1.0,
+                    low: close - 1.0,
+                    volume: 1000 + prices.len() as i64,
+                    ..daily_bar("AAPL", date, close)
+                });
+            }
+            date = date.succ_opt().unwrap();
+        }
+
+        db.upsert_daily_prices(&prices[..398]).unwrap();
+        assert_eq!(db.incremental_prices("AAPL").unwrap().len(), 398);
+        db.refresh_indicators("AAPL").unwrap();
+
+        // Two new bars: the warm-up window plus those, reaching back to January for VWAP
+        db.upsert_daily_prices(&prices[398..]).unwrap();
+        let window = db.incremental_prices("AAPL").unwrap();
+        let year_start = prices.iter().position(|p| p.date.year() == 2024).unwrap();
+        assert_eq!(
+            window.len(),
+            (400 - year_start).max(INCREMENTAL_WARMUP_BARS + 2)
+        );
+        assert!(window.len() < 400);
+        assert_eq!(window.last().unwrap().date, prices[399].date);
+
+        db.refresh_indicators("AAPL").unwrap();
+        let stored: std::collections::HashMap<(String, NaiveDate), f64> = db
+            .get_all_indicators("AAPL")
+            .unwrap()
+            .into_iter()
+            .map(|i| ((i.indicator_name, i.date), i.value))
+            .collect();
+        // Exact series; Wilder-smoothed ones only converge (see `calculate_incremental`)
+        let exact = [
+            "EMA_12",
+            "ATR_14",
+            "OBV",
+            "ADL",
+            "VWAP",
+            "SMA_50",
+            "BB_UPPER_20",
+            "MACD_HIST",
+        ];
+        for ind in crate::indicators::calculate_all(&prices) {
+            if ind.date < prices[398].date || !exact.contains(&ind.indicator_name.as_str()) {
+                continue;
+            }
+            let value = stored[&(ind.indicator_name.clone(), ind.date)];
+            assert!(
+                (value - ind.value).abs() < 1e-6,
+                "{} on {}",
+                ind.indicator_name,
+                ind.date
+            );
+        }
+    }
+
+    #[test]
+    fn test_clearing_prices_purges_indicators_and_refresh_recomputes() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices: Vec<DailyPrice> = (0..80)
+            .map(|i| {
+                let close = 100.0 + (i as f64 * 0.2).sin() * 5.0;
+                DailyPrice {
+                    high: close + 1.0,
+                    low: close - 1.0,
+                    ..daily_bar("AAPL", start + chrono::Duration::days(i), close)
+                }
+            })
+            .collect();
+        let full = crate::indicators::calculate_all(&prices).len();
+
+        db.upsert_daily_prices(&prices[..79]).unwrap();
+        db.refresh_indicators("AAPL").unwrap();
+        assert!(!db.indicators_dirty("AAPL").unwrap());
+
+        // Clearing prices takes the indicators with them
+        db.clear_symbol_prices("AAPL").unwrap();
+        assert!(db.get_all_indicators("AAPL").unwrap().is_empty());
+
+        // Refetched history is recomputed in full, then only the new bar's tail
+        db.upsert_daily_prices(&prices[..79]).unwrap();
+        db.refresh_indicators("AAPL").unwrap();
+        db.upsert_daily_prices(&prices[79..]).unwrap();
+        let tail = db.refresh_indicators("AAPL").unwrap();
+        assert!(tail > 0 && tail < full, "{} of {}", tail, full);
+        assert_eq!(db.get_all_indicators("AAPL").unwrap().len(), full);
+        assert_eq!(db.refresh_indicators("AAPL").unwrap(), 0);
+
+        // Rewriting an old bar marks the set dirty and forces a full recompute
+        let mut revised = prices[10].clone();
+        revised.close += 3.0;
+        db.upsert_daily_prices(&[revised]).unwrap();
+        assert!(db.indicators_dirty("AAPL").unwrap());
+        assert_eq!(db.refresh_indicators("AAPL").unwrap(), full);
+        assert_eq!(db.get_all_indicators("AAPL").unwrap().len(), full);
+    }
+
+    #[test]
+    fn test_stale_symbols_skip_recent_data() {
+        let mut db = test_db();
+        let today = Utc::now().date_naive();
+
+        // At most 3 weekdays old is fresh at a 5-day limit, whatever today is
+        for (symbol, days_ago) in [("FRESH", 0), ("RECENT", 3), ("STALE", 14), ("DEAD", 60)] {
+            let date = today - chrono::Duration::days(days_ago);
+            db.upsert_daily_prices(&[daily_bar(symbol, date, 10.0)])
+            .unwrap();
+            assert_eq!(db.get_last_price_date(symbol).unwrap(), Some(date));
+        }
+
+        assert_eq!(db.get_stale_symbols(5).unwrap(), vec!["DEAD", "STALE"]);
+        assert_eq!(db.get_stale_symbols(100).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_watchlist_create_list_delete() {
+        let db = test_db();
+
+        db.create_watchlist("tech", &["AAPL".to_string(), "MSFT".to_string()], None)
+            .unwrap();
+        db.create_watchlist("banks", &["JPM".to_string()], Some("Big banks"))
+            .unwrap();
+
+        let names: Vec<String> = db
+            .get_all_watchlists()
+            .unwrap()
+            .into_iter()
+            .map(|(_, name, _, _)| name)
+            .collect();
+        assert_eq!(names, vec!["banks", "tech"]);
+
+        let mut symbols = db.get_watchlist("tech").unwrap();
+        symbols.sort();
+        assert_eq!(symbols, vec!["AAPL", "MSFT"]);
+
+        assert!(db.delete_watchlist("tech").unwrap());
+        assert!(!db.delete_watchlist("tech").unwrap());
+        assert!(db.get_watchlist("tech").unwrap().is_empty());
+        assert_eq!(db.get_all_watchlists().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_regenerated_signal_keeps_stored_confirmation() {
+        let mut db = test_db();
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let oversold = signal(
+            "AAPL",
+            SignalType::RsiOversold,
+            SignalDirection::Bullish,
+            0.8,
+            day,
+        );
+        let confirmed = |db: &Database| -> Vec<Option<bool>> {
+            db.get_signals("AAPL", false)
+                .unwrap()
+                .iter()
+                .map(|s| s.confirmed)
+                .collect()
+        };
+
+        db.upsert_signals(std::slice::from_ref(&oversold)).unwrap();
+        assert_eq!(confirmed(&db), vec![None]);
+
+        db.upsert_signals(&[Signal {
+            confirmed: Some(true),
+            ..oversold.clone()
+        }])
+        .unwrap();
+        db.upsert_signals(std::slice::from_ref(&oversold)).unwrap();
+        db.upsert_signal(&oversold).unwrap();
+        assert_eq!(confirmed(&db), vec![Some(true)]);
+
+        // A fresh check still overrides it
+        db.upsert_signal(&Signal {
+            confirmed: Some(false),
+            ..oversold
+        })
+        .unwrap();
+        assert_eq!(confirmed(&db), vec![Some(false)]);
+    }
+
+    #[test]
+    fn test_signal_scoreboard_ordering() {
+        let mut db = test_db();
+        let today = Utc::now().date_naive();
+
+        db.upsert_signals(&[
+            signal("AAPL", SignalType::RsiOversold, SignalDirection::Bullish, 0.8, today),
+            signal("AAPL", SignalType::MacdBearishCross, SignalDirection::Bearish, 0.3, today),
+            signal("AAPL", SignalType::Doji, SignalDirection::Neutral, 0.9, today),
+            signal("MSFT", SignalType::RsiOverbought, SignalDirection::Bearish, 0.7, today),
+            signal("MSFT", SignalType::MacdBullishCross, SignalDirection::Bullish, 0.2, today),
+        ])
+        .unwrap();
+
+        let scoreboard = db.get_signal_scoreboard(7).unwrap();
+        assert_eq!(scoreboard.len(), 2);
+        assert_eq!(scoreboard[0].0, "AAPL");
+        assert!((scoreboard[0].1 - 0.5).abs() < 1e-9);
+        assert_eq!(scoreboard[1].0, "MSFT");
+        assert!((scoreboard[1].1 + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_import_prices_csv_streams_in_batches() {
+        let mut db = test_db();
+
+        let start = NaiveDate::from_ymd_opt(1990, 1, 1).unwrap();
+        let mut csv = String::from("date,open,high,low,close,volume\n");
+        for i in 0..10_000 {
+            let date = start + chrono::Duration::days(i);
+            csv.push_str(&format!("{},10.00,11.00,9.00,10.50,{}\n", date, 1000 + i));
+        }
+
+        let mut reported = Vec::new();
+        let mut on_progress = |n: usize| reported.push(n);
+        let summary = db
+            .import_prices_reader("aapl", "csv", csv.as_bytes(), Some(&mut on_progress))
+            .unwrap();
+
+        assert_eq!(summary.imported, 10_000);
+        assert_eq!(reported.len(), 10);
+        assert_eq!(reported.last(), Some(&10_000));
+
+        let prices = db.get_prices("AAPL").unwrap();
+        assert_eq!(prices.len(), 10_000);
+        assert_eq!(prices[0].date, start);
+        assert_eq!(prices[0].volume, 1000);
+        assert_eq!(prices[9_999].close, 10.5);
+    }
+
+    #[test]
+    fn test_correlation_matrix_pairs_symbols() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let bars = |symbol: &str, scale: f64, count: i64| -> Vec<DailyPrice> {
+            (0..count)
+                .map(|i| {
+                    let close = scale * (100.0 + (i as f64 * 0.9).sin() * 4.0);
+                    daily_bar(symbol, start + chrono::Duration::days(i), close)
+                })
+                .collect()
+        };
+        db.upsert_daily_prices(&bars("AAA", 1.0, 40)).unwrap();
+        db.upsert_daily_prices(&bars("BBB", 3.0, 40)).unwrap();
+        db.upsert_daily_prices(&bars("NEW", 1.0, 3)).unwrap();
+
+        let symbols: Vec<String> = ["aaa", "BBB", "NEW"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let matrix = db.correlation_matrix(&symbols, 20).unwrap();
+
+        assert_eq!(matrix.len(), 3);
+        assert_eq!((matrix[0].0.as_str(), matrix[0].1.as_str()), ("AAA", "BBB"));
+        assert!((matrix[0].2 - 1.0).abs() < 1e-9);
+        // NEW doesn't have enough history to correlate with anything
+        assert!(matrix[1].2.is_nan() && matrix[2].2.is_nan());
+    }
+
+    #[test]
+    fn test_relative_strength_ranks_outperformer_above_one() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let bars = |symbol: &str, step: f64| -> Vec<DailyPrice> {
+            (0..30)
+                .map(|i| {
+                    let close = 100.0 + step * i as f64;
+                    daily_bar(symbol, start + chrono::Duration::days(i), close)
+                })
+                .collect()
+        };
+        db.upsert_daily_prices(&bars("SPY", 0.0)).unwrap();
+        db.upsert_daily_prices(&bars("UP", 1.0)).unwrap();
+        db.upsert_daily_prices(&bars("DOWN", -1.0)).unwrap();
+
+        let symbols: Vec<String> = ["down", "UP", "NONE"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let ranking = db.relative_strength(&symbols, "SPY", 10).unwrap();
+
+        // 129/119 and 71/81 against a flat benchmark; NONE has no bars
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(ranking[0].0, "UP");
+        assert!((ranking[0].1 - 129.0 / 119.0).abs() < 1e-9);
+        assert!(ranking[0].1 > 1.0 && ranking[1].1 < 1.0);
+
+        let err = db.relative_strength(&symbols, "QQQ", 10).unwrap_err();
+        assert!(matches!(err, PipelineError::NoData(_)));
+    }
+
+    #[test]
+    fn test_import_prices_skips_malformed_rows() {
+        let mut db = test_db();
+        let csv = "date,open,high,low,close,volume\n\
+            01/02/2024,1,1,1,1,1\n\
+            2024-01-03,10,11,9,10.5,100\n\
+            2024-01-04,10,abc,9,10.5,100\n\
+            2024-01-05,10,9.5,9,10.5,100\n\
+            2024-01-08,10,11,9,NaN,100\n\
+            2024-01-09,10,11,9,10.5\n";
+
+        let summary = db
+            .import_prices_reader("AAPL", "broker", csv.as_bytes(), None)
+            .unwrap();
+        assert_eq!((summary.imported, summary.skipped), (1, 5));
+        let prices = db.get_prices("AAPL").unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].source, "broker");
+
+        // A file without the expected columns is rejected outright
+        let err = db
+            .import_prices_reader("AAPL", "csv", "day,price\n2024-01-03,1\n".as_bytes(), None)
+            .unwrap_err();
+        assert!(matches!(err, PipelineError::Config(_)));
+    }
+
+    #[test]
+    fn test_import_prices_csv_round_trips_export() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices: Vec<DailyPrice> = (0..5)
+            .map(|i| DailyPrice {
+                open: 100.0 + i as f64,
+                high: 102.5 + i as f64,
+                low: 99.25 + i as f64,
+                volume: 1_000 + i,
+                source: "yahoo_finance".to_string(),
+                ..daily_bar("AAPL", start + chrono::Duration::days(i), 101.75 + i as f64)
+            })
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
+
+        let path = std::env::temp_dir().join(format!("fp_import_{}.csv", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        db.export_prices_csv("AAPL", file, &ExportPrecision::default())
+            .unwrap();
+        db.clear_symbol_prices("AAPL").unwrap();
+        assert!(db.get_prices("AAPL").unwrap().is_empty());
+
+        let summary = db.import_prices_csv(&path, "AAPL", "csv").unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!((summary.imported, summary.skipped), (5, 0));
+        let imported = db.get_prices("AAPL").unwrap();
+        assert_eq!(imported.len(), prices.len());
+        for (got, want) in imported.iter().zip(&prices) {
+            assert_eq!(got.date, want.date);
+            assert_eq!(
+                (got.open, got.high, got.low, got.close, got.volume),
+                (want.open, want.high, want.low, want.close, want.volume)
+            );
+            assert_eq!(got.source, "csv");
+        }
+    }
+
+    #[test]
+    fn test_two_sources_stored_and_read_independently() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let bars = |source: &str, first: i64, days: i64, close: f64| -> Vec<DailyPrice> {
+            (first..first + days)
+                .map(|i| DailyPrice {
+                    source: source.to_string(),
+                    ..daily_bar("AAPL", start + chrono::Duration::days(i), close)
+                })
+                .collect()
+        };
+
+        // Imported CSV is preferred; Yahoo overlaps it on days 2-3 and adds day 4
+        db.set_setting(PRICE_SOURCE_PRIORITY_KEY, "csv, yahoo_finance")
+            .unwrap();
+        db.upsert_daily_prices(&bars("csv", 0, 4, 100.0)).unwrap();
+        db.upsert_daily_prices(&bars("yahoo_finance", 2, 3, 200.0))
+            .unwrap();
+
+        let csv = db.get_prices_by_source("AAPL", "csv").unwrap();
+        let yahoo = db.get_prices_by_source("AAPL", "yahoo_finance").unwrap();
+        assert_eq!(csv.len(), 4);
+        assert!(csv.iter().all(|p| p.close == 100.0 && p.source == "csv"));
+        assert_eq!(yahoo.len(), 3);
+        assert_eq!(yahoo[0].date, start + chrono::Duration::days(2));
+        assert_eq!(
+            db.get_latest_price_by_source("AAPL", "csv").unwrap(),
+            Some(100.0)
+        );
+        assert_eq!(
+            db.get_latest_price_by_source("AAPL", "yahoo_finance")
+                .unwrap(),
+            Some(200.0)
+        );
+        assert_eq!(
+            db.get_latest_price_by_source("AAPL", "manual").unwrap(),
+            None
+        );
+
+        // The merged series keeps CSV where both have a bar
+        let merged: Vec<(f64, String)> = db
+            .get_prices("AAPL")
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.close, p.source))
+            .collect();
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged[3], (100.0, "csv".to_string()));
+        assert_eq!(merged[4], (200.0, "yahoo_finance".to_string()));
+
+        let sources = db.list_sources("AAPL").unwrap();
+        let names: Vec<&str> = sources.iter().map(|s| s.source.as_str()).collect();
+        assert_eq!(names, vec!["csv", "yahoo_finance"]);
+        assert_eq!(sources[1].bars, 3);
+        assert_eq!(sources[1].last_date, start + chrono::Duration::days(4));
+
+        // Without a priority the latest write wins, as before
+        db.set_setting(PRICE_SOURCE_PRIORITY_KEY, "").unwrap();
+        db.upsert_daily_prices(&bars("yahoo_finance", 3, 1, 250.0))
+            .unwrap();
+        assert_eq!(db.get_prices("AAPL").unwrap()[3].close, 250.0);
+    }
+
+    #[test]
+    fn test_export_indicator_history_csv() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        let history: Vec<TechnicalIndicator> = [45.0, 52.5, 61.2, 68.9, 72.4]
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| TechnicalIndicator {
+                symbol: "AAPL".to_string(),
+                date: start + chrono::Duration::days(i as i64),
+                indicator_name: "RSI_14".to_string(),
+                value,
+            })
+            .collect();
+        db.upsert_indicators(&history).unwrap();
+
+        let mut out = Vec::new();
+        let rows = db
+            .export_indicator_history_csv("AAPL", "RSI_14", &mut out, &ExportPrecision::default())
+            .unwrap();
+        let csv = String::from_utf8(out).unwrap();
+
+        assert_eq!(rows, history.len());
+        assert_eq!(csv.lines().count(), history.len() + 1);
+        assert_eq!(csv.lines().next(), Some("date,value"));
+        assert_eq!(csv.lines().nth(1), Some("2024-01-01,45.0000"));
+    }
+
+    #[test]
+    fn test_export_precision() {
+        let mut db = test_db();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+
+        db.upsert_daily_prices(&[DailyPrice {
+            open: 171.123456,
+            high: 173.9,
+            low: 170.0,
+            volume: 52_164_500,
+            ..daily_bar("AAPL", date, 172.299_999_9)
+        }])
+        .unwrap();
+        db.upsert_indicator(&TechnicalIndicator {
+            symbol: "AAPL".to_string(),
+            date,
+            indicator_name: "RSI_14".to_string(),
+            value: 61.234567891,
+        })
+        .unwrap();
+
+        let precision = ExportPrecision::default();
+
+        let mut out = Vec::new();
+        db.export_prices_csv("AAPL", &mut out, &precision).unwrap();
+        let prices_csv = String::from_utf8(out).unwrap();
+        assert_eq!(
+            prices_csv.lines().nth(1),
+            Some("2024-01-02,171.12,173.90,170.00,172.30,52164500")
+        );
+
+        let mut out = Vec::new();
+        db.export_latest_indicators_csv("AAPL", &mut out, &precision).unwrap();
+        let indicators_csv = String::from_utf8(out).unwrap();
+        assert_eq!(indicators_csv.lines().nth(1), Some("RSI_14,61.2346,2024-01-02"));
+    }
+
+    #[test]
+    fn test_benchmark_setting_drives_beta_series() {
+        let mut db = test_db();
+        assert_eq!(db.benchmark_symbol().unwrap(), "SPY");
+
+        // AAPL tracks SPY one for one and moves twice as much as QQQ
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let bar = |symbol: &str, i: i64, close: f64| {
+            daily_bar(symbol, start + chrono::Duration::days(i), close)
+        };
+        let (mut aapl, mut qqq) = (100.0, 100.0);
+        let mut prices = Vec::new();
+        for i in 0..80 {
+            let r = 0.01 * (i as f64 * 0.7).sin();
+            aapl *= 1.0 + 2.0 * r;
+            qqq *= 1.0 + r;
+            prices.extend([
+                bar("AAPL", i, aapl),
+                bar("SPY", i, aapl),
+                bar("QQQ", i, qqq),
+            ]);
+        }
+        db.upsert_daily_prices(&prices).unwrap();
+        db.add_position("AAPL", 10.0, 100.0, PositionType::Buy, "2024-02-01", None)
+            .unwrap();
+        let long = 10.0 * aapl;
+
+        let exposure = db.portfolio_exposure().unwrap();
+        assert_eq!(exposure.benchmark, "SPY");
+        assert!((exposure.beta_weighted_exposure - long).abs() < 1e-6);
+
+        db.set_setting(BENCHMARK_SYMBOL_KEY, "qqq").unwrap();
+        assert_eq!(db.benchmark_symbol().unwrap(), "QQQ");
+        assert_eq!(
+            db.get_all_settings().unwrap(),
+            vec![(BENCHMARK_SYMBOL_KEY.to_string(), "qqq".to_string())]
+        );
+
+        // The cached SPY beta isn't reused for the new benchmark
+        let exposure = db.portfolio_exposure().unwrap();
+        assert_eq!(exposure.benchmark, "QQQ");
+        assert!((exposure.beta_weighted_exposure - 2.0 * long).abs() < 1e-6);
+        let name = format!("BETA_QQQ_{}", EXPOSURE_BETA_WINDOW);
+        let cached = db
+            .get_indicator_history_window("AAPL", &name, Some(1), 0)
+            .unwrap();
+        assert!((cached[0].value - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_typed_settings_round_trip() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Preset {
+            name: String,
+            periods: Vec<usize>,
+        }
+
+        let db = test_db();
+        assert_eq!(db.get_setting_json::<Preset>("indicator_preset").unwrap(), None);
+
+        let preset = Preset {
+            name: "swing".to_string(),
+            periods: vec![9, 21, 50],
+        };
+        db.set_setting_json("indicator_preset", &preset).unwrap();
+        assert_eq!(
+            db.get_setting_json::<Preset>("indicator_preset").unwrap(),
+            Some(preset)
+        );
+
+        db.set_setting_bool("dark_mode", true).unwrap();
+        assert_eq!(db.get_setting_bool("dark_mode").unwrap(), Some(true));
+
+        db.set_setting_f64("risk_free_rate", 0.045).unwrap();
+        assert_eq!(db.get_setting_f64("risk_free_rate").unwrap(), Some(0.045));
+        assert!(matches!(
+            db.get_setting_bool("risk_free_rate"),
+            Err(PipelineError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_find_price_gaps_reports_missing_weekdays() {
+        let mut db = test_db();
+        // 2024-01-01 is a Monday; Thu 4th, Fri 5th and Mon 8th are missing
+        let days = [1, 2, 3, 9, 10, 11, 12, 15];
+        let prices: Vec<DailyPrice> = days
+            .iter()
+            .map(|&d| daily_bar("AAPL", NaiveDate::from_ymd_opt(2024, 1, d).unwrap(), 100.0))
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
+
+        let gaps = db.find_price_gaps("AAPL").unwrap();
+        assert_eq!(
+            gaps,
+            vec![(
+                NaiveDate::from_ymd_opt(2024, 1, 4).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()
+            )]
+        );
+        assert!(db.find_price_gaps("MSFT").unwrap().is_empty());
+
+        // Independence Day between two sessions isn't a gap
+        let holiday_week: Vec<DailyPrice> = [3, 5]
+            .iter()
+            .map(|&d| DailyPrice {
+                symbol: "MSFT".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 7, d).unwrap(),
+                ..prices[0].clone()
+            })
+            .collect();
+        db.upsert_daily_prices(&holiday_week).unwrap();
+        assert!(db.find_price_gaps("MSFT").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_for_each_price_streams_in_date_order() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        // Inserted newest first to make sure the order comes from the query
+        let prices: Vec<DailyPrice> = (0..50)
+            .rev()
+            .map(|i| DailyPrice {
+                open: 100.0,
+                high: 101.0,
+                low: 99.0,
+                ..daily_bar("AAPL", start + chrono::Duration::days(i), 100.0 + i as f64)
+            })
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
+
+        let mut dates = Vec::new();
+        let count = db
+            .for_each_price("AAPL", |p| {
+                dates.push(p.date);
+                Ok(())
+            })
+            .unwrap();
+
+        let all = db.get_prices("AAPL").unwrap();
+        assert_eq!(count, all.len());
+        assert_eq!(dates, all.iter().map(|p| p.date).collect::<Vec<_>>());
+        assert!(dates.windows(2).all(|w| w[0] < w[1]));
+
+        // An error from the callback stops the scan
+        let mut seen = 0;
+        let err = db.for_each_price("AAPL", |_| {
+            seen += 1;
+            if seen == 3 {
+                return Err(PipelineError::Config("stop".to_string()));
+            }
+            Ok(())
+        });
+        assert!(err.is_err());
+        assert_eq!(seen, 3);
+    }
+
+    #[test]
+    fn test_price_and_indicator_windows_page_back_from_latest() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let dates: Vec<NaiveDate> = (0..10).map(|i| start + chrono::Duration::days(i)).collect();
+        let prices: Vec<DailyPrice> = dates
+            .iter()
+            .enumerate()
+            .map(|(i, &date)| DailyPrice {
+                open: 100.0,
+                high: 100.0,
+                low: 100.0,
+                ..daily_bar("AAPL", date, 100.0 + i as f64)
+            })
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
+        let indicators: Vec<TechnicalIndicator> = dates
+            .iter()
+            .enumerate()
+            .map(|(i, &date)| TechnicalIndicator {
+                symbol: "AAPL".to_string(),
+                date,
+                indicator_name: "RSI_14".to_string(),
+                value: i as f64,
+            })
+            .collect();
+        db.upsert_indicators(&indicators).unwrap();
+
+        // Newest page: last 3 bars, ascending
+        let page = db.get_prices_window("AAPL", Some(3), 0).unwrap();
+        let closes: Vec<f64> = page.iter().map(|p| p.close).collect();
+        assert_eq!(closes, vec![107.0, 108.0, 109.0]);
+
+        // Next page back starts right before the first one
+        let page = db.get_prices_window("AAPL", Some(3), 3).unwrap();
+        assert_eq!(page.first().unwrap().date, dates[4]);
+        assert_eq!(page.last().unwrap().date, dates[6]);
+
+        // Partial final page and a page past the end
+        assert_eq!(db.get_prices_window("AAPL", Some(4), 8).unwrap().len(), 2);
+        assert!(db.get_prices_window("AAPL", Some(4), 10).unwrap().is_empty());
+
+        // No limit returns everything older than the offset
+        let rest = db.get_prices_window("AAPL", None, 2).unwrap();
+        assert_eq!(rest.len(), 8);
+        assert_eq!(rest.last().unwrap().date, dates[7]);
+        assert_eq!(db.get_prices("AAPL").unwrap().len(), 10);
+
+        let page = db
+            .get_indicator_history_window("AAPL", "RSI_14", Some(2), 1)
+            .unwrap();
+        let values: Vec<f64> = page.iter().map(|i| i.value).collect();
+        assert_eq!(values, vec![7.0, 8.0]);
+        assert_eq!(db.get_indicator_history("AAPL", "RSI_14").unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_dividend_income_counts_only_ex_dates_after_purchase() {
+        let mut db = test_db();
+        let dividend = |month: u32, amount: f64| Dividend {
+            symbol: "AAPL".to_string(),
+            ex_date: NaiveDate::from_ymd_opt(2024, month, 9).unwrap(),
+            amount,
+            source: "test".to_string(),
+        };
+        db.upsert_dividends(&[dividend(2, 0.24), dividend(5, 0.25)]).unwrap();
+
+        // Bought between the two ex-dates: only May's dividend is earned
+        let bought = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let income = db.get_dividend_income("AAPL", bought, 100.0).unwrap();
+        assert!((income - 25.0).abs() < 1e-9);
+
+        // Buying on the ex-date itself misses that payment
+        let on_ex_date = NaiveDate::from_ymd_opt(2024, 5, 9).unwrap();
+        assert_eq!(db.get_dividend_income("AAPL", on_ex_date, 100.0).unwrap(), 0.0);
+
+        let before_both = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let income = db.get_dividend_income("AAPL", before_both, 10.0).unwrap();
+        assert!((income - 4.9).abs() < 1e-9);
+        assert_eq!(db.get_dividend_income("MSFT", before_both, 10.0).unwrap(), 0.0);
+
+        // A short opened before both owes them
+        let short = Position {
+            id: 0,
+            symbol: "AAPL".to_string(),
+            quantity: 10.0,
+            price: 180.0,
+            position_type: PositionType::Sell,
+            date: "2024-01-01".to_string(),
+            notes: None,
+            option: None,
+        };
+        let owed = db.get_position_dividend_income(&short).unwrap();
+        assert!((owed + 4.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_portfolio_exposure_nets_long_against_short() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let bar = |symbol: &str, i: i64, close: f64| {
+            daily_bar(symbol, start + chrono::Duration::days(i), close)
+        };
+
+        // AAA moves exactly twice as much as SPY every day; BBB is too new for a beta
+        let (mut spy, mut aaa) = (100.0, 50.0);
+        let mut prices = Vec::new();
+        for i in 0..80 {
+            let r = 0.01 * (i as f64 * 0.7).sin();
+            spy *= 1.0 + r;
+            aaa *= 1.0 + 2.0 * r;
+            prices.push(bar("SPY", i, spy));
+            prices.push(bar("AAA", i, aaa));
+        }
+        prices.extend((75..80).map(|i| bar("BBB", i, 40.0)));
+        db.upsert_daily_prices(&prices).unwrap();
+
+        db.add_position("AAA", 10.0, 45.0, PositionType::Buy, "2024-02-01", None)
+            .unwrap();
+        db.add_position("BBB", 20.0, 42.0, PositionType::Sell, "2024-03-16", None)
+            .unwrap();
+
+        let exposure = db.portfolio_exposure().unwrap();
+        let long = 10.0 * aaa;
+        assert_eq!(exposure.benchmark, "SPY");
+        assert!((exposure.long_exposure - long).abs() < 1e-9);
+        assert_eq!(exposure.short_exposure, 800.0);
+        assert!((exposure.net_exposure - (long - 800.0)).abs() < 1e-9);
+        assert!((exposure.beta_weighted_exposure - (2.0 * long - 800.0)).abs() < 1e-6);
+        assert_eq!(exposure.missing_beta, vec!["BBB".to_string()]);
+
+        // The beta is cached as an indicator on AAA's latest bar
+        let beta = db
+            .get_latest_indicator_value("AAA", &format!("BETA_SPY_{}", EXPOSURE_BETA_WINDOW))
+            .unwrap()
+            .unwrap();
+        assert!((beta - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_option_positions_round_trip_outside_fifo() {
+        let db = test_db();
+        let leg = OptionLeg {
+            strategy: OptionStrategy::CoveredCall,
+            strike: 200.0,
+            expiration: "2024-06-21".to_string(),
+        };
+        db.add_position("AAPL", 100.0, 180.0, PositionType::Buy, "2024-01-02", None)
+            .unwrap();
+        db.add_option_position("AAPL", 100.0, 3.5, &leg, "2024-01-03", None)
+            .unwrap();
+
+        let positions = db.get_positions().unwrap();
+        assert_eq!(positions.len(), 2);
+        let call = positions.iter().find(|p| p.option.is_some()).unwrap();
+        assert_eq!(call.position_type, PositionType::Sell);
+        assert_eq!(call.option.as_ref(), Some(&leg));
+
+        // The short call doesn't close any of the shares it covers
+        let holdings = db.compute_positions_fifo().unwrap();
+        assert_eq!(holdings[0].position_type, PositionType::Buy);
+        assert_eq!(holdings[0].quantity(), 100.0);
+    }
+
+    #[test]
+    fn test_migrate_upgrades_old_schema() {
+        // strategies and backtest_runs as first shipped, before direction,
+        // trailing stops and the newer metrics existed
+        let db = Database::open_in_memory().unwrap();
+        db.conn
+            .execute_batch(
+                r#"
+                CREATE TABLE strategies (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT UNIQUE NOT NULL,
+                    description TEXT,
+                    entry_condition TEXT NOT NULL,
+                    entry_threshold REAL NOT NULL,
+                    exit_condition TEXT NOT NULL,
+                    exit_threshold REAL NOT NULL,
+                    stop_loss_percent REAL,
+                    take_profit_percent REAL,
+                    position_size_percent REAL NOT NULL DEFAULT 100.0,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+                CREATE TABLE backtest_runs (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    strategy_id INTEGER NOT NULL,
+                    strategy_name TEXT NOT NULL,
+                    symbol TEXT NOT NULL,
+                    start_date DATE NOT NULL,
+                    end_date DATE NOT NULL,
+                    initial_capital REAL NOT NULL,
+                    final_capital REAL NOT NULL,
+                    total_return REAL NOT NULL,
+                    total_return_dollars REAL NOT NULL,
+                    max_drawdown REAL NOT NULL,
+                    sharpe_ratio REAL NOT NULL,
+                    win_rate REAL NOT NULL,
+                    total_trades INTEGER NOT NULL,
+                    winning_trades INTEGER NOT NULL,
+                    losing_trades INTEGER NOT NULL,
+                    avg_win_percent REAL NOT NULL,
+                    avg_loss_percent REAL NOT NULL,
+                    profit_factor REAL NOT NULL,
+                    avg_trade_duration_days REAL NOT NULL,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (strategy_id) REFERENCES strategies(id)
+                );
+                INSERT INTO strategies
+                (name, entry_condition, entry_threshold, exit_condition, exit_threshold)
+                VALUES ('old', 'rsi_oversold', 30, 'rsi_overbought', 70);
+                "#,
+            )
+            .unwrap();
+        assert_eq!(db.schema_version().unwrap(), 0);
+
+        let latest = MIGRATIONS.last().unwrap().version;
+        assert_eq!(db.migrate().unwrap(), latest);
+        assert_eq!(db.schema_version().unwrap(), latest);
+
+        let columns = |table: &str| -> Vec<String> {
+            db.conn
+                .prepare(&format!("PRAGMA table_info({})", table))
+                .unwrap()
+                .query_map([], |row| row.get::<_, String>(1))
+                .unwrap()
+                .collect::<SqliteResult<Vec<_>>>()
+                .unwrap()
+        };
+        let strategy_columns = columns("strategies");
+        assert!(strategy_columns.contains(&"direction".to_string()));
+        assert!(strategy_columns.contains(&"trailing_stop_percent".to_string()));
+        let run_columns = columns("backtest_runs");
+        for column in ["trades_per_year", "sortino_ratio", "calmar_ratio"] {
+            assert!(run_columns.contains(&column.to_string()), "{}", column);
+        }
 
+        // Existing rows pick up the column default
+        let direction: String = db
+            .conn
+            .query_row("SELECT direction FROM strategies WHERE name = 'old'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(direction, "long");
+
+        // Re-running is a no-op, and init_schema then fills in missing tables
+        assert_eq!(db.migrate().unwrap(), latest);
+        db.init_schema().unwrap();
+        assert!(db.get_dividends("AAPL").unwrap().is_empty());
+        let strategy = db.get_strategy("old").unwrap().unwrap();
+        assert_eq!(strategy.direction, TradeDirection::Long);
+        assert_eq!(strategy.trailing_stop_percent, None);
+    }
 
--- Daily price data
-CREATE TABLE IF NOT EXISTS daily_prices (
-    symbol TEXT,
-    timestamp DATE,
-    open REAL,
-    high REAL,
-    low REAL,
-    close REAL,
-    volume INTEGER,
-    adjusted_close REAL,
-    source TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    PRIMARY KEY (symbol, timestamp)
-);
+    #[test]
+    fn test_strategy_direction_round_trip() {
+        let db = test_db();
+        let strategy = Strategy {
+            id: 0,
+            name: "RSI fade".to_string(),
+            description: None,
+            entry_condition: StrategyConditionType::RsiOverbought,
+            entry_threshold: 70.0,
+            exit_condition: StrategyConditionType::RsiOversold,
+            exit_threshold: 30.0,
+            stop_loss_percent: None,
+            take_profit_percent: None,
+            trailing_stop_percent: Some(7.5),
+            position_size_percent: 100.0,
+            direction: TradeDirection::Short,
+            entry_rules: None,
+            exit_rules: None,
+            position_sizing: Some(PositionSizing::RiskPercent {
+                risk: 0.5,
+                atr_mult: 3.0,
+            }),
+            created_at: String::new(),
+        };
+        db.save_strategy(&strategy).unwrap();
 
--- Macro economic indicators
-CREATE TABLE IF NOT EXISTS macro_data (
-    indicator TEXT,
-    date DATE,
-    value REAL,
-    frequency TEXT,
-    source TEXT DEFAULT 'FRED',
-    PRIMARY KEY (indicator, date)
-);
+        let loaded = db.get_strategy("RSI fade").unwrap().unwrap();
+        assert_eq!(loaded.direction, TradeDirection::Short);
+        assert_eq!(loaded.trailing_stop_percent, Some(7.5));
+        assert_eq!(loaded.position_sizing, strategy.position_sizing);
+        assert_eq!(db.get_strategies().unwrap()[0].direction, TradeDirection::Short);
+    }
 
--- Watchlists
-CREATE TABLE IF NOT EXISTS watchlists (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    name TEXT UNIQUE,
-    description TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-);
+    #[test]
+    fn test_vacuum_into_copies_data() {
+        let dest = std::env::temp_dir().join(format!("fp_vacuum_into_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&dest);
+
+        let mut db = test_db();
+        db.upsert_daily_prices(&[DailyPrice {
+            open: 185.0,
+            high: 186.0,
+            low: 184.0,
+            ..daily_bar("AAPL", NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), 185.5)
+        }])
+        .unwrap();
+
+        db.vacuum_into(&dest).unwrap();
+        // A second attempt must not overwrite the copy
+        assert!(db.vacuum_into(&dest).is_err());
+
+        let copy = Database::open(&dest).unwrap();
+        let prices = copy.get_prices("AAPL").unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].close, 185.5);
+        assert_eq!(db.get_prices("AAPL").unwrap().len(), 1);
+
+        drop(copy);
+        let _ = std::fs::remove_file(&dest);
+    }
 
-CREATE TABLE IF NOT EXISTS watchlist_symbols (
-    watchlist_id INTEGER,
-    symbol TEXT,
-    added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    notes TEXT,
-    PRIMARY KEY (watchlist_id, symbol),
-    FOREIGN KEY (watchlist_id) REFERENCES watchlists(id)
-);
+    #[test]
+    fn test_merge_from_disjoint_databases() {
+        let dir = std::env::temp_dir();
+        let target_path = dir.join(format!("fp_merge_target_{}.db", std::process::id()));
+        let source_path = dir.join(format!("fp_merge_source_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&target_path);
+        let _ = std::fs::remove_file(&source_path);
 
--- API call tracking
-CREATE TABLE IF NOT EXISTS api_calls (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    source TEXT,
-    endpoint TEXT,
-    symbol TEXT,
-    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
-    success BOOLEAN,
-    error_message TEXT
-);
+        let price = |symbol: &str, close: f64| {
+            daily_bar(symbol, NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), close)
+        };
 
--- Indexes
-CREATE INDEX IF NOT EXISTS idx_prices_symbol ON daily_prices(symbol);
-CREATE INDEX IF NOT EXISTS idx_prices_timestamp ON daily_prices(timestamp);
-CREATE INDEX IF NOT EXISTS idx_prices_source ON daily_prices(source);
-CREATE INDEX IF NOT EXISTS idx_symbols_sector ON symbols(sector);
-CREATE INDEX IF NOT EXISTS idx_macro_indicator ON macro_data(indicator);
-CREATE INDEX IF NOT EXISTS idx_macro_date ON macro_data(date);
-CREATE INDEX IF NOT EXISTS idx_api_calls_source ON api_calls(source);
-CREATE INDEX IF NOT EXISTS idx_api_calls_timestamp ON api_calls(timestamp);
+        {
+            let mut source = Database::open(&source_path).unwrap();
+            source.init_schema().unwrap();
+            source.upsert_daily_prices(&[price("MSFT", 370.0)]).unwrap();
+            source
+                .add_position("MSFT", 5.0, 370.0, PositionType::Buy, "2024-01-02", None)
+                .unwrap();
+        }
 
--- Views
-CREATE VIEW IF NOT EXISTS latest_prices AS
-SELECT p.*
-FROM daily_prices p
-INNER JOIN (
-    SELECT symbol, MAX(timestamp) as max_date
-    FROM daily_prices
-    GROUP BY symbol
-) latest ON p.symbol = latest.symbol AND p.timestamp = latest.max_date;
+        let mut target = Database::open(&target_path).unwrap();
+        target.init_schema().unwrap();
+        target.upsert_daily_prices(&[price("AAPL", 185.0)]).unwrap();
+
+        let summary = target.merge_from(&source_path).unwrap();
+        assert_eq!(summary.prices, 1);
+        assert_eq!(summary.positions, 1);
+        assert_eq!(summary.conflicts, 0);
+
+        let symbols = target.get_symbols_with_data().unwrap();
+        assert!(symbols.contains(&"AAPL".to_string()));
+        assert!(symbols.contains(&"MSFT".to_string()));
+        assert_eq!(target.get_latest_price("MSFT").unwrap(), Some(370.0));
+
+        // Merging again only produces conflicts
+        let again = target.merge_from(&source_path).unwrap();
+        assert_eq!(again.prices, 0);
+        assert_eq!(again.positions, 0);
+        assert_eq!(again.conflicts, 2);
+
+        drop(target);
+        let _ = std::fs::remove_file(&target_path);
+        let _ = std::fs::remove_file(&source_path);
+    }
 
-CREATE VIEW IF NOT EXISTS api_rate_limits AS
-SELECT
-    source,
-    COUNT(*) as calls_today,
-    MAX(timestamp) as last_call
-FROM api_calls
-WHERE DATE(timestamp) = DATE('now')
-GROUP BY source;
+    #[test]
+    fn test_merge_keeps_strategy_settings() {
+        let dir = std::env::temp_dir();
+        let target_path = dir.join(format!(
+            "fp_merge_strategy_target_{}.db",
+            std::process::id()
+        ));
+        let source_path = dir.join(format!(
+            "fp_merge_strategy_source_{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&target_path);
+        let _ = std::fs::remove_file(&source_path);
+
+        let strategy = Strategy {
+            id: 0,
+            name: "RSI fade".to_string(),
+            description: None,
+            entry_condition: StrategyConditionType::RsiOverbought,
+            entry_threshold: 70.0,
+            exit_condition: StrategyConditionType::RsiOversold,
+            exit_threshold: 30.0,
+            stop_loss_percent: None,
+            take_profit_percent: None,
+            trailing_stop_percent: Some(7.5),
+            position_size_percent: 100.0,
+            direction: TradeDirection::Short,
+            entry_rules: Some(StrategyRules {
+                combinator: RuleCombinator::Any,
+                rules: vec![
+                    StrategyRule {
+                        condition: StrategyConditionType::RsiOverbought,
+                        threshold: 70.0,
+                    },
+                    StrategyRule {
+                        condition: StrategyConditionType::RsiOverbought,
+                        threshold: 80.0,
+                    },
+                ],
+            }),
+            exit_rules: None,
+            position_sizing: Some(PositionSizing::RiskPercent {
+                risk: 0.5,
+                atr_mult: 3.0,
+            }),
+            created_at: String::new(),
+        };
+        {
+            let source = Database::open(&source_path).unwrap();
+            source.init_schema().unwrap();
+            source.save_strategy(&strategy).unwrap();
+        }
 
--- Technical indicators
-CREATE TABLE IF NOT EXISTS technical_indicators (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    symbol TEXT NOT NULL,
-    timestamp DATE NOT NULL,
-    indicator_name TEXT NOT NULL,
-    value REAL NOT NULL,
-    params TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    UNIQUE(symbol, timestamp, indicator_name)
-);
+        let mut target = Database::open(&target_path).unwrap();
+        target.init_schema().unwrap();
+        assert_eq!(target.merge_from(&source_path).unwrap().strategies, 1);
 
-CREATE INDEX IF NOT EXISTS idx_ti_symbol_date ON technical_indicators(symbol, timestamp);
-CREATE INDEX IF NOT EXISTS idx_ti_indicator ON technical_indicators(indicator_name);
+        let merged = target.get_strategy("RSI fade").unwrap().unwrap();
+        assert_eq!(merged.direction, TradeDirection::Short);
+        assert_eq!(merged.trailing_stop_percent, Some(7.5));
+        assert_eq!(merged.entry_rules, strategy.entry_rules);
+        assert_eq!(merged.position_sizing, strategy.position_sizing);
 
--- Price alerts
-CREATE TABLE IF NOT EXISTS price_alerts (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    symbol TEXT NOT NULL,
-    target_price REAL NOT NULL,
-    condition TEXT NOT NULL CHECK(condition IN ('above', 'below')),
-    triggered BOOLEAN DEFAULT 0,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-);
+        drop(target);
+        let _ = std::fs::remove_file(&target_path);
+        let _ = std::fs::remove_file(&source_path);
+    }
 
-CREATE INDEX IF NOT EXISTS idx_alerts_symbol ON price_alerts(symbol);
-CREATE INDEX IF NOT EXISTS idx_alerts_triggered ON price_alerts(triggered);
+    #[test]
+    fn test_merge_from_pre_migration_database() {
+        let dir = std::env::temp_dir();
+        let target_path = dir.join(format!("fp_merge_v0_target_{}.db", std::process::id()));
+        let source_path = dir.join(format!("fp_merge_v0_source_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&target_path);
+        let _ = std::fs::remove_file(&source_path);
 
--- Portfolio positions
-CREATE TABLE IF NOT EXISTS portfolio_positions (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    symbol TEXT NOT NULL,
-    quantity REAL NOT NULL,
-    price REAL NOT NULL,
-    position_type TEXT NOT NULL CHECK(position_type IN ('buy', 'sell')),
-    date TEXT NOT NULL,
-    notes TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-);
+        // Tables as an unmigrated (v0) database has them
+        {
+            let old = Connection::open(&source_path).unwrap();
+            old.execute_batch(
+                r#"
+                CREATE TABLE symbols (symbol TEXT PRIMARY KEY, name TEXT, sector TEXT,
+                    industry TEXT, market_cap REAL, country TEXT, exchange TEXT,
+                    currency TEXT, updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP);
+                CREATE TABLE daily_prices (symbol TEXT, timestamp DATE, open REAL,
+                    high REAL, low REAL, close REAL, volume INTEGER, source TEXT,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    PRIMARY KEY (symbol, timestamp));
+                CREATE TABLE technical_indicators (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT, symbol TEXT NOT NULL,
+                    timestamp DATE NOT NULL, indicator_name TEXT NOT NULL,
+                    value REAL NOT NULL, params TEXT,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(symbol, timestamp, indicator_name));
+                CREATE TABLE portfolio_positions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT, symbol TEXT NOT NULL,
+                    quantity REAL NOT NULL, price REAL NOT NULL, position_type TEXT NOT NULL,
+                    date TEXT NOT NULL, notes TEXT,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP);
+                CREATE TABLE strategies (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT UNIQUE NOT NULL,
+                    description TEXT, entry_condition TEXT NOT NULL,
+                    entry_threshold REAL NOT NULL, exit_condition TEXT NOT NULL,
+                    exit_threshold REAL NOT NULL, stop_loss_percent REAL,
+                    take_profit_percent REAL,
+                    position_size_percent REAL NOT NULL DEFAULT 100.0,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP);
+
+                INSERT INTO symbols (symbol, name) VALUES ('MSFT', 'Microsoft');
+                INSERT INTO daily_prices (symbol, timestamp, open, high, low, close, volume, source)
+                VALUES ('MSFT', '2024-01-02', 370, 370, 370, 370, 1000, 'csv'),
+                       ('AAPL', '2024-01-02', 190, 190, 190, 190, 1000, 'csv'),
+                       ('AAPL', '2024-01-03', 191, 191, 191, 191, 1000, 'csv');
+                INSERT INTO technical_indicators (symbol, timestamp, indicator_name, value)
+                VALUES ('MSFT', '2024-01-02', 'RSI_14', 55.0),
+                       ('AAPL', '2024-01-03', 'RSI_14', 60.0);
+                INSERT INTO portfolio_positions (symbol, quantity, price, position_type, date)
+                VALUES ('MSFT', 5, 370, 'buy', '2024-01-02');
+                INSERT INTO strategies (name, entry_condition, entry_threshold,
+                    exit_condition, exit_threshold)
+                VALUES ('old', 'rsi_oversold', 30, 'rsi_overbought', 70);
+                "#,
+            )
+            .unwrap();
+        }
 
-CREATE INDEX IF NOT EXISTS idx_positions_symbol ON portfolio_positions(symbol);
+        let mut target = Database::open(&target_path).unwrap();
+        target.init_schema().unwrap();
+        target
+            .set_setting(PRICE_SOURCE_PRIORITY_KEY, "csv")
+            .unwrap();
+        let aapl = daily_bar("AAPL", NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), 185.0);
+        target.upsert_daily_prices(&[aapl]).unwrap();
+        target
+            .upsert_indicator(&TechnicalIndicator {
+                symbol: "AAPL".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+                indicator_name: "RSI_14".to_string(),
+                value: 50.0,
+            })
+            .unwrap();
+
+        let summary = target.merge_from(&source_path).unwrap();
+        assert_eq!(summary.symbols, 1);
+        assert_eq!(summary.prices, 3);
+        assert_eq!(summary.indicators, 1);
+        assert_eq!(summary.strategies, 1);
+        assert_eq!(summary.positions, 1);
+        // Only the AAPL indicator, dropped with the invalidated series
+        assert_eq!(summary.conflicts, 1);
+
+        // The higher-priority csv bar takes the date; the test bar is kept
+        let prices = target.get_prices("AAPL").unwrap();
+        assert_eq!(prices.len(), 2);
+        assert_eq!((prices[0].close, prices[0].source.as_str()), (190.0, "csv"));
+        assert_eq!(
+            target.get_prices_by_source("AAPL", "test").unwrap().len(),
+            1
+        );
 
--- Google Trends data
-CREATE TABLE IF NOT EXISTS trends_data (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    keyword TEXT NOT NULL,
-    date DATE NOT NULL,
-    value INTEGER NOT NULL,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    UNIQUE(keyword, date)
-);
+        // AAPL's prices changed, so its indicators are recomputed later
+        assert!(target.get_all_indicators("AAPL").unwrap().is_empty());
+        assert_eq!(target.get_all_indicators("MSFT").unwrap().len(), 1);
 
-CREATE INDEX IF NOT EXISTS idx_trends_keyword ON trends_data(keyword);
-CREATE INDEX IF NOT EXISTS idx_trends_date ON trends_data(date);
+        let strategy = target.get_strategy("old").unwrap().unwrap();
+        assert_eq!(strategy.direction, TradeDirection::Long);
+        assert_eq!(target.get_positions().unwrap().len(), 1);
 
--- Trading signals
-CREATE TABLE IF NOT EXISTS signals (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    symbol TEXT NOT NULL,
-    signal_type TEXT NOT NULL,
-    direction TEXT NOT NULL CHECK(direction IN ('bullish', 'bearish', 'neutral')),
-    strength REAL NOT NULL CHECK(strength >= 0.0 AND strength <= 1.0),
-    price_at_signal REAL NOT NULL,
-    triggered_by TEXT NOT NULL,
-    trigger_value REAL NOT NULL,
-    timestamp DATE NOT NULL,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    acknowledged BOOLEAN DEFAULT 0,
-    UNIQUE(symbol, signal_type, timestamp)
-);
+        drop(target);
+        let _ = std::fs::remove_file(&target_path);
+        let _ = std::fs::remove_file(&source_path);
+    }
 
-CREATE INDEX IF NOT EXISTS idx_signals_symbol ON signals(symbol);
-CREATE INDEX IF NOT EXISTS idx_signals_type ON signals(signal_type);
-CREATE INDEX IF NOT EXISTS idx_signals_timestamp ON signals(timestamp);
-CREATE INDEX IF NOT EXISTS idx_signals_direction ON signals(direction);
-CREATE INDEX IF NOT EXISTS idx_signals_acknowledged ON signals(acknowledged);
+    #[test]
+    fn test_recompute_only_stored_indicator_names() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let prices: Vec<DailyPrice> = (0..40)
+            .map(|i| DailyPrice {
+                open: 100.0,
+                high: 101.0,
+                low: 99.0,
+                ..daily_bar(
+                    "AAPL",
+                    start + chrono::Duration::days(i),
+                    100.0 + (i as f64 * 0.5).sin() * 3.0,
+                )
+            })
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
+
+        // Only a stale RSI_14 value is stored
+        db.upsert_indicator(&TechnicalIndicator {
+            symbol: "AAPL".to_string(),
+            date: prices[39].date,
+            indicator_name: "RSI_14".to_string(),
+            value: -1.0,
+        })
+        .unwrap();
 
--- Indicator-based alerts
-CREATE TABLE IF NOT EXISTS indicator_alerts (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    symbol TEXT NOT NULL,
-    alert_type TEXT NOT NULL CHECK(alert_type IN ('threshold', 'crossover', 'band_touch')),
-    indicator_name TEXT NOT NULL,
-    secondary_indicator TEXT,
-    condition TEXT NOT NULL CHECK(condition IN (
-        'crosses_above', 'crosses_below', 'bullish_crossover', 'bearish_crossover'
-    )),
-    threshold REAL,
-    triggered BOOLEAN DEFAULT 0,
-    last_value REAL,
-    message TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-);
+        let names = db.get_indicator_names("AAPL").unwrap();
+        let recomputed = crate::indicators::calculate_named(&prices, &names);
+        db.upsert_indicators(&recomputed).unwrap();
 
-CREATE INDEX IF NOT EXISTS idx_ind_alerts_symbol ON indicator_alerts(symbol);
-CREATE INDEX IF NOT EXISTS idx_ind_alerts_triggered ON indicator_alerts(triggered);
+        assert_eq!(db.get_indicator_names("AAPL").unwrap(), vec!["RSI_14"]);
+        let latest = db.get_latest_indicators("AAPL").unwrap();
+        assert_eq!(latest.len(), 1);
+        assert!(latest[0].value >= 0.0 && latest[0].value <= 100.0);
+        assert_eq!(db.get_all_indicators("AAPL").unwrap().len(), 40 - 14);
+    }
 
--- Backtesting strategies
-CREATE TABLE IF NOT EXISTS strategies (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    name TEXT UNIQUE NOT NULL,
-    description TEXT,
-    entry_condition TEXT NOT NULL,
-    entry_threshold REAL NOT NULL,
-    exit_condition TEXT NOT NULL,
-    exit_threshold REAL NOT NULL,
-    stop_loss_percent REAL,
-    take_profit_percent REAL,
-    position_size_percent REAL NOT NULL DEFAULT 100.0,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-);
+    #[test]
+    fn test_get_first_price_date() {
+        let mut db = test_db();
+        assert_eq!(db.get_first_price_date("AAPL").unwrap(), None);
 
-CREATE INDEX IF NOT EXISTS idx_strategies_name ON strategies(name);
+        let prices: Vec<DailyPrice> = [5, 2, 9]
+            .iter()
+            .map(|&day| daily_bar("AAPL", NaiveDate::from_ymd_opt(2024, 3, day).unwrap(), 1.0))
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
 
--- Backtest runs
-CREATE TABLE IF NOT EXISTS backtest_runs (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    strategy_id INTEGER NOT NULL,
-    strategy_name TEXT NOT NULL,
-    symbol TEXT NOT NULL,
-    start_date DATE NOT NULL,
-    end_date DATE NOT NULL,
-    initial_capital REAL NOT NULL,
-    final_capital REAL NOT NULL,
-    total_return REAL NOT NULL,
-    total_return_dollars REAL NOT NULL,
-    max_drawdown REAL NOT NULL,
-    sharpe_ratio REAL NOT NULL,
-    win_rate REAL NOT NULL,
-    total_trades INTEGER NOT NULL,
-    winning_trades INTEGER NOT NULL,
-    losing_trades INTEGER NOT NULL,
-    avg_win_percent REAL NOT NULL,
-    avg_loss_percent REAL NOT NULL,
-    profit_factor REAL NOT NULL,
-    avg_trade_duration_days REAL NOT NULL,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    FOREIGN KEY (strategy_id) REFERENCES strategies(id)
-);
+        assert_eq!(
+            db.get_first_price_date("AAPL").unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 2)
+        );
+    }
 
-CREATE INDEX IF NOT EXISTS idx_backtest_runs_strategy ON backtest_runs(strategy_id);
-CREATE INDEX IF NOT EXISTS idx_backtest_runs_symbol ON backtest_runs(symbol);
-CREATE INDEX IF NOT EXISTS idx_backtest_runs_date ON backtest_runs(created_at);
+    #[test]
+    fn test_get_recent_prices_ascending() {
+        let mut db = test_db();
+        let start = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        let prices: Vec<DailyPrice> = (0..500)
+            .map(|i| DailyPrice {
+                high: 101.0 + i as f64,
+                low: 99.0 + i as f64,
+                ..daily_bar("AAPL", start + chrono::Duration::days(i), 100.0 + i as f64)
+            })
+            .collect();
+        db.upsert_daily_prices(&prices).unwrap();
+
+        let recent = db.get_recent_prices("AAPL", 100).unwrap();
+        assert_eq!(recent.len(), 100);
+        assert_eq!(recent.first().unwrap().date, prices[400].date);
+        assert_eq!(recent.last().unwrap().date, prices[499].date);
+        assert!(recent.windows(2).all(|w| w[0].date < w[1].date));
+    }
 
--- Backtest trades
-CREATE TABLE IF NOT EXISTS backtest_trades (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    backtest_id INTEGER NOT NULL,
-    symbol TEXT NOT NULL,
-    direction TEXT NOT NULL CHECK(direction IN ('long', 'short')),
-    entry_date DATE NOT NULL,
-    entry_price REAL NOT NULL,
-    entry_reason TEXT NOT NULL,
-    exit_date DATE,
-    exit_price REAL,
-    exit_reason TEXT,
-    shares REAL NOT NULL,
-    profit_loss REAL,
-    profit_loss_percent REAL,
-    FOREIGN KEY (backtest_id) REFERENCES backtest_runs(id)
-);
+    #[test]
+    fn test_macro_freshness_flags_overdue_monthly() {
+        let mut db = test_db();
+        let today = Utc::now().date_naive();
+
+        // Monthly series, latest observation 90 days ago
+        let monthly: Vec<MacroData> = (0..12)
+            .map(|i| MacroData {
+                indicator: "UNRATE".to_string(),
+                date: today - chrono::Duration::days(90 + i * 30),
+                value: 4.0,
+                source: "FRED".to_string(),
+            })
+            .collect();
+        // Daily series, latest observation yesterday
+        let daily: Vec<MacroData> = (0..30)
+            .map(|i| MacroData {
+                indicator: "DFF".to_string(),
+                date: today - chrono::Duration::days(1 + i),
+                value: 5.33,
+                source: "FRED".to_string(),
+            })
+            .collect();
+        db.upsert_macro_data_batch(&monthly).unwrap();
+        db.upsert_macro_data_batch(&daily).unwrap();
 
-CREATE INDEX IF NOT EXISTS idx_backtest_trades_run ON backtest_trades(backtest_id);
-CREATE INDEX IF NOT EXISTS idx_backtest_trades_symbol ON backtest_trades(symbol);
-"#;
+        let report = db.macro_freshness().unwrap();
+        let unrate = report.iter().find(|r| r.indicator == "UNRATE").unwrap();
+        let dff = report.iter().find(|r| r.indicator == "DFF").unwrap();
+
+        assert_eq!(unrate.frequency, MacroFrequency::Monthly);
+        assert_eq!(unrate.age_days, 90);
+        assert!(unrate.overdue);
+
+        assert_eq!(dff.frequency, MacroFrequency::Daily);
+        assert!(!dff.overdue);
+    }
+}
@@ -8,7 +8,10 @@ pub enum PipelineError {
     Database(#[from] rusqlite::Error),
 
     #[error("HTTP request error: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(reqwest::Error),
+
+    #[error("Request timed out: {0}")]
+    Timeout(String),
 
     #[error("JSON parsing error: {0}")]
     Json(#[from] serde_json::Error),
@@ -30,6 +33,55 @@ pub enum PipelineError {
 
     #[error("API error: {0}")]
     ApiError(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+}
+
+/// Query parameters holding credentials, masked wherever a URL is shown
+const SECRET_QUERY_PARAMS: &[&str] = &["api_key"];
+
+/// Mask credentials in the URL a request error carries
+///
+/// A `reqwest::Error` displays its URL, so without this a FRED API key
+/// would end up in retry logs and error messages.
+pub(crate) fn redact_url(mut err: reqwest::Error) -> reqwest::Error {
+    if let Some(url) = err.url_mut() {
+        let is_secret = |key: &str| SECRET_QUERY_PARAMS.contains(&key);
+        if url.query_pairs().any(|(key, _)| is_secret(&key)) {
+            let pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .map(|(key, value)| {
+                    let value = if is_secret(&key) {
+                        "REDACTED".into()
+                    } else {
+                        value
+                    };
+                    (key.into_owned(), value.into_owned())
+                })
+                .collect();
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+    }
+    err
+}
+
+impl From<reqwest::Error> for PipelineError {
+    fn from(err: reqwest::Error) -> Self {
+        let err = redact_url(err);
+        if err.is_timeout() {
+            PipelineError::Timeout(err.to_string())
+        } else {
+            PipelineError::Http(err)
+        }
+    }
+}
+
+impl PipelineError {
+    /// Whether the failure is transient and the request is worth retrying
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, PipelineError::Timeout(_) | PipelineError::RateLimited(_))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, PipelineError>;
@@ -1,19 +1,99 @@
 //! FRED (Federal Reserve Economic Data) fetcher
 //!
 //! Fetches macro economic data from FRED's public CSV endpoint.
-//! FREE - no API key required for basic access!
+//! FREE - no API key required for basic access! With an API key the official
+//! `series/observations` API is used instead, which adds date ranges,
+//! frequency aggregation and units transformations.
+
+#[cfg(feature = "async")]
+use std::sync::Mutex;
+use std::time::Duration;
 
 use chrono::NaiveDate;
 use csv::ReaderBuilder;
 use reqwest::blocking::Client;
+use serde::Deserialize;
 
 use crate::db::Database;
 use crate::error::{PipelineError, Result};
 use crate::models::MacroData;
+use crate::retry::RetryPolicy;
+
+const DEFAULT_BASE_URL: &str = "https://fred.stlouisfed.org";
+const DEFAULT_API_BASE_URL: &str = "https://api.stlouisfed.org";
+
+/// Settings key holding the FRED API key
+pub const API_KEY_SETTING: &str = "fred_api_key";
+
+/// Aggregation frequency for API observations (`frequency` parameter)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FredFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Annual,
+}
+
+impl FredFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FredFrequency::Daily => "d",
+            FredFrequency::Weekly => "w",
+            FredFrequency::Monthly => "m",
+            FredFrequency::Quarterly => "q",
+            FredFrequency::Annual => "a",
+        }
+    }
+}
+
+/// Units transformation for API observations (`units` parameter)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FredUnits {
+    /// Levels, no transformation
+    #[default]
+    Levels,
+    /// Change from the previous observation
+    Change,
+    /// Percent change from the previous observation
+    PercentChange,
+    /// Compounded annual rate of change
+    PercentChangeAnnualized,
+}
+
+impl FredUnits {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FredUnits::Levels => "lin",
+            FredUnits::Change => "chg",
+            FredUnits::PercentChange => "pch",
+            FredUnits::PercentChangeAnnualized => "pca",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ObservationsResponse {
+    observations: Vec<Observation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Observation {
+    date: String,
+    value: String,
+}
 
 /// FRED API client
 pub struct Fred {
     client: Client,
+    base_url: String,
+    api_base_url: String,
+    api_key: Option<String>,
+    observation_start: Option<NaiveDate>,
+    observation_end: Option<NaiveDate>,
+    frequency: Option<FredFrequency>,
+    units: FredUnits,
+    retry: RetryPolicy,
 }
 
 impl Default for Fred {
@@ -26,30 +106,125 @@ impl Fred {
     /// Create a new FRED client
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
-                .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64)")
-                .build()
-                .expect("Failed to create HTTP client"),
+            client: Self::build_client(None),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+            api_key: None,
+            observation_start: None,
+            observation_end: None,
+            frequency: None,
+            units: FredUnits::Levels,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Set a request timeout; requests exceeding it fail with `PipelineError::Timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = Self::build_client(Some(timeout));
+        self
+    }
+
+    /// Override the API base URL (e.g. for a proxy or a local test server)
+    ///
+    /// Applies to both the CSV endpoint and the keyed observations API.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self.api_base_url = self.base_url.clone();
+        self
+    }
+
+    /// Use the official observations API with this key instead of the CSV endpoint
+    pub fn with_api_key(mut self, key: String) -> Self {
+        let key = key.trim().to_string();
+        self.api_key = if key.is_empty() { None } else { Some(key) };
+        self
+    }
+
+    /// Limit observations to a date range (inclusive)
+    pub fn with_observation_range(mut self, start: Option<NaiveDate>, end: Option<NaiveDate>) -> Self {
+        self.observation_start = start;
+        self.observation_end = end;
+        self
+    }
+
+    /// Aggregate observations to a lower frequency (requires an API key)
+    pub fn with_frequency(mut self, frequency: FredFrequency) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Transform observation values (requires an API key)
+    pub fn with_units(mut self, units: FredUnits) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Retry 429, 5xx and connection failures up to `retries` times (default 2)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retry.max_retries = retries;
+        self
+    }
+
+    /// Backoff before the first retry, doubled for each one after (default 500ms)
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry.base_delay = delay;
+        self
+    }
+
+    fn build_client(timeout: Option<Duration>) -> Client {
+        let mut builder = Client::builder().user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64)");
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder.build().expect("Failed to create HTTP client")
+    }
+
+    /// Name a fetch of `indicator` is stored under
+    ///
+    /// The series ID itself for levels at FRED's native frequency. A units
+    /// or frequency transformation gets its own series, suffixed with the
+    /// FRED codes (e.g. `GDP:pch:q`, `GDP:lin:a`), so it never overwrites
+    /// the stored levels.
+    pub fn series_name(&self, indicator: &str) -> String {
+        if self.units == FredUnits::Levels && self.frequency.is_none() {
+            return indicator.to_string();
         }
+        let mut name = format!("{}:{}", indicator, self.units.as_str());
+        if let Some(frequency) = self.frequency {
+            name.push(':');
+            name.push_str(frequency.as_str());
+        }
+        name
     }
 
     /// Fetch macro data for an indicator
     ///
+    /// Uses the observations API when an API key is set, otherwise the public
+    /// CSV endpoint. Without a key the observation range is applied locally,
+    /// and frequency/units transformations are rejected. Records are named
+    /// by `series_name`.
+    ///
     /// # Arguments
     /// * `indicator` - FRED series ID (e.g., "GDP", "UNRATE", "DFF", "CPI")
     ///
     /// # Returns
     /// Vector of macro data records
     pub fn fetch_indicator(&self, indicator: &str) -> Result<Vec<MacroData>> {
+        if let Some(key) = &self.api_key {
+            return self.fetch_observations(indicator, key);
+        }
+        if self.frequency.is_some() || self.units != FredUnits::Levels {
+            return Err(PipelineError::Config(
+                "FRED frequency and units transformations require an API key".to_string(),
+            ));
+        }
+
         println!("[FETCH] Fetching {} from FRED...", indicator);
 
         // FRED CSV endpoint (no API key required)
-        let url = format!(
-            "https://fred.stlouisfed.org/graph/fredgraph.csv?id={}",
-            indicator
-        );
+        let url = csv_url(&self.base_url, indicator);
 
-        let response = self.client.get(&url).send()?;
+        let response = self.retry.send(|| self.client.get(&url))?;
 
         if !response.status().is_success() {
             return Err(PipelineError::NoData(format!(
@@ -59,51 +234,52 @@ impl Fred {
             )));
         }
 
-        let csv_text = response.text()?;
-
-        // Parse CSV
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(csv_text.as_bytes());
-
-        let mut data = Vec::new();
+        let mut data = parse_csv(&response.text()?, indicator)?;
+        data.retain(|d| self.in_observation_range(d.date));
 
-        for result in reader.records() {
-            let record = result?;
-
-            // First column is date, second is value
-            if record.len() < 2 {
-                continue;
-            }
+        println!("[OK] Fetched {} records for {}", data.len(), indicator);
+        Ok(data)
+    }
 
-            let date_str = &record[0];
-            let value_str = &record[1];
+    fn in_observation_range(&self, date: NaiveDate) -> bool {
+        self.observation_start.is_none_or(|start| date >= start)
+            && self.observation_end.is_none_or(|end| date <= end)
+    }
 
-            // Skip missing values (FRED uses "." for missing)
-            if value_str == "." || value_str.is_empty() {
-                continue;
-            }
+    /// URL for the keyed `series/observations` endpoint
+    fn observations_url(&self, indicator: &str, api_key: &str) -> String {
+        let mut url = observations_url(&self.api_base_url, indicator, api_key);
+        if let Some(start) = self.observation_start {
+            url.push_str(&format!("&observation_start={}", start));
+        }
+        if let Some(end) = self.observation_end {
+            url.push_str(&format!("&observation_end={}", end));
+        }
+        if let Some(frequency) = self.frequency {
+            url.push_str(&format!("&frequency={}", frequency.as_str()));
+        }
+        if self.units != FredUnits::Levels {
+            url.push_str(&format!("&units={}", self.units.as_str()));
+        }
+        url
+    }
 
-            // Parse date (YYYY-MM-DD format)
-            let date = match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                Ok(d) => d,
-                Err(_) => continue,
-            };
+    fn fetch_observations(&self, indicator: &str, api_key: &str) -> Result<Vec<MacroData>> {
+        println!("[FETCH] Fetching {} from the FRED API...", indicator);
 
-            // Parse value
-            let value: f64 = match value_str.parse() {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
+        let url = self.observations_url(indicator, api_key);
+        let response = self.retry.send(|| self.client.get(&url))?;
 
-            data.push(MacroData {
-                indicator: indicator.to_string(),
-                date,
-                value,
-                source: "FRED".to_string(),
-            });
+        if !response.status().is_success() {
+            return Err(PipelineError::ApiError(format!(
+                "FRED API returned HTTP {} for {}",
+                response.status(),
+                indicator
+            )));
         }
 
+        let data = parse_observations(response.json()?, &self.series_name(indicator));
+
         println!("[OK] Fetched {} records for {}", data.len(), indicator);
         Ok(data)
     }
@@ -112,8 +288,9 @@ impl Fred {
     pub fn fetch_and_store(&self, db: &mut Database, indicator: &str) -> Result<usize> {
         let data = self.fetch_indicator(indicator)?;
         let count = db.upsert_macro_data_batch(&data)?;
-        db.log_api_call("FRED", "graph", indicator)?;
-        println!("[OK] Stored {} records for {}", count, indicator);
+        let endpoint = if self.api_key.is_some() { "observations" } else { "graph" };
+        db.log_api_call("FRED", endpoint, indicator)?;
+        println!("[OK] Stored {} records for {}", count, self.series_name(indicator));
         Ok(count)
     }
 
@@ -156,6 +333,203 @@ impl Fred {
     }
 }
 
+/// Non-blocking FRED client for use from async code (e.g. Tauri commands)
+///
+/// Covers the CSV endpoint and, with an API key, the observations API in
+/// levels; date ranges and transformations stay on the blocking `Fred`.
+#[cfg(feature = "async")]
+pub struct AsyncFred {
+    client: reqwest::Client,
+    base_url: String,
+    api_base_url: String,
+    api_key: Option<String>,
+    retry: RetryPolicy,
+}
+
+#[cfg(feature = "async")]
+impl Default for AsyncFred {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncFred {
+    /// Create a new async FRED client
+    pub fn new() -> Self {
+        Self {
+            client: Self::build_client(None),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+            api_key: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Set a request timeout; requests exceeding it fail with `PipelineError::Timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = Self::build_client(Some(timeout));
+        self
+    }
+
+    /// Override the API base URL for both endpoints (see `Fred::with_base_url`)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self.api_base_url = self.base_url.clone();
+        self
+    }
+
+    /// Use the official observations API with this key instead of the CSV endpoint
+    pub fn with_api_key(mut self, key: String) -> Self {
+        let key = key.trim().to_string();
+        self.api_key = if key.is_empty() { None } else { Some(key) };
+        self
+    }
+
+    /// Retry 429, 5xx and connection failures up to `retries` times (default 2)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retry.max_retries = retries;
+        self
+    }
+
+    /// Backoff before the first retry, doubled for each one after (default 500ms)
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry.base_delay = delay;
+        self
+    }
+
+    fn build_client(timeout: Option<Duration>) -> reqwest::Client {
+        let mut builder =
+            reqwest::Client::builder().user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64)");
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder.build().expect("Failed to create HTTP client")
+    }
+
+    /// Fetch macro data for an indicator; see `Fred::fetch_indicator`
+    pub async fn fetch_indicator(&self, indicator: &str) -> Result<Vec<MacroData>> {
+        let url = match &self.api_key {
+            Some(key) => observations_url(&self.api_base_url, indicator, key),
+            None => csv_url(&self.base_url, indicator),
+        };
+        println!("[FETCH] Fetching {} from FRED...", indicator);
+
+        let response = self.retry.send_async(|| self.client.get(&url)).await?;
+
+        if !response.status().is_success() {
+            let message = format!("HTTP {} for {}", response.status(), indicator);
+            return Err(match self.api_key {
+                Some(_) => PipelineError::ApiError(format!("FRED API returned {}", message)),
+                None => PipelineError::NoData(message),
+            });
+        }
+
+        let data = match self.api_key {
+            Some(_) => parse_observations(response.json().await?, indicator),
+            None => parse_csv(&response.text().await?, indicator)?,
+        };
+
+        println!("[OK] Fetched {} records for {}", data.len(), indicator);
+        Ok(data)
+    }
+
+    /// Fetch indicator data, then store it with `db` locked only for the write
+    pub async fn fetch_and_store(&self, db: &Mutex<Database>, indicator: &str) -> Result<usize> {
+        let data = self.fetch_indicator(indicator).await?;
+
+        let mut db = db.lock().unwrap_or_else(|e| e.into_inner());
+        let count = db.upsert_macro_data_batch(&data)?;
+        let endpoint = match self.api_key {
+            Some(_) => "observations",
+            None => "graph",
+        };
+        db.log_api_call("FRED", endpoint, indicator)?;
+        println!("[OK] Stored {} records for {}", count, indicator);
+        Ok(count)
+    }
+}
+
+/// URL for the public CSV endpoint
+fn csv_url(base_url: &str, indicator: &str) -> String {
+    format!("{}/graph/fredgraph.csv?id={}", base_url, indicator)
+}
+
+/// URL for the keyed `series/observations` endpoint, before any range,
+/// frequency or units parameters
+fn observations_url(api_base_url: &str, indicator: &str, api_key: &str) -> String {
+    format!(
+        "{}/fred/series/observations?series_id={}&api_key={}&file_type=json",
+        api_base_url,
+        urlencoding::encode(indicator),
+        urlencoding::encode(api_key)
+    )
+}
+
+/// Parse the CSV endpoint's `DATE,<series>` rows, skipping missing values
+fn parse_csv(csv_text: &str, indicator: &str) -> Result<Vec<MacroData>> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(csv_text.as_bytes());
+
+    let mut data = Vec::new();
+
+    for result in reader.records() {
+        let record = result?;
+
+        // First column is date, second is value
+        if record.len() < 2 {
+            continue;
+        }
+
+        let date_str = &record[0];
+        let value_str = &record[1];
+
+        // Skip missing values (FRED uses "." for missing)
+        if value_str == "." || value_str.is_empty() {
+            continue;
+        }
+
+        // Parse date (YYYY-MM-DD format)
+        let date = match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+
+        // Parse value
+        let value: f64 = match value_str.parse() {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+
+        data.push(MacroData {
+            indicator: indicator.to_string(),
+            date,
+            value,
+            source: "FRED".to_string(),
+        });
+    }
+
+    Ok(data)
+}
+
+/// Convert an observations API response; missing values come back as "."
+fn parse_observations(body: ObservationsResponse, indicator: &str) -> Vec<MacroData> {
+    body.observations
+        .into_iter()
+        .filter_map(|obs| {
+            let date = NaiveDate::parse_from_str(&obs.date, "%Y-%m-%d").ok()?;
+            let value = obs.value.parse::<f64>().ok()?;
+            Some(MacroData {
+                indicator: indicator.to_string(),
+                date,
+                value,
+                source: "FRED".to_string(),
+            })
+        })
+        .collect()
+}
+
 /// Common FRED indicators
 pub mod indicators {
     /// Federal Funds Effective Rate (daily)
@@ -192,6 +566,7 @@ pub mod indicators {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::{MockResponse, MockServer};
 
     #[test]
     fn test_fetch_fed_funds() {
@@ -200,4 +575,135 @@ mod tests {
         assert!(!data.is_empty());
         assert_eq!(data[0].indicator, "DFF");
     }
+
+    #[test]
+    fn test_api_key_uses_observations_endpoint() {
+        let base_url = MockServer::start(|target| {
+            let expected = "/fred/series/observations?series_id=CPIAUCSL&api_key=abc123\
+                &file_type=json&observation_start=2023-01-01&observation_end=2023-12-31\
+                &frequency=q&units=pch";
+            if target != expected {
+                return MockResponse::status(400, target);
+            }
+            MockResponse::ok(
+                r#"{"observations":[
+                {"date":"2023-01-01","value":"1.2"},
+                {"date":"2023-04-01","value":"."},
+                {"date":"2023-07-01","value":"0.9"}]}"#,
+            )
+        });
+
+        let start = NaiveDate::from_ymd_opt(2023, 1, 1);
+        let end = NaiveDate::from_ymd_opt(2023, 12, 31);
+        let client = Fred::new()
+            .with_base_url(&base_url)
+            .with_api_key("abc123".to_string())
+            .with_observation_range(start, end)
+            .with_frequency(FredFrequency::Quarterly)
+            .with_units(FredUnits::PercentChange);
+
+        let data = client.fetch_indicator(indicators::CPI).unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].indicator, "CPIAUCSL:pch:q");
+        assert_eq!(data[1].date, NaiveDate::from_ymd_opt(2023, 7, 1).unwrap());
+        assert_eq!(data[1].value, 0.9);
+    }
+
+    #[test]
+    fn test_transformed_series_stored_beside_levels() {
+        let base_url = MockServer::start(|target| {
+            let value = if target.contains("&units=pch") {
+                "2.5"
+            } else {
+                "27000"
+            };
+            MockResponse::ok(&format!(
+                r#"{{"observations":[{{"date":"2024-01-01","value":"{}"}}]}}"#,
+                value
+            ))
+        });
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+
+        let levels = Fred::new()
+            .with_base_url(&base_url)
+            .with_api_key("abc123".to_string());
+        levels.fetch_and_store(&mut db, indicators::GDP).unwrap();
+        let change = levels.with_units(FredUnits::PercentChange);
+        assert_eq!(change.series_name("GDP"), "GDP:pch");
+        change.fetch_and_store(&mut db, indicators::GDP).unwrap();
+
+        assert_eq!(db.get_macro_data("GDP").unwrap()[0].value, 27000.0);
+        assert_eq!(db.get_macro_data("GDP:pch").unwrap()[0].value, 2.5);
+    }
+
+    #[test]
+    fn test_request_errors_redact_api_key() {
+        // Nothing listens on port 1, so the request fails with its URL attached
+        let client = Fred::new()
+            .with_base_url("http://127.0.0.1:1")
+            .with_api_key("secret123".to_string())
+            .with_retries(0);
+        let err = client.fetch_indicator(indicators::GDP).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("api_key=REDACTED"), "{}", message);
+        assert!(!format!("{} {:?}", message, err).contains("secret123"));
+    }
+
+    #[test]
+    fn test_without_key_uses_csv_endpoint() {
+        let base_url = MockServer::start(|target| {
+            if target != "/graph/fredgraph.csv?id=UNRATE" {
+                return MockResponse::status(400, target);
+            }
+            MockResponse::ok("DATE,UNRATE\n2023-11-01,3.7\n2023-12-01,3.7\n2024-01-01,3.8\n")
+        });
+
+        let client = Fred::new().with_base_url(&base_url).with_observation_range(
+            NaiveDate::from_ymd_opt(2023, 12, 1),
+            None,
+        );
+        let data = client.fetch_indicator(indicators::UNEMPLOYMENT).unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[1].value, 3.8);
+
+        // Transformations need the keyed API
+        let err = client
+            .with_units(FredUnits::Change)
+            .fetch_indicator(indicators::UNEMPLOYMENT)
+            .unwrap_err();
+        assert!(matches!(err, PipelineError::Config(_)));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_client_uses_csv_or_observations_endpoint() {
+        let base_url = MockServer::start(|target| match target {
+            "/graph/fredgraph.csv?id=UNRATE" => {
+                MockResponse::ok("DATE,UNRATE\n2023-12-01,3.7\n2024-01-01,.\n")
+            }
+            "/fred/series/observations?series_id=UNRATE&api_key=abc123&file_type=json" => {
+                MockResponse::ok(r#"{"observations":[{"date":"2024-01-01","value":"3.8"}]}"#)
+            }
+            _ => MockResponse::status(400, target),
+        });
+
+        let db = Mutex::new(Database::open_in_memory().unwrap());
+        db.lock().unwrap().init_schema().unwrap();
+
+        let client = AsyncFred::new().with_base_url(&base_url);
+        let data = client
+            .fetch_indicator(indicators::UNEMPLOYMENT)
+            .await
+            .unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].value, 3.7);
+
+        let client = client.with_api_key("abc123".to_string());
+        let stored = client
+            .fetch_and_store(&db, indicators::UNEMPLOYMENT)
+            .await
+            .unwrap();
+        assert_eq!(stored, 1);
+    }
 }
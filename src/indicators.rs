@@ -1,10 +1,109 @@
 //! Technical indicators calculator
 
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
 use crate::models::{DailyPrice, TechnicalIndicator};
+pub use crate::resample::{resample, ResamplePeriod};
+
+/// Description of an indicator series produced by `calculate_all`
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorInfo {
+    /// Stored indicator name (e.g. "RSI_14")
+    pub name: &'static str,
+    pub default_params: &'static str,
+    /// Minimum number of price bars before any value is produced
+    pub min_bars: usize,
+    pub description: &'static str,
+}
+
+/// List every indicator series `calculate_all` can produce
+pub fn available() -> Vec<IndicatorInfo> {
+    let info = |name, default_params, min_bars, description| IndicatorInfo {
+        name,
+        default_params,
+        min_bars,
+        description,
+    };
+
+    vec![
+        info("RSI_14", "period=14", 15, "Relative Strength Index; >70 overbought, <30 oversold"),
+        info("SMA_20", "period=20", 20, "Simple Moving Average of close"),
+        info("SMA_50", "period=50", 50, "Simple Moving Average of close"),
+        info("EMA_12", "period=12", 12, "Exponential Moving Average of close"),
+        info("EMA_26", "period=26", 26, "Exponential Moving Average of close"),
+        info("MACD_12_26", "fast=12,slow=26,signal=9", 35, "MACD line (fast EMA - slow EMA)"),
+        info("MACD_SIGNAL_9", "fast=12,slow=26,signal=9", 35, "EMA of the MACD line"),
+        info("MACD_HIST", "fast=12,slow=26,signal=9", 35, "MACD line minus signal line"),
+        info("BB_UPPER_20", "period=20,std_dev=2", 20, "Upper Bollinger Band"),
+        info("BB_MIDDLE_20", "period=20,std_dev=2", 20, "Middle Bollinger Band (SMA)"),
+        info("BB_LOWER_20", "period=20,std_dev=2", 20, "Lower Bollinger Band"),
+        info("BB_PERCENT_B_20", "period=20,std_dev=2", 20, "Bollinger %B; >1 above the upper band, <0 below the lower"),
+        info("BB_BANDWIDTH_20", "period=20,std_dev=2", 20, "Bollinger Bandwidth (band width / middle band)"),
+        info("ATR_14", "period=14", 15, "Average True Range (volatility)"),
+        info("KC_UPPER_20", "period=20,atr=20,mult=1.5", 21, "Upper Keltner Channel (EMA + ATR multiple)"),
+        info("KC_MIDDLE_20", "period=20,atr=20,mult=1.5", 21, "Middle Keltner Channel (EMA)"),
+        info("KC_LOWER_20", "period=20,atr=20,mult=1.5", 21, "Lower Keltner Channel (EMA - ATR multiple)"),
+        info("STOCH_K_14", "k=14,d=3", 17, "Stochastic %K; >80 overbought, <20 oversold"),
+        info("STOCH_D_3", "k=14,d=3", 17, "Stochastic %D (SMA of %K)"),
+        info("OBV", "", 2, "On-Balance Volume"),
+        info("VWAP", "reset=yearly", 1, "Cumulative volume-weighted typical price, reset each calendar year"),
+        info("ADX_14", "period=14", 29, "Average Directional Index; >25 strong trend"),
+        info("+DI_14", "period=14", 29, "Positive Directional Indicator"),
+        info("-DI_14", "period=14", 29, "Negative Directional Indicator"),
+        info("AROON_UP_25", "period=25", 26, "Aroon Up; 100 on a fresh 25-bar high"),
+        info("AROON_DOWN_25", "period=25", 26, "Aroon Down; 100 on a fresh 25-bar low"),
+        info("AROON_OSC_25", "period=25", 26, "Aroon Up minus Aroon Down"),
+        info("DONCHIAN_UPPER_20", "period=20", 20, "Upper Donchian Channel (20-bar highest high)"),
+        info("DONCHIAN_MID_20", "period=20", 20, "Donchian midline (average of upper and lower)"),
+        info("DONCHIAN_LOWER_20", "period=20", 20, "Lower Donchian Channel (20-bar lowest low)"),
+        info("WILLR_14", "period=14", 14, "Williams %R; >-20 overbought, <-80 oversold"),
+        info("ULTOSC_7_14_28", "short=7,medium=14,long=28", 29, "Ultimate Oscillator; >70 overbought, <30 oversold"),
+        info("CCI_20", "period=20", 20, "Commodity Channel Index; >100 overbought, <-100 oversold"),
+        info("MFI_14", "period=14", 15, "Money Flow Index; >80 overbought, <20 oversold"),
+        info("ADL", "", 1, "Accumulation/Distribution line (cumulative money flow volume)"),
+        info("CMF_20", "period=20", 20, "Chaikin Money Flow; >0 buying, <0 selling pressure"),
+        info("ROC_12", "period=12", 13, "Rate of Change in percent"),
+        info("KST", "roc=10,15,20,30;sma=10,10,10,15", 45, "Know Sure Thing weighted ROC momentum"),
+        info("KST_SIGNAL", "period=9", 53, "9-period SMA of KST"),
+        info("ICHIMOKU_TENKAN_9", "tenkan=9", 9, "Ichimoku conversion line (9-bar midpoint)"),
+        info("ICHIMOKU_KIJUN_26", "kijun=26", 26, "Ichimoku base line (26-bar midpoint)"),
+        info("ICHIMOKU_SENKOU_A_9_26", "tenkan=9,kijun=26", 26, "Ichimoku leading span A, shifted 26 bars ahead"),
+        info("ICHIMOKU_SENKOU_B_52", "senkou_b=52,kijun=26", 52, "Ichimoku leading span B, shifted 26 bars ahead"),
+        info("ICHIMOKU_CHIKOU_26", "kijun=26", 27, "Ichimoku lagging span (close shifted 26 bars back)"),
+    ]
+}
+
+/// Averaging method used for RSI gains and losses
+///
+/// Both methods seed with a simple average of the first `period` changes.
+/// `Wilder` then smooths recursively (`(prev * (n - 1) + current) / n`), as in
+/// Wilder's original definition and on TradingView, MetaTrader and most
+/// charting platforms. `Cutler` uses a plain rolling simple average throughout,
+/// which forgets old data after `period` bars and so doesn't depend on where
+/// the series starts; some scanners and spreadsheets compute RSI this way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RsiMethod {
+    #[default]
+    Wilder,
+    Cutler,
+}
 
 /// Calculate RSI (Relative Strength Index)
 /// Period is typically 14
 pub fn calculate_rsi(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndicator> {
+    calculate_rsi_with_method(prices, period, RsiMethod::Wilder)
+}
+
+/// Calculate RSI with an explicit averaging method
+///
+/// Values are stored as `RSI_{period}` regardless of method.
+pub fn calculate_rsi_with_method(
+    prices: &[DailyPrice],
+    period: usize,
+    method: RsiMethod,
+) -> Vec<TechnicalIndicator> {
     if prices.len() < period + 1 {
         return vec![];
     }
@@ -38,10 +137,19 @@ pub fn calculate_rsi(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndic
         value: rsi,
     });
 
-    // Calculate subsequent RSI values using smoothed averages
+    // Calculate subsequent RSI values using smoothed or rolling averages
     for i in period..gains.len() {
-        avg_gain = (avg_gain * (period - 1) as f64 + gains[i]) / period as f64;
-        avg_loss = (avg_loss * (period - 1) as f64 + losses[i]) / period as f64;
+        match method {
+            RsiMethod::Wilder => {
+                avg_gain = (avg_gain * (period - 1) as f64 + gains[i]) / period as f64;
+                avg_loss = (avg_loss * (period - 1) as f64 + losses[i]) / period as f64;
+            }
+            RsiMethod::Cutler => {
+                let window = i + 1 - period..=i;
+                avg_gain = gains[window.clone()].iter().sum::<f64>() / period as f64;
+                avg_loss = losses[window].iter().sum::<f64>() / period as f64;
+            }
+        }
 
         let rs = if avg_loss == 0.0 {
             100.0
@@ -197,8 +305,12 @@ pub fn calculate_macd(
 }
 
 /// Calculate Bollinger Bands
-/// Returns upper band, middle band (SMA), and lower band
+/// Returns upper band, middle band (SMA), lower band, %B and bandwidth
 /// Default: 20-period SMA with 2 standard deviations
+///
+/// %B is `(close - lower) / (upper - lower)` and bandwidth is
+/// `(upper - lower) / middle`; neither is emitted on a bar where it is
+/// undefined (a flat window, or a zero middle band).
 pub fn calculate_bollinger_bands(
     prices: &[DailyPrice],
     period: usize,
@@ -252,6 +364,19 @@ pub fn calculate_bollinger_bands(
             indicator_name: format!("BB_LOWER_{}", period),
             value: lower,
         });
+
+        let percent_b = (prices[i].close - lower) / (upper - lower);
+        let bandwidth = (upper - lower) / sma;
+        for (name, value) in [("BB_PERCENT_B", percent_b), ("BB_BANDWIDTH", bandwidth)] {
+            if value.is_finite() {
+                indicators.push(TechnicalIndicator {
+                    symbol: prices[0].symbol.clone(),
+                    date: prices[i].date,
+                    indicator_name: format!("{}_{}", name, period),
+                    value,
+                });
+            }
+        }
     }
 
     indicators
@@ -306,6 +431,46 @@ pub fn calculate_atr(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndic
     indicators
 }
 
+/// Calculate Keltner Channels
+/// Returns upper channel, middle line (EMA of close), and lower channel
+/// Default: 20-period EMA with 1.5 x 20-period ATR, the width used by the
+/// Bollinger/Keltner squeeze
+pub fn calculate_keltner_channels(
+    prices: &[DailyPrice],
+    period: usize,
+    atr_period: usize,
+    multiplier: f64,
+) -> Vec<TechnicalIndicator> {
+    let atr: HashMap<NaiveDate, f64> = calculate_atr(prices, atr_period)
+        .into_iter()
+        .map(|i| (i.date, i.value))
+        .collect();
+
+    let mut indicators = Vec::new();
+
+    for ema in calculate_ema(prices, period) {
+        let Some(&atr) = atr.get(&ema.date) else {
+            continue;
+        };
+        let middle = ema.value;
+
+        for (name, value) in [
+            ("KC_UPPER", middle + multiplier * atr),
+            ("KC_MIDDLE", middle),
+            ("KC_LOWER", middle - multiplier * atr),
+        ] {
+            indicators.push(TechnicalIndicator {
+                symbol: ema.symbol.clone(),
+                date: ema.date,
+                indicator_name: format!("{}_{}", name, period),
+                value,
+            });
+        }
+    }
+
+    indicators
+}
+
 /// Calculate Stochastic Oscillator
 /// %K = (Close - Lowest Low) / (Highest High - Lowest Low) * 100
 /// %D = SMA of %K
@@ -412,6 +577,59 @@ pub fn calculate_obv(prices: &[DailyPrice]) -> Vec<TechnicalIndicator> {
     indicators
 }
 
+/// When a cumulative VWAP starts over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VwapReset {
+    /// Restart on the first bar of each calendar year
+    #[default]
+    Yearly,
+    /// Accumulate over the whole history
+    Never,
+}
+
+/// Calculate VWAP (Volume-Weighted Average Price), reset each calendar year
+pub fn calculate_vwap(prices: &[DailyPrice]) -> Vec<TechnicalIndicator> {
+    calculate_vwap_with_reset(prices, VwapReset::Yearly)
+}
+
+/// Calculate VWAP with an explicit reset boundary
+///
+/// Intraday VWAP averages every trade in a session. We only store daily bars,
+/// so here each bar contributes its typical price `(high + low + close) / 3`
+/// weighted by its volume, and the running sums carry across days until the
+/// `reset` boundary: the value on a bar is `sum(typical * volume) /
+/// sum(volume)` over all bars since the last reset. Read it as an anchored
+/// VWAP (year-to-date by default), not a session VWAP. Bars before any volume
+/// has traded since the reset produce no value. Stored as `VWAP` regardless of
+/// reset.
+pub fn calculate_vwap_with_reset(prices: &[DailyPrice], reset: VwapReset) -> Vec<TechnicalIndicator> {
+    let mut indicators = Vec::new();
+    let mut price_volume = 0.0;
+    let mut volume = 0.0;
+
+    for (i, bar) in prices.iter().enumerate() {
+        if reset == VwapReset::Yearly && i > 0 && bar.date.year() != prices[i - 1].date.year() {
+            price_volume = 0.0;
+            volume = 0.0;
+        }
+
+        let typical = (bar.high + bar.low + bar.close) / 3.0;
+        price_volume += typical * bar.volume as f64;
+        volume += bar.volume as f64;
+
+        if volume > 0.0 {
+            indicators.push(TechnicalIndicator {
+                symbol: bar.symbol.clone(),
+                date: bar.date,
+                indicator_name: "VWAP".to_string(),
+                value: price_volume / volume,
+            });
+        }
+    }
+
+    indicators
+}
+
 /// Calculate ADX (Average Directional Index)
 /// Measures trend strength (not direction)
 /// ADX > 25 = strong trend, ADX < 20 = weak/no trend
@@ -530,6 +748,87 @@ pub fn calculate_adx(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndic
     indicators
 }
 
+/// Calculate Aroon Up, Aroon Down and the Aroon Oscillator
+/// Up = (period - bars since the highest high) / period * 100 over the last
+/// period + 1 bars; Down does the same for the lowest low. Oscillator = Up - Down.
+/// When several bars tie for the extreme, the most recent one counts, so a
+/// bar matching the window's high reads as a fresh high (Up = 100).
+/// Default period is 25
+pub fn calculate_aroon(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndicator> {
+    if period == 0 || prices.len() <= period {
+        return vec![];
+    }
+
+    let mut indicators = Vec::new();
+
+    for i in period..prices.len() {
+        let window = &prices[(i - period)..=i];
+
+        // Scan newest to oldest so ties resolve to the most recent bar
+        let mut since_high = 0;
+        let mut since_low = 0;
+        for (age, p) in window.iter().rev().enumerate() {
+            if p.high > window[period - since_high].high {
+                since_high = age;
+            }
+            if p.low < window[period - since_low].low {
+                since_low = age;
+            }
+        }
+
+        let up = (period - since_high) as f64 / period as f64 * 100.0;
+        let down = (period - since_low) as f64 / period as f64 * 100.0;
+
+        for (name, value) in [
+            ("AROON_UP", up),
+            ("AROON_DOWN", down),
+            ("AROON_OSC", up - down),
+        ] {
+            indicators.push(TechnicalIndicator {
+                symbol: prices[0].symbol.clone(),
+                date: prices[i].date,
+                indicator_name: format!("{}_{}", name, period),
+                value,
+            });
+        }
+    }
+
+    indicators
+}
+
+/// Calculate Donchian Channels
+/// Upper = highest high and Lower = lowest low over the last period bars,
+/// including the current one; Mid is their average.
+/// Default period is 20
+pub fn calculate_donchian(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndicator> {
+    if period == 0 || prices.len() < period {
+        return vec![];
+    }
+
+    let mut indicators = Vec::new();
+
+    for i in (period - 1)..prices.len() {
+        let window = &prices[(i + 1 - period)..=i];
+        let upper = window.iter().map(|p| p.high).fold(f64::NEG_INFINITY, f64::max);
+        let lower = window.iter().map(|p| p.low).fold(f64::INFINITY, f64::min);
+
+        for (name, value) in [
+            ("DONCHIAN_UPPER", upper),
+            ("DONCHIAN_MID", (upper + lower) / 2.0),
+            ("DONCHIAN_LOWER", lower),
+        ] {
+            indicators.push(TechnicalIndicator {
+                symbol: prices[0].symbol.clone(),
+                date: prices[i].date,
+                indicator_name: format!("{}_{}", name, period),
+                value,
+            });
+        }
+    }
+
+    indicators
+}
+
 /// Calculate Williams %R
 /// Momentum indicator ranging from 0 to -100
 /// Similar to Stochastic but inverted scale
@@ -573,6 +872,55 @@ pub fn calculate_williams_r(prices: &[DailyPrice], period: usize) -> Vec<Technic
     indicators
 }
 
+/// Calculate the Ultimate Oscillator
+/// Buying pressure over true range across three windows, weighted 4/2/1
+/// Ranges from 0 to 100; >70 overbought, <30 oversold
+/// Default periods are 7, 14 and 28
+pub fn calculate_ultimate(
+    prices: &[DailyPrice],
+    short: usize,
+    medium: usize,
+    long: usize,
+) -> Vec<TechnicalIndicator> {
+    let longest = short.max(medium).max(long);
+    if short == 0 || prices.len() < longest + 1 {
+        return vec![];
+    }
+
+    // Buying pressure and true range, from the second bar on
+    let (pressure, range): (Vec<f64>, Vec<f64>) = prices
+        .windows(2)
+        .map(|w| {
+            let true_low = w[1].low.min(w[0].close);
+            let true_high = w[1].high.max(w[0].close);
+            (w[1].close - true_low, true_high - true_low)
+        })
+        .unzip();
+
+    // Pressure / range over the `n` bars ending at `i`; flat windows count as neutral
+    let average = |i: usize, n: usize| -> f64 {
+        let bp: f64 = pressure[i + 1 - n..=i].iter().sum();
+        let tr: f64 = range[i + 1 - n..=i].iter().sum();
+        if tr == 0.0 {
+            0.5
+        } else {
+            bp / tr
+        }
+    };
+
+    (longest - 1..pressure.len())
+        .map(|i| {
+            let weighted = 4.0 * average(i, short) + 2.0 * average(i, medium) + average(i, long);
+            TechnicalIndicator {
+                symbol: prices[0].symbol.clone(),
+                date: prices[i + 1].date,
+                indicator_name: format!("ULTOSC_{}_{}_{}", short, medium, long),
+                value: 100.0 * weighted / 7.0,
+            }
+        })
+        .collect()
+}
+
 /// Calculate CCI (Commodity Channel Index)
 /// Measures price deviation from statistical mean
 /// CCI > 100 = overbought, CCI < -100 = oversold
@@ -680,6 +1028,62 @@ pub fn calculate_mfi(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndic
     indicators
 }
 
+/// Money flow volume of a bar: volume scaled by where the close sits in its range
+///
+/// The multiplier `((C - L) - (H - C)) / (H - L)` runs from -1 (close on the
+/// low) to 1 (close on the high); a bar with no range counts as 0.
+fn money_flow_volume(bar: &DailyPrice) -> f64 {
+    let range = bar.high - bar.low;
+    if range == 0.0 {
+        return 0.0;
+    }
+    let multiplier = ((bar.close - bar.low) - (bar.high - bar.close)) / range;
+    multiplier * bar.volume as f64
+}
+
+/// Calculate the Accumulation/Distribution line
+/// Running total of money flow volume, starting from the first bar
+pub fn calculate_adl(prices: &[DailyPrice]) -> Vec<TechnicalIndicator> {
+    let mut adl = 0.0;
+    prices
+        .iter()
+        .map(|bar| {
+            adl += money_flow_volume(bar);
+            TechnicalIndicator {
+                symbol: bar.symbol.clone(),
+                date: bar.date,
+                indicator_name: "ADL".to_string(),
+                value: adl,
+            }
+        })
+        .collect()
+}
+
+/// Calculate CMF (Chaikin Money Flow)
+/// Money flow volume summed over the period divided by volume over the period
+/// Ranges from -1 to 1; default period is 20
+pub fn calculate_cmf(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndicator> {
+    if period == 0 || prices.len() < period {
+        return vec![];
+    }
+
+    let flows: Vec<f64> = prices.iter().map(money_flow_volume).collect();
+
+    (period - 1..prices.len())
+        .map(|i| {
+            let window = i + 1 - period..=i;
+            let volume: f64 = prices[window.clone()].iter().map(|p| p.volume as f64).sum();
+            let flow: f64 = flows[window].iter().sum();
+            TechnicalIndicator {
+                symbol: prices[0].symbol.clone(),
+                date: prices[i].date,
+                indicator_name: format!("CMF_{}", period),
+                value: if volume == 0.0 { 0.0 } else { flow / volume },
+            }
+        })
+        .collect()
+}
+
 /// Calculate ROC (Rate of Change)
 /// Momentum oscillator measuring percentage change over N periods
 /// Default period is 12
@@ -711,7 +1115,416 @@ pub fn calculate_roc(prices: &[DailyPrice], period: usize) -> Vec<TechnicalIndic
     indicators
 }
 
+/// Calculate rolling beta of an asset against a benchmark
+/// Benchmark closes are forward-filled onto the asset's dates; beta is
+/// cov(asset, benchmark) / var(benchmark) over the last `window` daily returns
+pub fn calculate_rolling_beta(
+    asset: &[DailyPrice],
+    benchmark: &[DailyPrice],
+    window: usize,
+) -> Vec<TechnicalIndicator> {
+    if asset.is_empty() || benchmark.is_empty() || window < 2 {
+        return vec![];
+    }
+
+    let mut asset_sorted: Vec<&DailyPrice> = asset.iter().collect();
+    asset_sorted.sort_by_key(|p| p.date);
+    let mut bench_sorted: Vec<&DailyPrice> = benchmark.iter().collect();
+    bench_sorted.sort_by_key(|p| p.date);
+
+    // Align: (date, asset close, last known benchmark close)
+    let mut aligned = Vec::with_capacity(asset_sorted.len());
+    let mut j = 0;
+    let mut last_bench: Option<f64> = None;
+    for p in &asset_sorted {
+        while j < bench_sorted.len() && bench_sorted[j].date <= p.date {
+            last_bench = Some(bench_sorted[j].close);
+            j += 1;
+        }
+        if let Some(bench_close) = last_bench {
+            aligned.push((p.date, p.close, bench_close));
+        }
+    }
+
+    if aligned.len() <= window {
+        return vec![];
+    }
+
+    // Daily returns on aligned dates
+    let returns: Vec<(f64, f64)> = aligned
+        .windows(2)
+        .map(|w| {
+            let asset_ret = if w[0].1 != 0.0 { (w[1].1 - w[0].1) / w[0].1 } else { 0.0 };
+            let bench_ret = if w[0].2 != 0.0 { (w[1].2 - w[0].2) / w[0].2 } else { 0.0 };
+            (asset_ret, bench_ret)
+        })
+        .collect();
+
+    let name = format!("BETA_{}_{}", bench_sorted[0].symbol, window);
+    let mut indicators = Vec::new();
+
+    for i in (window - 1)..returns.len() {
+        let slice = &returns[i + 1 - window..=i];
+        let mean_asset = slice.iter().map(|r| r.0).sum::<f64>() / window as f64;
+        let mean_bench = slice.iter().map(|r| r.1).sum::<f64>() / window as f64;
+
+        let covariance = slice
+            .iter()
+            .map(|r| (r.0 - mean_asset) * (r.1 - mean_bench))
+            .sum::<f64>()
+            / window as f64;
+        let variance = slice
+            .iter()
+            .map(|r| (r.1 - mean_bench).powi(2))
+            .sum::<f64>()
+            / window as f64;
+
+        if variance == 0.0 {
+            continue;
+        }
+
+        indicators.push(TechnicalIndicator {
+            symbol: asset_sorted[0].symbol.clone(),
+            // returns[i] ends on aligned[i + 1]
+            date: aligned[i + 1].0,
+            indicator_name: name.clone(),
+            value: covariance / variance,
+        });
+    }
+
+    indicators
+}
+
+/// Calculate Pring's KST (Know Sure Thing)
+///
+/// Sum of four smoothed rates of change, ROC 10/15/20/30 averaged over
+/// 10/10/10/15 bars and weighted 1/2/3/4, with a 9-period SMA signal line.
+/// The first KST value needs 30 + 15 - 1 = 44 prior bars (ROC 30 smoothed
+/// over 15), and the signal line another 8 on top of that.
+pub fn calculate_kst(prices: &[DailyPrice]) -> Vec<TechnicalIndicator> {
+    const ROC_PERIODS: [usize; 4] = [10, 15, 20, 30];
+    const SMA_PERIODS: [usize; 4] = [10, 10, 10, 15];
+    const SIGNAL_PERIOD: usize = 9;
+
+    // Index of the first bar where every smoothed ROC is available
+    let start = ROC_PERIODS
+        .iter()
+        .zip(SMA_PERIODS)
+        .map(|(roc, sma)| roc + sma - 1)
+        .max()
+        .unwrap_or(0);
+
+    if prices.len() <= start {
+        return vec![];
+    }
+
+    let roc_at = |i: usize, period: usize| {
+        let past = prices[i - period].close;
+        if past == 0.0 {
+            0.0
+        } else {
+            (prices[i].close - past) / past * 100.0
+        }
+    };
+
+    let kst: Vec<f64> = (start..prices.len())
+        .map(|i| {
+            ROC_PERIODS
+                .iter()
+                .zip(SMA_PERIODS)
+                .enumerate()
+                .map(|(k, (&roc, sma))| {
+                    let avg = (i + 1 - sma..=i).map(|j| roc_at(j, roc)).sum::<f64>() / sma as f64;
+                    (k + 1) as f64 * avg
+                })
+                .sum()
+        })
+        .collect();
+
+    let mut indicators = Vec::new();
+
+    for (idx, &value) in kst.iter().enumerate() {
+        let date = prices[start + idx].date;
+
+        indicators.push(TechnicalIndicator {
+            symbol: prices[0].symbol.clone(),
+            date,
+            indicator_name: "KST".to_string(),
+            value,
+        });
+
+        if idx + 1 >= SIGNAL_PERIOD {
+            let signal = kst[idx + 1 - SIGNAL_PERIOD..=idx].iter().sum::<f64>() / SIGNAL_PERIOD as f64;
+            indicators.push(TechnicalIndicator {
+                symbol: prices[0].symbol.clone(),
+                date,
+                indicator_name: "KST_SIGNAL".to_string(),
+                value: signal,
+            });
+        }
+    }
+
+    indicators
+}
+
+/// Midpoint of the highest high and lowest low over `period` bars ending at `end`
+fn range_midpoint(prices: &[DailyPrice], end: usize, period: usize) -> f64 {
+    let window = &prices[(end + 1 - period)..=end];
+    let high = window.iter().map(|p| p.high).fold(f64::NEG_INFINITY, f64::max);
+    let low = window.iter().map(|p| p.low).fold(f64::INFINITY, f64::min);
+    (high + low) / 2.0
+}
+
+/// Date of bar `index`, projecting past the last bar onto following weekdays
+fn bar_or_projected_date(prices: &[DailyPrice], index: usize) -> NaiveDate {
+    let last = prices.len() - 1;
+    if index <= last {
+        return prices[index].date;
+    }
+
+    let mut date = prices[last].date;
+    for _ in 0..(index - last) {
+        date = date.succ_opt().unwrap_or(date);
+        while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            date = date.succ_opt().unwrap_or(date);
+        }
+    }
+    date
+}
+
+/// Calculate the Ichimoku Cloud
+/// Standard parameters are 9/26/52
+///
+/// Emits five series:
+/// - `ICHIMOKU_TENKAN_{tenkan}`: midpoint of the last `tenkan` bars
+/// - `ICHIMOKU_KIJUN_{kijun}`: midpoint of the last `kijun` bars
+/// - `ICHIMOKU_SENKOU_A_{tenkan}_{kijun}`: (tenkan + kijun) / 2, shifted `kijun` bars ahead
+/// - `ICHIMOKU_SENKOU_B_{senkou_b}`: midpoint of the last `senkou_b` bars, shifted `kijun` bars ahead
+/// - `ICHIMOKU_CHIKOU_{kijun}`: close, shifted `kijun` bars back
+///
+/// Shifts count bars, not calendar days. The Senkou spans of the last
+/// `kijun` bars land after the final price bar; those are dated on the
+/// following weekdays (holidays are not known here), so the cloud extends
+/// up to `kijun` weekdays into the future. Chikou values are only emitted
+/// for bars that exist, so its series ends `kijun` bars before the last bar.
+pub fn calculate_ichimoku(
+    prices: &[DailyPrice],
+    tenkan: usize,
+    kijun: usize,
+    senkou_b: usize,
+) -> Vec<TechnicalIndicator> {
+    if tenkan == 0 || kijun == 0 || senkou_b == 0 || prices.len() < tenkan.max(kijun) {
+        return vec![];
+    }
+
+    let symbol = &prices[0].symbol;
+    let point = |date, name: String, value| TechnicalIndicator {
+        symbol: symbol.clone(),
+        date,
+        indicator_name: name,
+        value,
+    };
+
+    let mut indicators = Vec::new();
+
+    for i in 0..prices.len() {
+        let tenkan_value = (i + 1 >= tenkan).then(|| range_midpoint(prices, i, tenkan));
+        let kijun_value = (i + 1 >= kijun).then(|| range_midpoint(prices, i, kijun));
+
+        if let Some(value) = tenkan_value {
+            indicators.push(point(prices[i].date, format!("ICHIMOKU_TENKAN_{}", tenkan), value));
+        }
+        if let Some(value) = kijun_value {
+            indicators.push(point(prices[i].date, format!("ICHIMOKU_KIJUN_{}", kijun), value));
+        }
+
+        // Leading spans are plotted `kijun` bars ahead of the bar they're computed on
+        let ahead = bar_or_projected_date(prices, i + kijun);
+        if let (Some(t), Some(k)) = (tenkan_value, kijun_value) {
+            indicators.push(point(
+                ahead,
+                format!("ICHIMOKU_SENKOU_A_{}_{}", tenkan, kijun),
+                (t + k) / 2.0,
+            ));
+        }
+        if i + 1 >= senkou_b {
+            indicators.push(point(
+                ahead,
+                format!("ICHIMOKU_SENKOU_B_{}", senkou_b),
+                range_midpoint(prices, i, senkou_b),
+            ));
+        }
+
+        // Lagging span: today's close plotted `kijun` bars back
+        if i >= kijun {
+            indicators.push(point(
+                prices[i - kijun].date,
+                format!("ICHIMOKU_CHIKOU_{}", kijun),
+                prices[i].close,
+            ));
+        }
+    }
+
+    indicators
+}
+
+/// Recalculate only the named indicator series
+///
+/// Periods are parsed from the stored names (`RSI_9`, `SMA_200`,
+/// `BB_UPPER_20`, ...), so a symbol keeps exactly the indicator set it
+/// already had. Multi-series indicators are computed once and filtered to the
+/// requested names; MACD, stochastic and Ichimoku parameters missing from a name
+/// fall back to the `calculate_all` defaults. Names that can't be derived from
+/// prices alone (e.g. `BETA_*`, which needs a benchmark) are skipped.
+pub fn calculate_named(prices: &[DailyPrice], names: &[String]) -> Vec<TechnicalIndicator> {
+    let period = |name: &str, prefix: &str| -> Option<usize> {
+        name.strip_prefix(prefix).and_then(|p| p.parse().ok())
+    };
+
+    let mut macd = None::<(usize, usize, usize)>;
+    let mut stoch = None::<(usize, usize)>;
+    let mut ichimoku = None::<(usize, usize, usize)>;
+    let mut ultimate = Vec::new();
+    let mut single: Vec<Vec<TechnicalIndicator>> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for name in names {
+        let name = name.as_str();
+        if let Some(n) = period(name, "MACD_SIGNAL_") {
+            let (f, s, _) = macd.unwrap_or((12, 26, 9));
+            macd = Some((f, s, n));
+            continue;
+        }
+        if name == "MACD_HIST" {
+            macd.get_or_insert((12, 26, 9));
+            continue;
+        }
+        if let Some((f, s)) = name
+            .strip_prefix("MACD_")
+            .and_then(|rest| rest.split_once('_'))
+            .and_then(|(f, s)| Some((f.parse().ok()?, s.parse().ok()?)))
+        {
+            let (_, _, sig) = macd.unwrap_or((12, 26, 9));
+            macd = Some((f, s, sig));
+            continue;
+        }
+        if let Some(periods) = name.strip_prefix("ULTOSC_").and_then(|rest| {
+            let mut parts = rest.split('_').map(|p| p.parse::<usize>().ok());
+            Some((parts.next()??, parts.next()??, parts.next()??))
+        }) {
+            if !ultimate.contains(&periods) {
+                ultimate.push(periods);
+            }
+            continue;
+        }
+        if let Some(k) = period(name, "STOCH_K_") {
+            let (_, d) = stoch.unwrap_or((14, 3));
+            stoch = Some((k, d));
+            continue;
+        }
+        if let Some(d) = period(name, "STOCH_D_") {
+            let (k, _) = stoch.unwrap_or((14, 3));
+            stoch = Some((k, d));
+            continue;
+        }
+        if let Some(line) = name.strip_prefix("ICHIMOKU_") {
+            let (mut t, mut k, mut b) = ichimoku.unwrap_or((9, 26, 52));
+            if let Some(n) = period(line, "TENKAN_") {
+                t = n;
+            } else if let Some(n) = period(line, "KIJUN_").or(period(line, "CHIKOU_")) {
+                k = n;
+            } else if let Some(n) = period(line, "SENKOU_B_") {
+                b = n;
+            } else if let Some((nt, nk)) = line
+                .strip_prefix("SENKOU_A_")
+                .and_then(|rest| rest.split_once('_'))
+                .and_then(|(t, k)| Some((t.parse().ok()?, k.parse().ok()?)))
+            {
+                t = nt;
+                k = nk;
+            }
+            ichimoku = Some((t, k, b));
+            continue;
+        }
+
+        // One calculation per (kind, period), even if several of its series are named
+        let calc: Option<(&str, usize)> = [
+            "RSI_", "SMA_", "EMA_", "ATR_", "WILLR_", "CCI_", "MFI_", "ROC_", "ADX_", "+DI_",
+            "-DI_", "BB_UPPER_", "BB_MIDDLE_", "BB_LOWER_", "BB_PERCENT_B_", "BB_BANDWIDTH_",
+            "KC_UPPER_", "KC_MIDDLE_", "KC_LOWER_", "AROON_UP_", "AROON_DOWN_", "AROON_OSC_",
+            "DONCHIAN_UPPER_", "DONCHIAN_MID_", "DONCHIAN_LOWER_", "CMF_",
+        ]
+        .iter()
+        .find_map(|prefix| period(name, prefix).map(|n| (*prefix, n)))
+        .or(match name {
+            "OBV" => Some(("OBV", 0)),
+            "ADL" => Some(("ADL", 0)),
+            "VWAP" => Some(("VWAP", 0)),
+            "KST" | "KST_SIGNAL" => Some(("KST", 0)),
+            _ => None,
+        });
+
+        let Some((kind, n)) = calc else {
+            continue;
+        };
+        let kind = match kind {
+            "+DI_" | "-DI_" => "ADX_",
+            "BB_MIDDLE_" | "BB_LOWER_" | "BB_PERCENT_B_" | "BB_BANDWIDTH_" => "BB_UPPER_",
+            "KC_MIDDLE_" | "KC_LOWER_" => "KC_UPPER_",
+            "AROON_DOWN_" | "AROON_OSC_" => "AROON_UP_",
+            "DONCHIAN_MID_" | "DONCHIAN_LOWER_" => "DONCHIAN_UPPER_",
+            other => other,
+        };
+        if !seen.insert((kind, n)) {
+            continue;
+        }
+
+        single.push(match kind {
+            "RSI_" => calculate_rsi(prices, n),
+            "SMA_" => calculate_sma(prices, n),
+            "EMA_" => calculate_ema(prices, n),
+            "ATR_" => calculate_atr(prices, n),
+            "WILLR_" => calculate_williams_r(prices, n),
+            "CCI_" => calculate_cci(prices, n),
+            "MFI_" => calculate_mfi(prices, n),
+            "CMF_" => calculate_cmf(prices, n),
+            "ROC_" => calculate_roc(prices, n),
+            "ADX_" => calculate_adx(prices, n),
+            "BB_UPPER_" => calculate_bollinger_bands(prices, n, 2.0),
+            "KC_UPPER_" => calculate_keltner_channels(prices, n, n, 1.5),
+            "AROON_UP_" => calculate_aroon(prices, n),
+            "DONCHIAN_UPPER_" => calculate_donchian(prices, n),
+            "OBV" => calculate_obv(prices),
+            "ADL" => calculate_adl(prices),
+            "VWAP" => calculate_vwap(prices),
+            _ => calculate_kst(prices),
+        });
+    }
+
+    if let Some((fast, slow, signal)) = macd {
+        single.push(calculate_macd(prices, fast, slow, signal));
+    }
+    if let Some((k, d)) = stoch {
+        single.push(calculate_stochastic(prices, k, d));
+    }
+    if let Some((tenkan, kijun, senkou_b)) = ichimoku {
+        single.push(calculate_ichimoku(prices, tenkan, kijun, senkou_b));
+    }
+    for (short, medium, long) in ultimate {
+        single.push(calculate_ultimate(prices, short, medium, long));
+    }
+
+    single
+        .into_iter()
+        .flatten()
+        .filter(|ind| names.contains(&ind.indicator_name))
+        .collect()
+}
+
 /// Calculate all standard indicators for a symbol
+///
+/// NaN and infinite values (e.g. a ratio over a zero range) are dropped, so an
+/// undefined value shows up as a missing row rather than a poisoned one.
 pub fn calculate_all(prices: &[DailyPrice]) -> Vec<TechnicalIndicator> {
     let mut all = Vec::new();
 
@@ -735,26 +1548,707 @@ pub fn calculate_all(prices: &[DailyPrice]) -> Vec<TechnicalIndicator> {
     // ATR 14
     all.extend(calculate_atr(prices, 14));
 
+    // Keltner Channels 20, 1.5 x ATR 20
+    all.extend(calculate_keltner_channels(prices, 20, 20, 1.5));
+
     // Stochastic 14, 3
     all.extend(calculate_stochastic(prices, 14, 3));
 
     // OBV
     all.extend(calculate_obv(prices));
 
+    // VWAP, reset yearly
+    all.extend(calculate_vwap(prices));
+
     // ADX 14
     all.extend(calculate_adx(prices, 14));
 
+    // Aroon 25
+    all.extend(calculate_aroon(prices, 25));
+
+    // Donchian Channels 20
+    all.extend(calculate_donchian(prices, 20));
+
     // Williams %R 14
     all.extend(calculate_williams_r(prices, 14));
 
+    // Ultimate Oscillator 7/14/28
+    all.extend(calculate_ultimate(prices, 7, 14, 28));
+
     // CCI 20
     all.extend(calculate_cci(prices, 20));
 
     // MFI 14
     all.extend(calculate_mfi(prices, 14));
 
+    // Accumulation/Distribution line and CMF 20
+    all.extend(calculate_adl(prices));
+    all.extend(calculate_cmf(prices, 20));
+
     // ROC 12
     all.extend(calculate_roc(prices, 12));
 
+    // KST 10/15/20/30 with 9-period signal
+    all.extend(calculate_kst(prices));
+
+    // Ichimoku 9/26/52
+    all.extend(calculate_ichimoku(prices, 9, 26, 52));
+
+    all.retain(|ind| ind.value.is_finite());
     all
 }
+
+/// Calculate all standard indicators on daily bars resampled to `timeframe`
+///
+/// Values are dated by the last daily bar of each resampled bar (see `resample`).
+pub fn calculate_all_with_timeframe(
+    prices: &[DailyPrice],
+    timeframe: ResamplePeriod,
+) -> Vec<TechnicalIndicator> {
+    match timeframe {
+        ResamplePeriod::Daily => calculate_all(prices),
+        _ => calculate_all(&resample(prices, timeframe)),
+    }
+}
+
+/// Bars recomputed ahead of the new ones when an indicator can't be seeded
+/// from its stored value; covers the longest window in `calculate_all`
+pub const INCREMENTAL_WARMUP_BARS: usize = 250;
+
+/// Calculate indicators only for bars newer than those already stored
+///
+/// `existing` is the symbol's stored indicators and `prices` its date-sorted
+/// history; only the `INCREMENTAL_WARMUP_BARS` before the first new bar (and
+/// back to the start of that bar's year, for VWAP) are needed. EMA, ATR, OBV
+/// and the A/D line continue exactly from their stored values on the last
+/// indicator bar. Everything else is
+/// recomputed over a trailing warm-up window, which is exact for fixed-window
+/// indicators and converges for Wilder-smoothed ones (RSI, ADX) whose stored
+/// value alone can't seed the next step. Only values for new dates are returned, plus
+/// backward-shifted series (Ichimoku Chikou) that a new bar fills in. VWAP is
+/// recomputed from the start of the first new bar's year. With nothing stored
+/// this is `calculate_all`.
+pub fn calculate_incremental(
+    existing: &[TechnicalIndicator],
+    prices: &[DailyPrice],
+) -> Vec<TechnicalIndicator> {
+    // Only price bars can have been calculated. Senkou spans are projected
+    // past the bars they come from, onto dates a later bar may take, so their
+    // dates say nothing about which bars have been calculated
+    let price_dates: HashSet<NaiveDate> = prices.iter().map(|p| p.date).collect();
+    let stored_dates: HashSet<NaiveDate> = existing
+        .iter()
+        .filter(|i| !i.indicator_name.starts_with("ICHIMOKU_SENKOU"))
+        .map(|i| i.date)
+        .filter(|date| price_dates.contains(date))
+        .collect();
+    let Some(last_idx) = prices.iter().rposition(|p| stored_dates.contains(&p.date)) else {
+        return calculate_all(prices);
+    };
+    if last_idx + 1 == prices.len() {
+        return vec![];
+    }
+    let cutoff = prices[last_idx].date;
+
+    let stored: HashSet<(&str, NaiveDate)> = existing
+        .iter()
+        .map(|i| (i.indicator_name.as_str(), i.date))
+        .collect();
+    let stored_names: HashSet<&str> = existing.iter().map(|i| i.indicator_name.as_str()).collect();
+
+    // Continue seedable series from their values on the cutoff bar
+    let mut seeded = Vec::new();
+    let mut seeded_names = HashSet::new();
+    for ind in existing.iter().filter(|i| i.date == cutoff) {
+        if let Some(values) = continue_from_seed(&ind.indicator_name, ind.value, &prices[last_idx..]) {
+            seeded_names.insert(ind.indicator_name.clone());
+            seeded.extend(values);
+        }
+    }
+
+    // VWAP accumulates from its reset, which may lie beyond the warm-up window
+    let new_year = prices[last_idx + 1].date.year();
+    let year_start = prices[..=last_idx]
+        .iter()
+        .rposition(|p| p.date.year() != new_year)
+        .map_or(0, |i| i + 1);
+    seeded_names.insert("VWAP".to_string());
+    seeded.extend(
+        calculate_vwap(&prices[year_start..])
+            .into_iter()
+            .filter(|i| i.date > cutoff),
+    );
+
+    let tail_start = (last_idx + 1).saturating_sub(INCREMENTAL_WARMUP_BARS);
+    let mut result: Vec<TechnicalIndicator> = calculate_all(&prices[tail_start..])
+        .into_iter()
+        .filter(|i| !seeded_names.contains(&i.indicator_name))
+        .filter(|i| {
+            let name = i.indicator_name.as_str();
+            i.date > cutoff || (stored_names.contains(name) && !stored.contains(&(name, i.date)))
+        })
+        .collect();
+    result.extend(seeded);
+    result
+}
+
+/// Extend a recursively smoothed series over `prices[1..]`, given its value on `prices[0]`
+fn continue_from_seed(name: &str, seed: f64, prices: &[DailyPrice]) -> Option<Vec<TechnicalIndicator>> {
+    enum Rule {
+        Ema(f64),
+        Atr(f64),
+        Obv,
+        Adl,
+    }
+
+    let rule = if name == "OBV" {
+        Rule::Obv
+    } else if name == "ADL" {
+        Rule::Adl
+    } else if let Some(period) = name.strip_prefix("EMA_").and_then(|p| p.parse::<f64>().ok()) {
+        Rule::Ema(2.0 / (period + 1.0))
+    } else if let Some(period) = name.strip_prefix("ATR_").and_then(|p| p.parse::<f64>().ok()) {
+        Rule::Atr(period)
+    } else {
+        return None;
+    };
+
+    let mut value = seed;
+    let values = prices
+        .windows(2)
+        .map(|w| {
+            let (prev, bar) = (&w[0], &w[1]);
+            value = match rule {
+                Rule::Ema(multiplier) => (bar.close - value) * multiplier + value,
+                Rule::Atr(period) => {
+                    let tr = (bar.high - bar.low)
+                        .max((bar.high - prev.close).abs())
+                        .max((bar.low - prev.close).abs());
+                    (value * (period - 1.0) + tr) / period
+                }
+                Rule::Obv if bar.close > prev.close => value + bar.volume as f64,
+                Rule::Obv if bar.close < prev.close => value - bar.volume as f64,
+                Rule::Obv => value,
+                Rule::Adl => value + money_flow_volume(bar),
+            };
+            TechnicalIndicator {
+                symbol: bar.symbol.clone(),
+                date: bar.date,
+                indicator_name: name.to_string(),
+                value,
+            }
+        })
+        .collect();
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn series(symbol: &str, closes: &[f64]) -> Vec<DailyPrice> {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| DailyPrice {
+                symbol: symbol.to_string(),
+                date: start + chrono::Duration::days(i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1000,
+                adjusted_close: None,
+                source: "test".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rsi_methods_diverge_after_first_value() {
+        let closes = [
+            44.0, 44.3, 44.1, 43.6, 44.3, 44.8, 45.1, 45.4, 45.8, 46.1, 45.9, 46.3, 46.0, 46.4,
+            46.2, 45.6, 46.2, 46.3, 46.3, 46.0,
+        ];
+        let prices = series("TEST", &closes);
+
+        let wilder = calculate_rsi_with_method(&prices, 5, RsiMethod::Wilder);
+        let cutler = calculate_rsi_with_method(&prices, 5, RsiMethod::Cutler);
+
+        assert_eq!(wilder.len(), cutler.len());
+        assert_eq!(wilder[0].value, cutler[0].value);
+        assert_eq!(wilder[0].indicator_name, "RSI_5");
+        assert_eq!(cutler[0].indicator_name, "RSI_5");
+        assert!(wilder[1..]
+            .iter()
+            .zip(&cutler[1..])
+            .any(|(w, c)| (w.value - c.value).abs() > 1.0));
+
+        // Default path is Wilder
+        let default: Vec<f64> = calculate_rsi(&prices, 5).iter().map(|i| i.value).collect();
+        assert_eq!(default, wilder.iter().map(|i| i.value).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_kst_crosses_below_signal_after_top() {
+        // Accelerating rally for 80 bars, then a steady decline
+        let closes: Vec<f64> = (0..120)
+            .map(|i| {
+                let i = i as f64;
+                if i < 80.0 {
+                    100.0 * (0.0002 * i * i).exp()
+                } else {
+                    100.0 * (0.0002_f64 * 6400.0).exp() * (-0.01 * (i - 80.0)).exp()
+                }
+            })
+            .collect();
+        let prices = series("TEST", &closes);
+        let result = calculate_kst(&prices);
+
+        let kst: Vec<&TechnicalIndicator> =
+            result.iter().filter(|i| i.indicator_name == "KST").collect();
+        let signal: Vec<&TechnicalIndicator> =
+            result.iter().filter(|i| i.indicator_name == "KST_SIGNAL").collect();
+
+        assert_eq!(kst.len(), 120 - 44);
+        assert_eq!(kst[0].date, prices[44].date);
+        assert_eq!(signal.len(), kst.len() - 8);
+        assert_eq!(signal[0].date, prices[52].date);
+
+        // KST (aligned to the signal line) leads during the rally, then crosses below
+        let kst = &kst[8..];
+        let cross = (1..signal.len())
+            .find(|&i| kst[i - 1].value >= signal[i - 1].value && kst[i].value < signal[i].value)
+            .expect("KST never crossed below its signal");
+        let cross_bar = 52 + cross;
+        assert!(cross_bar > 80 && cross_bar < 95, "cross at bar {}", cross_bar);
+        assert!((0..cross).all(|i| kst[i].value >= signal[i].value));
+    }
+
+    #[test]
+    fn test_ichimoku_shift_offsets() {
+        // Mon 2024-01-01 .. Sat 2024-01-06
+        let bars = [
+            (10.0, 8.0, 9.0),
+            (12.0, 9.0, 11.0),
+            (11.0, 9.0, 10.0),
+            (13.0, 10.0, 12.0),
+            (15.0, 12.0, 14.0),
+            (14.0, 11.0, 13.0),
+        ];
+        let prices: Vec<DailyPrice> = series("TEST", &[0.0; 6])
+            .into_iter()
+            .zip(bars)
+            .map(|(mut p, (high, low, close))| {
+                p.high = high;
+                p.low = low;
+                p.close = close;
+                p
+            })
+            .collect();
+
+        let result = calculate_ichimoku(&prices, 2, 3, 4);
+        let line = |name: &str| -> Vec<(String, f64)> {
+            result
+                .iter()
+                .filter(|i| i.indicator_name == name)
+                .map(|i| (i.date.to_string(), i.value))
+                .collect()
+        };
+        let expect = |pairs: &[(&str, f64)]| -> Vec<(String, f64)> {
+            pairs.iter().map(|(d, v)| (d.to_string(), *v)).collect()
+        };
+
+        assert_eq!(
+            line("ICHIMOKU_TENKAN_2"),
+            expect(&[
+                ("2024-01-02", 10.0),
+                ("2024-01-03", 10.5),
+                ("2024-01-04", 11.0),
+                ("2024-01-05", 12.5),
+                ("2024-01-06", 13.0),
+            ])
+        );
+        assert_eq!(
+            line("ICHIMOKU_KIJUN_3"),
+            expect(&[
+                ("2024-01-03", 10.0),
+                ("2024-01-04", 11.0),
+                ("2024-01-05", 12.0),
+                ("2024-01-06", 12.5),
+            ])
+        );
+        // Shifted 3 bars ahead; past the last bar (Sat) onto Mon-Wed
+        assert_eq!(
+            line("ICHIMOKU_SENKOU_A_2_3"),
+            expect(&[
+                ("2024-01-06", 10.25),
+                ("2024-01-08", 11.0),
+                ("2024-01-09", 12.25),
+                ("2024-01-10", 12.75),
+            ])
+        );
+        assert_eq!(
+            line("ICHIMOKU_SENKOU_B_4"),
+            expect(&[("2024-01-08", 10.5), ("2024-01-09", 12.0), ("2024-01-10", 12.0)])
+        );
+        // Close shifted 3 bars back
+        assert_eq!(
+            line("ICHIMOKU_CHIKOU_3"),
+            expect(&[("2024-01-01", 12.0), ("2024-01-02", 14.0), ("2024-01-03", 13.0)])
+        );
+    }
+
+    #[test]
+    fn test_aroon_up_is_100_on_fresh_high() {
+        // Flat at 100 with a dip to 90 on bar 10, then a new high on the last bar
+        let mut closes = vec![100.0; 30];
+        closes[10] = 90.0;
+        closes[29] = 105.0;
+        let prices = series("TEST", &closes);
+
+        let aroon = calculate_aroon(&prices, 25);
+        assert_eq!(aroon.len(), 3 * (30 - 25));
+        let last: HashMap<&str, f64> = aroon[aroon.len() - 3..]
+            .iter()
+            .map(|i| (i.indicator_name.as_str(), i.value))
+            .collect();
+        assert_eq!(last["AROON_UP_25"], 100.0);
+        // The low was 19 bars ago: (25 - 19) / 25
+        assert!((last["AROON_DOWN_25"] - 24.0).abs() < 1e-9);
+        assert!((last["AROON_OSC_25"] - 76.0).abs() < 1e-9);
+
+        // On the flat bars every high ties, and the most recent one wins
+        let second_last = &aroon[aroon.len() - 6];
+        assert_eq!(second_last.indicator_name, "AROON_UP_25");
+        assert_eq!(second_last.value, 100.0);
+    }
+
+    #[test]
+    fn test_bollinger_percent_b_above_one_when_close_breaks_upper_band() {
+        // A flat window, then a spike well past two standard deviations
+        let mut closes = vec![100.0; 21];
+        closes[20] = 120.0;
+        let prices = series("TEST", &closes);
+
+        let bands = calculate_bollinger_bands(&prices, 20, 2.0);
+        let values = |name: &str| -> Vec<f64> {
+            bands
+                .iter()
+                .filter(|i| i.indicator_name == name)
+                .map(|i| i.value)
+                .collect()
+        };
+        let (upper, middle, lower) = (
+            values("BB_UPPER_20")[1],
+            values("BB_MIDDLE_20")[1],
+            values("BB_LOWER_20")[1],
+        );
+
+        // %B is undefined on the flat window, so only the spike bar has one
+        let percent_b = values("BB_PERCENT_B_20");
+        assert_eq!(percent_b.len(), 1);
+        assert!(percent_b[0] > 1.0);
+        assert!((percent_b[0] - (120.0 - lower) / (upper - lower)).abs() < 1e-12);
+
+        let bandwidth = values("BB_BANDWIDTH_20");
+        assert_eq!(bandwidth[0], 0.0);
+        assert!((bandwidth[1] - (upper - lower) / middle).abs() < 1e-12);
+
+        let names = ["BB_PERCENT_B_20".to_string()];
+        assert_eq!(calculate_named(&prices, &names).len(), 1);
+    }
+
+    #[test]
+    fn test_calculate_named_keeps_requested_set() {
+        let closes: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 * 0.4).sin() * 5.0).collect();
+        let prices = series("TEST", &closes);
+
+        let only_rsi = calculate_named(&prices, &["RSI_14".to_string()]);
+        assert!(!only_rsi.is_empty());
+        assert!(only_rsi.iter().all(|i| i.indicator_name == "RSI_14"));
+        assert_eq!(only_rsi.len(), calculate_rsi(&prices, 14).len());
+
+        // Custom periods and partial multi-series sets are honoured
+        let names: Vec<String> = ["SMA_5", "BB_LOWER_10", "MACD_SIGNAL_9", "BETA_SPY_60"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut produced: Vec<String> = calculate_named(&prices, &names)
+            .into_iter()
+            .map(|i| i.indicator_name)
+            .collect();
+        produced.sort();
+        produced.dedup();
+        assert_eq!(produced, vec!["BB_LOWER_10", "MACD_SIGNAL_9", "SMA_5"]);
+    }
+
+    #[test]
+    fn test_available_covers_calculate_all() {
+        let closes: Vec<f64> = (0..120).map(|i| 100.0 + (i as f64 * 0.3).sin() * 5.0).collect();
+        let prices: Vec<DailyPrice> = series("TEST", &closes)
+            .into_iter()
+            .map(|mut p| {
+                p.high = p.close + 1.0;
+                p.low = p.close - 1.0;
+                p
+            })
+            .collect();
+
+        let registered: Vec<&str> = available().iter().map(|i| i.name).collect();
+        let mut produced: Vec<String> = calculate_all(&prices)
+            .into_iter()
+            .map(|i| i.indicator_name)
+            .collect();
+        produced.sort();
+        produced.dedup();
+
+        for name in &produced {
+            assert!(
+                registered.contains(&name.as_str()),
+                "{} is produced by calculate_all but missing from available()",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_vwap_matches_manual_running_sum() {
+        // Two bars at the end of 2023, three in 2024
+        let start = NaiveDate::from_ymd_opt(2023, 12, 30).unwrap();
+        let bars = [
+            (12.0, 8.0, 10.0, 100),
+            (13.0, 11.0, 12.0, 300),
+            (21.0, 19.0, 20.0, 200),
+            (23.0, 21.0, 22.0, 0),
+            (24.0, 18.0, 21.0, 600),
+        ];
+        let prices: Vec<DailyPrice> = bars
+            .iter()
+            .enumerate()
+            .map(|(i, &(high, low, close, volume))| DailyPrice {
+                symbol: "TEST".to_string(),
+                date: start + chrono::Duration::days(i as i64),
+                open: close,
+                high,
+                low,
+                close,
+                volume,
+                adjusted_close: None,
+                source: "test".to_string(),
+            })
+            .collect();
+
+        let manual = |window: &[DailyPrice]| {
+            let pv: f64 = window
+                .iter()
+                .map(|p| (p.high + p.low + p.close) / 3.0 * p.volume as f64)
+                .sum();
+            pv / window.iter().map(|p| p.volume as f64).sum::<f64>()
+        };
+
+        let yearly = calculate_vwap(&prices);
+        assert_eq!(yearly.len(), 5);
+        assert!(yearly.iter().all(|i| i.indicator_name == "VWAP"));
+        assert!((yearly[1].value - manual(&prices[..2])).abs() < 1e-9);
+        // Resets on 2024-01-01, and a zero-volume bar leaves it unchanged
+        assert!((yearly[2].value - 20.0).abs() < 1e-9);
+        assert_eq!(yearly[3].value, yearly[2].value);
+        assert!((yearly[4].value - manual(&prices[2..])).abs() < 1e-9);
+
+        let never = calculate_vwap_with_reset(&prices, VwapReset::Never);
+        assert!((never[4].value - manual(&prices)).abs() < 1e-9);
+
+        // Incremental run for the last bar accumulates from the start of 2024
+        let existing: Vec<TechnicalIndicator> = calculate_all(&prices[..4]);
+        let vwap: Vec<_> = calculate_incremental(&existing, &prices)
+            .into_iter()
+            .filter(|i| i.indicator_name == "VWAP")
+            .collect();
+        assert_eq!(vwap.len(), 1);
+        assert!((vwap[0].value - yearly[4].value).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rolling_beta_rises_with_leverage() {
+        // Benchmark alternates up/down moves; asset tracks it 1x, then 2x
+        let bench_returns: Vec<f64> = (0..120)
+            .map(|i| if i % 3 == 0 { 0.02 } else { -0.008 })
+            .collect();
+
+        let mut bench = vec![100.0];
+        let mut asset = vec![100.0];
+        for (i, r) in bench_returns.iter().enumerate() {
+            let leverage = if i < 60 { 1.0 } else { 2.0 };
+            bench.push(bench.last().unwrap() * (1.0 + r));
+            asset.push(asset.last().unwrap() * (1.0 + r * leverage));
+        }
+
+        let betas = calculate_rolling_beta(&series("QQQ", &asset), &series("SPY", &bench), 20);
+
+        assert_eq!(betas.len(), 120 - 20 + 1);
+        assert!(betas.iter().all(|b| b.indicator_name == "BETA_SPY_20"));
+        assert!((betas.first().unwrap().value - 1.0).abs() < 1e-9);
+        assert!((betas.last().unwrap().value - 2.0).abs() < 1e-9);
+        assert!(betas.last().unwrap().value > betas.first().unwrap().value);
+    }
+    #[test]
+    fn test_cmf_bounded_and_adl_skips_zero_range() {
+        let closes: Vec<f64> = (0..60).map(|i| 50.0 + (i as f64 * 0.7).sin() * 4.0).collect();
+        let mut prices: Vec<DailyPrice> = series("TEST", &closes)
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut p)| {
+                p.high = p.close + 1.0 + (i % 3) as f64;
+                p.low = p.close - 2.0 + (i % 2) as f64;
+                p.volume = 1000 + (i as i64 % 7) * 300;
+                p
+            })
+            .collect();
+        // Zero-range bar contributes no money flow
+        prices[10].high = prices[10].close;
+        prices[10].low = prices[10].close;
+
+        let cmf = calculate_cmf(&prices, 20);
+        assert_eq!(cmf.len(), prices.len() - 19);
+        assert_eq!(cmf[0].indicator_name, "CMF_20");
+        assert!(cmf.iter().all(|i| (-1.0..=1.0).contains(&i.value)));
+
+        let adl = calculate_adl(&prices);
+        assert_eq!(adl.len(), prices.len());
+        assert_eq!(adl[10].value, adl[9].value);
+
+        // Closing on the high for the whole window pins CMF at 1
+        let all_high: Vec<DailyPrice> = prices
+            .iter()
+            .cloned()
+            .map(|mut p| {
+                p.low = p.close - 1.0;
+                p.high = p.close;
+                p
+            })
+            .collect();
+        let cmf = calculate_cmf(&all_high, 20);
+        assert!(cmf.iter().all(|i| (i.value - 1.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_ultimate_oscillator_bounded_and_high_in_uptrend() {
+        let closes: Vec<f64> = (0..80).map(|i| 50.0 + (i as f64 * 0.5).sin() * 3.0).collect();
+        let choppy: Vec<DailyPrice> = series("TEST", &closes)
+            .into_iter()
+            .map(|mut p| {
+                p.high = p.close + 1.0;
+                p.low = p.close - 1.5;
+                p
+            })
+            .collect();
+        let uo = calculate_ultimate(&choppy, 7, 14, 28);
+        assert_eq!(uo.len(), choppy.len() - 28);
+        assert_eq!(uo[0].indicator_name, "ULTOSC_7_14_28");
+        assert_eq!(uo[0].date, choppy[28].date);
+        assert!(uo.iter().all(|i| (0.0..=100.0).contains(&i.value)));
+
+        // Closing near the high of each bar while rising
+        let closes: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+        let rising: Vec<DailyPrice> = series("TEST", &closes)
+            .into_iter()
+            .map(|mut p| {
+                p.high = p.close + 0.2;
+                p.low = p.close - 1.0;
+                p
+            })
+            .collect();
+        let uo = calculate_ultimate(&rising, 7, 14, 28);
+        assert!(uo.iter().all(|i| i.value > 70.0), "{:?}", uo.last());
+
+        let named = calculate_named(&rising, &["ULTOSC_7_14_28".to_string()]);
+        assert_eq!(named.len(), uo.len());
+    }
+
+    #[test]
+    fn test_incremental_returns_only_new_bar() {
+        let closes: Vec<f64> = (0..300)
+            .map(|i| 100.0 + (i as f64 * 0.3).sin() * 8.0 + i as f64 * 0.05)
+            .collect();
+        let prices = series("TEST", &closes);
+        let (old, new_bar) = (&prices[..299], prices[299].date);
+
+        let existing = calculate_all(old);
+        let incremental = calculate_incremental(&existing, &prices);
+        assert!(!incremental.is_empty());
+
+        // Only the appended bar, apart from Ichimoku's shifted series
+        for ind in &incremental {
+            if ind.indicator_name.starts_with("ICHIMOKU_CHIKOU") {
+                continue;
+            }
+            if ind.indicator_name.starts_with("ICHIMOKU_SENKOU") {
+                assert!(ind.date >= new_bar, "{} {}", ind.indicator_name, ind.date);
+            } else {
+                assert_eq!(ind.date, new_bar, "{}", ind.indicator_name);
+            }
+        }
+
+        // Values agree with a full recompute
+        let full: HashMap<(String, NaiveDate), f64> = calculate_all(&prices)
+            .into_iter()
+            .map(|i| ((i.indicator_name, i.date), i.value))
+            .collect();
+        for name in ["EMA_12", "ATR_14", "OBV", "ADL", "CMF_20", "SMA_50", "RSI_14", "MACD_HIST"] {
+            let ind = incremental
+                .iter()
+                .find(|i| i.indicator_name == name)
+                .unwrap_or_else(|| panic!("missing {}", name));
+            let expected = full[&(name.to_string(), new_bar)];
+            assert!((ind.value - expected).abs() < 1e-6, "{}: {} vs {}", name, ind.value, expected);
+        }
+        assert_eq!(
+            incremental.iter().filter(|i| i.date == new_bar).count(),
+            full.keys().filter(|(_, d)| *d == new_bar).count()
+        );
+
+        // Nothing new, nothing returned; nothing stored, everything returned
+        assert!(calculate_incremental(&calculate_all(&prices), &prices).is_empty());
+        assert_eq!(calculate_incremental(&[], &prices).len(), full.len());
+    }
+
+    #[test]
+    fn test_incremental_on_weekday_bars_ignores_projected_senkou_dates() {
+        // Weekday-only bars, so the Senkou spans stored with the old bars
+        // land on the very dates the next bars take
+        let mut date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut prices = Vec::new();
+        while prices.len() < 120 {
+            if date.weekday().number_from_monday() <= 5 {
+                let close = 100.0 + (prices.len() as f64 * 0.3).sin() * 8.0;
+                prices.extend(
+                    series("TEST", &[close])
+                        .into_iter()
+                        .map(|p| DailyPrice { date, ..p }),
+                );
+            }
+            date = date.succ_opt().unwrap();
+        }
+        let new_bar = prices[119].date;
+
+        let existing = calculate_all(&prices[..119]);
+        assert!(existing
+            .iter()
+            .any(|i| i.indicator_name.starts_with("ICHIMOKU_SENKOU") && i.date == new_bar));
+
+        let incremental = calculate_incremental(&existing, &prices);
+        for name in ["SMA_20", "RSI_14", "EMA_12", "OBV"] {
+            assert!(
+                incremental
+                    .iter()
+                    .any(|i| i.indicator_name == name && i.date == new_bar),
+                "missing {}",
+                name
+            );
+        }
+    }
+}
@@ -16,7 +16,7 @@
 //!
 //! // Fetch stock prices
 //! let yahoo = YahooFinance::new();
-//! yahoo.fetch_and_store(&mut db, "AAPL", "1y").unwrap();
+//! yahoo.fetch_and_store(&mut db, "AAPL", "1d", "1y").unwrap();
 //!
 //! // Fetch macro data
 //! let fred = Fred::new();
@@ -27,32 +27,74 @@
 //! println!("AAPL: ${:.2}", price.unwrap_or(0.0));
 //! ```
 
+pub mod correlation;
 pub mod db;
 pub mod error;
 pub mod fred;
 pub mod indicators;
 pub mod models;
+pub mod notify;
+pub mod portfolio;
+pub mod resample;
+pub mod retry;
 pub mod backtest;
+pub mod seasonality;
 pub mod signals;
+pub mod trading_calendar;
 pub mod trends;
 pub mod yahoo;
 
+#[cfg(test)]
+mod testutil;
+
 // Re-exports for convenience
-pub use db::Database;
+pub use correlation::return_correlation;
+pub use db::{
+    BacktestExportFormat, Database, ExportPrecision, ImportSummary, MergeSummary, PriceSource,
+    BENCHMARK_SYMBOL_KEY, DEFAULT_BENCHMARK_SYMBOL, PRICE_SOURCE_PRIORITY_KEY,
+};
 pub use error::{PipelineError, Result};
-pub use fred::Fred;
+pub use fred::{Fred, FredFrequency, FredUnits, API_KEY_SETTING as FRED_API_KEY_SETTING};
+#[cfg(feature = "async")]
+pub use fred::AsyncFred;
 pub use indicators::{
-    calculate_adx, calculate_all, calculate_atr, calculate_bollinger_bands, calculate_cci,
-    calculate_ema, calculate_macd, calculate_mfi, calculate_obv, calculate_roc, calculate_rsi,
-    calculate_sma, calculate_stochastic, calculate_williams_r,
+    calculate_adl, calculate_adx, calculate_all, calculate_all_with_timeframe, calculate_aroon,
+    calculate_atr, calculate_bollinger_bands, calculate_cci, calculate_cmf, calculate_donchian,
+    calculate_ema, calculate_ichimoku, calculate_incremental, calculate_keltner_channels,
+    calculate_kst, calculate_macd, calculate_mfi, calculate_named, calculate_obv, calculate_roc,
+    calculate_rolling_beta, calculate_rsi, calculate_rsi_with_method, calculate_sma,
+    calculate_stochastic, calculate_ultimate, calculate_vwap, calculate_vwap_with_reset,
+    calculate_williams_r, IndicatorInfo, RsiMethod, VwapReset,
 };
 pub use models::{
-    AlertCondition, BacktestResult, BacktestTrade, DailyPrice, IndicatorAlert,
-    IndicatorAlertCondition, IndicatorAlertType, MacroData, PerformanceMetrics, Position,
-    PositionType, PriceAlert, Signal, SignalDirection, SignalType, Strategy,
-    StrategyConditionType, Symbol, TechnicalIndicator, TradeDirection, Watchlist,
+    AlertCondition, BacktestResult, BacktestTrade, DailyPrice, Dividend, IndicatorAlert,
+    IndicatorAlertCondition, IndicatorAlertType, IntradayPrice, MacroData, MacroFrequency,
+    MacroFreshness, OptionLeg, OptionStrategy, PerformanceMetrics, Position, PositionSizing,
+    PositionType, PriceAlert, RuleCombinator, Signal, SignalDirection, SignalType, Split, Strategy,
+    StrategyConditionType, StrategyRule, StrategyRules, Symbol, TechnicalIndicator, TradeDirection,
+    Watchlist,
+};
+pub use notify::{
+    deliver_in_background, validate_webhook_url, AlertNotification, LogFileSink,
+    NotificationSink, WebhookSink, LOG_FILE_SINK, WEBHOOK_SINK,
+};
+pub use portfolio::{
+    match_lots_fifo, FifoPosition, Lot, PortfolioExposure, PortfolioTotals, PositionValuation,
+};
+pub use backtest::{
+    information_ratio, BacktestConfig, BacktestEngine, BarDecision, CommissionModel,
+    MonteCarloStats, MultiSymbolBacktest, OptimizeMetric, ParamGrid, Rebalance, SizingBase,
+    StrategyParam, WalkForwardResult,
+};
+pub use resample::{downsample_prices, resample, ResamplePeriod};
+pub use retry::RetryPolicy;
+pub use seasonality::{
+    monthly_average_returns, monthly_return_stats, weekday_average_returns, weekday_return_stats,
+    SeasonalBucket,
 };
-pub use backtest::{BacktestConfig, BacktestEngine};
-pub use signals::{SignalConfig, SignalEngine};
-pub use trends::{GoogleTrends, TrendData};
-pub use yahoo::YahooFinance;
+pub use signals::{ConflictResolution, SignalConfig, SignalEngine, SymbolScan};
+pub use trading_calendar::{is_market_holiday, is_trading_day, market_holidays};
+pub use trends::{GoogleTrends, TrendData, DEFAULT_TRENDS_TIMEFRAME};
+pub use yahoo::{catch_up_period, normalize_symbol_for_yahoo, validate_interval, YahooFinance};
+#[cfg(feature = "async")]
+pub use yahoo::AsyncYahooFinance;
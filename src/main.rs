@@ -2,8 +2,13 @@
 //!
 //! Command-line interface for the financial data pipeline.
 
+use std::collections::HashSet;
+
 use clap::{Parser, Subcommand};
-use financial_pipeline::{Database, Fred, YahooFinance};
+use financial_pipeline::{
+    catch_up_period, BacktestConfig, BacktestEngine, BacktestResult, Database, Fred, Signal,
+    SignalEngine, Strategy, StrategyConditionType, TradeDirection, YahooFinance,
+};
 
 /// Financial Data Pipeline CLI
 #[derive(Parser)]
@@ -34,6 +39,14 @@ enum Commands {
         /// Time period (1d, 5d, 1mo, 3mo, 6mo, 1y, 2y, 5y, 10y, ytd, max)
         #[arg(short, long, default_value = "1y")]
         period: String,
+
+        /// Bar interval (1m, 5m, 15m, 30m, 1h, 1d); intraday bars are stored separately
+        #[arg(short, long, default_value = "1d")]
+        interval: String,
+
+        /// Store split/dividend-adjusted prices instead of raw
+        #[arg(long)]
+        adjusted: bool,
     },
 
     /// Fetch macro data from FRED
@@ -73,11 +86,118 @@ enum Commands {
     /// Optimize database (vacuum)
     Vacuum,
 
+    /// Write a compacted copy of the database to a new file
+    CompactTo {
+        /// Destination file (must not exist)
+        path: String,
+    },
+
+    /// Import daily prices from a date,open,high,low,close,volume CSV file
+    Import {
+        /// CSV file to read (e.g. one written by the export)
+        path: String,
+
+        /// Symbol the prices belong to
+        #[arg(short, long)]
+        symbol: String,
+
+        /// Source recorded on each imported bar
+        #[arg(long, default_value = "csv")]
+        source: String,
+    },
+
+    /// Save a single-condition strategy for backtesting
+    Strategy {
+        /// Strategy name (replaces an existing one with the same name)
+        name: String,
+
+        /// Entry condition (rsi_oversold, rsi_overbought, macd_cross_up, macd_cross_down,
+        /// price_above_sma, price_below_sma, sma_cross_up, sma_cross_down)
+        #[arg(long)]
+        entry: String,
+
+        /// Threshold for the entry condition (e.g. the RSI level)
+        #[arg(long, default_value_t = 30.0)]
+        entry_threshold: f64,
+
+        /// Exit condition (same choices as --entry)
+        #[arg(long)]
+        exit: String,
+
+        /// Threshold for the exit condition
+        #[arg(long, default_value_t = 70.0)]
+        exit_threshold: f64,
+
+        /// Stop loss, percent below entry
+        #[arg(long)]
+        stop_loss: Option<f64>,
+
+        /// Take profit, percent above entry
+        #[arg(long)]
+        take_profit: Option<f64>,
+
+        /// Percent of capital per trade
+        #[arg(long, default_value_t = 100.0)]
+        size: f64,
+
+        /// Sell short on entry and buy to cover on exit
+        #[arg(long)]
+        short: bool,
+
+        /// Description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+
+    /// Backtest a saved strategy on one symbol's stored prices
+    Backtest {
+        /// Strategy name
+        strategy: String,
+
+        /// Stock symbol
+        symbol: String,
+
+        /// Starting capital
+        #[arg(short, long, default_value_t = 10000.0)]
+        capital: f64,
+
+        /// Compute missing or stale indicators before running
+        #[arg(long)]
+        calculate: bool,
+
+        /// Save the result to the database
+        #[arg(long)]
+        save: bool,
+    },
+
+    /// Generate and store signals, then show the latest ones
+    Signals {
+        /// Stock symbol
+        #[arg(required_unless_present = "all")]
+        symbol: Option<String>,
+
+        /// Scan every symbol with price data
+        #[arg(long, conflicts_with = "symbol")]
+        all: bool,
+
+        /// Number of latest signals to show per symbol
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+    },
+
     /// Refetch all existing symbols
     Refetch {
-        /// Time period
+        /// Time period (with --stale-only, just enough to catch up is fetched)
         #[arg(short, long, default_value = "1y")]
         period: String,
+
+        /// Only update symbols whose data is out of date, without clearing them
+        #[arg(long)]
+        stale_only: bool,
+
+        /// Weekdays since the last bar before a symbol counts as stale
+        #[arg(long, default_value_t = 1)]
+        max_age_days: i64,
     },
 }
 
@@ -96,16 +216,21 @@ fn main() -> anyhow::Result<()> {
             println!("\nDatabase initialized at: {}", cli.database);
         }
 
-        Commands::Fetch { symbols, period } => {
+        Commands::Fetch {
+            symbols,
+            period,
+            interval,
+            adjusted,
+        } => {
             let symbol_list: Vec<String> =
                 symbols.split(',').map(|s| s.trim().to_uppercase()).collect();
 
-            let yahoo = YahooFinance::new();
+            let yahoo = YahooFinance::new().with_adjusted_prices(adjusted);
 
             if symbol_list.len() == 1 {
-                yahoo.fetch_and_store(&mut db, &symbol_list[0], &period)?;
+                yahoo.fetch_and_store(&mut db, &symbol_list[0], &interval, &period)?;
             } else {
-                yahoo.fetch_batch(&mut db, &symbol_list, &period)?;
+                yahoo.fetch_batch(&mut db, &symbol_list, &interval, &period)?;
             }
         }
 
@@ -175,7 +300,181 @@ fn main() -> anyhow::Result<()> {
             db.vacuum()?;
         }
 
-        Commands::Refetch { period } => {
+        Commands::CompactTo { path } => {
+            db.vacuum_into(std::path::Path::new(&path))?;
+        }
+
+        Commands::Import {
+            path,
+            symbol,
+            source,
+        } => {
+            let summary = db.import_prices_csv(&path, &symbol, &source)?;
+            println!(
+                "[OK] Imported {} rows for {} ({} skipped)",
+                summary.imported,
+                symbol.to_uppercase(),
+                summary.skipped
+            );
+        }
+
+        Commands::Strategy {
+            name,
+            entry,
+            entry_threshold,
+            exit,
+            exit_threshold,
+            stop_loss,
+            take_profit,
+            size,
+            short,
+            description,
+        } => {
+            let Some(entry_condition) = StrategyConditionType::from_str(&entry) else {
+                anyhow::bail!("Invalid entry condition: {}", entry);
+            };
+            let Some(exit_condition) = StrategyConditionType::from_str(&exit) else {
+                anyhow::bail!("Invalid exit condition: {}", exit);
+            };
+
+            db.init_schema()?;
+            db.save_strategy(&Strategy {
+                id: 0,
+                name: name.clone(),
+                description,
+                entry_condition,
+                entry_threshold,
+                exit_condition,
+                exit_threshold,
+                stop_loss_percent: stop_loss,
+                take_profit_percent: take_profit,
+                trailing_stop_percent: None,
+                position_size_percent: size,
+                direction: if short {
+                    TradeDirection::Short
+                } else {
+                    TradeDirection::Long
+                },
+                entry_rules: None,
+                exit_rules: None,
+                position_sizing: None,
+                created_at: String::new(),
+            })?;
+            println!("[OK] Saved strategy '{}'", name);
+        }
+
+        Commands::Backtest {
+            strategy,
+            symbol,
+            capital,
+            calculate,
+            save,
+        } => {
+            let symbol = symbol.to_uppercase();
+            let Some(strategy) = db.get_strategy(&strategy)? else {
+                anyhow::bail!(
+                    "Strategy '{}' not found; create it with the strategy subcommand",
+                    strategy
+                );
+            };
+
+            let prices = db.get_prices(&symbol)?;
+            if prices.is_empty() {
+                anyhow::bail!("No price data for {}; fetch or import it first", symbol);
+            }
+
+            if calculate {
+                db.refresh_indicators(&symbol)?;
+            }
+            let indicators = db.get_all_indicators(&symbol)?;
+            let available: HashSet<&str> = indicators
+                .iter()
+                .map(|i| i.indicator_name.as_str())
+                .collect();
+            let missing: Vec<&str> = strategy
+                .required_indicators()
+                .into_iter()
+                .filter(|name| !available.contains(name))
+                .collect();
+            if !missing.is_empty() {
+                anyhow::bail!(
+                    "{} has no {} data; rerun with --calculate to compute its indicators",
+                    symbol,
+                    missing.join(", ")
+                );
+            }
+
+            let engine = BacktestEngine::new(BacktestConfig {
+                initial_capital: capital,
+                ..BacktestConfig::default()
+            });
+            let benchmark = db.get_prices(&db.benchmark_symbol()?)?;
+            let mut result =
+                engine.run_against_benchmark(&strategy, &symbol, &prices, &indicators, &benchmark);
+
+            print_backtest_summary(&result);
+
+            if save {
+                result.id = db.save_backtest_result(&result)?;
+                println!("\n[OK] Saved backtest {}", result.id);
+            }
+        }
+
+        Commands::Signals { symbol, all, limit } => {
+            let symbols = if all {
+                db.get_symbols_with_data()?
+            } else {
+                symbol.into_iter().map(|s| s.to_uppercase()).collect()
+            };
+            if symbols.is_empty() {
+                println!("No symbols with price data");
+            }
+
+            let config = db.get_signal_config()?.unwrap_or_default();
+            let engine = SignalEngine::with_config(config);
+            for symbol in symbols {
+                let prices = db.get_prices(&symbol)?;
+                if prices.is_empty() {
+                    println!("No price data for {}", symbol);
+                    continue;
+                }
+
+                let mut indicators = db.get_all_indicators(&symbol)?;
+                if indicators.is_empty() {
+                    db.refresh_indicators(&symbol)?;
+                    indicators = db.get_all_indicators(&symbol)?;
+                }
+
+                let mut signals = engine.generate_signals(&symbol, &indicators, &prices);
+                engine.store_signals(&mut db, &signals)?;
+                println!("[OK] Generated {} signals for {}", signals.len(), symbol);
+
+                signals.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+                signals.truncate(limit);
+                print_signal_table(&signals);
+            }
+        }
+
+        Commands::Refetch {
+            stale_only: true,
+            max_age_days,
+            ..
+        } => {
+            let symbols = db.get_stale_symbols(max_age_days)?;
+            if symbols.is_empty() {
+                println!("All symbols are up to date");
+                return Ok(());
+            }
+
+            let period = catch_up_period(&db, &symbols)?;
+
+            println!("Updating {} stale symbols ({})...", symbols.len(), period);
+
+            let yahoo = YahooFinance::new();
+            yahoo.fetch_batch(&mut db, &symbols, "1d", period)?;
+        }
+
+        Commands::Refetch { period, .. } => {
             let symbols = db.get_symbols_with_data()?;
             if symbols.is_empty() {
                 println!("No symbols to refetch");
@@ -189,9 +488,69 @@ fn main() -> anyhow::Result<()> {
             }
 
             let yahoo = YahooFinance::new();
-            yahoo.fetch_batch(&mut db, &symbols, &period)?;
+            yahoo.fetch_batch(&mut db, &symbols, "1d", &period)?;
         }
     }
 
     Ok(())
 }
+
+/// Print a backtest's headline numbers and metrics
+fn print_backtest_summary(result: &BacktestResult) {
+    let m = &result.metrics;
+    println!("{}", "=".repeat(60));
+    println!("Backtest: {} on {}", result.strategy_name, result.symbol);
+    println!("{}", "=".repeat(60));
+    println!(
+        "Period:          {} to {}",
+        result.start_date, result.end_date
+    );
+    println!("Initial capital: ${:.2}", result.initial_capital);
+    println!("Final capital:   ${:.2}", result.final_capital);
+    println!(
+        "Total return:    {:.2}% (${:.2})",
+        m.total_return, m.total_return_dollars
+    );
+    println!("Max drawdown:    {:.2}%", m.max_drawdown);
+    println!("Sharpe ratio:    {:.2}", m.sharpe_ratio);
+    println!("Sortino ratio:   {:.2}", m.sortino_ratio);
+    println!(
+        "Trades:          {} ({} won, {} lost, {:.1}% win rate)",
+        m.total_trades, m.winning_trades, m.losing_trades, m.win_rate
+    );
+    println!(
+        "Avg win / loss:  {:.2}% / {:.2}%",
+        m.avg_win_percent, m.avg_loss_percent
+    );
+    println!("Profit factor:   {:.2}", m.profit_factor);
+    println!("Time in market:  {:.1}%", m.time_in_market_percent);
+    if result.skipped_bars > 0 {
+        println!(
+            "[WARN] Skipped {} bar(s) with non-positive close",
+            result.skipped_bars
+        );
+    }
+    for warning in &result.warnings {
+        println!("[WARN] {}", warning);
+    }
+}
+
+/// Print signals as a table, one row per signal
+fn print_signal_table(signals: &[Signal]) {
+    if signals.is_empty() {
+        return;
+    }
+    println!(
+        "  {:<12} {:<24} {:<9} {:>8}",
+        "Date", "Type", "Direction", "Strength"
+    );
+    for signal in signals {
+        println!(
+            "  {:<12} {:<24} {:<9} {:>8.2}",
+            signal.timestamp.to_string(),
+            signal.signal_type.as_str(),
+            signal.direction.as_str(),
+            signal.strength
+        );
+    }
+}
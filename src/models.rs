@@ -1,8 +1,10 @@
 //! Data models for Financial Pipeline
 
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
+use crate::error::PipelineError;
+
 /// Stock symbol metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
@@ -28,9 +30,81 @@ pub struct DailyPrice {
     pub low: f64,
     pub close: f64,
     pub volume: i64,
+    /// Split/dividend-adjusted close, when the source provides one
+    #[serde(default)]
+    pub adjusted_close: Option<f64>,
+    pub source: String,
+}
+
+impl DailyPrice {
+    /// This bar with OHLC rescaled onto the adjusted-close basis
+    ///
+    /// Bars without an adjusted close (or with a non-positive raw close) are
+    /// returned unchanged.
+    pub fn to_adjusted(&self) -> DailyPrice {
+        match self.adjusted_close {
+            Some(adjusted) if self.close > 0.0 => {
+                let factor = adjusted / self.close;
+                DailyPrice {
+                    open: self.open * factor,
+                    high: self.high * factor,
+                    low: self.low * factor,
+                    close: adjusted,
+                    ..self.clone()
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Intraday price bar (stored separately from daily bars)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntradayPrice {
+    pub symbol: String,
+    /// Bar size, e.g. "5m" or "1h"
+    pub interval: String,
+    /// Bar open time in UTC
+    pub timestamp: NaiveDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+    pub source: String,
+}
+
+/// Cash dividend per share, keyed by its ex-dividend date
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dividend {
+    pub symbol: String,
+    pub ex_date: NaiveDate,
+    pub amount: f64,
     pub source: String,
 }
 
+/// Stock split of `numerator` new shares for every `denominator` old ones
+/// (a 4:1 split is 4/1, a 1:10 reverse split 1/10)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Split {
+    pub symbol: String,
+    pub date: NaiveDate,
+    pub numerator: f64,
+    pub denominator: f64,
+    pub source: String,
+}
+
+impl Split {
+    /// Shares held after the split per share held before it
+    pub fn ratio(&self) -> f64 {
+        if self.denominator > 0.0 {
+            self.numerator / self.denominator
+        } else {
+            1.0
+        }
+    }
+}
+
 /// Macro economic indicator data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MacroData {
@@ -40,6 +114,68 @@ pub struct MacroData {
     pub source: String,
 }
 
+/// Observation frequency of a macro series, inferred from its dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MacroFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Annual,
+    Unknown,
+}
+
+impl MacroFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MacroFrequency::Daily => "daily",
+            MacroFrequency::Weekly => "weekly",
+            MacroFrequency::Monthly => "monthly",
+            MacroFrequency::Quarterly => "quarterly",
+            MacroFrequency::Annual => "annual",
+            MacroFrequency::Unknown => "unknown",
+        }
+    }
+
+    /// Classify from the typical spacing between observations
+    pub fn from_spacing_days(days: i64) -> Self {
+        match days {
+            d if d <= 0 => MacroFrequency::Unknown,
+            1..=4 => MacroFrequency::Daily,
+            5..=10 => MacroFrequency::Weekly,
+            11..=45 => MacroFrequency::Monthly,
+            46..=120 => MacroFrequency::Quarterly,
+            _ => MacroFrequency::Annual,
+        }
+    }
+
+    /// Max age (days) of the latest observation before a release counts as missed
+    ///
+    /// Covers the observation period plus the usual publication lag, e.g. a
+    /// monthly series dated the 1st is normally released within the next month.
+    pub fn max_age_days(&self) -> Option<i64> {
+        match self {
+            MacroFrequency::Daily => Some(7),
+            MacroFrequency::Weekly => Some(21),
+            MacroFrequency::Monthly => Some(75),
+            MacroFrequency::Quarterly => Some(210),
+            MacroFrequency::Annual => Some(500),
+            MacroFrequency::Unknown => None,
+        }
+    }
+}
+
+/// Freshness of a stored macro series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroFreshness {
+    pub indicator: String,
+    pub latest_date: NaiveDate,
+    pub frequency: MacroFrequency,
+    pub age_days: i64,
+    /// True when the latest observation is older than the frequency allows
+    pub overdue: bool,
+}
+
 /// Watchlist definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Watchlist {
@@ -83,6 +219,33 @@ pub struct PriceAlert {
     pub condition: AlertCondition,
     pub triggered: bool,
     pub created_at: String,
+    /// Reference price of a percent-change alert
+    #[serde(default)]
+    pub base_price: Option<f64>,
+    /// Move from `base_price` that triggers the alert, in percent (negative for a drop)
+    #[serde(default)]
+    pub percent_change: Option<f64>,
+    /// Re-arm once the price is back across the threshold instead of firing once
+    #[serde(default)]
+    pub recurring: bool,
+    #[serde(default)]
+    pub last_triggered_at: Option<String>,
+}
+
+impl PriceAlert {
+    /// Price the alert fires at: the move from `base_price` for percent-change
+    /// alerts, `target_price` otherwise
+    pub fn threshold(&self) -> f64 {
+        match (self.base_price, self.percent_change) {
+            (Some(base), Some(percent)) => percent_target(base, percent),
+            _ => self.target_price,
+        }
+    }
+}
+
+/// Price `percent` percent away from `base_price`
+pub fn percent_target(base_price: f64, percent: f64) -> f64 {
+    base_price * (1.0 + percent / 100.0)
 }
 
 /// Position type (buy or sell/short)
@@ -102,6 +265,53 @@ pub struct Position {
     pub position_type: PositionType,
     pub date: String,
     pub notes: Option<String>,
+    /// Set when the row is a short option leg rather than shares
+    #[serde(default)]
+    pub option: Option<OptionLeg>,
+}
+
+/// Option-writing strategy a portfolio position represents
+///
+/// Both are short option legs: the premium collected is the position's
+/// price, and the underlying shares (or cash) are tracked separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptionStrategy {
+    /// Short call written against shares already held
+    CoveredCall,
+    /// Short put backed by cash set aside to buy the shares
+    CashSecuredPut,
+}
+
+impl std::str::FromStr for OptionStrategy {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "covered_call" => Ok(OptionStrategy::CoveredCall),
+            "cash_secured_put" => Ok(OptionStrategy::CashSecuredPut),
+            _ => Err(PipelineError::Config(format!(
+                "Unknown option strategy: {} (expected covered_call or cash_secured_put)",
+                s
+            ))),
+        }
+    }
+}
+
+impl OptionStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionStrategy::CoveredCall => "covered_call",
+            OptionStrategy::CashSecuredPut => "cash_secured_put",
+        }
+    }
+}
+
+/// Contract terms of a short option position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionLeg {
+    pub strategy: OptionStrategy,
+    pub strike: f64,
+    pub expiration: String,
 }
 
 // ============================================================================
@@ -109,7 +319,7 @@ pub struct Position {
 // ============================================================================
 
 /// Type of trading signal
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SignalType {
     // RSI signals
     RsiOverbought,
@@ -138,9 +348,100 @@ pub enum SignalType {
     // MFI signals
     MfiOverbought,
     MfiOversold,
+    // KST signals
+    KstBullishCross,
+    KstBearishCross,
+    // Donchian Channel breakout signals
+    DonchianBreakoutUp,
+    DonchianBreakoutDown,
+    // Price/oscillator divergence signals
+    BullishDivergence,
+    BearishDivergence,
+    // Volatility squeeze (Bollinger Bands inside Keltner Channels)
+    SqueezeOn,
+    SqueezeOff,
+    // Candlestick pattern signals
+    BullishEngulfing,
+    BearishEngulfing,
+    Doji,
+    Hammer,
+    ShootingStar,
 }
 
 impl SignalType {
+    /// Every signal type the engine can emit
+    pub fn all() -> Vec<SignalType> {
+        vec![
+            SignalType::RsiOverbought,
+            SignalType::RsiOversold,
+            SignalType::MacdBullishCross,
+            SignalType::MacdBearishCross,
+            SignalType::BollingerUpperBreak,
+            SignalType::BollingerLowerBreak,
+            SignalType::MaCrossoverBullish,
+            SignalType::MaCrossoverBearish,
+            SignalType::AdxTrendStrong,
+            SignalType::AdxTrendWeak,
+            SignalType::StochBullishCross,
+            SignalType::StochBearishCross,
+            SignalType::WillrOverbought,
+            SignalType::WillrOversold,
+            SignalType::CciOverbought,
+            SignalType::CciOversold,
+            SignalType::MfiOverbought,
+            SignalType::MfiOversold,
+            SignalType::KstBullishCross,
+            SignalType::KstBearishCross,
+            SignalType::DonchianBreakoutUp,
+            SignalType::DonchianBreakoutDown,
+            SignalType::BullishDivergence,
+            SignalType::BearishDivergence,
+            SignalType::SqueezeOn,
+            SignalType::SqueezeOff,
+            SignalType::BullishEngulfing,
+            SignalType::BearishEngulfing,
+            SignalType::Doji,
+            SignalType::Hammer,
+            SignalType::ShootingStar,
+        ]
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SignalType::RsiOverbought => "RSI crossed above the overbought threshold",
+            SignalType::RsiOversold => "RSI crossed below the oversold threshold",
+            SignalType::MacdBullishCross => "MACD line crossed above its signal line",
+            SignalType::MacdBearishCross => "MACD line crossed below its signal line",
+            SignalType::BollingerUpperBreak => "Close broke above the upper Bollinger Band",
+            SignalType::BollingerLowerBreak => "Close broke below the lower Bollinger Band",
+            SignalType::MaCrossoverBullish => "Fast SMA crossed above slow SMA (golden cross)",
+            SignalType::MaCrossoverBearish => "Fast SMA crossed below slow SMA (death cross)",
+            SignalType::AdxTrendStrong => "ADX rose above the strong-trend threshold",
+            SignalType::AdxTrendWeak => "ADX fell below the weak-trend threshold",
+            SignalType::StochBullishCross => "Stochastic %K crossed above %D in oversold territory",
+            SignalType::StochBearishCross => "Stochastic %K crossed below %D in overbought territory",
+            SignalType::WillrOverbought => "Williams %R entered overbought territory",
+            SignalType::WillrOversold => "Williams %R entered oversold territory",
+            SignalType::CciOverbought => "CCI rose above the overbought threshold",
+            SignalType::CciOversold => "CCI fell below the oversold threshold",
+            SignalType::MfiOverbought => "Money Flow Index entered overbought territory",
+            SignalType::MfiOversold => "Money Flow Index entered oversold territory",
+            SignalType::KstBullishCross => "KST crossed above its signal line",
+            SignalType::KstBearishCross => "KST crossed below its signal line",
+            SignalType::DonchianBreakoutUp => "Close broke above the prior bar's upper Donchian Channel",
+            SignalType::DonchianBreakoutDown => "Close broke below the prior bar's lower Donchian Channel",
+            SignalType::BullishDivergence => "Price made a lower low while the oscillator made a higher low",
+            SignalType::BearishDivergence => "Price made a higher high while the oscillator made a lower high",
+            SignalType::SqueezeOn => "Bollinger Bands contracted inside the Keltner Channels",
+            SignalType::SqueezeOff => "Bollinger Bands expanded back outside the Keltner Channels",
+            SignalType::BullishEngulfing => "Green candle engulfing the prior red candle",
+            SignalType::BearishEngulfing => "Red candle engulfing the prior green candle",
+            SignalType::Doji => "Open and close nearly equal (indecision)",
+            SignalType::Hammer => "Small body with a long lower wick",
+            SignalType::ShootingStar => "Small body with a long upper wick",
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             SignalType::RsiOverbought => "RSI_OVERBOUGHT",
@@ -161,6 +462,19 @@ impl SignalType {
             SignalType::CciOversold => "CCI_OVERSOLD",
             SignalType::MfiOverbought => "MFI_OVERBOUGHT",
             SignalType::MfiOversold => "MFI_OVERSOLD",
+            SignalType::KstBullishCross => "KST_BULLISH_CROSS",
+            SignalType::KstBearishCross => "KST_BEARISH_CROSS",
+            SignalType::DonchianBreakoutUp => "DONCHIAN_BREAKOUT_UP",
+            SignalType::DonchianBreakoutDown => "DONCHIAN_BREAKOUT_DOWN",
+            SignalType::BullishDivergence => "BULLISH_DIVERGENCE",
+            SignalType::BearishDivergence => "BEARISH_DIVERGENCE",
+            SignalType::SqueezeOn => "SQUEEZE_ON",
+            SignalType::SqueezeOff => "SQUEEZE_OFF",
+            SignalType::BullishEngulfing => "BULLISH_ENGULFING",
+            SignalType::BearishEngulfing => "BEARISH_ENGULFING",
+            SignalType::Doji => "DOJI",
+            SignalType::Hammer => "HAMMER",
+            SignalType::ShootingStar => "SHOOTING_STAR",
         }
     }
 
@@ -184,13 +498,26 @@ impl SignalType {
             "CCI_OVERSOLD" => Some(SignalType::CciOversold),
             "MFI_OVERBOUGHT" => Some(SignalType::MfiOverbought),
             "MFI_OVERSOLD" => Some(SignalType::MfiOversold),
+            "KST_BULLISH_CROSS" => Some(SignalType::KstBullishCross),
+            "KST_BEARISH_CROSS" => Some(SignalType::KstBearishCross),
+            "DONCHIAN_BREAKOUT_UP" => Some(SignalType::DonchianBreakoutUp),
+            "DONCHIAN_BREAKOUT_DOWN" => Some(SignalType::DonchianBreakoutDown),
+            "BULLISH_DIVERGENCE" => Some(SignalType::BullishDivergence),
+            "BEARISH_DIVERGENCE" => Some(SignalType::BearishDivergence),
+            "SQUEEZE_ON" => Some(SignalType::SqueezeOn),
+            "SQUEEZE_OFF" => Some(SignalType::SqueezeOff),
+            "BULLISH_ENGULFING" => Some(SignalType::BullishEngulfing),
+            "BEARISH_ENGULFING" => Some(SignalType::BearishEngulfing),
+            "DOJI" => Some(SignalType::Doji),
+            "HAMMER" => Some(SignalType::Hammer),
+            "SHOOTING_STAR" => Some(SignalType::ShootingStar),
             _ => None,
         }
     }
 }
 
 /// Direction of the signal
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SignalDirection {
     Bullish,
     Bearish,
@@ -229,6 +556,13 @@ pub struct Signal {
     pub timestamp: NaiveDate,
     pub created_at: String,
     pub acknowledged: bool,
+    /// Derived from historical data in a backfill rather than generated live
+    #[serde(default)]
+    pub backfilled: bool,
+    /// Whether the higher-timeframe trend agrees with the direction; `None`
+    /// unless `SignalEngine::generate_signals_mtf` checked it
+    #[serde(default)]
+    pub confirmed: Option<bool>,
 }
 
 // ============================================================================
@@ -269,6 +603,11 @@ pub enum IndicatorAlertCondition {
     CrossesBelow,
     BullishCrossover,
     BearishCrossover,
+    /// Holds on any check where the indicator is above the secondary
+    /// indicator (or the threshold without one), not just when it crosses
+    StaysAbove,
+    /// Like `StaysAbove`, for below
+    StaysBelow,
 }
 
 impl IndicatorAlertCondition {
@@ -278,6 +617,8 @@ impl IndicatorAlertCondition {
             IndicatorAlertCondition::CrossesBelow => "crosses_below",
             IndicatorAlertCondition::BullishCrossover => "bullish_crossover",
             IndicatorAlertCondition::BearishCrossover => "bearish_crossover",
+            IndicatorAlertCondition::StaysAbove => "stays_above",
+            IndicatorAlertCondition::StaysBelow => "stays_below",
         }
     }
 
@@ -287,6 +628,8 @@ impl IndicatorAlertCondition {
             "crosses_below" => Some(IndicatorAlertCondition::CrossesBelow),
             "bullish_crossover" => Some(IndicatorAlertCondition::BullishCrossover),
             "bearish_crossover" => Some(IndicatorAlertCondition::BearishCrossover),
+            "stays_above" => Some(IndicatorAlertCondition::StaysAbove),
+            "stays_below" => Some(IndicatorAlertCondition::StaysBelow),
             _ => None,
         }
     }
@@ -306,6 +649,11 @@ pub struct IndicatorAlert {
     pub last_value: Option<f64>,
     pub created_at: String,
     pub message: Option<String>,
+    /// Re-arm once the condition no longer holds instead of firing once
+    #[serde(default)]
+    pub recurring: bool,
+    #[serde(default)]
+    pub last_triggered_at: Option<String>,
 }
 
 // ============================================================================
@@ -358,6 +706,61 @@ impl StrategyConditionType {
             _ => None,
         }
     }
+
+    /// Indicator names this condition reads (empty for price-only conditions)
+    pub fn required_indicators(&self) -> &'static [&'static str] {
+        match self {
+            StrategyConditionType::RsiOversold | StrategyConditionType::RsiOverbought => {
+                &["RSI_14"]
+            }
+            StrategyConditionType::MacdCrossUp | StrategyConditionType::MacdCrossDown => {
+                &["MACD_12_26", "MACD_SIGNAL_9"]
+            }
+            StrategyConditionType::PriceAboveSma | StrategyConditionType::PriceBelowSma => {
+                &["SMA_20"]
+            }
+            StrategyConditionType::SmaCrossUp | StrategyConditionType::SmaCrossDown => {
+                &["SMA_20", "SMA_50"]
+            }
+            StrategyConditionType::StopLoss | StrategyConditionType::TakeProfit => &[],
+        }
+    }
+}
+
+/// How a strategy's entry or exit rules combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RuleCombinator {
+    /// Every rule must hold (AND)
+    #[default]
+    All,
+    /// At least one rule must hold (OR)
+    Any,
+}
+
+/// One condition with its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StrategyRule {
+    pub condition: StrategyConditionType,
+    pub threshold: f64,
+}
+
+/// Several entry or exit rules and how they combine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyRules {
+    #[serde(default)]
+    pub combinator: RuleCombinator,
+    pub rules: Vec<StrategyRule>,
+}
+
+/// How a strategy sizes new positions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PositionSizing {
+    /// Spend this percent of the backtest's sizing base (cash or equity)
+    PercentOfEquity(f64),
+    /// Risk `risk` percent of equity on a stop `atr_mult` ATR_14s away:
+    /// shares = equity * risk% / (ATR * atr_mult), capped at available cash.
+    /// The position exits (`atr_stop`) once a close reaches that stop.
+    RiskPercent { risk: f64, atr_mult: f64 },
 }
 
 /// A trading strategy definition
@@ -372,13 +775,86 @@ pub struct Strategy {
     pub exit_threshold: f64,
     pub stop_loss_percent: Option<f64>,
     pub take_profit_percent: Option<f64>,
+    /// Exit once price falls this far below its peak since entry (above its
+    /// trough for shorts)
+    #[serde(default)]
+    pub trailing_stop_percent: Option<f64>,
     pub position_size_percent: f64, // % of capital per trade
+    /// Long strategies buy on entry; short strategies sell short and buy to cover
+    #[serde(default)]
+    pub direction: TradeDirection,
+    /// Combined entry rules; when set they replace `entry_condition` and
+    /// `entry_threshold`, which stay as the strategy's headline condition
+    #[serde(default)]
+    pub entry_rules: Option<StrategyRules>,
+    /// Combined exit rules, replacing `exit_condition`/`exit_threshold` when set
+    #[serde(default)]
+    pub exit_rules: Option<StrategyRules>,
+    /// Sizing mode; `None` sizes by `position_size_percent`
+    #[serde(default)]
+    pub position_sizing: Option<PositionSizing>,
     pub created_at: String,
 }
 
+impl Strategy {
+    /// Entry rules to evaluate; without `entry_rules` this is the single entry
+    /// condition as a one-element `All` list
+    pub fn entry_rule_set(&self) -> StrategyRules {
+        self.entry_rules
+            .clone()
+            .unwrap_or_else(|| StrategyRules::single(self.entry_condition, self.entry_threshold))
+    }
+
+    /// Exit rules to evaluate; see `entry_rule_set`
+    pub fn exit_rule_set(&self) -> StrategyRules {
+        self.exit_rules
+            .clone()
+            .unwrap_or_else(|| StrategyRules::single(self.exit_condition, self.exit_threshold))
+    }
+
+    /// Sizing mode in effect, defaulting to `PercentOfEquity(position_size_percent)`
+    pub fn sizing(&self) -> PositionSizing {
+        self.position_sizing
+            .unwrap_or(PositionSizing::PercentOfEquity(self.position_size_percent))
+    }
+
+    /// Indicator names any entry or exit rule, or ATR-based sizing, reads
+    pub fn required_indicators(&self) -> Vec<&'static str> {
+        let (entry, exit) = (self.entry_rule_set(), self.exit_rule_set());
+        let mut required: Vec<&'static str> = Vec::new();
+        for rule in entry.rules.iter().chain(&exit.rules) {
+            for name in rule.condition.required_indicators() {
+                if !required.contains(name) {
+                    required.push(name);
+                }
+            }
+        }
+        if matches!(self.sizing(), PositionSizing::RiskPercent { .. })
+            && !required.contains(&"ATR_14")
+        {
+            required.push("ATR_14");
+        }
+        required
+    }
+}
+
+impl StrategyRules {
+    /// A lone condition, as single-condition strategies are evaluated
+    pub fn single(condition: StrategyConditionType, threshold: f64) -> Self {
+        Self {
+            combinator: RuleCombinator::All,
+            rules: vec![StrategyRule {
+                condition,
+                threshold,
+            }],
+        }
+    }
+}
+
 /// Trade direction
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum TradeDirection {
+    #[default]
     Long,
     Short,
 }
@@ -430,8 +906,61 @@ pub struct PerformanceMetrics {
     pub losing_trades: usize,
     pub avg_win_percent: f64,
     pub avg_loss_percent: f64,
+    /// Infinite when there are winners but no losers
+    #[serde(with = "non_finite_f64")]
     pub profit_factor: f64,
     pub avg_trade_duration_days: f64,
+    /// Trades per calendar year over the backtest span
+    pub trades_per_year: f64,
+    /// Percent of priced bars on which a position was held
+    pub time_in_market_percent: f64,
+    /// Annualized mean active return over tracking error versus the benchmark
+    #[serde(default)]
+    pub information_ratio: f64,
+    /// Like Sharpe, but only losing days count toward risk. Infinite when
+    /// there are no losing days and the mean return is positive, else 0
+    #[serde(default, with = "non_finite_f64")]
+    pub sortino_ratio: f64,
+    /// Annualized return over max drawdown (both in percent). Infinite when
+    /// there is no drawdown and the annualized return is positive, else 0
+    #[serde(default, with = "non_finite_f64")]
+    pub calmar_ratio: f64,
+}
+
+/// Serde for metrics that can be infinite
+///
+/// JSON has no infinity (serde_json writes `null`, which doesn't read back as
+/// `f64`), so non-finite values are written as the strings `"inf"`, `"-inf"`
+/// and `"NaN"`. Numbers and those strings are read back; `null`, from exports
+/// made before this, reads as NaN.
+mod non_finite_f64 {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            serializer.serialize_f64(*value)
+        } else {
+            serializer.serialize_str(&value.to_string())
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Number(f64),
+        Text(String),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        match Option::<Repr>::deserialize(deserializer)? {
+            Some(Repr::Number(value)) => Ok(value),
+            Some(Repr::Text(text)) => text
+                .parse()
+                .map_err(|_| D::Error::custom(format!("invalid metric value: {}", text))),
+            None => Ok(f64::NAN),
+        }
+    }
 }
 
 /// Complete backtest result
@@ -448,11 +977,22 @@ pub struct BacktestResult {
     pub metrics: PerformanceMetrics,
     pub trades: Vec<BacktestTrade>,
     pub created_at: String,
+    /// Bars ignored because of a non-positive close (not persisted)
+    #[serde(default)]
+    pub skipped_bars: usize,
+    /// Data-quality notes such as a history shorter than requested (not persisted)
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Equity at each traded date's close; the last point is the final
+    /// capital after open positions are closed
+    #[serde(default)]
+    pub equity_curve: Vec<(NaiveDate, f64)>,
 }
 
-/// Yahoo Finance chart response structures
+/// Yahoo Finance chart and quoteSummary response structures
 pub mod yahoo {
     use serde::Deserialize;
+    use std::collections::HashMap;
 
     #[derive(Debug, Deserialize)]
     pub struct ChartResponse {
@@ -476,6 +1016,31 @@ pub mod yahoo {
         pub meta: ChartMeta,
         pub timestamp: Option<Vec<i64>>,
         pub indicators: Indicators,
+        /// Present when `events=div|split` is requested and any occurred
+        #[serde(default)]
+        pub events: Option<Events>,
+    }
+
+    /// Corporate actions keyed by their Unix timestamp (as a string)
+    #[derive(Debug, Default, Deserialize)]
+    pub struct Events {
+        #[serde(default)]
+        pub dividends: HashMap<String, DividendEvent>,
+        #[serde(default)]
+        pub splits: HashMap<String, SplitEvent>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct DividendEvent {
+        pub amount: f64,
+        pub date: i64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct SplitEvent {
+        pub date: i64,
+        pub numerator: f64,
+        pub denominator: f64,
     }
 
     #[derive(Debug, Deserialize)]
@@ -505,4 +1070,51 @@ pub mod yahoo {
     pub struct AdjClose {
         pub adjclose: Vec<Option<f64>>,
     }
+
+    #[derive(Debug, Deserialize)]
+    pub struct QuoteSummaryResponse {
+        #[serde(rename = "quoteSummary")]
+        pub quote_summary: QuoteSummary,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct QuoteSummary {
+        pub result: Option<Vec<QuoteSummaryResult>>,
+        pub error: Option<ChartError>,
+    }
+
+    /// Requested modules; any of them may be missing (ETFs have no asset profile)
+    #[derive(Debug, Deserialize)]
+    pub struct QuoteSummaryResult {
+        #[serde(rename = "assetProfile", default)]
+        pub asset_profile: Option<AssetProfile>,
+        #[serde(default)]
+        pub price: Option<PriceModule>,
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    pub struct AssetProfile {
+        pub sector: Option<String>,
+        pub industry: Option<String>,
+        pub country: Option<String>,
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PriceModule {
+        pub long_name: Option<String>,
+        pub short_name: Option<String>,
+        pub exchange_name: Option<String>,
+        pub currency: Option<String>,
+        /// e.g. "EQUITY", "ETF", "MUTUALFUND"
+        pub quote_type: Option<String>,
+        #[serde(default)]
+        pub market_cap: Option<RawValue>,
+    }
+
+    /// A formatted number; `raw` is absent when Yahoo sends `{}`
+    #[derive(Debug, Deserialize)]
+    pub struct RawValue {
+        pub raw: Option<f64>,
+    }
 }
@@ -0,0 +1,172 @@
+//! Alert notifications
+//!
+//! Triggered price and indicator alerts are pushed to every sink configured
+//! in the `notification_config` table, so the user hears about them even
+//! when the GUI isn't polling.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::Serialize;
+
+use crate::error::{PipelineError, Result};
+use crate::models::{IndicatorAlert, PriceAlert};
+
+/// `notification_config.sink` value for a webhook; the target is its URL
+pub const WEBHOOK_SINK: &str = "webhook";
+
+/// `notification_config.sink` value for a log file; the target is its path
+pub const LOG_FILE_SINK: &str = "log_file";
+
+/// How long a webhook may take before the notification is given up
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A triggered alert, serialized with a `kind` tag of `price` or `indicator`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertNotification {
+    Price(PriceAlert),
+    Indicator(IndicatorAlert),
+}
+
+/// Somewhere triggered alerts are delivered
+pub trait NotificationSink: Send {
+    /// Deliver one triggered alert
+    fn notify(&self, notification: &AlertNotification) -> Result<()>;
+}
+
+/// Check a webhook target is an absolute `http` or `https` URL
+pub fn validate_webhook_url(url: &str) -> Result<()> {
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        Ok(parsed) => Err(PipelineError::Config(format!(
+            "Webhook URL must use http or https, not '{}'",
+            parsed.scheme()
+        ))),
+        Err(e) => Err(PipelineError::Config(format!(
+            "Invalid webhook URL '{}': {}",
+            url, e
+        ))),
+    }
+}
+
+/// POSTs each triggered alert as JSON to a URL
+pub struct WebhookSink {
+    url: String,
+    client: Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .expect("Failed to create HTTP client"),
+        }
+    }
+}
+
+impl NotificationSink for WebhookSink {
+    fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        let response = self.client.post(&self.url).json(notification).send()?;
+        if !response.status().is_success() {
+            return Err(PipelineError::ApiError(format!(
+                "Webhook {} returned HTTP {}",
+                self.url,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Appends each triggered alert to a file as a timestamped JSON line
+pub struct LogFileSink {
+    path: PathBuf,
+}
+
+impl LogFileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl NotificationSink for LogFileSink {
+    fn notify(&self, notification: &AlertNotification) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(
+            file,
+            "{}\t{}",
+            Utc::now().to_rfc3339(),
+            serde_json::to_string(notification)?
+        )?;
+        Ok(())
+    }
+}
+
+/// Deliver notifications to every sink on a background thread
+///
+/// A webhook may take up to its timeout per alert, so delivery never runs
+/// on the caller's thread (which may hold the database lock). Failures are
+/// reported and otherwise ignored; join the handle to wait for delivery.
+pub fn deliver_in_background(
+    sinks: Vec<Box<dyn NotificationSink>>,
+    notifications: Vec<AlertNotification>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for notification in &notifications {
+            for sink in &sinks {
+                if let Err(e) = sink.notify(notification) {
+                    println!("[WARN] Alert notification failed: {}", e);
+                }
+            }
+        }
+    })
+}
+
+/// Build the sink for a `notification_config` row; `None` for unknown kinds
+pub fn sink_for(sink: &str, target: &str) -> Option<Box<dyn NotificationSink>> {
+    match sink {
+        WEBHOOK_SINK => Some(Box::new(WebhookSink::new(target))),
+        LOG_FILE_SINK => Some(Box::new(LogFileSink::new(target))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_webhook_url() {
+        for ok in [
+            "https://hooks.example.com/alerts",
+            "http://127.0.0.1:8080/hook",
+        ] {
+            assert!(validate_webhook_url(ok).is_ok(), "{}", ok);
+        }
+        for bad in [
+            "hooks.example.com",
+            "ftp://example.com/hook",
+            "file:///etc/passwd",
+            "",
+        ] {
+            assert!(
+                matches!(validate_webhook_url(bad), Err(PipelineError::Config(_))),
+                "{}",
+                bad
+            );
+        }
+    }
+}
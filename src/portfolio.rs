@@ -0,0 +1,463 @@
+//! Portfolio valuation
+//!
+//! Per-position and aggregate P&L for mixed long/short portfolios, and FIFO
+//! lot matching that turns a list of trades into open positions plus
+//! realized P&L. Short option legs (covered calls and cash-secured puts)
+//! are valued separately at their intrinsic value.
+
+use crate::models::{OptionLeg, OptionStrategy, Position, PositionType};
+
+/// Mark-to-market view of a single position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionValuation {
+    pub current_price: f64,
+    /// Market value of the shares (always non-negative)
+    pub current_value: f64,
+    /// Entry price times quantity (always non-negative)
+    pub cost_basis: f64,
+    /// Gain or loss in the position's favour (shorts profit when price drops)
+    pub profit_loss: f64,
+    pub profit_loss_percent: f64,
+}
+
+impl PositionValuation {
+    pub fn new(position: &Position, current_price: f64) -> Self {
+        Self::from_lot(
+            position.position_type,
+            position.quantity,
+            position.price,
+            current_price,
+        )
+    }
+
+    /// Value `quantity` shares entered at `entry_price` on the given side
+    pub fn from_lot(
+        position_type: PositionType,
+        quantity: f64,
+        entry_price: f64,
+        current_price: f64,
+    ) -> Self {
+        let cost_basis = (quantity * entry_price).abs();
+        let current_value = (quantity * current_price).abs();
+
+        let profit_loss = match position_type {
+            PositionType::Buy => current_value - cost_basis,
+            PositionType::Sell => cost_basis - current_value,
+        };
+
+        Self {
+            current_price,
+            current_value,
+            cost_basis,
+            profit_loss,
+            profit_loss_percent: percent_of(profit_loss, cost_basis),
+        }
+    }
+}
+
+impl OptionLeg {
+    /// Per-share value of the option if exercised with the underlying at `price`
+    pub fn intrinsic_value(&self, price: f64) -> f64 {
+        match self.strategy {
+            OptionStrategy::CoveredCall => (price - self.strike).max(0.0),
+            OptionStrategy::CashSecuredPut => (self.strike - price).max(0.0),
+        }
+    }
+
+    /// Value a short option position against the underlying's latest price
+    ///
+    /// The premium collected (`position.price` per share) is the cost basis
+    /// and the option is marked at its intrinsic value, so P&L is premium
+    /// minus intrinsic value. This is exact at expiration but ignores time
+    /// value before it, overstating the profit on an open contract.
+    pub fn valuation(&self, position: &Position, underlying_price: f64) -> PositionValuation {
+        PositionValuation::from_lot(
+            PositionType::Sell,
+            position.quantity,
+            position.price,
+            self.intrinsic_value(underlying_price),
+        )
+    }
+}
+
+/// Aggregate P&L across all positions
+///
+/// Longs and shorts are tracked separately. `net_value` is long exposure minus
+/// short liability, while the total percent is measured against the capital
+/// committed to both sides rather than a signed net cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PortfolioTotals {
+    pub long_value: f64,
+    pub long_cost: f64,
+    pub long_profit_loss: f64,
+    pub short_value: f64,
+    pub short_cost: f64,
+    pub short_profit_loss: f64,
+}
+
+impl PortfolioTotals {
+    /// Add one valued position to the totals
+    pub fn add(&mut self, position_type: PositionType, valuation: &PositionValuation) {
+        match position_type {
+            PositionType::Buy => {
+                self.long_value += valuation.current_value;
+                self.long_cost += valuation.cost_basis;
+                self.long_profit_loss += valuation.profit_loss;
+            }
+            PositionType::Sell => {
+                self.short_value += valuation.current_value;
+                self.short_cost += valuation.cost_basis;
+                self.short_profit_loss += valuation.profit_loss;
+            }
+        }
+    }
+
+    /// Long market value minus short liability
+    pub fn net_value(&self) -> f64 {
+        self.long_value - self.short_value
+    }
+
+    /// Gross exposure: long value plus short liability
+    pub fn gross_value(&self) -> f64 {
+        self.long_value + self.short_value
+    }
+
+    /// Capital committed on both sides (long cost plus short proceeds)
+    pub fn invested_capital(&self) -> f64 {
+        self.long_cost + self.short_cost
+    }
+
+    /// Combined P&L of longs and shorts
+    pub fn total_profit_loss(&self) -> f64 {
+        self.long_profit_loss + self.short_profit_loss
+    }
+
+    /// Combined P&L as a percent of invested capital
+    pub fn total_profit_loss_percent(&self) -> f64 {
+        percent_of(self.total_profit_loss(), self.invested_capital())
+    }
+}
+
+/// Directional and market exposure of the open holdings
+///
+/// Long and short exposure are market values (both non-negative). The
+/// beta-weighted figure scales each holding's signed value by its beta to
+/// `benchmark`, giving the equivalent position in the benchmark itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PortfolioExposure {
+    pub benchmark: String,
+    pub long_exposure: f64,
+    pub short_exposure: f64,
+    pub net_exposure: f64,
+    pub beta_weighted_exposure: f64,
+    /// Holdings with too little history against the benchmark for a beta;
+    /// they are weighted as if their beta were 1.0
+    pub missing_beta: Vec<String>,
+}
+
+impl PortfolioExposure {
+    /// Add one holding's market value, weighted by its beta when known
+    pub fn add(
+        &mut self,
+        symbol: &str,
+        position_type: PositionType,
+        market_value: f64,
+        beta: Option<f64>,
+    ) {
+        let signed = match position_type {
+            PositionType::Buy => {
+                self.long_exposure += market_value;
+                market_value
+            }
+            PositionType::Sell => {
+                self.short_exposure += market_value;
+                -market_value
+            }
+        };
+        self.net_exposure = self.long_exposure - self.short_exposure;
+
+        if beta.is_none() {
+            self.missing_beta.push(symbol.to_string());
+        }
+        self.beta_weighted_exposure += signed * beta.unwrap_or(1.0);
+    }
+}
+
+/// What is left open of one portfolio trade after FIFO matching
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    /// `portfolio_positions` row the lot came from
+    pub position_id: i64,
+    pub date: String,
+    /// Remaining shares (always positive; the side is the holding's)
+    pub quantity: f64,
+    pub price: f64,
+    pub notes: Option<String>,
+}
+
+/// Net holding of one symbol after FIFO lot matching
+#[derive(Debug, Clone, PartialEq)]
+pub struct FifoPosition {
+    pub symbol: String,
+    /// `Buy` for a net long holding, `Sell` for a net short one
+    pub position_type: PositionType,
+    /// Open lots, oldest first, all on the `position_type` side
+    pub lots: Vec<Lot>,
+    /// P&L locked in by trades that closed earlier lots
+    pub realized_profit_loss: f64,
+}
+
+impl FifoPosition {
+    /// Open shares (always non-negative)
+    pub fn quantity(&self) -> f64 {
+        self.lots.iter().map(|l| l.quantity).sum()
+    }
+
+    /// Entry cost of the open lots
+    pub fn cost_basis(&self) -> f64 {
+        self.lots.iter().map(|l| l.quantity * l.price).sum()
+    }
+
+    /// Quantity-weighted entry price of the open lots (0 when flat)
+    pub fn average_price(&self) -> f64 {
+        let quantity = self.quantity();
+        if quantity > 0.0 {
+            self.cost_basis() / quantity
+        } else {
+            0.0
+        }
+    }
+
+    /// Unrealized valuation of the open lots
+    pub fn valuation(&self, current_price: f64) -> PositionValuation {
+        PositionValuation::from_lot(
+            self.position_type,
+            self.quantity(),
+            self.average_price(),
+            current_price,
+        )
+    }
+}
+
+/// Match trades into open lots per symbol, first in first out
+///
+/// Trades are replayed in date order (ties by id). A trade first closes the
+/// oldest open lots on the opposite side, realizing their P&L; whatever is
+/// left opens a new lot on its own side, so selling more than is held
+/// leaves a short. Results are sorted by symbol and include symbols that
+/// are now flat but have realized P&L. Option legs are skipped; see
+/// `OptionLeg::valuation`.
+pub fn match_lots_fifo(positions: &[Position]) -> Vec<FifoPosition> {
+    let mut trades: Vec<&Position> = positions.iter().filter(|p| p.option.is_none()).collect();
+    trades.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+
+    let mut holdings: Vec<FifoPosition> = Vec::new();
+
+    for trade in trades {
+        let idx = match holdings.iter().position(|h| h.symbol == trade.symbol) {
+            Some(idx) => idx,
+            None => {
+                holdings.push(FifoPosition {
+                    symbol: trade.symbol.clone(),
+                    position_type: trade.position_type,
+                    lots: Vec::new(),
+                    realized_profit_loss: 0.0,
+                });
+                holdings.len() - 1
+            }
+        };
+        let holding = &mut holdings[idx];
+        let mut remaining = trade.quantity.abs();
+
+        if holding.position_type != trade.position_type {
+            while remaining > 0.0 && !holding.lots.is_empty() {
+                let lot = &mut holding.lots[0];
+                let matched = remaining.min(lot.quantity);
+                let per_share = match holding.position_type {
+                    PositionType::Buy => trade.price - lot.price,
+                    PositionType::Sell => lot.price - trade.price,
+                };
+                holding.realized_profit_loss += matched * per_share;
+
+                lot.quantity -= matched;
+                remaining -= matched;
+                if lot.quantity <= 0.0 {
+                    holding.lots.remove(0);
+                }
+            }
+        }
+
+        if remaining > 0.0 {
+            holding.position_type = trade.position_type;
+            holding.lots.push(Lot {
+                position_id: trade.id,
+                date: trade.date.clone(),
+                quantity: remaining,
+                price: trade.price,
+                notes: trade.notes.clone(),
+            });
+        }
+    }
+
+    holdings.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    holdings
+}
+
+fn percent_of(amount: f64, base: f64) -> f64 {
+    if base > 0.0 {
+        amount / base * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(position_type: PositionType, quantity: f64, price: f64) -> Position {
+        Position {
+            id: 0,
+            symbol: "TEST".to_string(),
+            quantity,
+            price,
+            position_type,
+            date: "2024-01-01".to_string(),
+            notes: None,
+            option: None,
+        }
+    }
+
+    fn trade(id: i64, day: u32, position_type: PositionType, quantity: f64, price: f64) -> Position {
+        Position {
+            id,
+            date: format!("2024-01-{:02}", day),
+            ..position(position_type, quantity, price)
+        }
+    }
+
+    #[test]
+    fn test_long_and_short_totals() {
+        // Long 10 @ 100 -> 110: +100 on 1000 cost
+        let long = position(PositionType::Buy, 10.0, 100.0);
+        let long_val = PositionValuation::new(&long, 110.0);
+        assert_eq!(long_val.profit_loss, 100.0);
+        assert_eq!(long_val.profit_loss_percent, 10.0);
+
+        // Short 20 @ 50 -> 40: +200 on 1000 proceeds
+        let short = position(PositionType::Sell, 20.0, 50.0);
+        let short_val = PositionValuation::new(&short, 40.0);
+        assert_eq!(short_val.profit_loss, 200.0);
+        assert_eq!(short_val.profit_loss_percent, 20.0);
+
+        let mut totals = PortfolioTotals::default();
+        totals.add(long.position_type, &long_val);
+        totals.add(short.position_type, &short_val);
+
+        assert_eq!(totals.long_profit_loss, 100.0);
+        assert_eq!(totals.short_profit_loss, 200.0);
+        assert_eq!(totals.total_profit_loss(), 300.0);
+        assert_eq!(totals.invested_capital(), 2000.0);
+        assert_eq!(totals.total_profit_loss_percent(), 15.0);
+        assert_eq!(totals.net_value(), 1100.0 - 800.0);
+        assert_eq!(totals.gross_value(), 1900.0);
+    }
+
+    #[test]
+    fn test_fifo_partial_sell_consumes_oldest_lot() {
+        // Sell listed first to check trades are replayed by date, not row order
+        let trades = vec![
+            trade(3, 10, PositionType::Sell, 15.0, 130.0),
+            trade(1, 2, PositionType::Buy, 10.0, 100.0),
+            trade(2, 5, PositionType::Buy, 10.0, 120.0),
+        ];
+
+        let holdings = match_lots_fifo(&trades);
+        assert_eq!(holdings.len(), 1);
+        let h = &holdings[0];
+
+        // 10 @ 100 and 5 @ 120 closed at 130: 300 + 50
+        assert_eq!(h.realized_profit_loss, 350.0);
+        assert_eq!(h.position_type, PositionType::Buy);
+        assert_eq!(h.quantity(), 5.0);
+        assert_eq!(h.cost_basis(), 600.0);
+        assert_eq!(h.lots[0].position_id, 2);
+
+        // Only the open 5 @ 120 is unrealized
+        let val = h.valuation(140.0);
+        assert_eq!(val.profit_loss, 100.0);
+    }
+
+    #[test]
+    fn test_fifo_full_close_leaves_flat_holding() {
+        let trades = vec![
+            trade(1, 2, PositionType::Buy, 10.0, 100.0),
+            trade(2, 9, PositionType::Sell, 10.0, 90.0),
+        ];
+
+        let holdings = match_lots_fifo(&trades);
+        let h = &holdings[0];
+        assert_eq!(h.realized_profit_loss, -100.0);
+        assert!(h.lots.is_empty());
+        assert_eq!(h.quantity(), 0.0);
+        assert_eq!(h.valuation(95.0).profit_loss, 0.0);
+    }
+
+    #[test]
+    fn test_fifo_over_sell_flips_to_short() {
+        let trades = vec![
+            trade(1, 2, PositionType::Buy, 10.0, 100.0),
+            trade(2, 5, PositionType::Sell, 15.0, 110.0),
+            trade(3, 8, PositionType::Buy, 2.0, 105.0),
+        ];
+
+        let holdings = match_lots_fifo(&trades);
+        let h = &holdings[0];
+
+        // +100 closing the long, then 2 of the 5 short covered at 105: +10
+        assert_eq!(h.realized_profit_loss, 110.0);
+        assert_eq!(h.position_type, PositionType::Sell);
+        assert_eq!(h.quantity(), 3.0);
+        assert_eq!(h.average_price(), 110.0);
+        assert_eq!(h.lots[0].position_id, 2);
+
+        // Short 3 @ 110 marked at 100
+        assert_eq!(h.valuation(100.0).profit_loss, 30.0);
+    }
+
+    #[test]
+    fn test_covered_call_pnl_at_expiration() {
+        // 100 shares covered by a 100-strike call sold for 2.00
+        let call = Position {
+            option: Some(OptionLeg {
+                strategy: OptionStrategy::CoveredCall,
+                strike: 100.0,
+                expiration: "2024-03-15".to_string(),
+            }),
+            ..position(PositionType::Sell, 100.0, 2.0)
+        };
+        let leg = call.option.as_ref().unwrap();
+
+        // In the money at 110: assigned 10 below market, 2 premium kept
+        let itm = leg.valuation(&call, 110.0);
+        assert_eq!(itm.current_price, 10.0);
+        assert_eq!(itm.cost_basis, 200.0);
+        assert_eq!(itm.current_value, 1000.0);
+        assert_eq!(itm.profit_loss, -800.0);
+
+        // Out of the money at 95: expires worthless, full premium kept
+        let otm = leg.valuation(&call, 95.0);
+        assert_eq!(otm.current_value, 0.0);
+        assert_eq!(otm.profit_loss, 200.0);
+        assert_eq!(otm.profit_loss_percent, 100.0);
+
+        // The put side mirrors it, and option legs stay out of share lots
+        let put = OptionLeg {
+            strategy: OptionStrategy::CashSecuredPut,
+            ..leg.clone()
+        };
+        assert_eq!(put.intrinsic_value(95.0), 5.0);
+        assert_eq!(put.intrinsic_value(110.0), 0.0);
+        assert!(match_lots_fifo(&[call]).is_empty());
+    }
+}
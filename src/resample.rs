@@ -0,0 +1,247 @@
+//! Price series resampling
+//!
+//! Aggregates daily bars into weekly or monthly ones, and reduces long daily
+//! series for charting without losing extremes
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::error::{PipelineError, Result};
+use crate::models::DailyPrice;
+
+/// Bar size to aggregate daily prices into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResamplePeriod {
+    /// Bars as stored
+    #[default]
+    Daily,
+    /// Weeks ending Friday (W-FRI)
+    Weekly,
+    /// Calendar months
+    Monthly,
+}
+
+impl std::str::FromStr for ResamplePeriod {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "daily" | "1d" => Ok(ResamplePeriod::Daily),
+            "weekly" | "1wk" => Ok(ResamplePeriod::Weekly),
+            "monthly" | "1mo" => Ok(ResamplePeriod::Monthly),
+            _ => Err(PipelineError::Config(format!(
+                "Unknown timeframe: {} (expected daily, weekly or monthly)",
+                s
+            ))),
+        }
+    }
+}
+
+impl ResamplePeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResamplePeriod::Daily => "daily",
+            ResamplePeriod::Weekly => "weekly",
+            ResamplePeriod::Monthly => "monthly",
+        }
+    }
+
+    /// Bars in a year, for annualizing per-bar statistics
+    pub fn bars_per_year(&self) -> f64 {
+        match self {
+            ResamplePeriod::Daily => 252.0,
+            ResamplePeriod::Weekly => 52.0,
+            ResamplePeriod::Monthly => 12.0,
+        }
+    }
+
+    /// Date identifying the bucket `date` falls in
+    fn bucket(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            ResamplePeriod::Daily => date,
+            ResamplePeriod::Weekly => {
+                let to_friday = (4 + 7 - date.weekday().num_days_from_monday() as i64) % 7;
+                date + Duration::days(to_friday)
+            }
+            ResamplePeriod::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// Aggregate daily bars into `period` bars
+///
+/// Open is the first bar's, high the highest, low the lowest, close (and
+/// adjusted close) the last's, and volume the sum. Each bar is dated by the
+/// last daily bar in it rather than the period end, so an unfinished week or
+/// month never carries a future date. `prices` may be unsorted.
+pub fn resample(prices: &[DailyPrice], period: ResamplePeriod) -> Vec<DailyPrice> {
+    let mut sorted = prices.to_vec();
+    sorted.sort_by_key(|p| p.date);
+    if period == ResamplePeriod::Daily {
+        return sorted;
+    }
+
+    let mut bars: Vec<DailyPrice> = Vec::new();
+    let mut current_bucket = None;
+    for p in sorted {
+        let bucket = period.bucket(p.date);
+        match bars.last_mut() {
+            Some(bar) if current_bucket == Some(bucket) => {
+                bar.date = p.date;
+                bar.high = bar.high.max(p.high);
+                bar.low = bar.low.min(p.low);
+                bar.close = p.close;
+                bar.adjusted_close = p.adjusted_close;
+                bar.volume += p.volume;
+            }
+            _ => {
+                current_bucket = Some(bucket);
+                bars.push(p);
+            }
+        }
+    }
+    bars
+}
+
+/// Fewest bars `downsample_prices` reduces a series to
+const MIN_DOWNSAMPLE_POINTS: usize = 4;
+
+/// Downsample a price series to roughly `max_points` bars
+///
+/// The first and last bars are always kept. The bars in between are split
+/// into equal buckets and each bucket contributes its lowest-low and
+/// highest-high bars (in date order), so spikes and troughs survive.
+/// `max_points` is raised to 4 (the two ends plus one bucket) when smaller.
+/// Series already within `max_points` are returned unchanged.
+pub fn downsample_prices(prices: &[DailyPrice], max_points: usize) -> Vec<DailyPrice> {
+    let max_points = max_points.max(MIN_DOWNSAMPLE_POINTS);
+    if prices.len() <= max_points {
+        return prices.to_vec();
+    }
+
+    let inner = &prices[1..prices.len() - 1];
+    let bucket_count = (max_points - 2) / 2;
+    let bucket_size = (inner.len() as f64 / bucket_count as f64).ceil() as usize;
+
+    let mut result = Vec::with_capacity(max_points);
+    result.push(prices[0].clone());
+
+    for bucket in inner.chunks(bucket_size) {
+        let mut low_idx = 0;
+        let mut high_idx = 0;
+        for (i, p) in bucket.iter().enumerate() {
+            if p.low < bucket[low_idx].low {
+                low_idx = i;
+            }
+            if p.high > bucket[high_idx].high {
+                high_idx = i;
+            }
+        }
+
+        let (first, second) = if low_idx <= high_idx {
+            (low_idx, high_idx)
+        } else {
+            (high_idx, low_idx)
+        };
+        result.push(bucket[first].clone());
+        if second != first {
+            result.push(bucket[second].clone());
+        }
+    }
+
+    result.push(prices[prices.len() - 1].clone());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(date: NaiveDate, open: f64, high: f64, low: f64, close: f64, volume: i64) -> DailyPrice {
+        DailyPrice {
+            symbol: "TEST".to_string(),
+            date,
+            open,
+            high,
+            low,
+            close,
+            volume,
+            adjusted_close: None,
+            source: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_week_of_daily_bars_becomes_one_weekly_bar() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        // Mon 4th to Fri 8th, then Mon 11th
+        let prices = vec![
+            bar(day(6), 102.0, 108.0, 101.0, 107.0, 300),
+            bar(day(4), 100.0, 103.0, 99.0, 102.0, 100),
+            bar(day(5), 102.0, 104.0, 97.0, 101.0, 200),
+            bar(day(7), 107.0, 109.0, 105.0, 106.0, 400),
+            bar(day(8), 106.0, 107.0, 103.0, 104.0, 500),
+            bar(day(11), 104.0, 106.0, 102.0, 105.0, 600),
+        ];
+
+        let weekly = resample(&prices, ResamplePeriod::Weekly);
+        assert_eq!(weekly.len(), 2);
+        let week = &weekly[0];
+        assert_eq!(week.date, day(8));
+        assert_eq!(week.open, 100.0);
+        assert_eq!(week.high, 109.0);
+        assert_eq!(week.low, 97.0);
+        assert_eq!(week.close, 104.0);
+        assert_eq!(week.volume, 1500);
+        assert_eq!(weekly[1].date, day(11));
+        assert_eq!(weekly[1].volume, 600);
+
+        let monthly = resample(&prices, ResamplePeriod::Monthly);
+        assert_eq!(monthly.len(), 1);
+        assert_eq!(
+            (monthly[0].open, monthly[0].close, monthly[0].volume),
+            (100.0, 105.0, 2100)
+        );
+
+        assert_eq!(resample(&prices, ResamplePeriod::Daily).len(), prices.len());
+    }
+
+    #[test]
+    fn test_downsample_preserves_extremes() {
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let prices: Vec<DailyPrice> = (0..1000)
+            .map(|i| {
+                let close = 100.0 + (i as f64 * 0.05).sin() * 10.0 + (i % 7) as f64 * 0.1;
+                DailyPrice {
+                    symbol: "TEST".to_string(),
+                    date: start + chrono::Duration::days(i),
+                    open: close,
+                    high: if i == 437 { 500.0 } else { close + 1.0 },
+                    low: if i == 812 { 1.0 } else { close - 1.0 },
+                    close,
+                    volume: 1000,
+                    adjusted_close: None,
+                    source: "test".to_string(),
+                }
+            })
+            .collect();
+
+        let sampled = downsample_prices(&prices, 200);
+
+        assert!(sampled.len() <= 200);
+        assert!(sampled.len() >= 150);
+        assert_eq!(sampled.first().unwrap().date, prices[0].date);
+        assert_eq!(sampled.last().unwrap().date, prices[999].date);
+        assert!(sampled.iter().any(|p| p.date == prices[437].date && p.high == 500.0));
+        assert!(sampled.iter().any(|p| p.date == prices[812].date && p.low == 1.0));
+        assert!(sampled.windows(2).all(|w| w[0].date < w[1].date));
+
+        // Short series pass through untouched
+        assert_eq!(downsample_prices(&prices[..50], 200).len(), 50);
+
+        // Tiny targets are clamped rather than ignored
+        let ends = downsample_prices(&prices, 2);
+        assert_eq!(ends.len(), 4);
+        assert_eq!(ends[0].date, prices[0].date);
+        assert_eq!(ends[3].date, prices[999].date);
+    }
+}
@@ -0,0 +1,154 @@
+//! Retry with exponential backoff for HTTP fetchers
+//!
+//! Shared by the Yahoo Finance, FRED and Google Trends clients. Only
+//! transient failures are retried: HTTP 429, 5xx responses and errors
+//! connecting to the server.
+
+use std::thread;
+use std::time::Duration;
+
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::StatusCode;
+
+use crate::error::{redact_url, Result};
+
+/// Retries after the first attempt unless a client overrides it
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// Wait before the first retry; doubled for each one after
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// How many times to retry a request and how long to back off between tries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_RETRY_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `retry` (0-based): `base_delay * 2^retry`
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(retry))
+    }
+
+    /// Send the request built by `build`, retrying transient failures
+    ///
+    /// `build` is called again for every attempt, since a sent request can't
+    /// be reused. When the retries run out the last response is returned
+    /// as-is, so callers keep their own handling of non-success statuses;
+    /// the last error is returned if no response came back at all.
+    pub fn send<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let mut retry = 0;
+        loop {
+            let transient = match build().send() {
+                Ok(response) if is_transient_status(response.status()) => Ok(response),
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_connect() => Err(redact_url(e)),
+                Err(e) => return Err(e.into()),
+            };
+
+            if retry >= self.max_retries {
+                return transient.map_err(Into::into);
+            }
+
+            let delay = self.delay_for(retry);
+            log_retry(
+                transient.as_ref().map(|r| r.status()),
+                delay,
+                retry + 1,
+                self.max_retries,
+            );
+            thread::sleep(delay);
+            retry += 1;
+        }
+    }
+
+    /// Async counterpart of `send`, backing off with `tokio::time::sleep`
+    #[cfg(feature = "async")]
+    pub async fn send_async<F>(&self, build: F) -> Result<reqwest::Response>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let mut retry = 0;
+        loop {
+            let transient = match build().send().await {
+                Ok(response) if is_transient_status(response.status()) => Ok(response),
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_connect() => Err(redact_url(e)),
+                Err(e) => return Err(e.into()),
+            };
+
+            if retry >= self.max_retries {
+                return transient.map_err(Into::into);
+            }
+
+            let delay = self.delay_for(retry);
+            log_retry(
+                transient.as_ref().map(|r| r.status()),
+                delay,
+                retry + 1,
+                self.max_retries,
+            );
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+}
+
+/// Report a transient failure (status or error) before backing off
+fn log_retry(
+    transient: std::result::Result<StatusCode, &reqwest::Error>,
+    delay: Duration,
+    attempt: u32,
+    max_retries: u32,
+) {
+    match transient {
+        Ok(status) => println!(
+            "[WARN] HTTP {}, retrying in {:?} ({}/{})",
+            status, delay, attempt, max_retries
+        ),
+        Err(e) => println!(
+            "[WARN] {}, retrying in {:?} ({}/{})",
+            e, delay, attempt, max_retries
+        ),
+    }
+}
+
+/// Rate limiting and server-side failures are worth another try
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_saturates() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+        };
+        assert_eq!(policy.delay_for(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(1), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(800));
+        // Huge retry counts saturate instead of overflowing
+        assert!(policy.delay_for(200) > policy.delay_for(3));
+
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+    }
+}
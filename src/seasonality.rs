@@ -0,0 +1,121 @@
+//! Seasonality analytics
+//!
+//! Average daily returns grouped by calendar month and weekday
+
+use chrono::{Datelike, Weekday};
+
+use crate::models::DailyPrice;
+
+/// Average return for one calendar bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SeasonalBucket {
+    /// Mean daily return in percent (0.0 when the bucket is empty)
+    pub average_return: f64,
+    /// Number of daily returns in the bucket
+    pub count: usize,
+}
+
+/// Daily percent returns paired with the date of the later bar
+fn daily_returns(prices: &[DailyPrice]) -> Vec<(chrono::NaiveDate, f64)> {
+    let mut sorted: Vec<&DailyPrice> = prices.iter().collect();
+    sorted.sort_by_key(|p| p.date);
+
+    sorted
+        .windows(2)
+        .filter(|w| w[0].close > 0.0)
+        .map(|w| (w[1].date, (w[1].close - w[0].close) / w[0].close * 100.0))
+        .collect()
+}
+
+fn average_buckets<const N: usize>(returns: impl Iterator<Item = (usize, f64)>) -> [SeasonalBucket; N] {
+    let mut sums = [0.0; N];
+    let mut buckets = [SeasonalBucket::default(); N];
+
+    for (idx, ret) in returns {
+        sums[idx] += ret;
+        buckets[idx].count += 1;
+    }
+
+    for (bucket, sum) in buckets.iter_mut().zip(sums) {
+        if bucket.count > 0 {
+            bucket.average_return = sum / bucket.count as f64;
+        }
+    }
+
+    buckets
+}
+
+/// Average daily return and sample count per month (index 0 = January)
+pub fn monthly_return_stats(prices: &[DailyPrice]) -> [SeasonalBucket; 12] {
+    average_buckets(
+        daily_returns(prices)
+            .into_iter()
+            .map(|(date, ret)| (date.month0() as usize, ret)),
+    )
+}
+
+/// Average daily return and sample count per weekday (index 0 = Monday)
+///
+/// Weekend bars, if any, are ignored.
+pub fn weekday_return_stats(prices: &[DailyPrice]) -> [SeasonalBucket; 5] {
+    average_buckets(
+        daily_returns(prices)
+            .into_iter()
+            .filter(|(date, _)| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+            .map(|(date, ret)| (date.weekday().num_days_from_monday() as usize, ret)),
+    )
+}
+
+/// Average daily return (%) per month (index 0 = January)
+pub fn monthly_average_returns(prices: &[DailyPrice]) -> [f64; 12] {
+    monthly_return_stats(prices).map(|b| b.average_return)
+}
+
+/// Average daily return (%) per weekday (index 0 = Monday)
+pub fn weekday_average_returns(prices: &[DailyPrice]) -> [f64; 5] {
+    weekday_return_stats(prices).map(|b| b.average_return)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_positive_january_average() {
+        // Two years of daily bars: rising in January, falling otherwise
+        let mut prices = Vec::new();
+        let mut date = NaiveDate::from_ymd_opt(2022, 12, 31).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let mut close = 100.0;
+
+        while date <= end {
+            if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                prices.push(DailyPrice {
+                    symbol: "TEST".to_string(),
+                    date,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 1000,
+                    adjusted_close: None,
+                    source: "test".to_string(),
+                });
+            }
+            date = date.succ_opt().unwrap();
+            close *= if date.month() == 1 { 1.01 } else { 0.999 };
+        }
+
+        let monthly = monthly_return_stats(&prices);
+        assert!(monthly[0].count > 30);
+        assert!(monthly[0].average_return > 0.0);
+        assert!(monthly[1..].iter().all(|b| b.average_return < 0.0));
+
+        let averages = monthly_average_returns(&prices);
+        assert_eq!(averages[0], monthly[0].average_return);
+
+        let weekdays = weekday_return_stats(&prices);
+        assert!(weekdays.iter().all(|b| b.count > 0));
+    }
+}
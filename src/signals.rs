@@ -1,13 +1,32 @@
 //! Signal Generation Engine
 //!
-//! Detects trading signals from technical indicators
+//! Detects trading signals from technical indicators and candlestick patterns
 
+use crate::db::Database;
+use crate::error::Result;
 use crate::models::{DailyPrice, Signal, SignalDirection, SignalType, TechnicalIndicator};
+use crate::resample::{resample, ResamplePeriod};
 use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// How to handle several signals firing on the same bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictResolution {
+    /// Store every signal (default)
+    #[default]
+    KeepAll,
+    /// Keep only the strongest signal per bar
+    Strongest,
+    /// Net bullish minus bearish strength into one directional signal per bar
+    Net,
+}
+
 /// Configuration for signal detection thresholds
-#[derive(Debug, Clone)]
+///
+/// Fields missing from a stored config fall back to their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SignalConfig {
     pub rsi_overbought: f64,
     pub rsi_oversold: f64,
@@ -21,6 +40,36 @@ pub struct SignalConfig {
     pub cci_oversold: f64,
     pub mfi_overbought: f64,
     pub mfi_oversold: f64,
+    /// Max body/range ratio for a candle to count as a doji
+    pub doji_body_ratio: f64,
+    /// Min wick/body ratio for hammer and shooting star candles
+    pub hammer_wick_ratio: f64,
+    /// Same-bar conflict handling; defaults to keeping all signals
+    pub conflict_resolution: ConflictResolution,
+    /// When storing, acknowledge earlier open signals that a newer
+    /// opposite-direction signal invalidates (off by default)
+    pub auto_acknowledge_opposing: bool,
+    /// Historical backfill: stamp `created_at` with each signal's own date
+    /// and mark it `backfilled` instead of using the time of storage
+    pub backfill: bool,
+    /// Max bars between the two swing points compared for divergence
+    pub divergence_lookback: usize,
+    /// Bars on each side a swing high/low must exceed to count as a pivot
+    pub pivot_bars: usize,
+    /// Fast and slow SMA periods for the MA crossover (`SMA_{n}`)
+    ///
+    /// `calculate_all` only emits SMA_20 and SMA_50; other periods must be
+    /// calculated separately (e.g. with `calculate_sma`) or the crossover
+    /// never fires.
+    pub ma_fast_period: usize,
+    pub ma_slow_period: usize,
+    /// MACD line and signal periods (`MACD_{fast}_{slow}`, `MACD_SIGNAL_{n}`)
+    ///
+    /// `calculate_all` only emits MACD 12/26/9, so other periods have the
+    /// same coupling as the MA crossover.
+    pub macd_fast_period: usize,
+    pub macd_slow_period: usize,
+    pub macd_signal_period: usize,
 }
 
 impl Default for SignalConfig {
@@ -38,10 +87,36 @@ impl Default for SignalConfig {
             cci_oversold: -100.0,
             mfi_overbought: 80.0,
             mfi_oversold: 20.0,
+            doji_body_ratio: 0.1,
+            hammer_wick_ratio: 2.0,
+            conflict_resolution: ConflictResolution::KeepAll,
+            auto_acknowledge_opposing: false,
+            backfill: false,
+            divergence_lookback: 30,
+            pivot_bars: 3,
+            ma_fast_period: 20,
+            ma_slow_period: 50,
+            macd_fast_period: 12,
+            macd_slow_period: 26,
+            macd_signal_period: 9,
         }
     }
 }
 
+/// One symbol's outcome in `SignalEngine::scan_all_symbols`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolScan {
+    pub symbol: String,
+    /// Signals generated and stored across the whole history
+    pub generated: usize,
+    /// Bullish signals on the symbol's latest bar
+    pub bullish: usize,
+    /// Bearish signals on the symbol's latest bar
+    pub bearish: usize,
+    /// `SignalEngine::score` of the latest bar's signals
+    pub score: f64,
+}
+
 /// Main signal generator
 pub struct SignalEngine {
     config: SignalConfig,
@@ -64,6 +139,37 @@ impl SignalEngine {
         Self { config }
     }
 
+    /// Store generated signals, applying `auto_acknowledge_opposing` if enabled
+    pub fn store_signals(&self, db: &mut Database, signals: &[Signal]) -> Result<usize> {
+        let count = db.upsert_signals(signals)?;
+
+        if self.config.auto_acknowledge_opposing {
+            // Latest bullish/bearish date per symbol among the new signals
+            let mut latest: HashMap<(&str, SignalDirection), NaiveDate> = HashMap::new();
+            for signal in signals {
+                if signal.direction == SignalDirection::Neutral {
+                    continue;
+                }
+                let entry = latest
+                    .entry((signal.symbol.as_str(), signal.direction))
+                    .or_insert(signal.timestamp);
+                if signal.timestamp > *entry {
+                    *entry = signal.timestamp;
+                }
+            }
+
+            for ((symbol, direction), date) in latest {
+                let opposing = match direction {
+                    SignalDirection::Bullish => SignalDirection::Bearish,
+                    _ => SignalDirection::Bullish,
+                };
+                db.acknowledge_signals_before(symbol, opposing, date)?;
+            }
+        }
+
+        Ok(count)
+    }
+
     /// Build a map of indicators by date for O(1) lookups
     fn build_indicator_map(
         &self,
@@ -80,6 +186,72 @@ impl SignalEngine {
         map
     }
 
+    /// Net bullish/bearish score of the signals on the most recent date
+    ///
+    /// Bullish signals add their strength, bearish signals subtract it and
+    /// neutral signals contribute zero. The net is divided by the total
+    /// directional strength, so the score lies in [-1, 1]; it is 0 when there
+    /// are no directional signals.
+    pub fn score(signals: &[Signal]) -> f64 {
+        let Some(latest) = signals.iter().map(|s| s.timestamp).max() else {
+            return 0.0;
+        };
+
+        let mut net = 0.0;
+        let mut total = 0.0;
+        for signal in signals.iter().filter(|s| s.timestamp == latest) {
+            match signal.direction {
+                SignalDirection::Bullish => net += signal.strength,
+                SignalDirection::Bearish => net -= signal.strength,
+                SignalDirection::Neutral => continue,
+            }
+            total += signal.strength;
+        }
+
+        if total > 0.0 {
+            (net / total).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Generate and store signals for every symbol with price and indicator data
+    ///
+    /// Symbols without indicators are skipped. The summary covers signals on
+    /// each symbol's latest bar and is sorted by score, highest first.
+    pub fn scan_all_symbols(&self, db: &mut Database) -> Result<Vec<SymbolScan>> {
+        let mut scans = Vec::new();
+
+        for symbol in db.get_symbols_with_data()? {
+            let indicators = db.get_all_indicators(&symbol)?;
+            if indicators.is_empty() {
+                continue;
+            }
+            let prices = db.get_prices(&symbol)?;
+            let signals = self.generate_signals(&symbol, &indicators, &prices);
+            self.store_signals(db, &signals)?;
+
+            let last_bar = prices.iter().map(|p| p.date).max();
+            let latest: Vec<Signal> = signals
+                .iter()
+                .filter(|s| Some(s.timestamp) == last_bar)
+                .cloned()
+                .collect();
+            let count = |direction| latest.iter().filter(|s| s.direction == direction).count();
+
+            scans.push(SymbolScan {
+                generated: signals.len(),
+                bullish: count(SignalDirection::Bullish),
+                bearish: count(SignalDirection::Bearish),
+                score: Self::score(&latest),
+                symbol,
+            });
+        }
+
+        scans.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.symbol.cmp(&b.symbol)));
+        Ok(scans)
+    }
+
     /// Generate all signals from indicators for a symbol
     pub fn generate_signals(
         &self,
@@ -87,7 +259,7 @@ impl SignalEngine {
         indicators: &[TechnicalIndicator],
         prices: &[DailyPrice],
     ) -> Vec<Signal> {
-        if prices.is_empty() || indicators.is_empty() {
+        if prices.is_empty() {
             return vec![];
         }
 
@@ -136,6 +308,13 @@ impl SignalEngine {
                 signals.push(sig);
             }
 
+            // Bollinger/Keltner squeeze signals
+            if let Some(sig) =
+                self.detect_squeeze_signal(symbol, *date, price, indicators_today, indicators_prev)
+            {
+                signals.push(sig);
+            }
+
             // MA Crossover signals
             if let Some(sig) =
                 self.detect_ma_crossover_signal(symbol, *date, price, indicators_today, indicators_prev)
@@ -177,11 +356,166 @@ impl SignalEngine {
             {
                 signals.push(sig);
             }
+
+            // KST signals
+            if let Some(sig) =
+                self.detect_kst_signal(symbol, *date, price, indicators_today, indicators_prev)
+            {
+                signals.push(sig);
+            }
+
+            // Donchian Channel breakouts
+            if let Some(sig) = self.detect_donchian_signal(symbol, *date, price, indicators_prev) {
+                signals.push(sig);
+            }
+        }
+
+        // Divergence between price swings and RSI/MACD histogram swings
+        signals.extend(self.detect_divergence(symbol, indicators, prices));
+
+        // Candlestick pattern signals (from OHLC, not indicators)
+        let mut sorted_prices: Vec<&DailyPrice> = prices.iter().collect();
+        sorted_prices.sort_by_key(|p| p.date);
+
+        for (i, bar) in sorted_prices.iter().enumerate() {
+            let prev_bar = if i > 0 { Some(sorted_prices[i - 1]) } else { None };
+            if let Some(sig) = self.detect_candlestick_pattern(symbol, bar, prev_bar) {
+                signals.push(sig);
+            }
+        }
+
+        let mut signals = self.resolve_conflicts(signals);
+
+        if self.config.backfill {
+            for signal in &mut signals {
+                signal.created_at = format!("{} 00:00:00", signal.timestamp);
+                signal.backfilled = true;
+            }
         }
 
         signals
     }
 
+    /// Generate daily signals and mark those the weekly trend agrees with
+    ///
+    /// `weekly_indicators` are calculated on weekly bars (see
+    /// `calculate_all_with_timeframe`) and `prices` are the daily bars. The
+    /// weekly trend on a signal's date is up when the close of the last week
+    /// finished before that date is above its weekly `SMA_{ma_fast_period}`,
+    /// and down when below. Bullish signals are `confirmed` in an uptrend and
+    /// bearish ones in a downtrend, and neutral signals never are. Signals
+    /// before the first weekly SMA value have no trend to check and keep
+    /// `confirmed: None`.
+    pub fn generate_signals_mtf(
+        &self,
+        symbol: &str,
+        daily_indicators: &[TechnicalIndicator],
+        weekly_indicators: &[TechnicalIndicator],
+        prices: &[DailyPrice],
+    ) -> Vec<Signal> {
+        let sma_name = format!("SMA_{}", self.config.ma_fast_period);
+        let weekly_sma: HashMap<NaiveDate, f64> = weekly_indicators
+            .iter()
+            .filter(|i| i.indicator_name == sma_name)
+            .map(|i| (i.date, i.value))
+            .collect();
+        // (date of the week's last bar, close minus SMA), oldest first
+        let weekly_trend: Vec<(NaiveDate, f64)> = resample(prices, ResamplePeriod::Weekly)
+            .iter()
+            .filter_map(|bar| {
+                weekly_sma
+                    .get(&bar.date)
+                    .map(|sma| (bar.date, bar.close - sma))
+            })
+            .collect();
+
+        let mut signals = self.generate_signals(symbol, daily_indicators, prices);
+        for signal in &mut signals {
+            // A week dated before the signal's bar was complete on that bar
+            let finished = weekly_trend.partition_point(|(date, _)| *date < signal.timestamp);
+            let trend = finished.checked_sub(1).map(|i| weekly_trend[i].1);
+            signal.confirmed = trend.map(|gap| match signal.direction {
+                SignalDirection::Bullish => gap > 0.0,
+                SignalDirection::Bearish => gap < 0.0,
+                SignalDirection::Neutral => false,
+            });
+        }
+        signals
+    }
+
+    /// Apply the configured same-bar conflict resolution
+    fn resolve_conflicts(&self, signals: Vec<Signal>) -> Vec<Signal> {
+        if self.config.conflict_resolution == ConflictResolution::KeepAll {
+            return signals;
+        }
+
+        let mut by_date: HashMap<NaiveDate, Vec<Signal>> = HashMap::new();
+        for signal in signals {
+            by_date.entry(signal.timestamp).or_default().push(signal);
+        }
+
+        let mut dates: Vec<NaiveDate> = by_date.keys().copied().collect();
+        dates.sort();
+
+        let strongest = |candidates: Vec<Signal>| {
+            candidates
+                .into_iter()
+                .max_by(|a, b| a.strength.total_cmp(&b.strength))
+        };
+
+        let mut resolved = Vec::with_capacity(dates.len());
+        for date in dates {
+            let bar_signals = by_date.remove(&date).unwrap_or_default();
+
+            let kept = match self.config.conflict_resolution {
+                ConflictResolution::KeepAll => unreachable!(),
+                ConflictResolution::Strongest => strongest(bar_signals),
+                ConflictResolution::Net => {
+                    let net: f64 = bar_signals
+                        .iter()
+                        .map(|s| match s.direction {
+                            SignalDirection::Bullish => s.strength,
+                            SignalDirection::Bearish => -s.strength,
+                            SignalDirection::Neutral => 0.0,
+                        })
+                        .sum();
+
+                    let has_directional = bar_signals
+                        .iter()
+                        .any(|s| s.direction != SignalDirection::Neutral);
+
+                    if !has_directional {
+                        strongest(bar_signals)
+                    } else if net == 0.0 {
+                        // Opposing signals cancel out
+                        None
+                    } else {
+                        // Represent the bar by the strongest signal on the winning side
+                        let winner = if net > 0.0 {
+                            SignalDirection::Bullish
+                        } else {
+                            SignalDirection::Bearish
+                        };
+                        strongest(
+                            bar_signals
+                                .into_iter()
+                                .filter(|s| s.direction == winner)
+                                .collect(),
+                        )
+                        .map(|mut s| {
+                            s.strength = net.abs().min(1.0);
+                            s
+                        })
+                    }
+                }
+            };
+
+            resolved.extend(kept);
+        }
+
+        resolved
+    }
+
     /// Detect RSI overbought/oversold signals
     fn detect_rsi_signal(
         &self,
@@ -210,6 +544,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -229,6 +565,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -245,10 +583,16 @@ impl SignalEngine {
         today: &HashMap<String, f64>,
         prev: Option<&HashMap<String, f64>>,
     ) -> Option<Signal> {
-        let macd = *today.get("MACD_12_26")?;
-        let signal = *today.get("MACD_SIGNAL_9")?;
-        let prev_macd = prev.and_then(|p| p.get("MACD_12_26").copied())?;
-        let prev_signal = prev.and_then(|p| p.get("MACD_SIGNAL_9").copied())?;
+        let macd_name = format!(
+            "MACD_{}_{}",
+            self.config.macd_fast_period, self.config.macd_slow_period
+        );
+        let signal_name = format!("MACD_SIGNAL_{}", self.config.macd_signal_period);
+
+        let macd = *today.get(&macd_name)?;
+        let signal = *today.get(&signal_name)?;
+        let prev_macd = prev.and_then(|p| p.get(&macd_name).copied())?;
+        let prev_signal = prev.and_then(|p| p.get(&signal_name).copied())?;
 
         // Bullish crossover: MACD crosses above signal
         if prev_macd <= prev_signal && macd > signal {
@@ -265,6 +609,8 @@ impl SignalEngine {
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
         // Bearish crossover: MACD crosses below signal
@@ -282,6 +628,8 @@ impl SignalEngine {
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
 
@@ -315,6 +663,8 @@ impl SignalEngine {
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
         // Price breaks below lower band (oversold/potential bounce)
@@ -332,13 +682,82 @@ impl SignalEngine {
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
 
         None
     }
 
-    /// Detect MA crossover signals (SMA 20/50)
+    /// Detect Bollinger Bands moving inside or back outside the Keltner Channels
+    ///
+    /// `SqueezeOn` fires on the bar both bands first sit inside the channels,
+    /// with strength from how far the tighter side sits inside relative to the
+    /// channel half-width. `SqueezeOff` fires when they expand back out; its
+    /// direction follows the close against the channel middle and its strength
+    /// how far the wider side has broken out.
+    fn detect_squeeze_signal(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+        price: f64,
+        today: &HashMap<String, f64>,
+        prev: Option<&HashMap<String, f64>>,
+    ) -> Option<Signal> {
+        let bands = |m: &HashMap<String, f64>| -> Option<[f64; 5]> {
+            Some([
+                *m.get("BB_UPPER_20")?,
+                *m.get("BB_LOWER_20")?,
+                *m.get("KC_UPPER_20")?,
+                *m.get("KC_MIDDLE_20")?,
+                *m.get("KC_LOWER_20")?,
+            ])
+        };
+        let inside = |[bb_upper, bb_lower, kc_upper, _, kc_lower]: [f64; 5]| {
+            bb_upper < kc_upper && bb_lower > kc_lower
+        };
+
+        let today_bands = bands(today)?;
+        let was_inside = inside(bands(prev?)?);
+        let is_inside = inside(today_bands);
+
+        let [bb_upper, bb_lower, kc_upper, kc_middle, kc_lower] = today_bands;
+        let half_width = ((kc_upper - kc_lower) / 2.0).max(0.01);
+
+        let (signal_type, direction, depth) = if is_inside && !was_inside {
+            let depth = (kc_upper - bb_upper).min(bb_lower - kc_lower);
+            (SignalType::SqueezeOn, SignalDirection::Neutral, depth)
+        } else if was_inside && !is_inside {
+            let direction = if price >= kc_middle {
+                SignalDirection::Bullish
+            } else {
+                SignalDirection::Bearish
+            };
+            let depth = (bb_upper - kc_upper).max(kc_lower - bb_lower);
+            (SignalType::SqueezeOff, direction, depth)
+        } else {
+            return None;
+        };
+
+        Some(Signal {
+            id: 0,
+            symbol: symbol.to_string(),
+            signal_type,
+            direction,
+            strength: (depth / half_width).clamp(0.0, 1.0),
+            price_at_signal: price,
+            triggered_by: "BB_KC_SQUEEZE".to_string(),
+            trigger_value: bb_upper - bb_lower,
+            timestamp: date,
+            created_at: String::new(),
+            acknowledged: false,
+            backfilled: false,
+            confirmed: None,
+        })
+    }
+
+    /// Detect MA crossover signals (SMA 20/50 unless configured otherwise)
     fn detect_ma_crossover_signal(
         &self,
         symbol: &str,
@@ -347,10 +766,14 @@ impl SignalEngine {
         today: &HashMap<String, f64>,
         prev: Option<&HashMap<String, f64>>,
     ) -> Option<Signal> {
-        let sma_fast = *today.get("SMA_20")?;
-        let sma_slow = *today.get("SMA_50")?;
-        let prev_fast = prev.and_then(|p| p.get("SMA_20").copied())?;
-        let prev_slow = prev.and_then(|p| p.get("SMA_50").copied())?;
+        let (fast, slow) = (self.config.ma_fast_period, self.config.ma_slow_period);
+        let fast_name = format!("SMA_{}", fast);
+        let slow_name = format!("SMA_{}", slow);
+
+        let sma_fast = *today.get(&fast_name)?;
+        let sma_slow = *today.get(&slow_name)?;
+        let prev_fast = prev.and_then(|p| p.get(&fast_name).copied())?;
+        let prev_slow = prev.and_then(|p| p.get(&slow_name).copied())?;
 
         // Golden cross: fast MA crosses above slow MA
         if prev_fast <= prev_slow && sma_fast > sma_slow {
@@ -362,11 +785,13 @@ impl SignalEngine {
                 direction: SignalDirection::Bullish,
                 strength,
                 price_at_signal: price,
-                triggered_by: "SMA_20/50".to_string(),
+                triggered_by: format!("SMA_{}/{}", fast, slow),
                 trigger_value: sma_fast,
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
         // Death cross: fast MA crosses below slow MA
@@ -379,11 +804,13 @@ impl SignalEngine {
                 direction: SignalDirection::Bearish,
                 strength,
                 price_at_signal: price,
-                triggered_by: "SMA_20/50".to_string(),
+                triggered_by: format!("SMA_{}/{}", fast, slow),
                 trigger_value: sma_fast,
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
 
@@ -418,6 +845,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -437,6 +866,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -473,6 +904,8 @@ impl SignalEngine {
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
         // Bearish crossover from overbought
@@ -490,6 +923,8 @@ impl SignalEngine {
                 timestamp: date,
                 created_at: String::new(),
                 acknowledged: false,
+                backfilled: false,
+                confirmed: None,
             });
         }
 
@@ -524,6 +959,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -543,6 +980,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -578,6 +1017,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -597,6 +1038,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -632,6 +1075,8 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
@@ -651,10 +1096,836 @@ impl SignalEngine {
                     timestamp: date,
                     created_at: String::new(),
                     acknowledged: false,
+                    backfilled: false,
+                    confirmed: None,
                 });
             }
         }
 
         None
     }
+
+    /// Detect KST signal line crossovers
+    fn detect_kst_signal(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+        price: f64,
+        today: &HashMap<String, f64>,
+        prev: Option<&HashMap<String, f64>>,
+    ) -> Option<Signal> {
+        let kst = *today.get("KST")?;
+        let signal = *today.get("KST_SIGNAL")?;
+        let prev_kst = prev.and_then(|p| p.get("KST").copied())?;
+        let prev_signal = prev.and_then(|p| p.get("KST_SIGNAL").copied())?;
+
+        let (signal_type, direction) = if prev_kst <= prev_signal && kst > signal {
+            (SignalType::KstBullishCross, SignalDirection::Bullish)
+        } else if prev_kst >= prev_signal && kst < signal {
+            (SignalType::KstBearishCross, SignalDirection::Bearish)
+        } else {
+            return None;
+        };
+
+        // Gap relative to the KST level; KST is in weighted percent units
+        let strength = ((kst - signal).abs() / kst.abs().max(1.0)).min(1.0);
+
+        Some(Signal {
+            id: 0,
+            symbol: symbol.to_string(),
+            signal_type,
+            direction,
+            strength,
+            price_at_signal: price,
+            triggered_by: "KST".to_string(),
+            trigger_value: kst,
+            timestamp: date,
+            created_at: String::new(),
+            acknowledged: false,
+            backfilled: false,
+            confirmed: None,
+        })
+    }
+
+    /// Detect closes beyond the prior bar's 20-bar Donchian Channel
+    ///
+    /// The channel includes the current bar's high and low, so a breakout is
+    /// measured against the previous bar's channel. Strength is the distance
+    /// past the broken side relative to the channel width.
+    fn detect_donchian_signal(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+        price: f64,
+        prev: Option<&HashMap<String, f64>>,
+    ) -> Option<Signal> {
+        let prev_upper = prev.and_then(|p| p.get("DONCHIAN_UPPER_20").copied())?;
+        let prev_lower = prev.and_then(|p| p.get("DONCHIAN_LOWER_20").copied())?;
+        let width = (prev_upper - prev_lower).max(0.01);
+
+        let (signal_type, direction, triggered_by, trigger_value, distance) = if price > prev_upper
+        {
+            (
+                SignalType::DonchianBreakoutUp,
+                SignalDirection::Bullish,
+                "DONCHIAN_UPPER_20",
+                prev_upper,
+                price - prev_upper,
+            )
+        } else if price < prev_lower {
+            (
+                SignalType::DonchianBreakoutDown,
+                SignalDirection::Bearish,
+                "DONCHIAN_LOWER_20",
+                prev_lower,
+                prev_lower - price,
+            )
+        } else {
+            return None;
+        };
+
+        Some(Signal {
+            id: 0,
+            symbol: symbol.to_string(),
+            signal_type,
+            direction,
+            strength: (distance / width).min(1.0),
+            price_at_signal: price,
+            triggered_by: triggered_by.to_string(),
+            trigger_value,
+            timestamp: date,
+            created_at: String::new(),
+            acknowledged: false,
+            backfilled: false,
+            confirmed: None,
+        })
+    }
+
+    /// Detect bullish/bearish divergence between price and RSI_14 or MACD_HIST
+    ///
+    /// A swing low (high) is a bar whose low (high) is strictly below (above)
+    /// the `pivot_bars` bars on either side; the oscillator's swing is its
+    /// extreme over the same window. Each new price swing is compared with the
+    /// previous one of the same kind at most `divergence_lookback` bars
+    /// earlier: a lower price low with a higher oscillator low is bullish, a
+    /// higher price high with a lower oscillator high is bearish. A pivot is
+    /// only known `pivot_bars` bars later, so signals are dated on that
+    /// confirming bar. When both oscillators diverge on the same bar the
+    /// stronger signal is kept.
+    pub fn detect_divergence(
+        &self,
+        symbol: &str,
+        indicators: &[TechnicalIndicator],
+        prices: &[DailyPrice],
+    ) -> Vec<Signal> {
+        let n = self.config.pivot_bars.max(1);
+        let mut bars: Vec<&DailyPrice> = prices.iter().collect();
+        bars.sort_by_key(|p| p.date);
+        if bars.len() < 2 * n + 1 {
+            return vec![];
+        }
+
+        let mut best: HashMap<(SignalType, NaiveDate), Signal> = HashMap::new();
+        for name in ["RSI_14", "MACD_HIST"] {
+            let values: HashMap<NaiveDate, f64> = indicators
+                .iter()
+                .filter(|i| i.indicator_name == name)
+                .map(|i| (i.date, i.value))
+                .collect();
+
+            // Oscillator extreme over a pivot window; None if any bar lacks a value
+            let osc_extreme = |j: usize, lows: bool| -> Option<f64> {
+                let window: Vec<f64> = bars[j - n..=j + n]
+                    .iter()
+                    .map(|b| values.get(&b.date).copied())
+                    .collect::<Option<_>>()?;
+                Some(if lows {
+                    window.into_iter().fold(f64::MAX, f64::min)
+                } else {
+                    window.into_iter().fold(f64::MIN, f64::max)
+                })
+            };
+
+            for lows in [true, false] {
+                let level = |b: &DailyPrice| if lows { b.low } else { b.high };
+                let mut prev: Option<(usize, f64, f64)> = None;
+
+                for j in n..bars.len() - n {
+                    let here = level(bars[j]);
+                    let is_pivot = (j - n..=j + n).filter(|&k| k != j).all(|k| {
+                        let other = level(bars[k]);
+                        if lows {
+                            here < other
+                        } else {
+                            here > other
+                        }
+                    });
+                    if !is_pivot {
+                        continue;
+                    }
+                    let Some(osc) = osc_extreme(j, lows) else {
+                        continue;
+                    };
+
+                    if let Some((i, prev_price, prev_osc)) = prev {
+                        let diverges = if lows {
+                            here < prev_price && osc > prev_osc
+                        } else {
+                            here > prev_price && osc < prev_osc
+                        };
+                        if j - i <= self.config.divergence_lookback && diverges {
+                            let (signal_type, direction) = if lows {
+                                (SignalType::BullishDivergence, SignalDirection::Bullish)
+                            } else {
+                                (SignalType::BearishDivergence, SignalDirection::Bearish)
+                            };
+                            let confirmed = bars[j + n];
+                            let strength = ((osc - prev_osc).abs()
+                                / prev_osc.abs().max(osc.abs()).max(f64::EPSILON))
+                            .min(1.0);
+                            let signal = Signal {
+                                id: 0,
+                                symbol: symbol.to_string(),
+                                signal_type,
+                                direction,
+                                strength,
+                                price_at_signal: confirmed.close,
+                                triggered_by: name.to_string(),
+                                trigger_value: osc,
+                                timestamp: confirmed.date,
+                                created_at: String::new(),
+                                acknowledged: false,
+                                backfilled: false,
+                                confirmed: None,
+                            };
+                            let key = (signal_type, confirmed.date);
+                            if best.get(&key).is_none_or(|s| s.strength < strength) {
+                                best.insert(key, signal);
+                            }
+                        }
+                    }
+                    prev = Some((j, here, osc));
+                }
+            }
+        }
+
+        let mut signals: Vec<Signal> = best.into_values().collect();
+        signals.sort_by_key(|s| (s.timestamp, s.signal_type.as_str()));
+        signals
+    }
+
+    /// Detect candlestick patterns from body/wick proportions
+    ///
+    /// At most one pattern per bar, checked in order: engulfing,
+    /// hammer/shooting star, doji.
+    fn detect_candlestick_pattern(
+        &self,
+        symbol: &str,
+        bar: &DailyPrice,
+        prev: Option<&DailyPrice>,
+    ) -> Option<Signal> {
+        let range = bar.high - bar.low;
+        if range <= 0.0 {
+            return None;
+        }
+
+        let body = (bar.close - bar.open).abs();
+        let upper_wick = bar.high - bar.open.max(bar.close);
+        let lower_wick = bar.open.min(bar.close) - bar.low;
+        let body_ratio = body / range;
+
+        let make_signal =
+            |signal_type: SignalType, direction: SignalDirection, strength: f64| Signal {
+                id: 0,
+                symbol: symbol.to_string(),
+                signal_type,
+                direction,
+                strength: strength.clamp(0.0, 1.0),
+                price_at_signal: bar.close,
+                triggered_by: "CANDLESTICK".to_string(),
+                trigger_value: body_ratio,
+                timestamp: bar.date,
+                created_at: String::new(),
+                acknowledged: false,
+                backfilled: false,
+                confirmed: None,
+            };
+
+        // Engulfing: today's body fully covers yesterday's opposite-colored body
+        if let Some(prev) = prev {
+            let prev_body = (prev.close - prev.open).abs();
+            if prev_body > 0.0 && body > prev_body {
+                let strength = body / prev_body - 1.0;
+
+                if prev.close < prev.open
+                    && bar.close > bar.open
+                    && bar.open <= prev.close
+                    && bar.close >= prev.open
+                {
+                    return Some(make_signal(
+                        SignalType::BullishEngulfing,
+                        SignalDirection::Bullish,
+                        strength,
+                    ));
+                }
+
+                if prev.close > prev.open
+                    && bar.close < bar.open
+                    && bar.open >= prev.close
+                    && bar.close <= prev.open
+                {
+                    return Some(make_signal(
+                        SignalType::BearishEngulfing,
+                        SignalDirection::Bearish,
+                        strength,
+                    ));
+                }
+            }
+        }
+
+        // Hammer / shooting star: small body with one long wick
+        if body_ratio > self.config.doji_body_ratio {
+            let min_wick = body * self.config.hammer_wick_ratio;
+
+            if lower_wick >= min_wick && upper_wick <= body {
+                let strength = lower_wick / (min_wick * 2.0);
+                return Some(make_signal(
+                    SignalType::Hammer,
+                    SignalDirection::Bullish,
+                    strength,
+                ));
+            }
+
+            if upper_wick >= min_wick && lower_wick <= body {
+                let strength = upper_wick / (min_wick * 2.0);
+                return Some(make_signal(
+                    SignalType::ShootingStar,
+                    SignalDirection::Bearish,
+                    strength,
+                ));
+            }
+        }
+
+        // Doji: open and close nearly equal
+        if body_ratio <= self.config.doji_body_ratio {
+            let strength = 1.0 - body_ratio / self.config.doji_body_ratio.max(f64::EPSILON);
+            return Some(make_signal(
+                SignalType::Doji,
+                SignalDirection::Neutral,
+                strength,
+            ));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::calculate_all_with_timeframe;
+
+    fn bar(day: u32, open: f64, high: f64, low: f64, close: f64) -> DailyPrice {
+        DailyPrice {
+            symbol: "TEST".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            open,
+            high,
+            low,
+            close,
+            volume: 1000,
+            adjusted_close: None,
+            source: "test".to_string(),
+        }
+    }
+
+    fn flat_bar(day: u32, close: f64) -> DailyPrice {
+        bar(day, close, close, close, close)
+    }
+
+    fn indicator(day: u32, name: &str, value: f64) -> TechnicalIndicator {
+        TechnicalIndicator {
+            symbol: "TEST".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            indicator_name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_mtf_bullish_signal_unconfirmed_in_weekly_downtrend() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let series = |step: f64| -> Vec<DailyPrice> {
+            (0..210)
+                .map(|i| DailyPrice {
+                    date: start + chrono::Duration::days(i),
+                    ..flat_bar(1, 200.0 + step * i as f64)
+                })
+                .collect()
+        };
+        // Daily RSI drops into oversold on the last bar
+        let last = start + chrono::Duration::days(209);
+        let daily_rsi = vec![
+            TechnicalIndicator {
+                date: last - chrono::Duration::days(1),
+                ..indicator(1, "RSI_14", 45.0)
+            },
+            TechnicalIndicator {
+                date: last,
+                ..indicator(1, "RSI_14", 25.0)
+            },
+        ];
+
+        let engine = SignalEngine::new();
+        let oversold = |prices: &[DailyPrice]| {
+            let weekly = calculate_all_with_timeframe(prices, ResamplePeriod::Weekly);
+            engine
+                .generate_signals_mtf("TEST", &daily_rsi, &weekly, prices)
+                .into_iter()
+                .find(|s| s.signal_type == SignalType::RsiOversold)
+                .unwrap()
+        };
+
+        let falling = oversold(&series(-0.5));
+        assert_eq!(falling.direction, SignalDirection::Bullish);
+        assert_eq!(falling.confirmed, Some(false));
+        assert_eq!(oversold(&series(0.5)).confirmed, Some(true));
+
+        // Plain generation leaves confirmation unchecked
+        let plain = engine.generate_signals("TEST", &daily_rsi, &series(0.5));
+        assert!(plain.iter().all(|s| s.confirmed.is_none()));
+    }
+
+    #[test]
+    fn test_scan_all_symbols_summarizes_latest_signals() {
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+
+        // RSI into oversold, into overbought, and staying neutral on day 2
+        for (symbol, rsi) in [("BULL", 20.0), ("BEAR", 80.0), ("QUIET", 45.0)] {
+            let prices: Vec<DailyPrice> = [flat_bar(1, 100.0), flat_bar(2, 101.0)]
+                .into_iter()
+                .map(|p| DailyPrice {
+                    symbol: symbol.to_string(),
+                    ..p
+                })
+                .collect();
+            let indicators: Vec<TechnicalIndicator> =
+                [indicator(1, "RSI_14", 40.0), indicator(2, "RSI_14", rsi)]
+                    .into_iter()
+                    .map(|i| TechnicalIndicator {
+                        symbol: symbol.to_string(),
+                        ..i
+                    })
+                    .collect();
+            db.upsert_daily_prices(&prices).unwrap();
+            db.upsert_indicators(&indicators).unwrap();
+        }
+
+        let scans = SignalEngine::new().scan_all_symbols(&mut db).unwrap();
+        let order: Vec<&str> = scans.iter().map(|s| s.symbol.as_str()).collect();
+        assert_eq!(order, vec!["BULL", "QUIET", "BEAR"]);
+
+        let summary: Vec<(usize, usize, f64)> = scans
+            .iter()
+            .map(|s| (s.bullish, s.bearish, s.score))
+            .collect();
+        assert_eq!(summary, vec![(1, 0, 1.0), (0, 0, 0.0), (0, 1, -1.0)]);
+
+        for scan in &scans {
+            assert_eq!(
+                db.get_signals(&scan.symbol, false).unwrap().len(),
+                scan.generated
+            );
+        }
+        assert_eq!(scans[0].generated, 1);
+        assert_eq!(scans[2].generated, 1);
+    }
+
+    #[test]
+    fn test_conflicting_signals_reduce_to_strongest() {
+        let prices = vec![flat_bar(1, 100.0), flat_bar(2, 110.0)];
+        let indicators = vec![
+            indicator(1, "RSI_14", 40.0),
+            // RSI drops into oversold: bullish, strength ~0.33
+            indicator(2, "RSI_14", 20.0),
+            // Close far above the upper band: bearish, strength 1.0
+            indicator(2, "BB_UPPER_20", 105.0),
+            indicator(2, "BB_MIDDLE_20", 100.0),
+            indicator(2, "BB_LOWER_20", 95.0),
+        ];
+
+        let all = SignalEngine::new().generate_signals("TEST", &indicators, &prices);
+        assert_eq!(all.len(), 2);
+
+        let engine = SignalEngine::with_config(SignalConfig {
+            conflict_resolution: ConflictResolution::Strongest,
+            ..SignalConfig::default()
+        });
+        let resolved = engine.generate_signals("TEST", &indicators, &prices);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].signal_type, SignalType::BollingerUpperBreak);
+        assert_eq!(resolved[0].direction, SignalDirection::Bearish);
+
+        let engine = SignalEngine::with_config(SignalConfig {
+            conflict_resolution: ConflictResolution::Net,
+            ..SignalConfig::default()
+        });
+        let netted = engine.generate_signals("TEST", &indicators, &prices);
+        assert_eq!(netted.len(), 1);
+        assert_eq!(netted[0].direction, SignalDirection::Bearish);
+        assert!((netted[0].strength - (1.0 - 1.0 / 3.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_score_nets_latest_signals() {
+        let signal = |day: u32, direction: SignalDirection, strength: f64| Signal {
+            id: 0,
+            symbol: "TEST".to_string(),
+            signal_type: SignalType::RsiOversold,
+            direction,
+            strength,
+            price_at_signal: 100.0,
+            triggered_by: "RSI_14".to_string(),
+            trigger_value: 25.0,
+            timestamp: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            created_at: String::new(),
+            acknowledged: false,
+            backfilled: false,
+            confirmed: None,
+        };
+
+        let signals = vec![
+            signal(2, SignalDirection::Bullish, 0.8),
+            signal(2, SignalDirection::Bullish, 0.6),
+            signal(2, SignalDirection::Bullish, 0.4),
+            signal(2, SignalDirection::Bearish, 0.6),
+            signal(2, SignalDirection::Neutral, 1.0),
+            // Older signals are ignored
+            signal(1, SignalDirection::Bearish, 1.0),
+        ];
+
+        let score = SignalEngine::score(&signals);
+        assert!(score > 0.0);
+        assert!((score - 1.2 / 2.4).abs() < 1e-9);
+        assert_eq!(SignalEngine::score(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_configured_ma_periods_detect_golden_cross() {
+        let prices = vec![flat_bar(1, 100.0), flat_bar(2, 103.0)];
+        let indicators = vec![
+            indicator(1, "SMA_10", 99.0),
+            indicator(1, "SMA_30", 100.0),
+            indicator(2, "SMA_10", 101.0),
+            indicator(2, "SMA_30", 100.0),
+        ];
+
+        // The default 20/50 periods never see the crossover
+        let defaults = SignalEngine::new().generate_signals("TEST", &indicators, &prices);
+        assert!(defaults.is_empty());
+
+        let engine = SignalEngine::with_config(SignalConfig {
+            ma_fast_period: 10,
+            ma_slow_period: 30,
+            ..SignalConfig::default()
+        });
+        let signals = engine.generate_signals("TEST", &indicators, &prices);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::MaCrossoverBullish);
+        assert_eq!(signals[0].triggered_by, "SMA_10/30");
+        assert_eq!(signals[0].timestamp, prices[1].date);
+    }
+
+    #[test]
+    fn test_donchian_breakouts_on_uptrend() {
+        // Each close clears the prior bar's high, so every bar with a prior
+        // channel breaks out
+        let prices: Vec<DailyPrice> = (1..=25)
+            .map(|day| {
+                let close = 100.0 + 2.0 * day as f64;
+                bar(day, close - 0.5, close + 1.0, close - 1.0, close)
+            })
+            .collect();
+        let indicators = crate::indicators::calculate_donchian(&prices, 20);
+
+        let breakouts: Vec<Signal> = SignalEngine::new()
+            .generate_signals("TEST", &indicators, &prices)
+            .into_iter()
+            .filter(|s| s.triggered_by.starts_with("DONCHIAN"))
+            .collect();
+
+        assert_eq!(breakouts.len(), 25 - 20);
+        for signal in &breakouts {
+            assert_eq!(signal.signal_type, SignalType::DonchianBreakoutUp);
+            assert_eq!(signal.direction, SignalDirection::Bullish);
+            // One point past a 40-point channel
+            assert!((signal.strength - 1.0 / 40.0).abs() < 1e-9);
+        }
+        assert_eq!(breakouts[0].timestamp, prices[20].date);
+    }
+
+    #[test]
+    fn test_stored_signal_config_changes_signals() {
+        let db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        assert!(db.get_signal_config().unwrap().is_none());
+
+        let custom = SignalConfig {
+            rsi_oversold: 20.0,
+            conflict_resolution: ConflictResolution::Strongest,
+            ..SignalConfig::default()
+        };
+        db.save_signal_config(&custom).unwrap();
+        let loaded = db.get_signal_config().unwrap().unwrap();
+        assert_eq!(loaded.rsi_oversold, 20.0);
+        assert_eq!(loaded.conflict_resolution, ConflictResolution::Strongest);
+        assert_eq!(loaded.rsi_overbought, 70.0);
+
+        // RSI dips to 25: oversold at the default 30, not at the stored 20
+        let prices = vec![flat_bar(1, 100.0), flat_bar(2, 100.0)];
+        let indicators = vec![indicator(1, "RSI_14", 40.0), indicator(2, "RSI_14", 25.0)];
+        let default = SignalEngine::new().generate_signals("TEST", &indicators, &prices);
+        let stored =
+            SignalEngine::with_config(loaded).generate_signals("TEST", &indicators, &prices);
+        assert_eq!(default.len(), 1);
+        assert_eq!(default[0].signal_type, SignalType::RsiOversold);
+        assert!(stored.is_empty());
+
+        // Configs saved before a field existed still load, with its default
+        let partial: SignalConfig = serde_json::from_str(r#"{"rsi_oversold":25.0}"#).unwrap();
+        assert_eq!(partial.rsi_oversold, 25.0);
+        assert_eq!(partial.mfi_overbought, 80.0);
+    }
+
+    #[test]
+    fn test_opposing_signal_auto_acknowledges() {
+        let prices: Vec<DailyPrice> = (1..=4).map(|d| flat_bar(d, 100.0)).collect();
+        let indicators = vec![
+            indicator(1, "RSI_14", 40.0),
+            indicator(2, "RSI_14", 20.0), // bullish: oversold
+            indicator(3, "RSI_14", 50.0),
+            indicator(4, "RSI_14", 80.0), // bearish: overbought
+        ];
+
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+
+        let engine = SignalEngine::with_config(SignalConfig {
+            auto_acknowledge_opposing: true,
+            ..SignalConfig::default()
+        });
+        let signals = engine.generate_signals("TEST", &indicators, &prices);
+        assert_eq!(signals.len(), 2);
+        engine.store_signals(&mut db, &signals).unwrap();
+
+        let stored = db.get_signals("TEST", false).unwrap();
+        let bullish = stored.iter().find(|s| s.signal_type == SignalType::RsiOversold).unwrap();
+        let bearish = stored.iter().find(|s| s.signal_type == SignalType::RsiOverbought).unwrap();
+        assert!(bullish.acknowledged);
+        assert!(!bearish.acknowledged);
+
+        // Default config leaves both open
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        SignalEngine::new().store_signals(&mut db, &signals).unwrap();
+        assert_eq!(db.get_signals("TEST", true).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_backfill_stamps_created_at_with_signal_date() {
+        let prices: Vec<DailyPrice> = (1..=4).map(|d| flat_bar(d, 100.0)).collect();
+        let indicators = vec![
+            indicator(1, "RSI_14", 40.0),
+            indicator(2, "RSI_14", 20.0),
+            indicator(3, "RSI_14", 50.0),
+            indicator(4, "RSI_14", 80.0),
+        ];
+
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+
+        let engine = SignalEngine::with_config(SignalConfig {
+            backfill: true,
+            ..SignalConfig::default()
+        });
+        let signals = engine.generate_signals("TEST", &indicators, &prices);
+        engine.store_signals(&mut db, &signals).unwrap();
+
+        let stored = db.get_signals("TEST", false).unwrap();
+        assert_eq!(stored.len(), 2);
+        for signal in &stored {
+            assert!(signal.backfilled);
+            assert_eq!(signal.created_at, format!("{} 00:00:00", signal.timestamp));
+        }
+
+        // Live generation keeps the storage time
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let engine = SignalEngine::new();
+        let signals = engine.generate_signals("TEST", &indicators, &prices);
+        engine.store_signals(&mut db, &signals).unwrap();
+
+        let today = chrono::Utc::now().date_naive().to_string();
+        for signal in db.get_signals("TEST", false).unwrap() {
+            assert!(!signal.backfilled);
+            assert!(signal.created_at.starts_with(&today));
+        }
+    }
+
+    #[test]
+    fn test_kst_cross_signals() {
+        let prices: Vec<DailyPrice> = (1..=3).map(|d| flat_bar(d, 100.0)).collect();
+        let indicators = vec![
+            indicator(1, "KST", 5.0),
+            indicator(1, "KST_SIGNAL", 6.0),
+            indicator(2, "KST", 7.0), // crosses above
+            indicator(2, "KST_SIGNAL", 6.0),
+            indicator(3, "KST", 4.0), // crosses below
+            indicator(3, "KST_SIGNAL", 6.0),
+        ];
+
+        let signals = SignalEngine::new().generate_signals("TEST", &indicators, &prices);
+        let kst: Vec<_> = signals.iter().filter(|s| s.triggered_by == "KST").collect();
+
+        assert_eq!(kst.len(), 2);
+        assert_eq!(kst[0].signal_type, SignalType::KstBullishCross);
+        assert_eq!(kst[1].signal_type, SignalType::KstBearishCross);
+        assert_eq!(kst[1].direction, SignalDirection::Bearish);
+    }
+
+    #[test]
+    fn test_squeeze_on_then_off_from_volatility_regime() {
+        use crate::indicators::{calculate_bollinger_bands, calculate_keltner_channels};
+
+        // Steady trend (wide bands, small true range), then quiet chop (bands
+        // shrink inside the ATR channel), then a breakout
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let day_bar = |i: usize, close: f64, range: f64| DailyPrice {
+            date: start + chrono::Duration::days(i as i64),
+            ..bar(1, close, close + range, close - range, close)
+        };
+        let mut prices: Vec<DailyPrice> = (0..30)
+            .map(|i| day_bar(i, 70.0 + i as f64, 0.5))
+            .collect();
+        prices.extend((30..60).map(|i| day_bar(i, if i % 2 == 0 { 100.2 } else { 99.8 }, 3.0)));
+        for (i, close) in [104.0, 109.0, 115.0, 122.0, 130.0].into_iter().enumerate() {
+            prices.push(day_bar(60 + i, close, 1.0));
+        }
+
+        let indicators: Vec<TechnicalIndicator> = calculate_bollinger_bands(&prices, 20, 2.0)
+            .into_iter()
+            .chain(calculate_keltner_channels(&prices, 20, 20, 1.5))
+            .collect();
+        let signals = SignalEngine::new().generate_signals("TEST", &indicators, &prices);
+        let squeeze: Vec<_> = signals
+            .iter()
+            .filter(|s| s.triggered_by == "BB_KC_SQUEEZE")
+            .collect();
+
+        assert_eq!(squeeze.len(), 2);
+        assert_eq!(squeeze[0].signal_type, SignalType::SqueezeOn);
+        assert_eq!(squeeze[0].direction, SignalDirection::Neutral);
+        assert!(squeeze[0].timestamp > prices[30].date);
+        assert_eq!(squeeze[1].signal_type, SignalType::SqueezeOff);
+        assert_eq!(squeeze[1].direction, SignalDirection::Bullish);
+        assert!(squeeze[1].timestamp >= prices[60].date);
+        assert!(squeeze.iter().all(|s| s.strength > 0.0 && s.strength <= 1.0));
+    }
+
+    #[test]
+    fn test_divergence_from_price_and_rsi_swings() {
+        // Swing lows at bars 5 (90) and 15 (85); RSI there 25 then 35
+        let closes = [
+            100.0, 98.0, 96.0, 94.0, 92.0, 90.0, 92.0, 94.0, 96.0, 98.0, 100.0, 97.0, 94.0, 91.0,
+            88.0, 85.0, 88.0, 91.0, 94.0, 97.0, 100.0, 102.0,
+        ];
+        let prices: Vec<DailyPrice> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| flat_bar(i as u32 + 1, c))
+            .collect();
+        let rsi = |lows: [f64; 2]| -> Vec<TechnicalIndicator> {
+            (0..closes.len())
+                .map(|i| {
+                    let value = match i {
+                        5 => lows[0],
+                        15 => lows[1],
+                        _ => 50.0,
+                    };
+                    indicator(i as u32 + 1, "RSI_14", value)
+                })
+                .collect()
+        };
+        let engine = SignalEngine::with_config(SignalConfig {
+            pivot_bars: 2,
+            ..SignalConfig::default()
+        });
+
+        let signals = engine.detect_divergence("TEST", &rsi([25.0, 35.0]), &prices);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::BullishDivergence);
+        assert_eq!(signals[0].direction, SignalDirection::Bullish);
+        assert_eq!(signals[0].triggered_by, "RSI_14");
+        assert_eq!(signals[0].trigger_value, 35.0);
+        // Confirmed two bars after the second low
+        assert_eq!(signals[0].timestamp, prices[17].date);
+        assert!(engine
+            .generate_signals("TEST", &rsi([25.0, 35.0]), &prices)
+            .iter()
+            .any(|s| s.signal_type == SignalType::BullishDivergence));
+
+        // RSI confirming the lower low is not a divergence
+        assert!(engine.detect_divergence("TEST", &rsi([35.0, 25.0]), &prices).is_empty());
+
+        // Swings further apart than the lookback aren't compared
+        let short = SignalEngine::with_config(SignalConfig {
+            pivot_bars: 2,
+            divergence_lookback: 5,
+            ..SignalConfig::default()
+        });
+        assert!(short.detect_divergence("TEST", &rsi([25.0, 35.0]), &prices).is_empty());
+
+        // Mirrored series: higher high with a lower MACD histogram high
+        let mirrored: Vec<DailyPrice> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| flat_bar(i as u32 + 1, 200.0 - c))
+            .collect();
+        let hist: Vec<TechnicalIndicator> = rsi([25.0, 35.0])
+            .into_iter()
+            .map(|mut i| {
+                i.indicator_name = "MACD_HIST".to_string();
+                i.value = (50.0 - i.value) / 10.0;
+                i
+            })
+            .collect();
+        let signals = engine.detect_divergence("TEST", &hist, &mirrored);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::BearishDivergence);
+        assert_eq!(signals[0].triggered_by, "MACD_HIST");
+        assert!(signals[0].strength > 0.0 && signals[0].strength <= 1.0);
+    }
+
+    #[test]
+    fn test_bullish_engulfing() {
+        let prices = vec![
+            // Small red bar
+            bar(1, 101.0, 101.5, 99.5, 100.0),
+            // Larger green bar engulfing it
+            bar(2, 99.5, 103.5, 99.0, 103.0),
+        ];
+
+        let engine = SignalEngine::new();
+        let signals = engine.generate_signals("TEST", &[], &prices);
+
+        let engulfing: Vec<_> = signals
+            .iter()
+            .filter(|s| s.signal_type == SignalType::BullishEngulfing)
+            .collect();
+        assert_eq!(engulfing.len(), 1);
+        assert_eq!(engulfing[0].direction, SignalDirection::Bullish);
+        assert_eq!(engulfing[0].timestamp, prices[1].date);
+        assert!(engulfing[0].strength > 0.0 && engulfing[0].strength <= 1.0);
+    }
 }
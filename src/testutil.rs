@@ -0,0 +1,160 @@
+//! Test helpers
+//!
+//! A minimal blocking HTTP server for exercising the fetchers without network access.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Canned response returned by `MockServer`
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub content_type: &'static str,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn ok(body: &str) -> Self {
+        Self::status(200, body)
+    }
+
+    pub fn status(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+            content_type: "application/json",
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request as received by `MockServer`
+pub struct MockRequest {
+    pub method: String,
+    pub target: String,
+    pub body: String,
+}
+
+/// Local HTTP server answering each request via a handler
+pub struct MockServer;
+
+impl MockServer {
+    /// Start a server on an ephemeral port and return its base URL.
+    ///
+    /// The handler receives the request target (path plus query string).
+    /// The server thread runs until the test process exits.
+    pub fn start<F>(handler: F) -> String
+    where
+        F: Fn(&str) -> MockResponse + Send + 'static,
+    {
+        Self::start_with_requests(move |request| handler(&request.target))
+    }
+
+    /// Like `start`, but the handler sees the whole request, including its
+    /// method and body (e.g. to check what a client POSTed)
+    pub fn start_with_requests<F>(handler: F) -> String
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let addr = listener.local_addr().expect("Mock server has no address");
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                serve(stream, &handler);
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    /// Like `start`, but each connection is served on its own thread so
+    /// requests can overlap (e.g. to observe client-side concurrency)
+    pub fn start_concurrent<F>(handler: F) -> String
+    where
+        F: Fn(&str) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let addr = listener.local_addr().expect("Mock server has no address");
+        let handler = Arc::new(handler);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = Arc::clone(&handler);
+                thread::spawn(move || {
+                    serve(stream, &|request: &MockRequest| handler(&request.target))
+                });
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+}
+
+/// Read one request from `stream` and write the handler's response
+fn serve<F>(mut stream: TcpStream, handler: &F)
+where
+    F: Fn(&MockRequest) -> MockResponse,
+{
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+
+    // Request line, then headers until a blank line
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line == "\r\n" || line == "\n" => break,
+            Ok(_) => {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let request = MockRequest {
+        method: parts.next().unwrap_or("GET").to_string(),
+        target: parts.next().unwrap_or("/").to_string(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    let response = handler(&request);
+
+    if !response.delay.is_zero() {
+        thread::sleep(response.delay);
+    }
+
+    let payload = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body
+    );
+    // The client may have hung up already (e.g. after a timeout)
+    let _ = stream.write_all(payload.as_bytes());
+    let _ = stream.flush();
+}
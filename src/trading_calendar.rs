@@ -0,0 +1,125 @@
+//! US equity market trading calendar
+//!
+//! Weekends and NYSE full-day holidays are closed. Holidays follow the
+//! exchange's observance rules: one falling on a Saturday is observed the
+//! Friday before and one on a Sunday the Monday after, except New Year's
+//! Day, which isn't made up when it falls on a Saturday. Early closes are
+//! ordinary trading days, and one-off closures (e.g. national days of
+//! mourning) are not included.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Whether US equity markets are open on `date`
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !is_market_holiday(date)
+}
+
+/// Whether `date` is an observed NYSE full-day holiday
+pub fn is_market_holiday(date: NaiveDate) -> bool {
+    market_holidays(date.year()).contains(&date)
+}
+
+/// Observed NYSE full-day holidays in `year`, in date order
+pub fn market_holidays(year: i32) -> Vec<NaiveDate> {
+    let date = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+
+    let mut holidays = Vec::with_capacity(10);
+    // A Saturday New Year's Day isn't moved back into the previous year
+    let new_year = date(1, 1);
+    match new_year.weekday() {
+        Weekday::Sat => {}
+        Weekday::Sun => holidays.push(new_year + Duration::days(1)),
+        _ => holidays.push(new_year),
+    }
+    holidays.push(nth_weekday(year, 1, Weekday::Mon, 3)); // Martin Luther King Jr. Day
+    holidays.push(nth_weekday(year, 2, Weekday::Mon, 3)); // Washington's Birthday
+    holidays.push(easter_sunday(year) - Duration::days(2)); // Good Friday
+    holidays.push(last_weekday(year, 5, Weekday::Mon)); // Memorial Day
+    if year >= 2022 {
+        holidays.push(observed(date(6, 19))); // Juneteenth
+    }
+    holidays.push(observed(date(7, 4))); // Independence Day
+    holidays.push(nth_weekday(year, 9, Weekday::Mon, 1)); // Labor Day
+    holidays.push(nth_weekday(year, 11, Weekday::Thu, 4)); // Thanksgiving
+    holidays.push(observed(date(12, 25))); // Christmas
+    holidays
+}
+
+/// Saturday holidays are observed on Friday, Sunday ones on Monday
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+/// The `n`th (1-based) `weekday` of a month
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+}
+
+/// The last `weekday` of a month
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+/// Easter Sunday in the Gregorian calendar (anonymous Gregorian algorithm)
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_2023_holidays_excluded_and_weekdays_included() {
+        let day = |month, day| NaiveDate::from_ymd_opt(2023, month, day).unwrap();
+
+        let expected = vec![
+            day(1, 2), // New Year's Day, observed from Sunday
+            day(1, 16),
+            day(2, 20),
+            day(4, 7),
+            day(5, 29),
+            day(6, 19),
+            day(7, 4),
+            day(9, 4),
+            day(11, 23),
+            day(12, 25),
+        ];
+        assert_eq!(market_holidays(2023), expected);
+        assert!(expected.iter().all(|&d| !is_trading_day(d)));
+
+        // An ordinary Wednesday trades; a weekend doesn't
+        assert!(is_trading_day(day(7, 5)));
+        assert!(!is_trading_day(day(7, 8)));
+
+        // Saturday holidays move to Friday, except New Year's Day
+        assert!(is_market_holiday(
+            NaiveDate::from_ymd_opt(2026, 7, 3).unwrap()
+        ));
+        assert!(is_trading_day(
+            NaiveDate::from_ymd_opt(2021, 12, 31).unwrap()
+        ));
+        assert!(is_market_holiday(
+            NaiveDate::from_ymd_opt(2024, 3, 29).unwrap()
+        ));
+    }
+}
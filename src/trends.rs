@@ -9,12 +9,95 @@ use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, REFERER,
 use serde::{Deserialize, Serialize};
 
 use crate::db::Database;
-use crate::error::Result;
+use crate::error::{PipelineError, Result};
+use crate::retry::RetryPolicy;
+
+const DEFAULT_BASE_URL: &str = "https://trends.google.com";
+
+/// Timeframe used when the caller doesn't ask for one
+pub const DEFAULT_TRENDS_TIMEFRAME: &str = "today 12-m";
+
+/// Shorter windows tried after a blocked relative timeframe. The longer
+/// explore flows are blocked most often; these return daily points and tend
+/// to get through.
+const FALLBACK_WINDOWS: [&str; 2] = ["today 3-m", "today 1-m"];
+
+/// Check a Google Trends `time` value
+///
+/// Accepts `all`, relative windows (`now 7-d`, `now 4-H`, `today 3-m`,
+/// `today 5-y`) and explicit ranges (`2020-01-01 2023-01-01`).
+pub fn validate_timeframe(timeframe: &str) -> Result<()> {
+    let invalid = || {
+        PipelineError::Config(format!(
+            "Invalid Google Trends timeframe '{}' (expected e.g. 'today 12-m', 'today 5-y', \
+             'now 7-d', 'all' or 'YYYY-MM-DD YYYY-MM-DD')",
+            timeframe
+        ))
+    };
+
+    if timeframe == "all" {
+        return Ok(());
+    }
+
+    let (first, second) = timeframe.split_once(' ').ok_or_else(invalid)?;
+
+    let relative_units = match first {
+        "now" => "Hd",
+        "today" => "my",
+        _ => {
+            let start = NaiveDate::parse_from_str(first, "%Y-%m-%d").map_err(|_| invalid())?;
+            let end = NaiveDate::parse_from_str(second, "%Y-%m-%d").map_err(|_| invalid())?;
+            if start >= end {
+                return Err(PipelineError::Config(format!(
+                    "Google Trends timeframe '{}' must start before it ends",
+                    timeframe
+                )));
+            }
+            return Ok(());
+        }
+    };
+
+    let (count, unit) = second.split_once('-').ok_or_else(invalid)?;
+    let count_ok = count.parse::<u32>().is_ok_and(|n| n > 0);
+    let unit_ok = unit.len() == 1 && relative_units.contains(unit);
+    if count_ok && unit_ok {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Check a Google Trends `geo` value: empty (worldwide), a two-letter
+/// country code (`US`) or a country-region code (`US-CA`)
+pub fn validate_geo(geo: &str) -> Result<()> {
+    let is_code = |part: &str, max_len: usize| {
+        (1..=max_len).contains(&part.len())
+            && part.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    };
+
+    let valid = match geo.split_once('-') {
+        _ if geo.is_empty() => true,
+        Some((country, region)) => country.len() == 2 && is_code(country, 2) && is_code(region, 3),
+        None => geo.len() == 2 && is_code(geo, 2),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PipelineError::Config(format!(
+            "Invalid Google Trends geo '{}' (expected '' for worldwide, 'US' or 'US-CA')",
+            geo
+        )))
+    }
+}
 
 /// Google Trends data point
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendData {
     pub keyword: String,
+    /// Region the interest is relative to, empty for worldwide
+    #[serde(default)]
+    pub geo: String,
     pub date: NaiveDate,
     pub value: i32, // 0-100 relative interest
 }
@@ -22,6 +105,9 @@ pub struct TrendData {
 /// Google Trends fetcher
 pub struct GoogleTrends {
     client: Client,
+    base_url: String,
+    request_delay: std::time::Duration,
+    retry: RetryPolicy,
 }
 
 impl GoogleTrends {
@@ -41,37 +127,101 @@ impl GoogleTrends {
             .build()
             .unwrap_or_else(|_| Client::new());
 
-        Self { client }
+        Self {
+            client,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            request_delay: std::time::Duration::from_millis(500),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Override the base URL (e.g. for a local test server)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Pause between requests in one fetch (default 500ms)
+    pub fn with_request_delay(mut self, delay: std::time::Duration) -> Self {
+        self.request_delay = delay;
+        self
+    }
+
+    /// Retry 429, 5xx and connection failures up to `retries` times (default 2)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retry.max_retries = retries;
+        self
+    }
+
+    /// Backoff before the first retry, doubled for each one after (default 500ms)
+    pub fn with_retry_delay(mut self, delay: std::time::Duration) -> Self {
+        self.retry.base_delay = delay;
+        self
     }
 
     /// Fetch trends data for a keyword (symbol or company name)
     /// Returns interest over time data (0-100 scale)
-    pub fn fetch(&self, keyword: &str) -> Result<Vec<TrendData>> {
+    ///
+    /// `timeframe` is a Google Trends `time` value (see [`validate_timeframe`])
+    /// and `geo` a region code, empty for worldwide. When a relative timeframe
+    /// such as `today 12-m` is blocked or garbled, shorter daily windows are
+    /// tried next; an explicit date range is only tried as given. Returns
+    /// `PipelineError::RateLimited` only once every window has failed.
+    pub fn fetch(&self, keyword: &str, timeframe: &str, geo: &str) -> Result<Vec<TrendData>> {
+        validate_timeframe(timeframe)?;
+        validate_geo(geo)?;
+
+        let mut windows = vec![timeframe];
+        if timeframe.starts_with("today ") || timeframe == "all" {
+            windows.extend(FALLBACK_WINDOWS.iter().filter(|w| **w != timeframe));
+        }
+
+        let mut failures = Vec::new();
+
+        for window in windows {
+            match self.fetch_window(keyword, window, geo) {
+                Ok(data) => return Ok(data),
+                Err(PipelineError::ApiError(msg)) => {
+                    println!("[WARN] Google Trends '{}' failed: {}", window, msg);
+                    failures.push(format!("{}: {}", window, msg));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(PipelineError::RateLimited(format!(
+            "Google Trends blocked every fallback for {} ({})",
+            keyword,
+            failures.join("; ")
+        )))
+    }
+
+    /// Run the explore + widget data flow for one time window
+    fn fetch_window(&self, keyword: &str, window: &str, geo: &str) -> Result<Vec<TrendData>> {
         // First, visit the main trends page to get cookies
         let _homepage = self.client
-            .get("https://trends.google.com/trends/")
+            .get(format!("{}/trends/", self.base_url))
             .send();
 
         // Small delay to appear more human-like
-        std::thread::sleep(std::time::Duration::from_millis(500));
+        std::thread::sleep(self.request_delay);
 
         // Build the explore URL with proper encoding
-        let req_json = format!(
-            r#"{{"comparisonItem":[{{"keyword":"{}","geo":"","time":"today 12-m"}}],"category":0,"property":""}}"#,
-            keyword
-        );
+        let req_json = explore_request(keyword, window, geo);
 
         let explore_url = format!(
-            "https://trends.google.com/trends/api/explore?hl=en-US&tz=360&req={}",
+            "{}/trends/api/explore?hl=en-US&tz=360&req={}",
+            self.base_url,
             urlencoding::encode(&req_json)
         );
 
         // Get the explore page to extract tokens
-        let explore_resp = self.client
-            .get(&explore_url)
-            .header(REFERER, "https://trends.google.com/trends/explore")
-            .header(ACCEPT, "application/json, text/plain, */*")
-            .send()?;
+        let explore_resp = self.retry.send(|| {
+            self.client
+                .get(&explore_url)
+                .header(REFERER, "https://trends.google.com/trends/explore")
+                .header(ACCEPT, "application/json, text/plain, */*")
+        })?;
 
         let status = explore_resp.status();
         let explore_text = explore_resp.text()?;
@@ -116,20 +266,22 @@ impl GoogleTrends {
                 "No TIMESERIES widget found in response".to_string()
             ))?;
 
-        std::thread::sleep(std::time::Duration::from_millis(300));
+        std::thread::sleep(self.request_delay);
 
         // Fetch the actual trend data using the token
         let multiline_url = format!(
-            "https://trends.google.com/trends/api/widgetdata/multiline?hl=en-US&tz=360&req={}&token={}",
+            "{}/trends/api/widgetdata/multiline?hl=en-US&tz=360&req={}&token={}",
+            self.base_url,
             urlencoding::encode(&timeseries_widget.request),
             urlencoding::encode(&timeseries_widget.token)
         );
 
-        let data_resp = self.client
-            .get(&multiline_url)
-            .header(REFERER, "https://trends.google.com/trends/explore")
-            .header(ACCEPT, "application/json, text/plain, */*")
-            .send()?;
+        let data_resp = self.retry.send(|| {
+            self.client
+                .get(&multiline_url)
+                .header(REFERER, "https://trends.google.com/trends/explore")
+                .header(ACCEPT, "application/json, text/plain, */*")
+        })?;
 
         let data_status = data_resp.status();
         let data_text = data_resp.text()?;
@@ -144,7 +296,8 @@ impl GoogleTrends {
             ));
         }
 
-        let data_json = data_text.trim_start_matches(")]}'").trim();
+        // The widget endpoint prefixes ")]}'," (note the trailing comma)
+        let data_json = data_text.trim_start_matches(")]}'").trim_start_matches(',').trim();
 
         let trend_response: TrendResponse = serde_json::from_str(data_json)
             .map_err(|e| crate::error::PipelineError::ApiError(
@@ -167,6 +320,7 @@ impl GoogleTrends {
 
                         results.push(TrendData {
                             keyword: keyword.to_string(),
+                            geo: geo.to_string(),
                             date,
                             value,
                         });
@@ -186,14 +340,32 @@ impl GoogleTrends {
     }
 
     /// Fetch and store trends data
-    pub fn fetch_and_store(&self, db: &mut Database, keyword: &str) -> Result<usize> {
-        let data = self.fetch(keyword)?;
+    ///
+    /// Each `geo` is kept as its own series, so a regional fetch never
+    /// replaces the worldwide one.
+    pub fn fetch_and_store(
+        &self,
+        db: &mut Database,
+        keyword: &str,
+        timeframe: &str,
+        geo: &str,
+    ) -> Result<usize> {
+        let data = self.fetch(keyword, timeframe, geo)?;
         let count = data.len();
         db.upsert_trends(&data)?;
+        db.log_api_call("google_trends", "interest_over_time", keyword)?;
         Ok(count)
     }
 }
 
+/// Build the `req` JSON for the explore endpoint
+fn explore_request(keyword: &str, timeframe: &str, geo: &str) -> String {
+    format!(
+        r#"{{"comparisonItem":[{{"keyword":"{}","geo":"{}","time":"{}"}}],"category":0,"property":""}}"#,
+        keyword, geo, timeframe
+    )
+}
+
 impl Default for GoogleTrends {
     fn default() -> Self {
         Self::new()
@@ -270,3 +442,105 @@ struct TimelinePoint {
     time: Option<String>,
     value: Option<Vec<i32>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{MockResponse, MockServer};
+    use std::time::Duration;
+
+    const EXPLORE_OK: &str = r#")]}'
+{"widgets":[{"id":"TIMESERIES","token":"tok","request":{"time":"today 3-m"}}]}"#;
+    const MULTILINE_OK: &str = r#")]}',
+{"default":{"timelineData":[{"time":"1704067200","value":[42]},{"time":"1704153600","value":[55]}]}}"#;
+
+    #[test]
+    fn test_falls_back_when_12_month_is_blocked() {
+        let base_url = MockServer::start(|target| {
+            if target.contains("/api/explore") {
+                if target.contains(&*urlencoding::encode("today 12-m")) {
+                    MockResponse::ok("<!DOCTYPE html><html>captcha</html>")
+                } else {
+                    MockResponse::ok(EXPLORE_OK)
+                }
+            } else if target.contains("/api/widgetdata/multiline") {
+                MockResponse::ok(MULTILINE_OK)
+            } else {
+                MockResponse::ok("")
+            }
+        });
+
+        let trends = GoogleTrends::new()
+            .with_base_url(&base_url)
+            .with_request_delay(Duration::ZERO);
+        let data = trends.fetch("AAPL", DEFAULT_TRENDS_TIMEFRAME, "").unwrap();
+
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].value, 42);
+        assert_eq!(data[0].date, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn test_rate_limited_after_all_fallbacks() {
+        let base_url =
+            MockServer::start(|_| MockResponse::ok("<!DOCTYPE html><html>captcha</html>"));
+
+        let trends = GoogleTrends::new()
+            .with_base_url(&base_url)
+            .with_request_delay(Duration::ZERO);
+        let err = trends.fetch("AAPL", DEFAULT_TRENDS_TIMEFRAME, "").unwrap_err();
+
+        assert!(matches!(err, PipelineError::RateLimited(_)));
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_request_carries_timeframe_and_geo() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let base_url = MockServer::start(move |target| {
+            if target.contains("/api/explore") {
+                recorder.lock().unwrap().push(target.to_string());
+                MockResponse::ok(EXPLORE_OK)
+            } else if target.contains("/api/widgetdata/multiline") {
+                MockResponse::ok(MULTILINE_OK)
+            } else {
+                MockResponse::ok("")
+            }
+        });
+
+        let trends = GoogleTrends::new()
+            .with_base_url(&base_url)
+            .with_request_delay(Duration::ZERO);
+        trends.fetch("AAPL", "2020-01-01 2023-01-01", "US").unwrap();
+
+        let explore = seen.lock().unwrap();
+        assert_eq!(explore.len(), 1);
+        let expected = explore_request("AAPL", "2020-01-01 2023-01-01", "US");
+        assert!(expected.contains(r#""geo":"US","time":"2020-01-01 2023-01-01""#));
+        assert!(explore[0].contains(&*urlencoding::encode(&expected)));
+    }
+
+    #[test]
+    fn test_rejects_bad_timeframe_and_geo() {
+        let valid = ["today 5-y", "today 12-m", "now 7-d", "now 4-H", "all", "2020-01-01 2023-01-01"];
+        for ok in valid {
+            assert!(validate_timeframe(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "today", "today 5-x", "now 0-d", "2023-01-01 2020-01-01", "last year"] {
+            assert!(matches!(validate_timeframe(bad), Err(PipelineError::Config(_))), "{}", bad);
+        }
+
+        for ok in ["", "US", "GB", "US-CA"] {
+            assert!(validate_geo(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["us", "USA", "U", "US-", "\"US\""] {
+            assert!(matches!(validate_geo(bad), Err(PipelineError::Config(_))), "{}", bad);
+        }
+
+        // Rejected before any request goes out
+        let trends = GoogleTrends::new().with_base_url("http://127.0.0.1:9");
+        let err = trends.fetch("AAPL", "today 5-x", "").unwrap_err();
+        assert!(matches!(err, PipelineError::Config(_)));
+    }
+}
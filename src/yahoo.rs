@@ -3,17 +3,163 @@
 //! Uses Yahoo Finance's public API to fetch stock price data.
 //! FREE and UNLIMITED - no API key required!
 
-use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
 use reqwest::blocking::Client;
 
 use crate::db::Database;
 use crate::error::{PipelineError, Result};
-use crate::models::yahoo::ChartResponse;
-use crate::models::DailyPrice;
+use crate::models::yahoo::{ChartResponse, ChartResult, QuoteSummaryResponse};
+use crate::models::{DailyPrice, Dividend, IntradayPrice, Split, Symbol};
+use crate::retry::RetryPolicy;
+
+const DEFAULT_BASE_URL: &str = "https://query1.finance.yahoo.com";
+
+/// Intervals shorter than a day; only these can include pre/post-market bars
+const INTRADAY_INTERVALS: &[&str] = &["1m", "2m", "5m", "15m", "30m", "60m", "90m", "1h"];
+
+/// Intervals accepted by `fetch_and_store`, with the ranges Yahoo serves for
+/// each (1m data only goes back 7 days, other minute bars 60 days, hourly 730)
+const INTERVAL_RANGES: &[(&str, &[&str])] = &[
+    ("1m", &["1d", "5d"]),
+    ("5m", &["1d", "5d", "1mo"]),
+    ("15m", &["1d", "5d", "1mo"]),
+    ("30m", &["1d", "5d", "1mo"]),
+    ("1h", &["1d", "5d", "1mo", "3mo", "6mo", "ytd", "1y", "2y"]),
+    (
+        "1d",
+        &["1d", "5d", "1mo", "3mo", "6mo", "ytd", "1y", "2y", "5y", "10y", "max"],
+    ),
+];
+
+/// Check that Yahoo serves `interval` bars over `period`
+pub fn validate_interval(interval: &str, period: &str) -> Result<()> {
+    let Some((_, ranges)) = INTERVAL_RANGES.iter().find(|(i, _)| *i == interval) else {
+        let allowed: Vec<&str> = INTERVAL_RANGES.iter().map(|(i, _)| *i).collect();
+        return Err(PipelineError::Config(format!(
+            "Unsupported interval {} (expected one of {})",
+            interval,
+            allowed.join(", ")
+        )));
+    };
+
+    if !ranges.contains(&period) {
+        return Err(PipelineError::Config(format!(
+            "Interval {} is only available for periods {}, not {}",
+            interval,
+            ranges.join(", "),
+            period
+        )));
+    }
+
+    Ok(())
+}
+
+/// Shortest daily range period reaching back more than `days_back` days
+fn period_covering(days_back: i64) -> &'static str {
+    [
+        (30, "1mo"),
+        (90, "3mo"),
+        (180, "6mo"),
+        (365, "1y"),
+        (730, "2y"),
+        (1825, "5y"),
+        (3650, "10y"),
+    ]
+    .iter()
+    .find(|(days, _)| days_back < *days)
+    .map_or("max", |(_, period)| *period)
+}
+
+/// Range period that reaches back past the oldest last bar of `symbols`
+///
+/// Used to bring stale symbols up to date without clearing them; bars
+/// already stored are simply upserted again.
+pub fn catch_up_period(db: &Database, symbols: &[String]) -> Result<&'static str> {
+    let today = Utc::now().date_naive();
+    let mut oldest = today;
+    for symbol in symbols {
+        if let Some(last) = db.get_last_price_date(symbol)? {
+            oldest = oldest.min(last);
+        }
+    }
+    Ok(period_covering((today - oldest).num_days()))
+}
+
+/// Convert a canonical ticker to the form Yahoo Finance expects in URLs
+///
+/// Share classes written with a dot (`BRK.B`, `BF.A`) use a dash on Yahoo
+/// (`BRK-B`, `BF-A`). Only single-letter A/B/C suffixes are treated as share
+/// classes, since other dotted suffixes are Yahoo exchange codes (`VOD.L`,
+/// `SHOP.TO`) and must be kept. The result is uppercased and trimmed; callers
+/// should keep storing the canonical symbol.
+pub fn normalize_symbol_for_yahoo(symbol: &str) -> String {
+    let symbol = symbol.trim().to_uppercase();
+
+    if let Some((base, suffix)) = symbol.rsplit_once('.') {
+        if !base.is_empty() && matches!(suffix, "A" | "B" | "C") {
+            return format!("{}-{}", base, suffix);
+        }
+    }
+
+    symbol
+}
+
+/// Default number of concurrent requests made by `fetch_batch`
+const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+/// Default minimum gap between request starts in `fetch_batch`
+const DEFAULT_MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bars fetched for one symbol, ready to store
+enum FetchedBars {
+    Daily(Vec<DailyPrice>),
+    Intraday(Vec<IntradayPrice>),
+}
+
+/// Spaces out request starts across worker threads
+struct RequestPacer {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RequestPacer {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Block until this caller's request slot comes up
+    fn wait(&self) {
+        let delay = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + self.interval;
+            slot - now
+        };
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+    }
+}
 
 /// Yahoo Finance API client
 pub struct YahooFinance {
     client: Client,
+    base_url: String,
+    retain_years: Option<u32>,
+    adjusted: bool,
+    include_prepost: bool,
+    max_in_flight: usize,
+    min_request_interval: Duration,
+    retry: RetryPolicy,
 }
 
 impl Default for YahooFinance {
@@ -26,11 +172,94 @@ impl YahooFinance {
     /// Create a new Yahoo Finance client
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
-                .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-                .build()
-                .expect("Failed to create HTTP client"),
+            client: Self::build_client(None),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            retain_years: None,
+            adjusted: false,
+            include_prepost: false,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            min_request_interval: DEFAULT_MIN_REQUEST_INTERVAL,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Set a request timeout; requests exceeding it fail with `PipelineError::Timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = Self::build_client(Some(timeout));
+        self
+    }
+
+    /// Override the API base URL (e.g. for a proxy or a local test server)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// After each store, drop a symbol's bars (and derived indicators and
+    /// signals) older than this many years. `None` keeps everything.
+    pub fn with_retain_years(mut self, retain_years: Option<u32>) -> Self {
+        self.retain_years = retain_years;
+        self
+    }
+
+    /// Return (and store) split/dividend-adjusted OHLC instead of raw prices.
+    /// `adjusted_close` is populated either way when Yahoo provides it.
+    pub fn with_adjusted_prices(mut self, adjusted: bool) -> Self {
+        self.adjusted = adjusted;
+        self
+    }
+
+    /// Maximum concurrent requests in `fetch_batch` (default 4, minimum 1)
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Minimum time between request starts in `fetch_batch` (default 250ms)
+    pub fn with_min_request_interval(mut self, interval: Duration) -> Self {
+        self.min_request_interval = interval;
+        self
+    }
+
+    /// Retry 429, 5xx and connection failures up to `retries` times (default 2)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retry.max_retries = retries;
+        self
+    }
+
+    /// Backoff before the first retry, doubled for each one after (default 500ms)
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry.base_delay = delay;
+        self
+    }
+
+    /// Ask Yahoo for pre- and post-market bars (`includePrePost=true`)
+    ///
+    /// Only meaningful for intraday intervals: requesting a daily or longer
+    /// interval with this enabled fails with `PipelineError::Config`.
+    pub fn with_include_prepost(mut self, include_prepost: bool) -> Self {
+        self.include_prepost = include_prepost;
+        self
+    }
+
+    /// Chart API URL for a symbol, interval and range
+    fn chart_url(&self, symbol: &str, interval: &str, period: &str) -> Result<String> {
+        chart_url(
+            &self.base_url,
+            symbol,
+            interval,
+            period,
+            self.include_prepost,
+        )
+    }
+
+    fn build_client(timeout: Option<Duration>) -> Client {
+        let mut builder = Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36");
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
         }
+        builder.build().expect("Failed to create HTTP client")
     }
 
     /// Fetch daily prices for a symbol
@@ -40,20 +269,60 @@ impl YahooFinance {
     /// * `period` - Time period: "1d", "5d", "1mo", "3mo", "6mo", "1y", "2y", "5y", "10y", "ytd", "max"
     ///
     /// # Returns
-    /// Vector of daily price records
+    /// Vector of daily price records, adjusted if `with_adjusted_prices(true)`
     pub fn fetch_prices(&self, symbol: &str, period: &str) -> Result<Vec<DailyPrice>> {
+        let bars = self.fetch_chart(symbol, "1d", period)?;
+        Ok(bars.into_iter().map(|(_, bar)| bar).collect())
+    }
+
+    /// Fetch intraday bars for a symbol
+    ///
+    /// # Arguments
+    /// * `symbol` - Stock ticker symbol
+    /// * `interval` - Bar size: "1m", "5m", "15m", "30m" or "1h"
+    /// * `period` - Time period; must be one Yahoo serves for the interval (see `validate_interval`)
+    pub fn fetch_intraday(
+        &self,
+        symbol: &str,
+        interval: &str,
+        period: &str,
+    ) -> Result<Vec<IntradayPrice>> {
+        if interval == "1d" {
+            return Err(PipelineError::Config(
+                "Use fetch_prices for daily bars".to_string(),
+            ));
+        }
+        validate_interval(interval, period)?;
+
+        let bars = self.fetch_chart(symbol, interval, period)?;
+        Ok(intraday_bars(bars, interval))
+    }
+
+    /// Request a chart and parse its bars, keeping each bar's full timestamp
+    fn fetch_chart(
+        &self,
+        symbol: &str,
+        interval: &str,
+        period: &str,
+    ) -> Result<Vec<(DateTime<Utc>, DailyPrice)>> {
         println!(
-            "[FETCH] Fetching {} from Yahoo Finance (period: {})...",
-            symbol, period
+            "[FETCH] Fetching {} from Yahoo Finance (interval: {}, period: {})...",
+            symbol, interval, period
         );
 
         // Yahoo Finance API endpoint
-        let url = format!(
-            "https://query1.finance.yahoo.com/v8/finance/chart/{}?interval=1d&range={}",
-            symbol, period
-        );
+        let url = self.chart_url(symbol, interval, period)?;
+        let data = self.request_chart(&url, symbol)?;
+
+        let prices = chart_bars(&data, symbol, self.adjusted)?;
 
-        let response = self.client.get(&url).send()?;
+        println!("[OK] Fetched {} records for {}", prices.len(), symbol);
+        Ok(prices)
+    }
+
+    /// Request a chart URL and return its (single) result
+    fn request_chart(&self, url: &str, symbol: &str) -> Result<ChartResult> {
+        let response = self.retry.send(|| self.client.get(url))?;
 
         if !response.status().is_success() {
             return Err(PipelineError::NoData(format!(
@@ -63,95 +332,178 @@ impl YahooFinance {
             )));
         }
 
-        let chart_response: ChartResponse = response.json()?;
+        chart_result(response.json()?, symbol)
+    }
 
-        // Check for API errors
-        if let Some(chart) = &chart_response.chart.result {
-            if chart.is_empty() {
-                return Err(PipelineError::NoData(symbol.to_string()));
-            }
-        } else if let Some(err) = &chart_response.chart.error {
+    /// Fetch dividends and splits for a symbol over a period
+    ///
+    /// Uses the chart API with `events=div|split`; both lists are sorted by
+    /// date and empty when nothing happened in the period.
+    pub fn fetch_events(&self, symbol: &str, period: &str) -> Result<(Vec<Dividend>, Vec<Split>)> {
+        println!("[FETCH] Fetching dividends and splits for {} ({})...", symbol, period);
+
+        let url = format!("{}&events=div%7Csplit", self.chart_url(symbol, "1d", period)?);
+        let events = self.request_chart(&url, symbol)?.events.unwrap_or_default();
+        let date_of = |ts: i64| DateTime::from_timestamp(ts, 0).map(|dt| dt.date_naive());
+
+        let mut dividends: Vec<Dividend> = events
+            .dividends
+            .into_values()
+            .filter_map(|d| {
+                Some(Dividend {
+                    symbol: symbol.to_string(),
+                    ex_date: date_of(d.date)?,
+                    amount: d.amount,
+                    source: "yahoo_finance".to_string(),
+                })
+            })
+            .collect();
+        dividends.sort_by_key(|d| d.ex_date);
+
+        let mut splits: Vec<Split> = events
+            .splits
+            .into_values()
+            .filter_map(|s| {
+                Some(Split {
+                    symbol: symbol.to_string(),
+                    date: date_of(s.date)?,
+                    numerator: s.numerator,
+                    denominator: s.denominator,
+                    source: "yahoo_finance".to_string(),
+                })
+            })
+            .collect();
+        splits.sort_by_key(|s| s.date);
+
+        println!(
+            "[OK] Fetched {} dividends and {} splits for {}",
+            dividends.len(),
+            splits.len(),
+            symbol
+        );
+        Ok((dividends, splits))
+    }
+
+    /// Fetch and store dividends and splits; returns (dividends, splits) stored
+    pub fn fetch_and_store_events(
+        &self,
+        db: &mut Database,
+        symbol: &str,
+        period: &str,
+    ) -> Result<(usize, usize)> {
+        let (dividends, splits) = self.fetch_events(symbol, period)?;
+        let dividend_count = db.upsert_dividends(&dividends)?;
+        let split_count = db.upsert_splits(&splits)?;
+        db.log_api_call("yahoo_finance", "events", symbol)?;
+        Ok((dividend_count, split_count))
+    }
+
+    /// Fetch a symbol's name, sector, industry, market cap and listing details
+    ///
+    /// Uses the quoteSummary API's `assetProfile` and `price` modules. Funds
+    /// and ETFs have no asset profile, so their sector, industry and country
+    /// stay `None`; only a response with neither module is an error.
+    pub fn fetch_profile(&self, symbol: &str) -> Result<Symbol> {
+        println!("[FETCH] Fetching profile for {} from Yahoo...", symbol);
+
+        let url = profile_url(&self.base_url, symbol);
+        let response = self.retry.send(|| self.client.get(&url))?;
+
+        if !response.status().is_success() {
             return Err(PipelineError::NoData(format!(
-                "{}: {}",
-                err.code, err.description
+                "HTTP {} for {} profile",
+                response.status(),
+                symbol
             )));
         }
 
-        let result = chart_response
-            .chart
-            .result
-            .ok_or_else(|| PipelineError::NoData(symbol.to_string()))?;
-
-        let data = &result[0];
-        let timestamps = data
-            .timestamp
-            .as_ref()
-            .ok_or_else(|| PipelineError::NoData(symbol.to_string()))?;
-
-        let quote = &data.indicators.quote[0];
-
-        let mut prices = Vec::with_capacity(timestamps.len());
-
-        for (i, &ts) in timestamps.iter().enumerate() {
-            // Skip if any value is None
-            let open = match quote.open.get(i).and_then(|v| *v) {
-                Some(v) => v,
-                None => continue,
-            };
-            let high = match quote.high.get(i).and_then(|v| *v) {
-                Some(v) => v,
-                None => continue,
-            };
-            let low = match quote.low.get(i).and_then(|v| *v) {
-                Some(v) => v,
-                None => continue,
-            };
-            let close = match quote.close.get(i).and_then(|v| *v) {
-                Some(v) => v,
-                None => continue,
-            };
-            let volume = quote.volume.get(i).and_then(|v| *v).unwrap_or(0);
-
-            // Convert Unix timestamp to date
-            let datetime = DateTime::from_timestamp(ts, 0)
-                .unwrap_or_else(|| Utc::now());
-            let date = datetime.date_naive();
-
-            prices.push(DailyPrice {
-                symbol: symbol.to_string(),
-                date,
-                open,
-                high,
-                low,
-                close,
-                volume,
-                source: "yahoo_finance".to_string(),
-            });
-        }
-
-        println!("[OK] Fetched {} records for {}", prices.len(), symbol);
-        Ok(prices)
+        let profile = profile_symbol(response.json()?, symbol)?;
+        println!("[OK] Fetched profile for {}", symbol);
+        Ok(profile)
     }
 
     /// Fetch and store prices directly to database
+    ///
+    /// Daily ("1d") bars go to `daily_prices`; intraday intervals are stored
+    /// separately in `intraday_prices` with their full timestamps. Symbols
+    /// without stored metadata are also enriched via `fetch_profile`; a
+    /// failed profile lookup is only warned about.
     pub fn fetch_and_store(
         &self,
         db: &mut Database,
         symbol: &str,
+        interval: &str,
         period: &str,
     ) -> Result<usize> {
-        let prices = self.fetch_prices(symbol, period)?;
+        let bars = self.fetch_bars(symbol, interval, period)?;
+        let count = store_bars(db, symbol, interval, bars, self.retain_years)?;
+
+        if db.get_symbol(symbol)?.and_then(|s| s.name).is_none() {
+            match self.fetch_profile(symbol) {
+                Ok(profile) => {
+                    db.upsert_symbol(&profile)?;
+                    db.log_api_call("yahoo_finance", "profile", symbol)?;
+                }
+                Err(e) => println!("[WARN] No profile stored for {}: {}", symbol, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Network half of `fetch_and_store`; safe to call from worker threads
+    fn fetch_bars(&self, symbol: &str, interval: &str, period: &str) -> Result<FetchedBars> {
+        validate_interval(interval, period)?;
+
+        if interval == "1d" {
+            Ok(FetchedBars::Daily(self.fetch_prices(symbol, period)?))
+        } else {
+            Ok(FetchedBars::Intraday(self.fetch_intraday(symbol, interval, period)?))
+        }
+    }
+
+    /// Refetch daily bars for the weekday gaps `Database::find_price_gaps` reports
+    ///
+    /// Yahoo's chart API takes a range ending today, so this requests the
+    /// shortest range reaching back to the oldest gap and stores only the bars
+    /// that fall inside a gap. Returns the number of bars filled; days Yahoo
+    /// has no bar for (e.g. holidays) stay missing.
+    pub fn fill_gaps(&self, db: &mut Database, symbol: &str) -> Result<usize> {
+        let gaps = db.find_price_gaps(symbol)?;
+        let Some(&(oldest, _)) = gaps.first() else {
+            return Ok(0);
+        };
+
+        let period = period_covering((Utc::now().date_naive() - oldest).num_days());
+
+        let in_gap = |date: NaiveDate| gaps.iter().any(|&(start, end)| (start..=end).contains(&date));
+        let prices: Vec<DailyPrice> = self
+            .fetch_prices(symbol, period)?
+            .into_iter()
+            .filter(|p| in_gap(p.date))
+            .collect();
+
         let count = db.upsert_daily_prices(&prices)?;
         db.log_api_call("yahoo_finance", "history", symbol)?;
-        println!("[OK] Stored {} records for {}", count, symbol);
+        println!(
+            "[OK] Filled {} bar(s) across {} gap(s) for {}",
+            count,
+            gaps.len(),
+            symbol
+        );
         Ok(count)
     }
 
     /// Batch fetch multiple symbols
+    ///
+    /// Up to `max_in_flight` requests run concurrently on worker threads,
+    /// with request starts spaced by `min_request_interval`. Results are
+    /// written to the database one at a time on the calling thread.
     pub fn fetch_batch(
         &self,
         db: &mut Database,
         symbols: &[String],
+        interval: &str,
         period: &str,
     ) -> Result<(usize, usize)> {
         println!(
@@ -164,20 +516,46 @@ impl YahooFinance {
         let mut success_count = 0;
         let mut fail_count = 0;
 
-        for (i, symbol) in symbols.iter().enumerate() {
-            print!("\n[{}/{}] {}... ", i + 1, symbols.len(), symbol);
+        let pacer = RequestPacer::new(self.min_request_interval);
+        let next_index = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::channel();
 
-            match self.fetch_and_store(db, symbol, period) {
-                Ok(_) => {
-                    success_count += 1;
-                    println!("[OK]");
-                }
-                Err(e) => {
-                    fail_count += 1;
-                    println!("[FAIL] {}", e);
+        thread::scope(|scope| {
+            for _ in 0..self.max_in_flight.min(symbols.len()) {
+                let tx = tx.clone();
+                let (pacer, next_index) = (&pacer, &next_index);
+                scope.spawn(move || loop {
+                    let i = next_index.fetch_add(1, Ordering::SeqCst);
+                    let Some(symbol) = symbols.get(i) else {
+                        break;
+                    };
+                    pacer.wait();
+                    let fetched = self.fetch_bars(symbol, interval, period);
+                    if tx.send((symbol, fetched)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(tx);
+
+            // Database writes stay on this thread
+            for (done, (symbol, fetched)) in rx.iter().enumerate() {
+                print!("\n[{}/{}] {}... ", done + 1, symbols.len(), symbol);
+
+                match fetched
+                    .and_then(|bars| store_bars(db, symbol, interval, bars, self.retain_years))
+                {
+                    Ok(_) => {
+                        success_count += 1;
+                        println!("[OK]");
+                    }
+                    Err(e) => {
+                        fail_count += 1;
+                        println!("[FAIL] {}", e);
+                    }
                 }
             }
-        }
+        });
 
         println!("\n{}", "=".repeat(60));
         println!("[OK] Batch fetch complete!");
@@ -188,9 +566,419 @@ impl YahooFinance {
     }
 }
 
+/// Non-blocking Yahoo Finance client for use from async code (e.g. Tauri commands)
+///
+/// Mirrors `YahooFinance` on top of `reqwest::Client`, so network calls don't
+/// tie up a thread. Parsing and storage are shared with the blocking client.
+#[cfg(feature = "async")]
+pub struct AsyncYahooFinance {
+    client: reqwest::Client,
+    base_url: String,
+    retain_years: Option<u32>,
+    adjusted: bool,
+    include_prepost: bool,
+    retry: RetryPolicy,
+}
+
+#[cfg(feature = "async")]
+impl Default for AsyncYahooFinance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncYahooFinance {
+    /// Create a new async Yahoo Finance client
+    pub fn new() -> Self {
+        Self {
+            client: Self::build_client(None),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            retain_years: None,
+            adjusted: false,
+            include_prepost: false,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Set a request timeout; requests exceeding it fail with `PipelineError::Timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = Self::build_client(Some(timeout));
+        self
+    }
+
+    /// Override the API base URL (e.g. for a proxy or a local test server)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// See `YahooFinance::with_retain_years`
+    pub fn with_retain_years(mut self, retain_years: Option<u32>) -> Self {
+        self.retain_years = retain_years;
+        self
+    }
+
+    /// See `YahooFinance::with_adjusted_prices`
+    pub fn with_adjusted_prices(mut self, adjusted: bool) -> Self {
+        self.adjusted = adjusted;
+        self
+    }
+
+    /// See `YahooFinance::with_include_prepost`
+    pub fn with_include_prepost(mut self, include_prepost: bool) -> Self {
+        self.include_prepost = include_prepost;
+        self
+    }
+
+    /// Retry 429, 5xx and connection failures up to `retries` times (default 2)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retry.max_retries = retries;
+        self
+    }
+
+    /// Backoff before the first retry, doubled for each one after (default 500ms)
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry.base_delay = delay;
+        self
+    }
+
+    fn build_client(timeout: Option<Duration>) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36");
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder.build().expect("Failed to create HTTP client")
+    }
+
+    /// Fetch daily prices for a symbol; see `YahooFinance::fetch_prices`
+    pub async fn fetch_prices(&self, symbol: &str, period: &str) -> Result<Vec<DailyPrice>> {
+        let bars = self.fetch_chart(symbol, "1d", period).await?;
+        Ok(bars.into_iter().map(|(_, bar)| bar).collect())
+    }
+
+    /// Fetch intraday bars for a symbol; see `YahooFinance::fetch_intraday`
+    pub async fn fetch_intraday(
+        &self,
+        symbol: &str,
+        interval: &str,
+        period: &str,
+    ) -> Result<Vec<IntradayPrice>> {
+        if interval == "1d" {
+            return Err(PipelineError::Config(
+                "Use fetch_prices for daily bars".to_string(),
+            ));
+        }
+        validate_interval(interval, period)?;
+
+        let bars = self.fetch_chart(symbol, interval, period).await?;
+        Ok(intraday_bars(bars, interval))
+    }
+
+    /// Fetch a symbol's metadata; see `YahooFinance::fetch_profile`
+    pub async fn fetch_profile(&self, symbol: &str) -> Result<Symbol> {
+        println!("[FETCH] Fetching profile for {} from Yahoo...", symbol);
+
+        let url = profile_url(&self.base_url, symbol);
+        let response = self.retry.send_async(|| self.client.get(&url)).await?;
+
+        if !response.status().is_success() {
+            return Err(PipelineError::NoData(format!(
+                "HTTP {} for {} profile",
+                response.status(),
+                symbol
+            )));
+        }
+
+        let profile = profile_symbol(response.json().await?, symbol)?;
+        println!("[OK] Fetched profile for {}", symbol);
+        Ok(profile)
+    }
+
+    /// Fetch bars, then store them with `db` locked only for the write
+    ///
+    /// The lock is taken after the response has arrived, so other commands
+    /// can use the database while the request is in flight. Like
+    /// `YahooFinance::fetch_and_store`, symbols without stored metadata are
+    /// enriched via `fetch_profile`, again without holding the lock.
+    pub async fn fetch_and_store(
+        &self,
+        db: &Mutex<Database>,
+        symbol: &str,
+        interval: &str,
+        period: &str,
+    ) -> Result<usize> {
+        validate_interval(interval, period)?;
+
+        let bars = if interval == "1d" {
+            FetchedBars::Daily(self.fetch_prices(symbol, period).await?)
+        } else {
+            FetchedBars::Intraday(self.fetch_intraday(symbol, interval, period).await?)
+        };
+
+        let (count, needs_profile) = {
+            let mut db = db.lock().unwrap_or_else(|e| e.into_inner());
+            let count = store_bars(&mut db, symbol, interval, bars, self.retain_years)?;
+            (count, db.get_symbol(symbol)?.and_then(|s| s.name).is_none())
+        };
+
+        if needs_profile {
+            match self.fetch_profile(symbol).await {
+                Ok(profile) => {
+                    let db = db.lock().unwrap_or_else(|e| e.into_inner());
+                    db.upsert_symbol(&profile)?;
+                    db.log_api_call("yahoo_finance", "profile", symbol)?;
+                }
+                Err(e) => println!("[WARN] No profile stored for {}: {}", symbol, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn fetch_chart(
+        &self,
+        symbol: &str,
+        interval: &str,
+        period: &str,
+    ) -> Result<Vec<(DateTime<Utc>, DailyPrice)>> {
+        println!(
+            "[FETCH] Fetching {} from Yahoo Finance (interval: {}, period: {})...",
+            symbol, interval, period
+        );
+
+        let url = chart_url(
+            &self.base_url,
+            symbol,
+            interval,
+            period,
+            self.include_prepost,
+        )?;
+        let response = self.retry.send_async(|| self.client.get(&url)).await?;
+
+        if !response.status().is_success() {
+            return Err(PipelineError::NoData(format!(
+                "HTTP {} for {}",
+                response.status(),
+                symbol
+            )));
+        }
+
+        let data = chart_result(response.json().await?, symbol)?;
+        let prices = chart_bars(&data, symbol, self.adjusted)?;
+
+        println!("[OK] Fetched {} records for {}", prices.len(), symbol);
+        Ok(prices)
+    }
+}
+
+/// Chart API URL for a symbol, interval and range
+fn chart_url(
+    base_url: &str,
+    symbol: &str,
+    interval: &str,
+    period: &str,
+    include_prepost: bool,
+) -> Result<String> {
+    let mut url = format!(
+        "{}/v8/finance/chart/{}?interval={}&range={}",
+        base_url,
+        urlencoding::encode(&normalize_symbol_for_yahoo(symbol)),
+        interval,
+        period
+    );
+
+    if include_prepost {
+        if !INTRADAY_INTERVALS.contains(&interval) {
+            return Err(PipelineError::Config(format!(
+                "Pre/post-market bars require an intraday interval, got {}",
+                interval
+            )));
+        }
+        url.push_str("&includePrePost=true");
+    }
+
+    Ok(url)
+}
+
+/// quoteSummary API URL for a symbol's asset profile and price modules
+fn profile_url(base_url: &str, symbol: &str) -> String {
+    format!(
+        "{}/v10/finance/quoteSummary/{}?modules=assetProfile%2Cprice",
+        base_url,
+        urlencoding::encode(&normalize_symbol_for_yahoo(symbol))
+    )
+}
+
+/// Build symbol metadata from a quoteSummary response
+fn profile_symbol(response: QuoteSummaryResponse, symbol: &str) -> Result<Symbol> {
+    let summary = response.quote_summary;
+    let result = match (summary.result, summary.error) {
+        (Some(results), _) => results.into_iter().next(),
+        (None, Some(err)) => {
+            return Err(PipelineError::NoData(format!(
+                "{}: {}",
+                err.code, err.description
+            )))
+        }
+        (None, None) => None,
+    }
+    .filter(|r| r.asset_profile.is_some() || r.price.is_some())
+    .ok_or_else(|| PipelineError::NoData(format!("No profile for {}", symbol)))?;
+
+    let profile = result.asset_profile.unwrap_or_default();
+    let price = result.price.unwrap_or_default();
+    Ok(Symbol {
+        symbol: symbol.to_string(),
+        name: price.long_name.or(price.short_name),
+        sector: profile.sector,
+        industry: profile.industry,
+        market_cap: price.market_cap.and_then(|cap| cap.raw),
+        country: profile.country,
+        exchange: price.exchange_name,
+        currency: price.currency,
+        isin: None,
+        asset_class: price.quote_type.map(|t| t.to_lowercase()),
+    })
+}
+
+/// The (single) result of a chart response, or the API's error
+fn chart_result(chart_response: ChartResponse, symbol: &str) -> Result<ChartResult> {
+    match (chart_response.chart.result, chart_response.chart.error) {
+        (Some(results), _) => results
+            .into_iter()
+            .next()
+            .ok_or_else(|| PipelineError::NoData(symbol.to_string())),
+        (None, Some(err)) => Err(PipelineError::NoData(format!(
+            "{}: {}",
+            err.code, err.description
+        ))),
+        (None, None) => Err(PipelineError::NoData(symbol.to_string())),
+    }
+}
+
+/// Parse a chart's bars, keeping each bar's full timestamp
+fn chart_bars(
+    data: &ChartResult,
+    symbol: &str,
+    adjusted: bool,
+) -> Result<Vec<(DateTime<Utc>, DailyPrice)>> {
+    let timestamps = data
+        .timestamp
+        .as_ref()
+        .ok_or_else(|| PipelineError::NoData(symbol.to_string()))?;
+
+    let quote = &data.indicators.quote[0];
+    let adjcloses = data
+        .indicators
+        .adjclose
+        .as_ref()
+        .and_then(|a| a.first())
+        .map(|a| a.adjclose.as_slice())
+        .unwrap_or(&[]);
+
+    let mut prices = Vec::with_capacity(timestamps.len());
+
+    for (i, &ts) in timestamps.iter().enumerate() {
+        // Skip if any value is None (pre/post-market bars are often sparse)
+        let open = match quote.open.get(i).and_then(|v| *v) {
+            Some(v) => v,
+            None => continue,
+        };
+        let high = match quote.high.get(i).and_then(|v| *v) {
+            Some(v) => v,
+            None => continue,
+        };
+        let low = match quote.low.get(i).and_then(|v| *v) {
+            Some(v) => v,
+            None => continue,
+        };
+        let close = match quote.close.get(i).and_then(|v| *v) {
+            Some(v) => v,
+            None => continue,
+        };
+        let volume = quote.volume.get(i).and_then(|v| *v).unwrap_or(0);
+
+        // Convert Unix timestamp to date
+        let datetime = DateTime::from_timestamp(ts, 0)
+            .unwrap_or_else(|| Utc::now());
+        let date = datetime.date_naive();
+
+        let bar = DailyPrice {
+            symbol: symbol.to_string(),
+            date,
+            open,
+            high,
+            low,
+            close,
+            volume,
+            adjusted_close: adjcloses.get(i).and_then(|v| *v),
+            source: "yahoo_finance".to_string(),
+        };
+        prices.push((datetime, if adjusted { bar.to_adjusted() } else { bar }));
+    }
+
+    Ok(prices)
+}
+
+/// Relabel parsed chart bars as intraday bars of `interval`
+fn intraday_bars(bars: Vec<(DateTime<Utc>, DailyPrice)>, interval: &str) -> Vec<IntradayPrice> {
+    bars.into_iter()
+        .map(|(timestamp, bar)| IntradayPrice {
+            symbol: bar.symbol,
+            interval: interval.to_string(),
+            timestamp: timestamp.naive_utc(),
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            source: bar.source,
+        })
+        .collect()
+}
+
+/// Database half of `fetch_and_store`
+fn store_bars(
+    db: &mut Database,
+    symbol: &str,
+    interval: &str,
+    bars: FetchedBars,
+    retain_years: Option<u32>,
+) -> Result<usize> {
+    let prices = match bars {
+        FetchedBars::Daily(prices) => prices,
+        FetchedBars::Intraday(bars) => {
+            let count = db.upsert_intraday_prices(&bars)?;
+            db.log_api_call("yahoo_finance", "intraday", symbol)?;
+            println!("[OK] Stored {} {} bars for {}", count, interval, symbol);
+            return Ok(count);
+        }
+    };
+
+    let count = db.upsert_daily_prices(&prices)?;
+    db.log_api_call("yahoo_finance", "history", symbol)?;
+    println!("[OK] Stored {} records for {}", count, symbol);
+
+    if let Some(years) = retain_years {
+        let today = Utc::now().date_naive();
+        if let Some(cutoff) = today.checked_sub_months(Months::new(years * 12)) {
+            let stored_symbol = prices.first().map_or(symbol, |p| p.symbol.as_str());
+            let pruned = db.prune_history_before(stored_symbol, cutoff)?;
+            if pruned > 0 {
+                println!("[OK] Pruned {} bars before {} for {}", pruned, cutoff, stored_symbol);
+            }
+        }
+    }
+
+    Ok(count)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::MockResponse;
 
     #[test]
     fn test_fetch_aapl() {
@@ -199,4 +987,507 @@ mod tests {
         assert!(!prices.is_empty());
         assert_eq!(prices[0].symbol, "AAPL");
     }
+
+    #[test]
+    fn test_normalize_symbol_for_yahoo() {
+        assert_eq!(normalize_symbol_for_yahoo("BRK.B"), "BRK-B");
+        assert_eq!(normalize_symbol_for_yahoo(" brk.a "), "BRK-A");
+        assert_eq!(normalize_symbol_for_yahoo("VOD.L"), "VOD.L");
+        assert_eq!(normalize_symbol_for_yahoo("SHOP.TO"), "SHOP.TO");
+        assert_eq!(normalize_symbol_for_yahoo("AAPL"), "AAPL");
+    }
+
+    #[test]
+    fn test_dotted_symbol_requested_as_dash_and_stored_canonical() {
+        let base_url = crate::testutil::MockServer::start(|target| {
+            if !target.starts_with("/v8/finance/chart/BRK-B?") {
+                return MockResponse::status(404, "{}");
+            }
+            MockResponse::ok(
+                r#"{"chart":{"result":[{"meta":{"symbol":"BRK-B"},"timestamp":[1704205800],
+                "indicators":{"quote":[{"open":[362.0],"high":[364.0],"low":[360.5],
+                "close":[363.2],"volume":[3500000]}]}}],"error":null}}"#,
+            )
+        });
+
+        let client = YahooFinance::new().with_base_url(&base_url);
+        let prices = client.fetch_prices("BRK.B", "5d").unwrap();
+
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].symbol, "BRK.B");
+    }
+
+    #[test]
+    fn test_fetch_profile_extracts_sector_and_market_cap() {
+        let base_url = crate::testutil::MockServer::start(|target| {
+            if target.starts_with("/v8/finance/chart/") {
+                return MockResponse::ok(
+                    r#"{"chart":{"result":[{"meta":{"symbol":"AAPL"},"timestamp":[1704205800],
+                    "indicators":{"quote":[{"open":[185.0],"high":[186.0],"low":[184.0],
+                    "close":[185.5],"volume":[1000000]}]}}],"error":null}}"#,
+                );
+            }
+            if target.starts_with("/v10/finance/quoteSummary/AAPL?") {
+                return MockResponse::ok(
+                    r#"{"quoteSummary":{"result":[{"assetProfile":{"address1":"One Apple Park Way",
+                    "city":"Cupertino","country":"United States","industry":"Consumer Electronics",
+                    "sector":"Technology","fullTimeEmployees":161000},
+                    "price":{"maxAge":1,"marketCap":{"raw":2994222596096,"fmt":"2.99T",
+                    "longFmt":"2,994,222,596,096"},"currency":"USD","exchangeName":"NasdaqGS",
+                    "quoteType":"EQUITY","symbol":"AAPL","shortName":"Apple Inc.",
+                    "longName":"Apple Inc."}}],"error":null}}"#,
+                );
+            }
+            if target.starts_with("/v10/finance/quoteSummary/SPY?") {
+                return MockResponse::ok(
+                    r#"{"quoteSummary":{"result":[{"price":{"maxAge":1,"marketCap":{},
+                    "currency":"USD","exchangeName":"NYSEArca","quoteType":"ETF","symbol":"SPY",
+                    "shortName":"SPDR S&P 500","longName":"SPDR S&P 500 ETF Trust"}}],
+                    "error":null}}"#,
+                );
+            }
+            MockResponse::status(
+                404,
+                r#"{"quoteSummary":{"result":null,"error":{"code":"Not Found",
+                "description":"Quote not found for ticker symbol: NOPE"}}}"#,
+            )
+        });
+        let client = YahooFinance::new().with_base_url(&base_url);
+
+        let apple = client.fetch_profile("AAPL").unwrap();
+        assert_eq!(apple.name.as_deref(), Some("Apple Inc."));
+        assert_eq!(apple.sector.as_deref(), Some("Technology"));
+        assert_eq!(apple.industry.as_deref(), Some("Consumer Electronics"));
+        assert_eq!(apple.market_cap, Some(2994222596096.0));
+        assert_eq!(apple.asset_class.as_deref(), Some("equity"));
+
+        // ETFs come back without an asset profile or market cap
+        let spy = client.fetch_profile("SPY").unwrap();
+        assert_eq!(spy.name.as_deref(), Some("SPDR S&P 500 ETF Trust"));
+        assert_eq!(spy.sector, None);
+        assert_eq!(spy.market_cap, None);
+        assert_eq!(spy.asset_class.as_deref(), Some("etf"));
+
+        assert!(client.fetch_profile("NOPE").is_err());
+
+        // Storing prices fills in the metadata and keeps the favorite flag
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        db.toggle_symbol_favorite("AAPL").unwrap();
+        client.fetch_and_store(&mut db, "AAPL", "1d", "5d").unwrap();
+        let stored = db.get_symbol("AAPL").unwrap().unwrap();
+        assert_eq!(stored.sector.as_deref(), Some("Technology"));
+        assert!(db.is_symbol_favorited("AAPL").unwrap());
+    }
+
+    #[test]
+    fn test_retries_transient_failures_then_returns_data() {
+        let attempts = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let base_url = crate::testutil::MockServer::start(move |_| {
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => MockResponse::status(503, "{}"),
+                1 => MockResponse::status(429, "{}"),
+                _ => MockResponse::ok(
+                    r#"{"chart":{"result":[{"meta":{"symbol":"AAPL"},"timestamp":[1704205800],
+                    "indicators":{"quote":[{"open":[185.0],"high":[186.0],"low":[184.0],
+                    "close":[185.5],"volume":[1000000]}]}}],"error":null}}"#,
+                ),
+            }
+        });
+
+        let client = YahooFinance::new()
+            .with_base_url(&base_url)
+            .with_retries(2)
+            .with_retry_delay(Duration::from_millis(1));
+        let prices = client.fetch_prices("AAPL", "5d").unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].close, 185.5);
+
+        // One retry fewer and the 429 is what comes back
+        attempts.store(0, Ordering::SeqCst);
+        let err = client.with_retries(1).fetch_prices("AAPL", "5d").unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert!(err.to_string().contains("429"));
+    }
+
+    #[test]
+    fn test_retain_years_prunes_old_bars() {
+        let now = Utc::now().timestamp();
+        let old = now - 2 * 365 * 86_400;
+        let recent = now - 30 * 86_400;
+        let body = format!(
+            r#"{{"chart":{{"result":[{{"meta":{{"symbol":"AAPL"}},"timestamp":[{},{}],
+            "indicators":{{"quote":[{{"open":[100.0,150.0],"high":[101.0,151.0],
+            "low":[99.0,149.0],"close":[100.5,150.5],"volume":[1000,2000]}}]}}}}],"error":null}}}}"#,
+            old, recent
+        );
+        let base_url = crate::testutil::MockServer::start(move |_| MockResponse::ok(&body));
+
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+
+        let old_date = DateTime::from_timestamp(old, 0).unwrap().date_naive();
+        db.upsert_indicator(&crate::models::TechnicalIndicator {
+            symbol: "AAPL".to_string(),
+            date: old_date,
+            indicator_name: "RSI_14".to_string(),
+            value: 50.0,
+        })
+        .unwrap();
+
+        let client = YahooFinance::new()
+            .with_base_url(&base_url)
+            .with_retain_years(Some(1));
+        let stored = client.fetch_and_store(&mut db, "AAPL", "1d", "5y").unwrap();
+        assert_eq!(stored, 2);
+
+        let prices = db.get_prices("AAPL").unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].close, 150.5);
+        assert!(db.get_all_indicators("AAPL").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_adjusted_close_across_aapl_2020_split() {
+        // AAPL 4:1 split effective 2020-08-31: raw closes jump, adjusted ones don't
+        let base_url = crate::testutil::MockServer::start(|_| {
+            MockResponse::ok(
+                r#"{"chart":{"result":[{"meta":{"symbol":"AAPL"},"timestamp":[1598621400,1598880600],
+                "indicators":{"quote":[{"open":[504.05,127.58],"high":[505.77,131.0],
+                "low":[498.31,126.0],"close":[499.23,129.04],"volume":[46907500,225702700]}],
+                "adjclose":[{"adjclose":[124.8075,129.04]}]}}],"error":null}}"#,
+            )
+        });
+
+        let raw = YahooFinance::new().with_base_url(&base_url);
+        let prices = raw.fetch_prices("AAPL", "5d").unwrap();
+        assert_eq!(prices[0].close, 499.23);
+        assert_eq!(prices[0].adjusted_close, Some(124.8075));
+        assert_eq!(prices[1].adjusted_close, Some(129.04));
+
+        let adjusted = YahooFinance::new()
+            .with_base_url(&base_url)
+            .with_adjusted_prices(true);
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        adjusted.fetch_and_store(&mut db, "AAPL", "1d", "5d").unwrap();
+
+        let stored = db.get_prices("AAPL").unwrap();
+        assert_eq!(stored[0].close, 124.8075);
+        assert!((stored[0].open - 504.05 / 4.0).abs() < 0.01);
+        assert_eq!(stored[0].adjusted_close, Some(124.8075));
+
+        // Raw return shows a ~74% "crash"; adjusted shows the real ~3% gain
+        let raw_return = prices[1].close / prices[0].close - 1.0;
+        let adj_return = stored[1].close / stored[0].close - 1.0;
+        assert!(raw_return < -0.7);
+        assert!((adj_return - 0.0339).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_fetch_events_extracts_aapl_split_and_dividends() {
+        // Recorded AAPL response around the 2020-08-31 4:1 split
+        let base_url = crate::testutil::MockServer::start(|target| {
+            if !target.contains("events=div%7Csplit") {
+                return MockResponse::status(400, "{}");
+            }
+            MockResponse::ok(
+                r#"{"chart":{"result":[{"meta":{"symbol":"AAPL","currency":"USD"},
+                "timestamp":[1596807000,1598880600,1604586600],
+                "events":{
+                  "dividends":{
+                    "1604586600":{"amount":0.205,"date":1604586600},
+                    "1596807000":{"amount":0.82,"date":1596807000}},
+                  "splits":{
+                    "1598880600":{"date":1598880600,"numerator":4,"denominator":1,
+                                  "splitRatio":"4:1"}}},
+                "indicators":{"quote":[{"open":[452.82,127.58,117.95],
+                "high":[454.7,131.0,119.62],"low":[441.17,126.0,116.87],
+                "close":[444.45,129.04,118.69],"volume":[49511403,225702700,126387100]}]}}],
+                "error":null}}"#,
+            )
+        });
+
+        let client = YahooFinance::new().with_base_url(&base_url);
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let stored = client.fetch_and_store_events(&mut db, "AAPL", "1y").unwrap();
+        assert_eq!(stored, (2, 1));
+
+        let splits = db.get_splits("AAPL").unwrap();
+        assert_eq!(splits.len(), 1);
+        assert_eq!(splits[0].date, NaiveDate::from_ymd_opt(2020, 8, 31).unwrap());
+        assert_eq!(splits[0].ratio(), 4.0);
+
+        let dividends = db.get_dividends("AAPL").unwrap();
+        let amounts: Vec<f64> = dividends.iter().map(|d| d.amount).collect();
+        assert_eq!(amounts, vec![0.82, 0.205]);
+        assert_eq!(dividends[0].ex_date, NaiveDate::from_ymd_opt(2020, 8, 7).unwrap());
+
+        // Refetching replaces rather than duplicates
+        client.fetch_and_store_events(&mut db, "AAPL", "1y").unwrap();
+        assert_eq!(db.get_dividends("AAPL").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_prepost_parameter_only_for_intraday() {
+        let client = YahooFinance::new().with_include_prepost(true);
+
+        let url = client.chart_url("AAPL", "5m", "1d").unwrap();
+        assert!(url.ends_with("interval=5m&range=1d&includePrePost=true"));
+
+        assert!(matches!(
+            client.chart_url("AAPL", "1d", "5d"),
+            Err(PipelineError::Config(_))
+        ));
+        assert!(matches!(
+            client.fetch_prices("AAPL", "5d"),
+            Err(PipelineError::Config(_))
+        ));
+
+        let regular = YahooFinance::new().chart_url("AAPL", "5m", "1d").unwrap();
+        assert!(!regular.contains("includePrePost"));
+    }
+
+    #[test]
+    fn test_intraday_5m_bars_stored_separately() {
+        // Three 5-minute bars on 2024-01-02, the middle one pre-market and sparse
+        let base_url = crate::testutil::MockServer::start(|target| {
+            if !target.contains("interval=5m&range=1d") {
+                return MockResponse::status(400, "{}");
+            }
+            MockResponse::ok(
+                r#"{"chart":{"result":[{"meta":{"symbol":"AAPL"},
+                "timestamp":[1704205800,1704206100,1704206400],
+                "indicators":{"quote":[{"open":[187.15,null,186.4],"high":[187.9,null,186.9],
+                "low":[186.8,null,186.1],"close":[187.0,null,186.7],"volume":[1200000,null,800000]}]}}],
+                "error":null}}"#,
+            )
+        });
+
+        let client = YahooFinance::new().with_base_url(&base_url);
+        let bars = client.fetch_intraday("AAPL", "5m", "1d").unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp.to_string(), "2024-01-02 14:30:00");
+        assert_eq!(bars[1].timestamp.to_string(), "2024-01-02 14:40:00");
+        assert_eq!(bars[0].interval, "5m");
+
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let stored = client.fetch_and_store(&mut db, "AAPL", "5m", "1d").unwrap();
+        assert_eq!(stored, 2);
+
+        let loaded = db.get_intraday_prices("AAPL", "5m").unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].timestamp, bars[1].timestamp);
+        assert_eq!(loaded[1].close, 186.7);
+        assert!(db.get_prices("AAPL").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fill_gaps_stores_only_missing_bars() {
+        // Yahoo returns Jan 2-5 2024; Jan 3-4 are missing locally
+        let base_url = crate::testutil::MockServer::start(|target| {
+            if !target.contains("interval=1d&range=") {
+                return MockResponse::status(400, "{}");
+            }
+            MockResponse::ok(
+                r#"{"chart":{"result":[{"meta":{"symbol":"AAPL"},
+                "timestamp":[1704205800,1704292200,1704378600,1704465000],
+                "indicators":{"quote":[{"open":[1.0,2.0,3.0,4.0],"high":[1.0,2.0,3.0,4.0],
+                "low":[1.0,2.0,3.0,4.0],"close":[1.0,2.0,3.0,4.0],"volume":[10,20,30,40]}]}}],
+                "error":null}}"#,
+            )
+        });
+
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let stored = |day: u32, close: f64| DailyPrice {
+            symbol: "AAPL".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1,
+            adjusted_close: None,
+            source: "test".to_string(),
+        };
+        db.upsert_daily_prices(&[stored(2, 100.0), stored(5, 400.0)]).unwrap();
+
+        let client = YahooFinance::new().with_base_url(&base_url);
+        assert_eq!(client.fill_gaps(&mut db, "AAPL").unwrap(), 2);
+
+        let closes: Vec<f64> = db.get_prices("AAPL").unwrap().iter().map(|p| p.close).collect();
+        assert_eq!(closes, vec![100.0, 2.0, 3.0, 400.0]);
+        assert!(db.find_price_gaps("AAPL").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_interval_ranges() {
+        assert!(validate_interval("1m", "5d").is_ok());
+        assert!(validate_interval("5m", "1mo").is_ok());
+        assert!(validate_interval("1d", "max").is_ok());
+        assert!(matches!(validate_interval("1m", "1mo"), Err(PipelineError::Config(_))));
+        assert!(matches!(validate_interval("1h", "5y"), Err(PipelineError::Config(_))));
+        assert!(matches!(validate_interval("2h", "1d"), Err(PipelineError::Config(_))));
+    }
+
+    #[test]
+    fn test_fetch_batch_caps_concurrency_and_stores_all() {
+        use std::sync::Arc;
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (current, max_seen) = (in_flight.clone(), peak.clone());
+        let base_url = crate::testutil::MockServer::start_concurrent(move |target| {
+            let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+            max_seen.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(100));
+            current.fetch_sub(1, Ordering::SeqCst);
+
+            let symbol = target
+                .trim_start_matches("/v8/finance/chart/")
+                .split('?')
+                .next()
+                .unwrap_or_default();
+            if symbol == "BAD" {
+                return MockResponse::status(404, "{}");
+            }
+            MockResponse::ok(&format!(
+                r#"{{"chart":{{"result":[{{"meta":{{"symbol":"{}"}},"timestamp":[1704205800],
+                "indicators":{{"quote":[{{"open":[10.0],"high":[11.0],"low":[9.0],
+                "close":[10.5],"volume":[1000]}}]}}}}],"error":null}}}}"#,
+                symbol
+            ))
+        });
+
+        let symbols: Vec<String> = ["AAA", "BBB", "CCC", "DDD", "BAD", "FFF", "GGG", "HHH"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let mut db = Database::open_in_memory().unwrap();
+        db.init_schema().unwrap();
+        let client = YahooFinance::new()
+            .with_base_url(&base_url)
+            .with_max_in_flight(3)
+            .with_min_request_interval(Duration::ZERO);
+        let (ok, failed) = client.fetch_batch(&mut db, &symbols, "1d", "5d").unwrap();
+
+        assert_eq!((ok, failed), (7, 1));
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 3, "peak in-flight was {}", peak);
+        for symbol in symbols.iter().filter(|s| *s != "BAD") {
+            let prices = db.get_prices(symbol).unwrap();
+            assert_eq!(prices.len(), 1, "{} not stored", symbol);
+            assert_eq!(prices[0].close, 10.5);
+        }
+    }
+
+    #[test]
+    fn test_request_pacer_spaces_requests() {
+        let pacer = RequestPacer::new(Duration::from_millis(50));
+        let start = Instant::now();
+        for _ in 0..3 {
+            pacer.wait();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_timeout_maps_to_timeout_error() {
+        let base_url = crate::testutil::MockServer::start(|_| {
+            MockResponse::ok("{}").with_delay(Duration::from_secs(2))
+        });
+
+        let client = YahooFinance::new()
+            .with_timeout(Duration::from_millis(200))
+            .with_base_url(&base_url);
+
+        let err = client.fetch_prices("AAPL", "5d").unwrap_err();
+        assert!(matches!(err, PipelineError::Timeout(_)), "got {:?}", err);
+        assert!(err.is_retryable());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_fetch_and_store_leaves_db_unlocked_while_fetching() {
+        let attempts = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let base_url = crate::testutil::MockServer::start(move |target| {
+            if target.starts_with("/v10/finance/quoteSummary/") {
+                return MockResponse::status(404, "{}");
+            }
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => MockResponse::status(503, "{}"),
+                _ => MockResponse::ok(
+                    r#"{"chart":{"result":[{"meta":{"symbol":"AAPL"},"timestamp":[1704205800],
+                    "indicators":{"quote":[{"open":[185.0],"high":[186.0],"low":[184.0],
+                    "close":[185.5],"volume":[1000000]}]}}],"error":null}}"#,
+                )
+                .with_delay(Duration::from_millis(300)),
+            }
+        });
+
+        let db = Mutex::new(Database::open_in_memory().unwrap());
+        db.lock().unwrap().init_schema().unwrap();
+        let client = AsyncYahooFinance::new()
+            .with_base_url(&base_url)
+            .with_retry_delay(Duration::from_millis(1));
+
+        let (stored, unlocked_mid_fetch) = tokio::join!(
+            client.fetch_and_store(&db, "AAPL", "1d", "5d"),
+            async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                db.try_lock().is_ok()
+            }
+        );
+
+        assert_eq!(stored.unwrap(), 1);
+        assert!(unlocked_mid_fetch);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        let prices = db.lock().unwrap().get_prices("AAPL").unwrap();
+        assert_eq!(prices[0].close, 185.5);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_fetch_and_store_enriches_profile() {
+        let base_url = crate::testutil::MockServer::start(|target| {
+            if target.starts_with("/v10/finance/quoteSummary/MSFT?") {
+                return MockResponse::ok(
+                    r#"{"quoteSummary":{"result":[{"assetProfile":{"country":"United States",
+                    "industry":"Software - Infrastructure","sector":"Technology"},
+                    "price":{"marketCap":{"raw":3100000000000},"exchangeName":"NasdaqGS",
+                    "quoteType":"EQUITY","symbol":"MSFT","longName":"Microsoft Corporation"}}],
+                    "error":null}}"#,
+                );
+            }
+            MockResponse::ok(
+                r#"{"chart":{"result":[{"meta":{"symbol":"MSFT"},"timestamp":[1704205800],
+                "indicators":{"quote":[{"open":[370.0],"high":[372.0],"low":[368.0],
+                "close":[371.0],"volume":[1000000]}]}}],"error":null}}"#,
+            )
+        });
+
+        let db = Mutex::new(Database::open_in_memory().unwrap());
+        db.lock().unwrap().init_schema().unwrap();
+        let client = AsyncYahooFinance::new().with_base_url(&base_url);
+
+        assert_eq!(
+            client
+                .fetch_and_store(&db, "MSFT", "1d", "5d")
+                .await
+                .unwrap(),
+            1
+        );
+        let stored = db.lock().unwrap().get_symbol("MSFT").unwrap().unwrap();
+        assert_eq!(stored.name.as_deref(), Some("Microsoft Corporation"));
+        assert_eq!(stored.sector.as_deref(), Some("Technology"));
+        assert_eq!(stored.market_cap, Some(3100000000000.0));
+    }
 }
@@ -1,12 +1,20 @@
 //! Tauri GUI backend for Financial Pipeline
 
 use financial_pipeline::{
-    calculate_all, AlertCondition, BacktestConfig, BacktestEngine, Database, Fred, GoogleTrends,
-    IndicatorAlert, IndicatorAlertCondition, IndicatorAlertType, PositionType, SignalEngine,
-    Strategy, StrategyConditionType, YahooFinance,
+    calculate_all_with_timeframe, calculate_incremental, calculate_named, catch_up_period,
+    downsample_prices, indicators, monthly_return_stats, validate_interval, validate_webhook_url,
+    weekday_return_stats, AlertCondition, AsyncFred, AsyncYahooFinance, BENCHMARK_SYMBOL_KEY,
+    BacktestConfig, BacktestEngine, BacktestExportFormat, BacktestResult, DEFAULT_BENCHMARK_SYMBOL,
+    DEFAULT_TRENDS_TIMEFRAME, Database, ExportPrecision, FRED_API_KEY_SETTING, GoogleTrends,
+    IndicatorAlert, IndicatorAlertCondition, IndicatorAlertType, IndicatorInfo, OptimizeMetric,
+    OptionLeg, OptionStrategy, ParamGrid, PerformanceMetrics, PipelineError, PortfolioTotals,
+    PositionSizing, PositionType, ResamplePeriod, SeasonalBucket, SignalConfig, SignalEngine,
+    SignalType, Strategy, StrategyConditionType, StrategyParam, StrategyRules, TradeDirection,
+    WEBHOOK_SINK, YahooFinance,
 };
 use serde::Serialize;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
 use tauri::State;
 
 /// Application state holding the database connection
@@ -14,6 +22,76 @@ struct AppState {
     db: Mutex<Database>,
 }
 
+/// Error returned by every command
+///
+/// `kind` lets the frontend tell missing data ("NotFound") from network
+/// failures ("NetworkError"), data a provider or file sent that couldn't be
+/// parsed ("UpstreamError"), bad input ("InvalidInput"), file read/write
+/// failures ("IoError") and storage failures ("DatabaseError"); `message` is
+/// for display.
+#[derive(Debug, Serialize)]
+struct CommandError {
+    kind: String,
+    message: String,
+}
+
+impl CommandError {
+    fn new(kind: &str, message: impl Into<String>) -> Self {
+        Self {
+            kind: kind.to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("NotFound", message)
+    }
+
+    fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new("InvalidInput", message)
+    }
+}
+
+impl From<PipelineError> for CommandError {
+    fn from(err: PipelineError) -> Self {
+        let kind = match err {
+            PipelineError::Http(_)
+            | PipelineError::Timeout(_)
+            | PipelineError::ApiError(_)
+            | PipelineError::RateLimited(_) => "NetworkError",
+            PipelineError::NoData(_) => "NotFound",
+            PipelineError::DateParse(_) | PipelineError::Json(_) | PipelineError::Csv(_) => {
+                "UpstreamError"
+            }
+            PipelineError::Config(_) => "InvalidInput",
+            PipelineError::Io(_) => "IoError",
+            PipelineError::Database(_) => "DatabaseError",
+        };
+        Self::new(kind, err.to_string())
+    }
+}
+
+/// Export files written directly by a command
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        PipelineError::from(err).into()
+    }
+}
+
+/// Validation failures raised inside the commands themselves
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::invalid_input(message)
+    }
+}
+
+/// A poisoned database lock
+impl<T> From<PoisonError<T>> for CommandError {
+    fn from(err: PoisonError<T>) -> Self {
+        Self::new("DatabaseError", err.to_string())
+    }
+}
+
 /// Symbol with latest price and percent change
 #[derive(Serialize)]
 struct SymbolPrice {
@@ -50,10 +128,10 @@ struct MacroDataResponse {
 
 /// Get all symbols with their latest prices and percent change
 #[tauri::command]
-fn get_symbols(state: State<AppState>) -> Result<Vec<SymbolPrice>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_symbols(state: State<AppState>) -> Result<Vec<SymbolPrice>, CommandError> {
+    let db = state.db.lock()?;
 
-    let symbols = db.get_symbols_with_data().map_err(|e| e.to_string())?;
+    let symbols = db.get_symbols_with_data()?;
 
     let mut result = Vec::new();
     for symbol in symbols {
@@ -104,27 +182,76 @@ fn get_symbols(state: State<AppState>) -> Result<Vec<SymbolPrice>, String> {
 
 /// Toggle symbol favorite status (moon icon)
 #[tauri::command]
-fn toggle_favorite(state: State<AppState>, symbol: String) -> Result<bool, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.toggle_symbol_favorite(&symbol).map_err(|e| e.to_string())
+fn toggle_favorite(state: State<AppState>, symbol: String) -> Result<bool, CommandError> {
+    let db = state.db.lock()?;
+    db.toggle_symbol_favorite(&symbol)
+        .map_err(CommandError::from)
 }
 
 /// Get all favorited symbols
 #[tauri::command]
-fn get_favorited_symbols(state: State<AppState>) -> Result<Vec<String>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_favorited_symbols().map_err(|e| e.to_string())
+fn get_favorited_symbols(state: State<AppState>) -> Result<Vec<String>, CommandError> {
+    let db = state.db.lock()?;
+    db.get_favorited_symbols().map_err(CommandError::from)
+}
+
+/// Symbol metadata for frontend
+#[derive(Serialize)]
+struct SymbolInfoData {
+    symbol: String,
+    name: Option<String>,
+    sector: Option<String>,
+    industry: Option<String>,
+    market_cap: Option<f64>,
+    country: Option<String>,
+    exchange: Option<String>,
+    currency: Option<String>,
+    asset_class: Option<String>,
+}
+
+/// Get a symbol's name, sector, industry and market cap
+///
+/// Looked up from Yahoo Finance and stored when nothing is on record yet.
+#[tauri::command]
+fn get_symbol_info(state: State<AppState>, symbol: String) -> Result<SymbolInfoData, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.trim().to_uppercase();
+
+    let info = match db.get_symbol(&symbol)? {
+        Some(info) if info.name.is_some() => info,
+        _ => {
+            let info = YahooFinance::new().fetch_profile(&symbol)?;
+            db.upsert_symbol(&info)?;
+            db.log_api_call("yahoo_finance", "profile", &symbol)?;
+            info
+        }
+    };
+
+    Ok(SymbolInfoData {
+        symbol: info.symbol,
+        name: info.name,
+        sector: info.sector,
+        industry: info.industry,
+        market_cap: info.market_cap,
+        country: info.country,
+        exchange: info.exchange,
+        currency: info.currency,
+        asset_class: info.asset_class,
+    })
 }
 
 /// Fetch stock prices from Yahoo Finance
+///
+/// Runs off the UI thread; the database is only locked to store each
+/// symbol's bars once its request has completed.
 #[tauri::command]
-fn fetch_prices(
-    state: State<AppState>,
+async fn fetch_prices(
+    state: State<'_, AppState>,
     symbols: String,
     period: String,
-) -> Result<CommandResult, String> {
-    let mut db = state.db.lock().map_err(|e| e.to_string())?;
-
+    adjusted: Option<bool>,
+    interval: Option<String>,
+) -> Result<CommandResult, CommandError> {
     let symbol_list: Vec<String> = symbols
         .split(',')
         .map(|s| s.trim().to_uppercase())
@@ -138,13 +265,18 @@ fn fetch_prices(
         });
     }
 
-    let yahoo = YahooFinance::new();
+    let yahoo = AsyncYahooFinance::new().with_adjusted_prices(adjusted.unwrap_or(false));
+    let interval = interval.unwrap_or_else(|| "1d".to_string());
+    validate_interval(&interval, &period)?;
 
     let mut success_count = 0;
     let mut fail_count = 0;
 
     for symbol in &symbol_list {
-        match yahoo.fetch_and_store(&mut db, symbol, &period) {
+        match yahoo
+            .fetch_and_store(&state.db, symbol, &interval, &period)
+            .await
+        {
             Ok(_) => success_count += 1,
             Err(_) => fail_count += 1,
         }
@@ -161,11 +293,103 @@ fn fetch_prices(
     })
 }
 
-/// Fetch FRED macro data
+/// Fetch dividends and splits from Yahoo Finance
+#[tauri::command]
+fn fetch_corporate_actions(
+    state: State<AppState>,
+    symbols: String,
+    period: Option<String>,
+) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
+
+    let symbol_list: Vec<String> = symbols
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if symbol_list.is_empty() {
+        return Ok(CommandResult {
+            success: false,
+            message: "No symbols provided".to_string(),
+        });
+    }
+
+    let yahoo = YahooFinance::new();
+    let period = period.unwrap_or_else(|| "max".to_string());
+
+    let mut dividend_count = 0;
+    let mut split_count = 0;
+    let mut fail_count = 0;
+
+    for symbol in &symbol_list {
+        match yahoo.fetch_and_store_events(&mut db, symbol, &period) {
+            Ok((dividends, splits)) => {
+                dividend_count += dividends;
+                split_count += splits;
+            }
+            Err(_) => fail_count += 1,
+        }
+    }
+
+    Ok(CommandResult {
+        success: fail_count == 0,
+        message: format!(
+            "Stored {} dividends and {} splits for {} symbols ({} failed)",
+            dividend_count,
+            split_count,
+            symbol_list.len(),
+            fail_count
+        ),
+    })
+}
+
+/// Missing weekday range for frontend
+#[derive(Serialize)]
+struct DataGapData {
+    start: String,
+    end: String,
+}
+
+/// List weekday runs with no stored price bar for a symbol
 #[tauri::command]
-fn fetch_fred(state: State<AppState>, indicators: String) -> Result<CommandResult, String> {
-    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+fn check_data_gaps(
+    state: State<AppState>,
+    symbol: String,
+) -> Result<Vec<DataGapData>, CommandError> {
+    let db = state.db.lock()?;
+
+    let gaps = db.find_price_gaps(&symbol.to_uppercase())?;
+
+    Ok(gaps
+        .into_iter()
+        .map(|(start, end)| DataGapData {
+            start: start.to_string(),
+            end: end.to_string(),
+        })
+        .collect())
+}
+
+/// Refetch only the missing days for a symbol from Yahoo Finance
+#[tauri::command]
+fn fill_data_gaps(state: State<AppState>, symbol: String) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    let filled = YahooFinance::new().fill_gaps(&mut db, &symbol)?;
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("Filled {} missing bars for {}", filled, symbol),
+    })
+}
 
+/// Fetch FRED macro data without blocking the UI thread
+#[tauri::command]
+async fn fetch_fred(
+    state: State<'_, AppState>,
+    indicators: String,
+) -> Result<CommandResult, CommandError> {
     let indicator_list: Vec<&str> = indicators
         .split(',')
         .map(|s| s.trim())
@@ -179,13 +403,21 @@ fn fetch_fred(state: State<AppState>, indicators: String) -> Result<CommandResul
         });
     }
 
-    let fred = Fred::new();
+    // Use the official API when a key has been saved in settings
+    let api_key = {
+        let db = state.db.lock()?;
+        db.get_setting(FRED_API_KEY_SETTING)?
+    };
+    let mut fred = AsyncFred::new();
+    if let Some(key) = api_key {
+        fred = fred.with_api_key(key);
+    }
 
     let mut success_count = 0;
     let mut fail_count = 0;
 
     for indicator in &indicator_list {
-        match fred.fetch_and_store(&mut db, indicator) {
+        match fred.fetch_and_store(&state.db, indicator).await {
             Ok(_) => success_count += 1,
             Err(_) => fail_count += 1,
         }
@@ -204,10 +436,10 @@ fn fetch_fred(state: State<AppState>, indicators: String) -> Result<CommandResul
 
 /// Get macro data summary (latest value for each indicator)
 #[tauri::command]
-fn get_macro_data(state: State<AppState>) -> Result<Vec<MacroDataResponse>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_macro_data(state: State<AppState>) -> Result<Vec<MacroDataResponse>, CommandError> {
+    let db = state.db.lock()?;
 
-    let data = db.get_macro_summary().map_err(|e| e.to_string())?;
+    let data = db.get_macro_summary()?;
 
     Ok(data
         .into_iter()
@@ -220,22 +452,55 @@ fn get_macro_data(state: State<AppState>) -> Result<Vec<MacroDataResponse>, Stri
         .collect())
 }
 
+/// Macro series freshness for frontend
+#[derive(Serialize)]
+struct MacroFreshnessData {
+    indicator: String,
+    latest_date: String,
+    frequency: String,
+    age_days: i64,
+    overdue: bool,
+}
+
+/// Report which macro series are overdue for an update
+#[tauri::command]
+fn get_macro_freshness(state: State<AppState>) -> Result<Vec<MacroFreshnessData>, CommandError> {
+    let db = state.db.lock()?;
+
+    let report = db.macro_freshness()?;
+
+    Ok(report
+        .into_iter()
+        .map(|f| MacroFreshnessData {
+            indicator: f.indicator,
+            latest_date: f.latest_date.to_string(),
+            frequency: f.frequency.as_str().to_string(),
+            age_days: f.age_days,
+            overdue: f.overdue,
+        })
+        .collect())
+}
+
 /// Get price for a single symbol
 #[tauri::command]
-fn get_price(state: State<AppState>, symbol: String) -> Result<Option<f64>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_price(state: State<AppState>, symbol: String) -> Result<Option<f64>, CommandError> {
+    let db = state.db.lock()?;
     db.get_latest_price(&symbol.to_uppercase())
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// Calculate indicators for a symbol
 #[tauri::command]
-fn calculate_indicators(state: State<AppState>, symbol: String) -> Result<CommandResult, String> {
-    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+fn calculate_indicators(
+    state: State<AppState>,
+    symbol: String,
+    only_stored: Option<bool>,
+) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
     // Get price history
-    let prices = db.get_prices(&symbol).map_err(|e| e.to_string())?;
+    let prices = db.get_prices(&symbol)?;
 
     if prices.is_empty() {
         return Ok(CommandResult {
@@ -244,13 +509,19 @@ fn calculate_indicators(state: State<AppState>, symbol: String) -> Result<Comman
         });
     }
 
-    // Calculate all indicators
-    let indicators = calculate_all(&prices);
-    let count = indicators.len();
-
-    // Store them
-    db.upsert_indicators(&indicators)
-        .map_err(|e| e.to_string())?;
+    // Recompute only the ones already stored for this symbol, or bring all of
+    // them up to date (in full if prices changed underneath, else the new tail)
+    let stored_names = if only_stored.unwrap_or(false) {
+        db.get_indicator_names(&symbol)?
+    } else {
+        Vec::new()
+    };
+    let count = if stored_names.is_empty() {
+        db.refresh_indicators(&symbol)?
+    } else {
+        let indicators = calculate_named(&prices, &stored_names);
+        db.upsert_indicators(&indicators)?
+    };
 
     println!("[OK] Calculated {} indicator values for {}", count, symbol);
 
@@ -260,15 +531,196 @@ fn calculate_indicators(state: State<AppState>, symbol: String) -> Result<Comman
     })
 }
 
+/// Calculate indicators only for bars added since the last calculation
+#[tauri::command]
+fn calculate_indicators_incremental(
+    state: State<AppState>,
+    symbol: String,
+) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    let prices = db.incremental_prices(&symbol)?;
+    if prices.is_empty() {
+        return Ok(CommandResult {
+            success: false,
+            message: format!("No price data for {}", symbol),
+        });
+    }
+
+    let existing = db.get_all_indicators(&symbol)?;
+    let indicators = calculate_incremental(&existing, &prices);
+    let count = indicators.len();
+
+    db.upsert_indicators(&indicators)?;
+
+    println!("[OK] Calculated {} new indicator values for {}", count, symbol);
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("Calculated {} new indicator values for {}", count, symbol),
+    })
+}
+
+/// Calculate and store rolling beta of a symbol against a benchmark
+#[tauri::command]
+fn calculate_rolling_beta(
+    state: State<AppState>,
+    symbol: String,
+    benchmark: Option<String>,
+    window: usize,
+) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+    // Fall back to the configured benchmark (SPY unless changed in settings)
+    let benchmark = match benchmark {
+        Some(b) => b.to_uppercase(),
+        None => db.benchmark_symbol()?,
+    };
+
+    let prices = db.get_prices(&symbol)?;
+    let benchmark_prices = db.get_prices(&benchmark)?;
+
+    if prices.is_empty() || benchmark_prices.is_empty() {
+        return Ok(CommandResult {
+            success: false,
+            message: format!("Need price data for both {} and {}", symbol, benchmark),
+        });
+    }
+
+    let betas = financial_pipeline::calculate_rolling_beta(&prices, &benchmark_prices, window);
+    let count = betas.len();
+
+    db.upsert_indicators(&betas)?;
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("Calculated {} BETA_{}_{} values for {}", count, benchmark, window, symbol),
+    })
+}
+
+/// Get all settings, including defaults for unset keys
+#[tauri::command]
+fn get_settings(state: State<AppState>) -> Result<HashMap<String, String>, CommandError> {
+    let db = state.db.lock()?;
+
+    let mut settings: HashMap<String, String> = db.get_all_settings()?.into_iter().collect();
+    settings
+        .entry(BENCHMARK_SYMBOL_KEY.to_string())
+        .or_insert_with(|| DEFAULT_BENCHMARK_SYMBOL.to_string());
+
+    Ok(settings)
+}
+
+/// Get a single setting, or null if unset
+#[tauri::command]
+fn get_setting(state: State<AppState>, key: String) -> Result<Option<String>, CommandError> {
+    let db = state.db.lock()?;
+    db.get_setting(&key).map_err(CommandError::from)
+}
+
+/// Set a single setting
+#[tauri::command]
+fn set_setting(
+    state: State<AppState>,
+    key: String,
+    value: String,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+
+    let value = if key == BENCHMARK_SYMBOL_KEY {
+        value.trim().to_uppercase()
+    } else {
+        value
+    };
+    db.set_setting(&key, &value)?;
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("Set {} = {}", key, value),
+    })
+}
+
+/// Send triggered alerts to a webhook URL; an empty or missing URL turns it off
+#[tauri::command]
+fn set_webhook_url(
+    state: State<AppState>,
+    url: Option<String>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    if let Some(url) = &url {
+        validate_webhook_url(url)?;
+    }
+    db.set_notification_target(WEBHOOK_SINK, url.as_deref())?;
+
+    Ok(CommandResult {
+        success: true,
+        message: match url {
+            Some(url) => format!("Alerts will be posted to {}", url),
+            None => "Webhook notifications disabled".to_string(),
+        },
+    })
+}
+
+/// Get the signal thresholds, or the defaults if none are stored
+#[tauri::command]
+fn get_signal_config(state: State<AppState>) -> Result<SignalConfig, CommandError> {
+    let db = state.db.lock()?;
+    Ok(db.get_signal_config()?.unwrap_or_default())
+}
+
+/// Store the signal thresholds used by `generate_signals`
+#[tauri::command]
+fn set_signal_config(
+    state: State<AppState>,
+    config: SignalConfig,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+    db.save_signal_config(&config)?;
+
+    Ok(CommandResult {
+        success: true,
+        message: "Saved signal configuration".to_string(),
+    })
+}
+
+/// List indicators that `calculate_indicators` produces
+#[tauri::command]
+fn get_available_indicators() -> Vec<IndicatorInfo> {
+    indicators::available()
+}
+
+/// Signal type description for frontend
+#[derive(Serialize)]
+struct SignalTypeData {
+    name: String,
+    description: String,
+}
+
+/// List signal types the signal engine can emit
+#[tauri::command]
+fn get_available_signal_types() -> Vec<SignalTypeData> {
+    SignalType::all()
+        .into_iter()
+        .map(|t| SignalTypeData {
+            name: t.as_str().to_string(),
+            description: t.description().to_string(),
+        })
+        .collect()
+}
+
 /// Get latest indicators for a symbol
 #[tauri::command]
-fn get_indicators(state: State<AppState>, symbol: String) -> Result<Vec<IndicatorData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_indicators(
+    state: State<AppState>,
+    symbol: String,
+) -> Result<Vec<IndicatorData>, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
-    let indicators = db
-        .get_latest_indicators(&symbol)
-        .map_err(|e| e.to_string())?;
+    let indicators = db.get_latest_indicators(&symbol)?;
 
     Ok(indicators
         .into_iter()
@@ -281,18 +733,22 @@ fn get_indicators(state: State<AppState>, symbol: String) -> Result<Vec<Indicato
 }
 
 /// Get indicator history for charting
+///
+/// `limit` and `offset` page back from the most recent value; omit both for
+/// the full history.
 #[tauri::command]
 fn get_indicator_history(
     state: State<AppState>,
     symbol: String,
     indicator_name: String,
-) -> Result<Vec<IndicatorData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<IndicatorData>, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
-    let indicators = db
-        .get_indicator_history(&symbol, &indicator_name)
-        .map_err(|e| e.to_string())?;
+    let indicators =
+        db.get_indicator_history_window(&symbol, &indicator_name, limit, offset.unwrap_or(0))?;
 
     Ok(indicators
         .into_iter()
@@ -316,12 +772,26 @@ struct PricePoint {
 }
 
 /// Get price history for charting
+///
+/// `limit` and `offset` select a window counted back from the most recent
+/// bar; omit both for the full history. When `max_points` is given, the
+/// window is downsampled to about that many bars while keeping the highs and
+/// lows.
 #[tauri::command]
-fn get_price_history(state: State<AppState>, symbol: String) -> Result<Vec<PricePoint>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_price_history(
+    state: State<AppState>,
+    symbol: String,
+    max_points: Option<usize>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<PricePoint>, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
-    let prices = db.get_prices(&symbol).map_err(|e| e.to_string())?;
+    let mut prices = db.get_prices_window(&symbol, limit, offset.unwrap_or(0))?;
+    if let Some(max_points) = max_points {
+        prices = downsample_prices(&prices, max_points);
+    }
 
     Ok(prices
         .into_iter()
@@ -336,44 +806,232 @@ fn get_price_history(state: State<AppState>, symbol: String) -> Result<Vec<Price
         .collect())
 }
 
-/// Export data to CSV
+/// Bars one source has for a symbol
+#[derive(Serialize)]
+struct PriceSourceData {
+    source: String,
+    bars: usize,
+    first_date: String,
+    last_date: String,
+}
+
+/// List the data sources with bars for a symbol, most preferred first
 #[tauri::command]
-fn export_csv(state: State<AppState>, symbol: String) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn list_price_sources(
+    state: State<AppState>,
+    symbol: String,
+) -> Result<Vec<PriceSourceData>, CommandError> {
+    let db = state.db.lock()?;
+
+    Ok(db
+        .list_sources(&symbol.to_uppercase())?
+        .into_iter()
+        .map(|s| PriceSourceData {
+            source: s.source,
+            bars: s.bars,
+            first_date: s.first_date.to_string(),
+            last_date: s.last_date.to_string(),
+        })
+        .collect())
+}
+
+/// Get one source's price history, ignoring the source priority
+#[tauri::command]
+fn get_prices_by_source(
+    state: State<AppState>,
+    symbol: String,
+    source: String,
+) -> Result<Vec<PricePoint>, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
-    // Get price data
-    let prices = db.get_prices(&symbol).map_err(|e| e.to_string())?;
+    let prices = db.get_prices_by_source(&symbol, &source)?;
     if prices.is_empty() {
-        return Ok(CommandResult {
-            success: false,
-            message: format!("No data for {}", symbol),
-        });
+        return Err(CommandError::not_found(format!(
+            "No {} prices for {}",
+            source, symbol
+        )));
     }
 
-    // Get indicators
-    let indicators = db.get_latest_indicators(&symbol).map_err(|e| e.to_string())?;
+    Ok(prices
+        .into_iter()
+        .map(|p| PricePoint {
+            date: p.date.to_string(),
+            open: p.open,
+            high: p.high,
+            low: p.low,
+            close: p.close,
+            volume: p.volume,
+        })
+        .collect())
+}
+
+/// Average return for a calendar bucket
+#[derive(Serialize)]
+struct SeasonalBucketData {
+    label: String,
+    average_return: f64,
+    count: usize,
+}
+
+/// Month and weekday seasonality for a symbol
+#[derive(Serialize)]
+struct SeasonalityData {
+    symbol: String,
+    monthly: Vec<SeasonalBucketData>,
+    weekday: Vec<SeasonalBucketData>,
+}
+
+/// Get average daily returns by month and weekday
+#[tauri::command]
+fn get_seasonality(
+    state: State<AppState>,
+    symbol: String,
+) -> Result<SeasonalityData, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    let prices = db.get_prices(&symbol)?;
+
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    const WEEKDAYS: [&str; 5] = ["Mon", "Tue", "Wed", "Thu", "Fri"];
+
+    let to_data = |label: &str, bucket: &SeasonalBucket| SeasonalBucketData {
+        label: label.to_string(),
+        average_return: bucket.average_return,
+        count: bucket.count,
+    };
+
+    Ok(SeasonalityData {
+        monthly: MONTHS
+            .iter()
+            .zip(monthly_return_stats(&prices).iter())
+            .map(|(label, b)| to_data(label, b))
+            .collect(),
+        weekday: WEEKDAYS
+            .iter()
+            .zip(weekday_return_stats(&prices).iter())
+            .map(|(label, b)| to_data(label, b))
+            .collect(),
+        symbol,
+    })
+}
+
+/// Return correlation for one pair of symbols
+#[derive(Serialize)]
+struct CorrelationData {
+    symbol_a: String,
+    symbol_b: String,
+    /// `None` when the pair has too little overlapping history
+    correlation: Option<f64>,
+}
+
+/// Get pairwise correlations of daily returns for comma-separated symbols
+///
+/// Uses each symbol's last `days` bars (default 90).
+#[tauri::command]
+fn get_correlation_matrix(
+    state: State<AppState>,
+    symbols: String,
+    days: Option<usize>,
+) -> Result<Vec<CorrelationData>, CommandError> {
+    let db = state.db.lock()?;
+
+    let symbol_list: Vec<String> = symbols
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let matrix = db.correlation_matrix(&symbol_list, days.unwrap_or(90))?;
+
+    Ok(matrix
+        .into_iter()
+        .map(|(symbol_a, symbol_b, correlation)| CorrelationData {
+            symbol_a,
+            symbol_b,
+            correlation: (!correlation.is_nan()).then_some(correlation),
+        })
+        .collect())
+}
+
+/// Relative strength of one symbol against the benchmark
+#[derive(Serialize)]
+struct RelativeStrengthData {
+    symbol: String,
+    relative_strength: f64,
+}
+
+/// Rank comma-separated symbols by relative strength against the benchmark
+///
+/// Compares growth over the last `days` bars (default 63, about a quarter)
+/// with the configured benchmark's; the strongest symbol comes first.
+#[tauri::command]
+fn get_relative_strength(
+    state: State<AppState>,
+    symbols: String,
+    days: Option<usize>,
+) -> Result<Vec<RelativeStrengthData>, CommandError> {
+    let db = state.db.lock()?;
+
+    let symbol_list: Vec<String> = symbols
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let benchmark = db.benchmark_symbol()?;
+
+    let ranking = db.relative_strength(&symbol_list, &benchmark, days.unwrap_or(63))?;
+
+    Ok(ranking
+        .into_iter()
+        .map(|(symbol, relative_strength)| RelativeStrengthData {
+            symbol,
+            relative_strength,
+        })
+        .collect())
+}
+
+/// Export data to CSV
+///
+/// Prices default to 2 decimals and indicators to 4; volume is always an integer.
+#[tauri::command]
+fn export_csv(
+    state: State<AppState>,
+    symbol: String,
+    price_decimals: Option<usize>,
+    indicator_decimals: Option<usize>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    let defaults = ExportPrecision::default();
+    let precision = ExportPrecision {
+        price_decimals: price_decimals.unwrap_or(defaults.price_decimals),
+        indicator_decimals: indicator_decimals.unwrap_or(defaults.indicator_decimals),
+    };
 
     // Create export directory
     std::fs::create_dir_all("exports").ok();
 
     // Export prices
     let price_file = format!("exports/{}_prices.csv", symbol);
-    let mut wtr = std::fs::File::create(&price_file).map_err(|e| e.to_string())?;
-    use std::io::Write;
-    writeln!(wtr, "date,open,high,low,close,volume").map_err(|e| e.to_string())?;
-    for p in &prices {
-        writeln!(wtr, "{},{},{},{},{},{}", p.date, p.open, p.high, p.low, p.close, p.volume)
-            .map_err(|e| e.to_string())?;
+    let wtr = std::fs::File::create(&price_file)?;
+    let rows = db.export_prices_csv(&symbol, std::io::BufWriter::new(wtr), &precision)?;
+    if rows == 0 {
+        std::fs::remove_file(&price_file).ok();
+        return Ok(CommandResult {
+            success: false,
+            message: format!("No data for {}", symbol),
+        });
     }
 
     // Export indicators
     let ind_file = format!("exports/{}_indicators.csv", symbol);
-    let mut wtr = std::fs::File::create(&ind_file).map_err(|e| e.to_string())?;
-    writeln!(wtr, "indicator,value,date").map_err(|e| e.to_string())?;
-    for i in &indicators {
-        writeln!(wtr, "{},{},{}", i.indicator_name, i.value, i.date).map_err(|e| e.to_string())?;
-    }
+    let wtr = std::fs::File::create(&ind_file)?;
+    db.export_latest_indicators_csv(&symbol, std::io::BufWriter::new(wtr), &precision)?;
 
     println!("[OK] Exported {} to CSV", symbol);
 
@@ -383,6 +1041,64 @@ fn export_csv(state: State<AppState>, symbol: String) -> Result<CommandResult, S
     })
 }
 
+/// Import daily prices for a symbol from a `date,open,high,low,close,volume` CSV
+#[tauri::command]
+fn import_csv(
+    state: State<AppState>,
+    symbol: String,
+    path: String,
+    source: Option<String>,
+) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    let summary = db.import_prices_csv(&path, &symbol, source.as_deref().unwrap_or("csv"))?;
+
+    Ok(CommandResult {
+        success: summary.imported > 0,
+        message: format!(
+            "Imported {} rows for {} ({} skipped)",
+            summary.imported, symbol, summary.skipped
+        ),
+    })
+}
+
+/// Export the full history of one indicator to CSV
+#[tauri::command]
+fn export_indicator_history(
+    state: State<AppState>,
+    symbol: String,
+    indicator_name: String,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    std::fs::create_dir_all("exports").ok();
+
+    let file_name = format!("exports/{}_{}_history.csv", symbol, indicator_name);
+    let file = std::fs::File::create(&file_name)?;
+    let rows = db.export_indicator_history_csv(
+        &symbol,
+        &indicator_name,
+        std::io::BufWriter::new(file),
+        &ExportPrecision::default(),
+    )?;
+
+    if rows == 0 {
+        return Ok(CommandResult {
+            success: false,
+            message: format!("No {} history for {}", indicator_name, symbol),
+        });
+    }
+
+    println!("[OK] Exported {} {} rows for {}", rows, indicator_name, symbol);
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("Exported {} rows to {}", rows, file_name),
+    })
+}
+
 /// Company name to symbol mapping for fuzzy search
 fn get_symbol_mapping() -> std::collections::HashMap<&'static str, &'static str> {
     let mut map = std::collections::HashMap::new();
@@ -457,7 +1173,7 @@ fn get_symbol_mapping() -> std::collections::HashMap<&'static str, &'static str>
 
 /// Search for symbol by name (fuzzy match)
 #[tauri::command]
-fn search_symbol(query: String) -> Result<Vec<String>, String> {
+fn search_symbol(query: String) -> Result<Vec<String>, CommandError> {
     let query = query.to_lowercase();
     let mapping = get_symbol_mapping();
 
@@ -497,6 +1213,10 @@ struct AlertData {
     condition: String,
     triggered: bool,
     created_at: String,
+    base_price: Option<f64>,
+    percent_change: Option<f64>,
+    recurring: bool,
+    last_triggered_at: Option<String>,
 }
 
 /// Add a price alert
@@ -506,18 +1226,25 @@ fn add_alert(
     symbol: String,
     target_price: f64,
     condition: String,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    recurring: Option<bool>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
     let alert_condition = match condition.to_lowercase().as_str() {
         "above" => AlertCondition::Above,
         "below" => AlertCondition::Below,
-        _ => return Err("Invalid condition. Use 'above' or 'below'".to_string()),
+        _ => {
+            return Err(CommandError::invalid_input(
+                "Invalid condition. Use 'above' or 'below'",
+            ))
+        }
     };
 
-    db.add_alert(&symbol, target_price, alert_condition)
-        .map_err(|e| e.to_string())?;
+    let alert_id = db.add_alert(&symbol, target_price, alert_condition)?;
+    if recurring.unwrap_or(false) {
+        db.set_alert_recurring(alert_id, true)?;
+    }
 
     println!("[OK] Added alert for {} {} ${:.2}", symbol, condition, target_price);
 
@@ -527,12 +1254,33 @@ fn add_alert(
     })
 }
 
+/// Add an alert for a percent move from a base price (the latest close if omitted)
+#[tauri::command]
+fn add_percent_alert(
+    state: State<AppState>,
+    symbol: String,
+    percent: f64,
+    base_price: Option<f64>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    db.add_percent_alert(&symbol, base_price, percent)?;
+
+    println!("[OK] Added alert for {} {:+.2}%", symbol, percent);
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("Alert set: {} {:+.2}%", symbol, percent),
+    })
+}
+
 /// Get all alerts
 #[tauri::command]
-fn get_alerts(state: State<AppState>, only_active: bool) -> Result<Vec<AlertData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_alerts(state: State<AppState>, only_active: bool) -> Result<Vec<AlertData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let alerts = db.get_alerts(only_active).map_err(|e| e.to_string())?;
+    let alerts = db.get_alerts(only_active)?;
 
     Ok(alerts
         .into_iter()
@@ -546,16 +1294,20 @@ fn get_alerts(state: State<AppState>, only_active: bool) -> Result<Vec<AlertData
             },
             triggered: a.triggered,
             created_at: a.created_at,
+            base_price: a.base_price,
+            percent_change: a.percent_change,
+            recurring: a.recurring,
+            last_triggered_at: a.last_triggered_at,
         })
         .collect())
 }
 
 /// Delete an alert
 #[tauri::command]
-fn delete_alert(state: State<AppState>, alert_id: i64) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn delete_alert(state: State<AppState>, alert_id: i64) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    db.delete_alert(alert_id).map_err(|e| e.to_string())?;
+    db.delete_alert(alert_id)?;
 
     Ok(CommandResult {
         success: true,
@@ -565,10 +1317,10 @@ fn delete_alert(state: State<AppState>, alert_id: i64) -> Result<CommandResult,
 
 /// Check alerts against current prices
 #[tauri::command]
-fn check_alerts(state: State<AppState>) -> Result<Vec<AlertData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn check_alerts(state: State<AppState>) -> Result<Vec<AlertData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let triggered = db.check_alerts().map_err(|e| e.to_string())?;
+    let triggered = db.check_alerts()?;
 
     Ok(triggered
         .into_iter()
@@ -582,11 +1334,18 @@ fn check_alerts(state: State<AppState>) -> Result<Vec<AlertData>, String> {
             },
             triggered: a.triggered,
             created_at: a.created_at,
+            base_price: a.base_price,
+            percent_change: a.percent_change,
+            recurring: a.recurring,
+            last_triggered_at: a.last_triggered_at,
         })
         .collect())
 }
 
-/// Position data for frontend
+/// Open FIFO holding for frontend
+///
+/// `id`, `date` and `notes` come from the oldest open lot's trade, and
+/// `price` is the average entry price of the open lots.
 #[derive(Serialize)]
 struct PositionData {
     id: i64,
@@ -601,9 +1360,19 @@ struct PositionData {
     cost_basis: f64,
     profit_loss: f64,
     profit_loss_percent: f64,
+    realized_profit_loss: f64,
+    dividend_income: f64,
+    /// Strike and expiration of a `covered_call` or `cash_secured_put`
+    strike: Option<f64>,
+    expiration: Option<String>,
 }
 
 /// Portfolio summary for frontend
+///
+/// The `total_*` P&L figures are unrealized, on open lots only;
+/// `realized_profit_loss` covers every lot closed so far, including symbols
+/// that are now flat. The `*_exposure` figures come from
+/// `Database::portfolio_exposure`.
 #[derive(Serialize)]
 struct PortfolioSummary {
     positions: Vec<PositionData>,
@@ -611,6 +1380,18 @@ struct PortfolioSummary {
     total_cost: f64,
     total_profit_loss: f64,
     total_profit_loss_percent: f64,
+    long_profit_loss: f64,
+    short_profit_loss: f64,
+    gross_value: f64,
+    realized_profit_loss: f64,
+    total_dividend_income: f64,
+    long_exposure: f64,
+    short_exposure: f64,
+    net_exposure: f64,
+    beta_weighted_exposure: f64,
+    exposure_benchmark: String,
+    /// Holdings weighted at beta 1.0 for lack of history
+    missing_beta: Vec<String>,
 }
 
 /// Add a portfolio position
@@ -623,18 +1404,21 @@ fn add_position(
     position_type: String,
     date: String,
     notes: Option<String>,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
     let pos_type = match position_type.to_lowercase().as_str() {
         "buy" => PositionType::Buy,
         "sell" => PositionType::Sell,
-        _ => return Err("Invalid position type. Use 'buy' or 'sell'".to_string()),
+        _ => {
+            return Err(CommandError::invalid_input(
+                "Invalid position type. Use 'buy' or 'sell'",
+            ))
+        }
     };
 
-    db.add_position(&symbol, quantity, price, pos_type, &date, notes.as_deref())
-        .map_err(|e| e.to_string())?;
+    db.add_position(&symbol, quantity, price, pos_type, &date, notes.as_deref())?;
 
     println!(
         "[OK] Added {} position: {} x {} @ ${:.2}",
@@ -650,95 +1434,164 @@ fn add_position(
     })
 }
 
+/// Add a covered call or cash-secured put
+///
+/// `strategy` is `covered_call` or `cash_secured_put`; `quantity` is in
+/// shares (100 per contract) and `premium` is per share.
+#[tauri::command]
+fn add_option_position(
+    state: State<AppState>,
+    symbol: String,
+    strategy: String,
+    quantity: f64,
+    premium: f64,
+    strike: f64,
+    expiration: String,
+    date: String,
+    notes: Option<String>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    let leg = OptionLeg {
+        strategy: strategy.parse::<OptionStrategy>()?,
+        strike,
+        expiration,
+    };
+    db.add_option_position(&symbol, quantity, premium, &leg, &date, notes.as_deref())?;
+
+    println!(
+        "[OK] Added {}: {} x {} {:.2} exp {} @ ${:.2}",
+        strategy, quantity, symbol, strike, leg.expiration, premium
+    );
+
+    Ok(CommandResult {
+        success: true,
+        message: format!(
+            "Added {} on {} shares of {} (strike ${:.2}, exp {}) for ${:.2}",
+            strategy, quantity, symbol, strike, leg.expiration, premium
+        ),
+    })
+}
+
 /// Get portfolio with current values and P&L
+///
+/// Trades are matched first in, first out: sells close the oldest buys (and
+/// buys cover the oldest shorts), so partial exits aren't double-counted.
+/// Covered calls and cash-secured puts are listed individually, valued at
+/// intrinsic value only (see `OptionLeg::valuation`).
 #[tauri::command]
-fn get_portfolio(state: State<AppState>) -> Result<PortfolioSummary, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_portfolio(state: State<AppState>) -> Result<PortfolioSummary, CommandError> {
+    let mut db = state.db.lock()?;
 
-    let positions = db.get_positions().map_err(|e| e.to_string())?;
+    let holdings = db.compute_positions_fifo()?;
 
     let mut position_data = Vec::new();
-    let mut total_value = 0.0;
-    let mut total_cost = 0.0;
+    let mut totals = PortfolioTotals::default();
+    let mut realized_profit_loss = 0.0;
+    let mut total_dividend_income = 0.0;
+
+    for holding in holdings {
+        realized_profit_loss += holding.realized_profit_loss;
+        let Some(oldest) = holding.lots.first() else {
+            continue;
+        };
 
-    for pos in positions {
         let current_price = db
-            .get_latest_price(&pos.symbol)
-            .map_err(|e| e.to_string())?
-            .unwrap_or(pos.price);
-
-        let cost_basis = pos.quantity * pos.price;
-        let current_value = pos.quantity * current_price;
-
-        // For sell positions, P&L is inverted (profit when price drops)
-        let (profit_loss, profit_loss_percent) = match pos.position_type {
-            PositionType::Buy => {
-                let pl = current_value - cost_basis;
-                let pl_pct = if cost_basis > 0.0 {
-                    (pl / cost_basis) * 100.0
-                } else {
-                    0.0
-                };
-                total_value += current_value;
-                total_cost += cost_basis;
-                (pl, pl_pct)
-            }
-            PositionType::Sell => {
-                // Short position: profit when price goes down
-                let pl = cost_basis - current_value;
-                let pl_pct = if cost_basis > 0.0 {
-                    (pl / cost_basis) * 100.0
-                } else {
-                    0.0
-                };
-                // For shorts, we track the liability
-                total_value -= current_value;
-                total_cost -= cost_basis;
-                (pl, pl_pct)
-            }
-        };
+            .get_latest_price(&holding.symbol)?
+            .unwrap_or(holding.average_price());
+
+        // Shorts profit when price drops; longs and shorts are totalled separately
+        let valuation = holding.valuation(current_price);
+        totals.add(holding.position_type, &valuation);
+
+        // Dividends with an ex-date after each open lot's trade; shorts pay them
+        let dividend_income = db.get_fifo_dividend_income(&holding)?;
+        total_dividend_income += dividend_income;
 
         position_data.push(PositionData {
-            id: pos.id,
-            symbol: pos.symbol,
-            quantity: pos.quantity,
-            price: pos.price,
-            position_type: match pos.position_type {
+            id: oldest.position_id,
+            symbol: holding.symbol.clone(),
+            quantity: holding.quantity(),
+            price: holding.average_price(),
+            position_type: match holding.position_type {
                 PositionType::Buy => "buy".to_string(),
                 PositionType::Sell => "sell".to_string(),
             },
-            date: pos.date,
-            notes: pos.notes,
+            date: oldest.date.clone(),
+            notes: oldest.notes.clone(),
             current_price,
-            current_value,
-            cost_basis,
-            profit_loss,
-            profit_loss_percent,
+            current_value: valuation.current_value,
+            cost_basis: valuation.cost_basis,
+            profit_loss: valuation.profit_loss,
+            profit_loss_percent: valuation.profit_loss_percent,
+            realized_profit_loss: holding.realized_profit_loss,
+            dividend_income,
+            strike: None,
+            expiration: None,
         });
     }
 
-    let total_profit_loss = total_value - total_cost;
-    let total_profit_loss_percent = if total_cost.abs() > 0.0 {
-        (total_profit_loss / total_cost.abs()) * 100.0
-    } else {
-        0.0
-    };
+    // Option legs are marked at intrinsic value against the underlying
+    for position in db.get_positions()? {
+        let Some(leg) = &position.option else {
+            continue;
+        };
+        let underlying = db.get_latest_price(&position.symbol)?.unwrap_or(leg.strike);
+        let valuation = leg.valuation(&position, underlying);
+        totals.add(PositionType::Sell, &valuation);
+
+        position_data.push(PositionData {
+            id: position.id,
+            symbol: position.symbol.clone(),
+            quantity: position.quantity,
+            price: position.price,
+            position_type: leg.strategy.as_str().to_string(),
+            date: position.date.clone(),
+            notes: position.notes.clone(),
+            current_price: valuation.current_price,
+            current_value: valuation.current_value,
+            cost_basis: valuation.cost_basis,
+            profit_loss: valuation.profit_loss,
+            profit_loss_percent: valuation.profit_loss_percent,
+            realized_profit_loss: 0.0,
+            dividend_income: 0.0,
+            strike: Some(leg.strike),
+            expiration: Some(leg.expiration.clone()),
+        });
+    }
+
+    let exposure = db.portfolio_exposure()?;
 
     Ok(PortfolioSummary {
         positions: position_data,
-        total_value,
-        total_cost,
-        total_profit_loss,
-        total_profit_loss_percent,
+        total_value: totals.net_value(),
+        total_cost: totals.invested_capital(),
+        total_profit_loss: totals.total_profit_loss(),
+        total_profit_loss_percent: totals.total_profit_loss_percent(),
+        long_profit_loss: totals.long_profit_loss,
+        short_profit_loss: totals.short_profit_loss,
+        gross_value: totals.gross_value(),
+        realized_profit_loss,
+        total_dividend_income,
+        long_exposure: exposure.long_exposure,
+        short_exposure: exposure.short_exposure,
+        net_exposure: exposure.net_exposure,
+        beta_weighted_exposure: exposure.beta_weighted_exposure,
+        exposure_benchmark: exposure.benchmark,
+        missing_beta: exposure.missing_beta,
     })
 }
 
 /// Delete a portfolio position
 #[tauri::command]
-fn delete_position(state: State<AppState>, position_id: i64) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn delete_position(
+    state: State<AppState>,
+    position_id: i64,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    db.delete_position(position_id).map_err(|e| e.to_string())?;
+    db.delete_position(position_id)?;
 
     Ok(CommandResult {
         success: true,
@@ -754,13 +1607,22 @@ struct TrendPoint {
 }
 
 /// Fetch Google Trends data for a keyword
+///
+/// `timeframe` defaults to the last 12 months and `geo` to worldwide.
 #[tauri::command]
-fn fetch_trends(state: State<AppState>, keyword: String) -> Result<CommandResult, String> {
-    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+fn fetch_trends(
+    state: State<AppState>,
+    keyword: String,
+    timeframe: Option<String>,
+    geo: Option<String>,
+) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
 
     let trends = GoogleTrends::new();
+    let timeframe = timeframe.unwrap_or_else(|| DEFAULT_TRENDS_TIMEFRAME.to_string());
+    let geo = geo.unwrap_or_default().trim().to_uppercase();
 
-    match trends.fetch_and_store(&mut db, &keyword) {
+    match trends.fetch_and_store(&mut db, &keyword, timeframe.trim(), &geo) {
         Ok(count) => {
             println!("[OK] Fetched {} trend points for {}", count, keyword);
             Ok(CommandResult {
@@ -779,11 +1641,18 @@ fn fetch_trends(state: State<AppState>, keyword: String) -> Result<CommandResult
 }
 
 /// Get stored trends data for a keyword
+///
+/// `geo` selects the region fetched with `fetch_trends`, worldwide by default.
 #[tauri::command]
-fn get_trends(state: State<AppState>, keyword: String) -> Result<Vec<TrendPoint>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_trends(
+    state: State<AppState>,
+    keyword: String,
+    geo: Option<String>,
+) -> Result<Vec<TrendPoint>, CommandError> {
+    let db = state.db.lock()?;
 
-    let trends = db.get_trends(&keyword).map_err(|e| e.to_string())?;
+    let geo = geo.unwrap_or_default().trim().to_uppercase();
+    let trends = db.get_trends(&keyword, &geo)?;
 
     Ok(trends
         .into_iter()
@@ -794,6 +1663,35 @@ fn get_trends(state: State<AppState>, keyword: String) -> Result<Vec<TrendPoint>
         .collect())
 }
 
+/// API calls made on one day
+#[derive(Serialize)]
+struct ApiUsageData {
+    date: String,
+    calls: i64,
+}
+
+/// Daily API call counts for a source ("yahoo_finance", "FRED", "google_trends")
+/// since a YYYY-MM-DD date
+#[tauri::command]
+fn get_api_usage(
+    state: State<AppState>,
+    source: String,
+    since: String,
+) -> Result<Vec<ApiUsageData>, CommandError> {
+    let since = since
+        .parse()
+        .map_err(|_| format!("Invalid date: {}", since))?;
+
+    let db = state.db.lock()?;
+
+    let usage = db.get_api_usage(&source, since)?;
+
+    Ok(usage
+        .into_iter()
+        .map(|(date, calls)| ApiUsageData { date, calls })
+        .collect())
+}
+
 // ============================================================================
 // Signal Commands
 // ============================================================================
@@ -812,17 +1710,25 @@ struct SignalData {
     timestamp: String,
     created_at: String,
     acknowledged: bool,
+    backfilled: bool,
+    /// Weekly trend agreement; null unless generated with `confirm_weekly`
+    confirmed: Option<bool>,
 }
 
-/// Generate signals for a symbol
+/// Generate signals for a symbol, optionally confirmed against the weekly trend
 #[tauri::command]
-fn generate_signals(state: State<AppState>, symbol: String) -> Result<CommandResult, String> {
-    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+fn generate_signals(
+    state: State<AppState>,
+    symbol: String,
+    backfill: Option<bool>,
+    confirm_weekly: Option<bool>,
+) -> Result<CommandResult, CommandError> {
+    let mut db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
     // Get prices and indicators
-    let prices = db.get_prices(&symbol).map_err(|e| e.to_string())?;
-    let indicators = db.get_all_indicators(&symbol).map_err(|e| e.to_string())?;
+    let prices = db.get_prices(&symbol)?;
+    let indicators = db.get_all_indicators(&symbol)?;
 
     if prices.is_empty() {
         return Ok(CommandResult {
@@ -838,13 +1744,23 @@ fn generate_signals(state: State<AppState>, symbol: String) -> Result<CommandRes
         });
     }
 
-    // Generate signals
-    let engine = SignalEngine::new();
-    let signals = engine.generate_signals(&symbol, &indicators, &prices);
+    // Generate signals with the stored thresholds; backfills are stamped
+    // with each signal's own date
+    let config = db.get_signal_config()?.unwrap_or_default();
+    let engine = SignalEngine::with_config(SignalConfig {
+        backfill: backfill.unwrap_or(config.backfill),
+        ..config
+    });
+    let signals = if confirm_weekly.unwrap_or(false) {
+        let weekly = calculate_all_with_timeframe(&prices, ResamplePeriod::Weekly);
+        engine.generate_signals_mtf(&symbol, &indicators, &weekly, &prices)
+    } else {
+        engine.generate_signals(&symbol, &indicators, &prices)
+    };
     let count = signals.len();
 
     // Store signals
-    db.upsert_signals(&signals).map_err(|e| e.to_string())?;
+    engine.store_signals(&mut db, &signals)?;
 
     println!("[OK] Generated {} signals for {}", count, symbol);
 
@@ -854,19 +1770,50 @@ fn generate_signals(state: State<AppState>, symbol: String) -> Result<CommandRes
     })
 }
 
+/// Per-symbol summary from a batch signal scan
+#[derive(Serialize)]
+struct SignalScanData {
+    symbol: String,
+    generated: usize,
+    bullish: usize,
+    bearish: usize,
+    score: f64,
+}
+
+/// Generate and store signals for every symbol with prices and indicators,
+/// strongest bullish score first
+#[tauri::command]
+fn scan_all_symbols(state: State<AppState>) -> Result<Vec<SignalScanData>, CommandError> {
+    let mut db = state.db.lock()?;
+
+    let config = db.get_signal_config()?.unwrap_or_default();
+    let scans = SignalEngine::with_config(config).scan_all_symbols(&mut db)?;
+
+    println!("[OK] Scanned {} symbols for signals", scans.len());
+
+    Ok(scans
+        .into_iter()
+        .map(|s| SignalScanData {
+            symbol: s.symbol,
+            generated: s.generated,
+            bullish: s.bullish,
+            bearish: s.bearish,
+            score: s.score,
+        })
+        .collect())
+}
+
 /// Get signals for a symbol
 #[tauri::command]
 fn get_signals(
     state: State<AppState>,
     symbol: String,
     only_unacknowledged: bool,
-) -> Result<Vec<SignalData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<SignalData>, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
-    let signals = db
-        .get_signals(&symbol, only_unacknowledged)
-        .map_err(|e| e.to_string())?;
+    let signals = db.get_signals(&symbol, only_unacknowledged)?;
 
     Ok(signals
         .into_iter()
@@ -882,16 +1829,18 @@ fn get_signals(
             timestamp: s.timestamp.to_string(),
             created_at: s.created_at,
             acknowledged: s.acknowledged,
+            backfilled: s.backfilled,
+            confirmed: s.confirmed,
         })
         .collect())
 }
 
 /// Get all recent signals across all symbols
 #[tauri::command]
-fn get_all_signals(state: State<AppState>, limit: usize) -> Result<Vec<SignalData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_all_signals(state: State<AppState>, limit: usize) -> Result<Vec<SignalData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let signals = db.get_recent_signals(limit).map_err(|e| e.to_string())?;
+    let signals = db.get_recent_signals(limit)?;
 
     Ok(signals
         .into_iter()
@@ -907,40 +1856,102 @@ fn get_all_signals(state: State<AppState>, limit: usize) -> Result<Vec<SignalDat
             timestamp: s.timestamp.to_string(),
             created_at: s.created_at,
             acknowledged: s.acknowledged,
+            backfilled: s.backfilled,
+            confirmed: s.confirmed,
         })
         .collect())
 }
 
-/// Acknowledge a signal
+/// Acknowledge a signal
+#[tauri::command]
+fn acknowledge_signal(
+    state: State<AppState>,
+    signal_id: i64,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+
+    db.acknowledge_signal(signal_id)?;
+
+    Ok(CommandResult {
+        success: true,
+        message: "Signal acknowledged".to_string(),
+    })
+}
+
+/// Acknowledge all signals for a symbol
+#[tauri::command]
+fn acknowledge_all_signals(
+    state: State<AppState>,
+    symbol: String,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    db.acknowledge_all_signals(&symbol)?;
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("All signals for {} acknowledged", symbol),
+    })
+}
+
+/// Net signal score for a symbol
+#[derive(Serialize)]
+struct SignalScoreData {
+    symbol: String,
+    score: f64,
+}
+
+/// Rank symbols by net signal score over the last `days` days
+#[tauri::command]
+fn get_signal_scoreboard(
+    state: State<AppState>,
+    days: i64,
+) -> Result<Vec<SignalScoreData>, CommandError> {
+    let db = state.db.lock()?;
+
+    let scores = db.get_signal_scoreboard(days)?;
+
+    Ok(scores
+        .into_iter()
+        .map(|(symbol, score)| SignalScoreData { symbol, score })
+        .collect())
+}
+
+/// Net score of a symbol's open signals on their most recent date, in [-1, 1]
 #[tauri::command]
-fn acknowledge_signal(state: State<AppState>, signal_id: i64) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_signal_score(
+    state: State<AppState>,
+    symbol: String,
+) -> Result<SignalScoreData, CommandError> {
+    let db = state.db.lock()?;
 
-    db.acknowledge_signal(signal_id)
-        .map_err(|e| e.to_string())?;
+    let signals = db.get_signals(&symbol, true)?;
 
-    Ok(CommandResult {
-        success: true,
-        message: "Signal acknowledged".to_string(),
+    Ok(SignalScoreData {
+        score: SignalEngine::score(&signals),
+        symbol,
     })
 }
 
-/// Acknowledge all signals for a symbol
+/// Rank every symbol with price data by its latest signal score, highest first
 #[tauri::command]
-fn acknowledge_all_signals(
-    state: State<AppState>,
-    symbol: String,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let symbol = symbol.to_uppercase();
+fn get_all_signal_scores(state: State<AppState>) -> Result<Vec<SignalScoreData>, CommandError> {
+    let db = state.db.lock()?;
 
-    db.acknowledge_all_signals(&symbol)
-        .map_err(|e| e.to_string())?;
+    let symbols = db.get_symbols_with_data()?;
 
-    Ok(CommandResult {
-        success: true,
-        message: format!("All signals for {} acknowledged", symbol),
-    })
+    let mut scores = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let signals = db.get_signals(&symbol, true)?;
+        scores.push(SignalScoreData {
+            score: SignalEngine::score(&signals),
+            symbol,
+        });
+    }
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.symbol.cmp(&b.symbol)));
+
+    Ok(scores)
 }
 
 // ============================================================================
@@ -961,6 +1972,8 @@ struct IndicatorAlertData {
     last_value: Option<f64>,
     created_at: String,
     message: Option<String>,
+    recurring: bool,
+    last_triggered_at: Option<String>,
 }
 
 /// Add an indicator alert
@@ -974,15 +1987,16 @@ fn add_indicator_alert(
     condition: String,
     threshold: Option<f64>,
     message: Option<String>,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    recurring: Option<bool>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
     let alert_type_enum = IndicatorAlertType::from_str(&alert_type)
         .ok_or_else(|| "Invalid alert type. Use 'threshold', 'crossover', or 'band_touch'".to_string())?;
 
     let condition_enum = IndicatorAlertCondition::from_str(&condition)
-        .ok_or_else(|| "Invalid condition. Use 'crosses_above', 'crosses_below', 'bullish_crossover', or 'bearish_crossover'".to_string())?;
+        .ok_or_else(|| "Invalid condition. Use 'crosses_above', 'crosses_below', 'bullish_crossover', 'bearish_crossover', 'stays_above', or 'stays_below'".to_string())?;
 
     let alert = IndicatorAlert {
         id: 0,
@@ -996,9 +2010,11 @@ fn add_indicator_alert(
         last_value: None,
         created_at: String::new(),
         message,
+        recurring: recurring.unwrap_or(false),
+        last_triggered_at: None,
     };
 
-    db.add_indicator_alert(&alert).map_err(|e| e.to_string())?;
+    db.add_indicator_alert(&alert)?;
 
     println!(
         "[OK] Added indicator alert for {} {} {} {}",
@@ -1019,10 +2035,10 @@ fn add_indicator_alert(
 fn get_indicator_alerts(
     state: State<AppState>,
     only_active: bool,
-) -> Result<Vec<IndicatorAlertData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<IndicatorAlertData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let alerts = db.get_indicator_alerts(only_active).map_err(|e| e.to_string())?;
+    let alerts = db.get_indicator_alerts(only_active)?;
 
     Ok(alerts
         .into_iter()
@@ -1038,6 +2054,8 @@ fn get_indicator_alerts(
             last_value: a.last_value,
             created_at: a.created_at,
             message: a.message,
+            recurring: a.recurring,
+            last_triggered_at: a.last_triggered_at,
         })
         .collect())
 }
@@ -1047,10 +2065,10 @@ fn get_indicator_alerts(
 fn delete_indicator_alert(
     state: State<AppState>,
     alert_id: i64,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    db.delete_indicator_alert(alert_id).map_err(|e| e.to_string())?;
+    db.delete_indicator_alert(alert_id)?;
 
     Ok(CommandResult {
         success: true,
@@ -1060,10 +2078,10 @@ fn delete_indicator_alert(
 
 /// Check all indicator alerts, returns triggered alerts
 #[tauri::command]
-fn check_indicator_alerts(state: State<AppState>) -> Result<Vec<IndicatorAlertData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn check_indicator_alerts(state: State<AppState>) -> Result<Vec<IndicatorAlertData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let triggered = db.check_indicator_alerts().map_err(|e| e.to_string())?;
+    let triggered = db.check_indicator_alerts()?;
 
     Ok(triggered
         .into_iter()
@@ -1079,6 +2097,8 @@ fn check_indicator_alerts(state: State<AppState>) -> Result<Vec<IndicatorAlertDa
             last_value: a.last_value,
             created_at: a.created_at,
             message: a.message,
+            recurring: a.recurring,
+            last_triggered_at: a.last_triggered_at,
         })
         .collect())
 }
@@ -1099,7 +2119,12 @@ struct StrategyData {
     exit_threshold: f64,
     stop_loss_percent: Option<f64>,
     take_profit_percent: Option<f64>,
+    trailing_stop_percent: Option<f64>,
     position_size_percent: f64,
+    direction: String,
+    entry_rules: Option<StrategyRules>,
+    exit_rules: Option<StrategyRules>,
+    position_sizing: Option<PositionSizing>,
     created_at: String,
 }
 
@@ -1133,8 +2158,40 @@ struct MetricsData {
     losing_trades: usize,
     avg_win_percent: f64,
     avg_loss_percent: f64,
-    profit_factor: f64,
+    /// None when infinite (no losing trades)
+    profit_factor: Option<f64>,
     avg_trade_duration_days: f64,
+    trades_per_year: f64,
+    time_in_market_percent: f64,
+    information_ratio: f64,
+    /// None when infinite (no losing days)
+    sortino_ratio: Option<f64>,
+    /// None when infinite (no drawdown)
+    calmar_ratio: Option<f64>,
+}
+
+impl From<&PerformanceMetrics> for MetricsData {
+    fn from(m: &PerformanceMetrics) -> Self {
+        Self {
+            total_return: m.total_return,
+            total_return_dollars: m.total_return_dollars,
+            max_drawdown: m.max_drawdown,
+            sharpe_ratio: m.sharpe_ratio,
+            win_rate: m.win_rate,
+            total_trades: m.total_trades,
+            winning_trades: m.winning_trades,
+            losing_trades: m.losing_trades,
+            avg_win_percent: m.avg_win_percent,
+            avg_loss_percent: m.avg_loss_percent,
+            profit_factor: m.profit_factor.is_finite().then_some(m.profit_factor),
+            avg_trade_duration_days: m.avg_trade_duration_days,
+            trades_per_year: m.trades_per_year,
+            time_in_market_percent: m.time_in_market_percent,
+            information_ratio: m.information_ratio,
+            sortino_ratio: m.sortino_ratio.is_finite().then_some(m.sortino_ratio),
+            calmar_ratio: m.calmar_ratio.is_finite().then_some(m.calmar_ratio),
+        }
+    }
 }
 
 /// Backtest result data for frontend
@@ -1151,6 +2208,76 @@ struct BacktestResultData {
     metrics: MetricsData,
     trades: Vec<BacktestTradeData>,
     created_at: String,
+    warnings: Vec<String>,
+    /// Bars ignored because of a non-positive close
+    skipped_bars: usize,
+    equity_curve: Vec<EquityPointData>,
+}
+
+/// One point of a backtest equity curve
+#[derive(Serialize)]
+struct EquityPointData {
+    date: String,
+    value: f64,
+}
+
+impl From<BacktestResult> for BacktestResultData {
+    fn from(result: BacktestResult) -> Self {
+        Self {
+            id: result.id,
+            strategy_id: result.strategy_id,
+            strategy_name: result.strategy_name,
+            symbol: result.symbol,
+            start_date: result.start_date.to_string(),
+            end_date: result.end_date.to_string(),
+            initial_capital: result.initial_capital,
+            final_capital: result.final_capital,
+            metrics: MetricsData::from(&result.metrics),
+            trades: result
+                .trades
+                .into_iter()
+                .map(|t| BacktestTradeData {
+                    id: t.id,
+                    symbol: t.symbol,
+                    direction: t.direction.as_str().to_string(),
+                    entry_date: t.entry_date.to_string(),
+                    entry_price: t.entry_price,
+                    entry_reason: t.entry_reason,
+                    exit_date: t.exit_date.map(|d| d.to_string()),
+                    exit_price: t.exit_price,
+                    exit_reason: t.exit_reason,
+                    shares: t.shares,
+                    profit_loss: t.profit_loss,
+                    profit_loss_percent: t.profit_loss_percent,
+                })
+                .collect(),
+            created_at: result.created_at,
+            warnings: result.warnings,
+            skipped_bars: result.skipped_bars,
+            equity_curve: result
+                .equity_curve
+                .into_iter()
+                .map(|(date, value)| EquityPointData {
+                    date: date.to_string(),
+                    value,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A symbol skipped by a multi-symbol backtest
+#[derive(Serialize)]
+struct SkippedSymbolData {
+    symbol: String,
+    reason: String,
+}
+
+/// Ranked results of one strategy across all symbols
+#[derive(Serialize)]
+struct MultiSymbolBacktestData {
+    results: Vec<BacktestResultData>,
+    skipped: Vec<SkippedSymbolData>,
 }
 
 /// Save a strategy
@@ -1166,8 +2293,13 @@ fn save_strategy(
     stop_loss_percent: Option<f64>,
     take_profit_percent: Option<f64>,
     position_size_percent: f64,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    direction: Option<String>,
+    trailing_stop_percent: Option<f64>,
+    entry_rules: Option<StrategyRules>,
+    exit_rules: Option<StrategyRules>,
+    position_sizing: Option<PositionSizing>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
     let entry_cond = StrategyConditionType::from_str(&entry_condition)
         .ok_or_else(|| format!("Invalid entry condition: {}", entry_condition))?;
@@ -1184,11 +2316,18 @@ fn save_strategy(
         exit_threshold,
         stop_loss_percent,
         take_profit_percent,
+        trailing_stop_percent,
         position_size_percent,
+        direction: direction
+            .map(|d| TradeDirection::from_str(&d))
+            .unwrap_or_default(),
+        entry_rules,
+        exit_rules,
+        position_sizing,
         created_at: String::new(),
     };
 
-    db.save_strategy(&strategy).map_err(|e| e.to_string())?;
+    db.save_strategy(&strategy)?;
 
     println!("[OK] Saved strategy: {}", name);
 
@@ -1200,10 +2339,10 @@ fn save_strategy(
 
 /// Get all strategies
 #[tauri::command]
-fn get_strategies(state: State<AppState>) -> Result<Vec<StrategyData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_strategies(state: State<AppState>) -> Result<Vec<StrategyData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let strategies = db.get_strategies().map_err(|e| e.to_string())?;
+    let strategies = db.get_strategies()?;
 
     Ok(strategies
         .into_iter()
@@ -1217,7 +2356,12 @@ fn get_strategies(state: State<AppState>) -> Result<Vec<StrategyData>, String> {
             exit_threshold: s.exit_threshold,
             stop_loss_percent: s.stop_loss_percent,
             take_profit_percent: s.take_profit_percent,
+            trailing_stop_percent: s.trailing_stop_percent,
             position_size_percent: s.position_size_percent,
+            direction: s.direction.as_str().to_string(),
+            entry_rules: s.entry_rules,
+            exit_rules: s.exit_rules,
+            position_sizing: s.position_sizing,
             created_at: s.created_at,
         })
         .collect())
@@ -1225,10 +2369,10 @@ fn get_strategies(state: State<AppState>) -> Result<Vec<StrategyData>, String> {
 
 /// Delete a strategy
 #[tauri::command]
-fn delete_strategy(state: State<AppState>, name: String) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn delete_strategy(state: State<AppState>, name: String) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    db.delete_strategy(&name).map_err(|e| e.to_string())?;
+    db.delete_strategy(&name)?;
 
     Ok(CommandResult {
         success: true,
@@ -1243,90 +2387,355 @@ fn run_backtest(
     strategy_name: String,
     symbol: String,
     initial_capital: f64,
-) -> Result<BacktestResultData, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    start_date: Option<String>,
+    use_adjusted_close: Option<bool>,
+    timeframe: Option<String>,
+    max_positions: Option<usize>,
+    allow_pyramiding: Option<bool>,
+) -> Result<BacktestResultData, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
     // Get strategy
-    let strategy = db
-        .get_strategy(&strategy_name)
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| format!("Strategy '{}' not found", strategy_name))?;
+    let strategy = db.get_strategy(&strategy_name)?.ok_or_else(|| {
+        CommandError::not_found(format!("Strategy '{}' not found", strategy_name))
+    })?;
 
     // Get prices and indicators
-    let prices = db.get_prices(&symbol).map_err(|e| e.to_string())?;
-    let indicators = db.get_all_indicators(&symbol).map_err(|e| e.to_string())?;
+    let prices = db.get_prices(&symbol)?;
+    let indicators = db.get_all_indicators(&symbol)?;
 
     if prices.is_empty() {
-        return Err(format!("No price data for {}", symbol));
+        return Err(CommandError::not_found(format!(
+            "No price data for {}",
+            symbol
+        )));
     }
 
     if indicators.is_empty() {
-        return Err(format!(
+        return Err(CommandError::not_found(format!(
             "No indicator data for {}. Calculate indicators first.",
             symbol
-        ));
+        )));
     }
 
+    // Optional requested window start (YYYY-MM-DD)
+    let requested_start = start_date
+        .map(|d| d.parse().map_err(|_| format!("Invalid start date: {}", d)))
+        .transpose()?;
+
+    let timeframe = match timeframe {
+        Some(t) => t.parse::<ResamplePeriod>()?,
+        None => ResamplePeriod::Daily,
+    };
+
     // Run backtest
     let config = BacktestConfig {
         initial_capital,
-        commission_per_trade: 0.0,
+        requested_start,
+        use_adjusted_close: use_adjusted_close.unwrap_or(false),
+        timeframe,
+        max_positions: max_positions.unwrap_or(1),
+        allow_pyramiding: allow_pyramiding.unwrap_or(false),
+        ..BacktestConfig::default()
     };
+    let benchmark_symbol = db.benchmark_symbol()?;
+    let benchmark = db.get_prices(&benchmark_symbol)?;
+
     let engine = BacktestEngine::new(config);
-    let result = engine.run(&strategy, &symbol, &prices, &indicators);
+    let result = engine.run_against_benchmark(&strategy, &symbol, &prices, &indicators, &benchmark);
 
     // Save result
-    db.save_backtest_result(&result).map_err(|e| e.to_string())?;
+    db.save_backtest_result(&result)?;
 
     println!(
         "[OK] Backtest completed for {} on {}: {:.2}% return",
         strategy_name, symbol, result.metrics.total_return
     );
 
-    // Convert to frontend format
-    Ok(BacktestResultData {
-        id: result.id,
-        strategy_id: result.strategy_id,
-        strategy_name: result.strategy_name,
-        symbol: result.symbol,
-        start_date: result.start_date.to_string(),
-        end_date: result.end_date.to_string(),
-        initial_capital: result.initial_capital,
-        final_capital: result.final_capital,
-        metrics: MetricsData {
-            total_return: result.metrics.total_return,
-            total_return_dollars: result.metrics.total_return_dollars,
-            max_drawdown: result.metrics.max_drawdown,
-            sharpe_ratio: result.metrics.sharpe_ratio,
-            win_rate: result.metrics.win_rate,
-            total_trades: result.metrics.total_trades,
-            winning_trades: result.metrics.winning_trades,
-            losing_trades: result.metrics.losing_trades,
-            avg_win_percent: result.metrics.avg_win_percent,
-            avg_loss_percent: result.metrics.avg_loss_percent,
-            profit_factor: result.metrics.profit_factor,
-            avg_trade_duration_days: result.metrics.avg_trade_duration_days,
+    Ok(BacktestResultData::from(result))
+}
+
+/// One strategy's row in a comparison leaderboard
+#[derive(Serialize)]
+struct StrategyRankData {
+    strategy_name: String,
+    total_return: f64,
+    sharpe_ratio: f64,
+    max_drawdown: f64,
+    win_rate: f64,
+    total_trades: usize,
+    /// Saved run id, when the comparison was asked to save
+    backtest_id: Option<i64>,
+}
+
+/// Backtest several saved strategies on one symbol, best total return first
+///
+/// Runs are only saved when `save` is true.
+#[tauri::command]
+fn compare_strategies(
+    state: State<AppState>,
+    symbol: String,
+    strategy_names: Vec<String>,
+    initial_capital: f64,
+    save: Option<bool>,
+) -> Result<Vec<StrategyRankData>, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    if strategy_names.is_empty() {
+        return Err(CommandError::invalid_input("No strategies to compare"));
+    }
+    let strategies = strategy_names
+        .iter()
+        .map(|name| {
+            db.get_strategy(name)?
+                .ok_or_else(|| CommandError::not_found(format!("Strategy '{}' not found", name)))
+        })
+        .collect::<Result<Vec<Strategy>, CommandError>>()?;
+
+    let prices = db.get_prices(&symbol)?;
+    let indicators = db.get_all_indicators(&symbol)?;
+    if prices.is_empty() || indicators.is_empty() {
+        return Err(CommandError::not_found(format!(
+            "No price or indicator data for {}",
+            symbol
+        )));
+    }
+
+    let benchmark = db.get_prices(&db.benchmark_symbol()?)?;
+    let engine = BacktestEngine::new(BacktestConfig {
+        initial_capital,
+        ..BacktestConfig::default()
+    });
+    let results = engine.compare_strategies(&strategies, &symbol, &prices, &indicators, &benchmark);
+
+    println!("[OK] Compared {} strategies on {}", results.len(), symbol);
+
+    let save = save.unwrap_or(false);
+    results
+        .into_iter()
+        .map(|result| {
+            let backtest_id = if save {
+                Some(db.save_backtest_result(&result)?)
+            } else {
+                None
+            };
+            Ok(StrategyRankData {
+                strategy_name: result.strategy_name,
+                total_return: result.metrics.total_return,
+                sharpe_ratio: result.metrics.sharpe_ratio,
+                max_drawdown: result.metrics.max_drawdown,
+                win_rate: result.metrics.win_rate,
+                total_trades: result.metrics.total_trades,
+                backtest_id,
+            })
+        })
+        .collect()
+}
+
+/// In-sample score of one grid value
+#[derive(Serialize)]
+struct SweepPointData {
+    value: f64,
+    metrics: MetricsData,
+}
+
+/// Walk-forward optimization result for frontend
+#[derive(Serialize)]
+struct WalkForwardData {
+    strategy_name: String,
+    symbol: String,
+    param: String,
+    objective: String,
+    best_value: f64,
+    split_date: String,
+    sweep: Vec<SweepPointData>,
+    in_sample: MetricsData,
+    out_of_sample: MetricsData,
+}
+
+/// Tune one strategy parameter in-sample and report it out-of-sample
+///
+/// `param` is e.g. `entry_threshold`; `objective` is `total_return`
+/// (default) or `sharpe_ratio`. The first `train_frac` of the bars
+/// (default 0.7) is in-sample. The saved strategy is left unchanged.
+#[tauri::command]
+fn optimize_strategy(
+    state: State<AppState>,
+    strategy_name: String,
+    symbol: String,
+    param: String,
+    values: Vec<f64>,
+    objective: Option<String>,
+    train_frac: Option<f64>,
+    initial_capital: Option<f64>,
+) -> Result<WalkForwardData, CommandError> {
+    let db = state.db.lock()?;
+    let symbol = symbol.to_uppercase();
+
+    let strategy = db.get_strategy(&strategy_name)?.ok_or_else(|| {
+        CommandError::not_found(format!("Strategy '{}' not found", strategy_name))
+    })?;
+
+    let prices = db.get_prices(&symbol)?;
+    let indicators = db.get_all_indicators(&symbol)?;
+    if prices.is_empty() || indicators.is_empty() {
+        return Err(CommandError::not_found(format!(
+            "No price or indicator data for {}",
+            symbol
+        )));
+    }
+
+    let grid = ParamGrid {
+        param: param.parse::<StrategyParam>()?,
+        values,
+        objective: match objective {
+            Some(o) => o.parse::<OptimizeMetric>()?,
+            None => OptimizeMetric::default(),
         },
-        trades: result
-            .trades
-            .into_iter()
-            .map(|t| BacktestTradeData {
-                id: t.id,
-                symbol: t.symbol,
-                direction: t.direction.as_str().to_string(),
-                entry_date: t.entry_date.to_string(),
-                entry_price: t.entry_price,
-                entry_reason: t.entry_reason,
-                exit_date: t.exit_date.map(|d| d.to_string()),
-                exit_price: t.exit_price,
-                exit_reason: t.exit_reason,
-                shares: t.shares,
-                profit_loss: t.profit_loss,
-                profit_loss_percent: t.profit_loss_percent,
+    };
+
+    let mut config = BacktestConfig::default();
+    if let Some(capital) = initial_capital {
+        config.initial_capital = capital;
+    }
+    let engine = BacktestEngine::new(config);
+    let result = engine.walk_forward(
+        &strategy,
+        &grid,
+        &prices,
+        &indicators,
+        train_frac.unwrap_or(0.7),
+    )?;
+
+    println!(
+        "[OK] Walk-forward {} on {}: {} = {} ({:.2}% in-sample, {:.2}% out-of-sample)",
+        strategy_name,
+        symbol,
+        result.param.as_str(),
+        result.best_value,
+        result.in_sample.total_return,
+        result.out_of_sample.total_return
+    );
+
+    Ok(WalkForwardData {
+        strategy_name,
+        symbol,
+        param: result.param.as_str().to_string(),
+        objective: result.objective.as_str().to_string(),
+        best_value: result.best_value,
+        split_date: result.split_date.to_string(),
+        sweep: result
+            .sweep
+            .iter()
+            .map(|(value, metrics)| SweepPointData {
+                value: *value,
+                metrics: MetricsData::from(metrics),
             })
             .collect(),
-        created_at: result.created_at,
+        in_sample: MetricsData::from(&result.in_sample),
+        out_of_sample: MetricsData::from(&result.out_of_sample),
+    })
+}
+
+/// Run a strategy across a watchlist sharing one capital pool
+#[tauri::command]
+fn run_portfolio_backtest(
+    state: State<AppState>,
+    strategy_name: String,
+    watchlist_name: String,
+    initial_capital: f64,
+    start_date: Option<String>,
+    use_adjusted_close: Option<bool>,
+) -> Result<BacktestResultData, CommandError> {
+    let db = state.db.lock()?;
+
+    let strategy = db.get_strategy(&strategy_name)?.ok_or_else(|| {
+        CommandError::not_found(format!("Strategy '{}' not found", strategy_name))
+    })?;
+
+    let symbols = db.get_watchlist(&watchlist_name)?;
+    if symbols.is_empty() {
+        return Err(CommandError::not_found(format!(
+            "Watchlist '{}' is empty or doesn't exist",
+            watchlist_name
+        )));
+    }
+
+    let mut basket = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let prices = db.get_prices(&symbol)?;
+        let indicators = db.get_all_indicators(&symbol)?;
+        basket.push((symbol, prices, indicators));
+    }
+
+    let requested_start = start_date
+        .map(|d| d.parse().map_err(|_| format!("Invalid start date: {}", d)))
+        .transpose()?;
+
+    let engine = BacktestEngine::new(BacktestConfig {
+        initial_capital,
+        requested_start,
+        use_adjusted_close: use_adjusted_close.unwrap_or(false),
+        ..BacktestConfig::default()
+    });
+    let mut result = engine.run_portfolio(&strategy, &basket);
+    result.id = db.save_backtest_result(&result)?;
+
+    println!(
+        "[OK] Portfolio backtest of {} on '{}' ({} symbols): {:.2}% return",
+        strategy_name,
+        watchlist_name,
+        basket.len(),
+        result.metrics.total_return
+    );
+
+    Ok(BacktestResultData::from(result))
+}
+
+/// Run a strategy on every symbol with data and rank by total return
+#[tauri::command]
+fn backtest_across_symbols(
+    state: State<AppState>,
+    strategy_name: String,
+    initial_capital: f64,
+) -> Result<MultiSymbolBacktestData, CommandError> {
+    let db = state.db.lock()?;
+
+    let strategy = db.get_strategy(&strategy_name)?.ok_or_else(|| {
+        CommandError::not_found(format!("Strategy '{}' not found", strategy_name))
+    })?;
+
+    let engine = BacktestEngine::new(BacktestConfig {
+        initial_capital,
+        ..BacktestConfig::default()
+    });
+    let outcome = engine.run_across_symbols(&db, &strategy)?;
+
+    let mut results = Vec::with_capacity(outcome.results.len());
+    for mut result in outcome.results {
+        result.id = db.save_backtest_result(&result)?;
+        results.push(BacktestResultData::from(result));
+    }
+
+    for (symbol, reason) in &outcome.skipped {
+        println!("[WARN] Skipped {} for {}: {}", symbol, strategy_name, reason);
+    }
+    println!(
+        "[OK] Backtested {} on {} symbols ({} skipped)",
+        strategy_name,
+        results.len(),
+        outcome.skipped.len()
+    );
+
+    Ok(MultiSymbolBacktestData {
+        results,
+        skipped: outcome
+            .skipped
+            .into_iter()
+            .map(|(symbol, reason)| SkippedSymbolData { symbol, reason })
+            .collect(),
     })
 }
 
@@ -1337,45 +2746,14 @@ fn get_backtest_results(
     strategy_name: Option<String>,
     symbol: Option<String>,
     limit: usize,
-) -> Result<Vec<BacktestResultData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<BacktestResultData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let results = db
-        .get_backtest_results(
-            strategy_name.as_deref(),
-            symbol.as_deref(),
-            limit,
-        )
-        .map_err(|e| e.to_string())?;
+    let results = db.get_backtest_results(strategy_name.as_deref(), symbol.as_deref(), limit)?;
 
     Ok(results
         .into_iter()
-        .map(|r| BacktestResultData {
-            id: r.id,
-            strategy_id: r.strategy_id,
-            strategy_name: r.strategy_name,
-            symbol: r.symbol,
-            start_date: r.start_date.to_string(),
-            end_date: r.end_date.to_string(),
-            initial_capital: r.initial_capital,
-            final_capital: r.final_capital,
-            metrics: MetricsData {
-                total_return: r.metrics.total_return,
-                total_return_dollars: r.metrics.total_return_dollars,
-                max_drawdown: r.metrics.max_drawdown,
-                sharpe_ratio: r.metrics.sharpe_ratio,
-                win_rate: r.metrics.win_rate,
-                total_trades: r.metrics.total_trades,
-                winning_trades: r.metrics.winning_trades,
-                losing_trades: r.metrics.losing_trades,
-                avg_win_percent: r.metrics.avg_win_percent,
-                avg_loss_percent: r.metrics.avg_loss_percent,
-                profit_factor: r.metrics.profit_factor,
-                avg_trade_duration_days: r.metrics.avg_trade_duration_days,
-            },
-            trades: Vec::new(), // Trades not loaded in list view
-            created_at: r.created_at,
-        })
+        .map(BacktestResultData::from) // Trades are not loaded for list view
         .collect())
 }
 
@@ -1384,64 +2762,103 @@ fn get_backtest_results(
 fn get_backtest_detail(
     state: State<AppState>,
     backtest_id: i64,
-) -> Result<Option<BacktestResultData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<Option<BacktestResultData>, CommandError> {
+    let db = state.db.lock()?;
+
+    let result = db.get_backtest_detail(backtest_id)?;
+
+    Ok(result.map(BacktestResultData::from))
+}
+
+/// Monte Carlo robustness check for frontend
+#[derive(Serialize)]
+struct MonteCarloData {
+    backtest_id: i64,
+    iterations: usize,
+    trades: usize,
+    observed_return: f64,
+    return_p5: f64,
+    return_p50: f64,
+    return_p95: f64,
+    drawdown_p5: f64,
+    drawdown_p50: f64,
+    drawdown_p95: f64,
+}
+
+/// Resample a saved backtest's trades to gauge how much of it was luck
+///
+/// `iterations` defaults to 1000.
+#[tauri::command]
+fn monte_carlo_backtest(
+    state: State<AppState>,
+    backtest_id: i64,
+    iterations: Option<usize>,
+) -> Result<MonteCarloData, CommandError> {
+    let db = state.db.lock()?;
 
     let result = db
-        .get_backtest_detail(backtest_id)
-        .map_err(|e| e.to_string())?;
-
-    Ok(result.map(|r| BacktestResultData {
-        id: r.id,
-        strategy_id: r.strategy_id,
-        strategy_name: r.strategy_name,
-        symbol: r.symbol,
-        start_date: r.start_date.to_string(),
-        end_date: r.end_date.to_string(),
-        initial_capital: r.initial_capital,
-        final_capital: r.final_capital,
-        metrics: MetricsData {
-            total_return: r.metrics.total_return,
-            total_return_dollars: r.metrics.total_return_dollars,
-            max_drawdown: r.metrics.max_drawdown,
-            sharpe_ratio: r.metrics.sharpe_ratio,
-            win_rate: r.metrics.win_rate,
-            total_trades: r.metrics.total_trades,
-            winning_trades: r.metrics.winning_trades,
-            losing_trades: r.metrics.losing_trades,
-            avg_win_percent: r.metrics.avg_win_percent,
-            avg_loss_percent: r.metrics.avg_loss_percent,
-            profit_factor: r.metrics.profit_factor,
-            avg_trade_duration_days: r.metrics.avg_trade_duration_days,
-        },
-        trades: r
-            .trades
-            .into_iter()
-            .map(|t| BacktestTradeData {
-                id: t.id,
-                symbol: t.symbol,
-                direction: t.direction.as_str().to_string(),
-                entry_date: t.entry_date.to_string(),
-                entry_price: t.entry_price,
-                entry_reason: t.entry_reason,
-                exit_date: t.exit_date.map(|d| d.to_string()),
-                exit_price: t.exit_price,
-                exit_reason: t.exit_reason,
-                shares: t.shares,
-                profit_loss: t.profit_loss,
-                profit_loss_percent: t.profit_loss_percent,
-            })
-            .collect(),
-        created_at: r.created_at,
-    }))
+        .get_backtest_detail(backtest_id)?
+        .ok_or_else(|| CommandError::not_found(format!("Backtest {} not found", backtest_id)))?;
+
+    let stats = BacktestEngine::default().monte_carlo(&result, iterations.unwrap_or(1000));
+
+    Ok(MonteCarloData {
+        backtest_id,
+        iterations: stats.iterations,
+        trades: stats.trades,
+        observed_return: stats.observed_return,
+        return_p5: stats.return_p5,
+        return_p50: stats.return_p50,
+        return_p95: stats.return_p95,
+        drawdown_p5: stats.drawdown_p5,
+        drawdown_p50: stats.drawdown_p50,
+        drawdown_p95: stats.drawdown_p95,
+    })
+}
+
+/// Export a saved backtest to `exports/` as JSON (default) or CSV
+#[tauri::command]
+fn export_backtest(
+    state: State<AppState>,
+    backtest_id: i64,
+    format: Option<String>,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
+
+    let format = match format.as_deref() {
+        None => BacktestExportFormat::Json,
+        Some(f) => f.parse::<BacktestExportFormat>()?,
+    };
+
+    std::fs::create_dir_all("exports").ok();
+
+    let file_name = format!("exports/backtest_{}.{}", backtest_id, format.extension());
+    let file = std::fs::File::create(&file_name)?;
+    let found = db.export_backtest(backtest_id, format, std::io::BufWriter::new(file))?;
+
+    if !found {
+        std::fs::remove_file(&file_name).ok();
+        return Ok(CommandResult {
+            success: false,
+            message: format!("Backtest {} not found", backtest_id),
+        });
+    }
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("Exported to {}", file_name),
+    })
 }
 
 /// Delete a backtest result
 #[tauri::command]
-fn delete_backtest(state: State<AppState>, backtest_id: i64) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn delete_backtest(
+    state: State<AppState>,
+    backtest_id: i64,
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    db.delete_backtest(backtest_id).map_err(|e| e.to_string())?;
+    db.delete_backtest(backtest_id)?;
 
     Ok(CommandResult {
         success: true,
@@ -1479,13 +2896,12 @@ fn create_watchlist(
     name: String,
     symbols: Vec<String>,
     description: Option<String>,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
     let symbols_upper: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
 
-    db.create_watchlist(&name, &symbols_upper, description.as_deref())
-        .map_err(|e| e.to_string())?;
+    db.create_watchlist(&name, &symbols_upper, description.as_deref())?;
 
     println!("[OK] Created watchlist '{}' with {} symbols", name, symbols_upper.len());
 
@@ -1497,10 +2913,10 @@ fn create_watchlist(
 
 /// Get all watchlists (summary view)
 #[tauri::command]
-fn get_all_watchlists(state: State<AppState>) -> Result<Vec<WatchlistSummary>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_all_watchlists(state: State<AppState>) -> Result<Vec<WatchlistSummary>, CommandError> {
+    let db = state.db.lock()?;
 
-    let watchlists = db.get_all_watchlists().map_err(|e| e.to_string())?;
+    let watchlists = db.get_all_watchlists()?;
 
     Ok(watchlists
         .into_iter()
@@ -1515,10 +2931,13 @@ fn get_all_watchlists(state: State<AppState>) -> Result<Vec<WatchlistSummary>, S
 
 /// Get a watchlist with its symbols
 #[tauri::command]
-fn get_watchlist_detail(state: State<AppState>, name: String) -> Result<Option<WatchlistData>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn get_watchlist_detail(
+    state: State<AppState>,
+    name: String,
+) -> Result<Option<WatchlistData>, CommandError> {
+    let db = state.db.lock()?;
 
-    let result = db.get_watchlist_full(&name).map_err(|e| e.to_string())?;
+    let result = db.get_watchlist_full(&name)?;
 
     Ok(result.map(|(id, name, description, symbols)| WatchlistData {
         id,
@@ -1529,12 +2948,33 @@ fn get_watchlist_detail(state: State<AppState>, name: String) -> Result<Option<W
     }))
 }
 
+/// List watchlist names, alphabetically
+#[tauri::command]
+fn get_watchlists(state: State<AppState>) -> Result<Vec<String>, CommandError> {
+    let db = state.db.lock()?;
+
+    let watchlists = db.get_all_watchlists()?;
+
+    Ok(watchlists.into_iter().map(|(_, name, _, _)| name).collect())
+}
+
+/// Get the symbols in a watchlist (empty if it doesn't exist)
+#[tauri::command]
+fn get_watchlist_symbols(
+    state: State<AppState>,
+    name: String,
+) -> Result<Vec<String>, CommandError> {
+    let db = state.db.lock()?;
+
+    db.get_watchlist(&name).map_err(CommandError::from)
+}
+
 /// Delete a watchlist
 #[tauri::command]
-fn delete_watchlist(state: State<AppState>, name: String) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+fn delete_watchlist(state: State<AppState>, name: String) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    let deleted = db.delete_watchlist(&name).map_err(|e| e.to_string())?;
+    let deleted = db.delete_watchlist(&name)?;
 
     if deleted {
         println!("[OK] Deleted watchlist '{}'", name);
@@ -1556,13 +2996,11 @@ fn add_symbol_to_watchlist(
     state: State<AppState>,
     watchlist_name: String,
     symbol: String,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
-    let success = db
-        .add_symbol_to_watchlist(&watchlist_name, &symbol)
-        .map_err(|e| e.to_string())?;
+    let success = db.add_symbol_to_watchlist(&watchlist_name, &symbol)?;
 
     if success {
         println!("[OK] Added {} to watchlist '{}'", symbol, watchlist_name);
@@ -1584,13 +3022,11 @@ fn remove_symbol_from_watchlist(
     state: State<AppState>,
     watchlist_name: String,
     symbol: String,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
     let symbol = symbol.to_uppercase();
 
-    let success = db
-        .remove_symbol_from_watchlist(&watchlist_name, &symbol)
-        .map_err(|e| e.to_string())?;
+    let success = db.remove_symbol_from_watchlist(&watchlist_name, &symbol)?;
 
     if success {
         println!("[OK] Removed {} from watchlist '{}'", symbol, watchlist_name);
@@ -1612,12 +3048,10 @@ fn update_watchlist_description(
     state: State<AppState>,
     name: String,
     description: Option<String>,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    let success = db
-        .update_watchlist_description(&name, description.as_deref())
-        .map_err(|e| e.to_string())?;
+    let success = db.update_watchlist_description(&name, description.as_deref())?;
 
     if success {
         Ok(CommandResult {
@@ -1638,12 +3072,10 @@ fn rename_watchlist(
     state: State<AppState>,
     old_name: String,
     new_name: String,
-) -> Result<CommandResult, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+) -> Result<CommandResult, CommandError> {
+    let db = state.db.lock()?;
 
-    let success = db
-        .rename_watchlist(&old_name, &new_name)
-        .map_err(|e| e.to_string())?;
+    let success = db.rename_watchlist(&old_name, &new_name)?;
 
     if success {
         println!("[OK] Renamed watchlist '{}' to '{}'", old_name, new_name);
@@ -1659,6 +3091,103 @@ fn rename_watchlist(
     }
 }
 
+/// Fetch daily prices from Yahoo Finance for every symbol in a watchlist
+#[tauri::command]
+async fn fetch_watchlist(
+    state: State<'_, AppState>,
+    name: String,
+    period: String,
+    adjusted: Option<bool>,
+) -> Result<CommandResult, CommandError> {
+    let symbols = {
+        let db = state.db.lock()?;
+        db.get_watchlist(&name)?
+    };
+
+    if symbols.is_empty() {
+        return Ok(CommandResult {
+            success: false,
+            message: format!("Watchlist '{}' is empty or doesn't exist", name),
+        });
+    }
+
+    let yahoo = AsyncYahooFinance::new().with_adjusted_prices(adjusted.unwrap_or(false));
+
+    let mut success_count = 0;
+    let mut fail_count = 0;
+
+    for symbol in &symbols {
+        match yahoo.fetch_and_store(&state.db, symbol, "1d", &period).await {
+            Ok(_) => success_count += 1,
+            Err(_) => fail_count += 1,
+        }
+    }
+
+    println!(
+        "[OK] Fetched watchlist '{}' ({} success, {} failed)",
+        name, success_count, fail_count
+    );
+
+    Ok(CommandResult {
+        success: fail_count == 0,
+        message: format!(
+            "Fetched {} symbols from '{}' ({} success, {} failed)",
+            symbols.len(),
+            name,
+            success_count,
+            fail_count
+        ),
+    })
+}
+
+/// Bring stale symbols up to date without clearing their stored prices
+#[tauri::command]
+async fn refetch_stale(
+    state: State<'_, AppState>,
+    max_age_days: Option<i64>,
+) -> Result<CommandResult, CommandError> {
+    let (symbols, period) = {
+        let db = state.db.lock()?;
+        let symbols = db.get_stale_symbols(max_age_days.unwrap_or(1))?;
+        let period = catch_up_period(&db, &symbols)?;
+        (symbols, period)
+    };
+
+    if symbols.is_empty() {
+        return Ok(CommandResult {
+            success: true,
+            message: "All symbols are up to date".to_string(),
+        });
+    }
+
+    let yahoo = AsyncYahooFinance::new();
+
+    let mut success_count = 0;
+    let mut fail_count = 0;
+
+    for symbol in &symbols {
+        match yahoo.fetch_and_store(&state.db, symbol, "1d", period).await {
+            Ok(_) => success_count += 1,
+            Err(_) => fail_count += 1,
+        }
+    }
+
+    println!(
+        "[OK] Refetched stale symbols ({} success, {} failed)",
+        success_count, fail_count
+    );
+
+    Ok(CommandResult {
+        success: fail_count == 0,
+        message: format!(
+            "Updated {} stale symbols ({} success, {} failed)",
+            symbols.len(),
+            success_count,
+            fail_count
+        ),
+    })
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize database
@@ -1672,31 +3201,60 @@ pub fn run() {
             get_symbols,
             toggle_favorite,
             get_favorited_symbols,
+            get_symbol_info,
             fetch_prices,
+            fetch_corporate_actions,
+            check_data_gaps,
+            fill_data_gaps,
             fetch_fred,
             get_macro_data,
+            get_macro_freshness,
             get_price,
             calculate_indicators,
+            calculate_indicators_incremental,
+            calculate_rolling_beta,
+            get_settings,
+            get_setting,
+            set_setting,
+            set_webhook_url,
+            get_signal_config,
+            set_signal_config,
+            get_available_indicators,
+            get_available_signal_types,
             get_indicators,
             get_indicator_history,
             get_price_history,
+            list_price_sources,
+            get_prices_by_source,
+            get_seasonality,
+            get_correlation_matrix,
+            get_relative_strength,
             export_csv,
+            import_csv,
+            export_indicator_history,
             search_symbol,
             add_alert,
+            add_percent_alert,
             get_alerts,
             delete_alert,
             check_alerts,
             add_position,
+            add_option_position,
             get_portfolio,
             delete_position,
             fetch_trends,
             get_trends,
+            get_api_usage,
             // Signal commands
             generate_signals,
+            scan_all_symbols,
             get_signals,
             get_all_signals,
             acknowledge_signal,
             acknowledge_all_signals,
+            get_signal_scoreboard,
+            get_signal_score,
+            get_all_signal_scores,
             // Indicator alert commands
             add_indicator_alert,
             get_indicator_alerts,
@@ -1707,18 +3265,28 @@ pub fn run() {
             get_strategies,
             delete_strategy,
             run_backtest,
+            optimize_strategy,
+            compare_strategies,
+            backtest_across_symbols,
+            run_portfolio_backtest,
             get_backtest_results,
             get_backtest_detail,
+            monte_carlo_backtest,
+            export_backtest,
             delete_backtest,
             // Watchlist/Symbol Group commands
             create_watchlist,
             get_all_watchlists,
             get_watchlist_detail,
+            get_watchlists,
+            get_watchlist_symbols,
             delete_watchlist,
             add_symbol_to_watchlist,
             remove_symbol_from_watchlist,
             update_watchlist_description,
             rename_watchlist,
+            fetch_watchlist,
+            refetch_stale,
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -1733,3 +3301,19 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_errors_map_to_command_error_kinds() {
+        let err = CommandError::from(PipelineError::NoData("AAPL".to_string()));
+        assert_eq!(err.kind, "NotFound");
+        assert_eq!(err.message, "No data returned for symbol: AAPL");
+
+        let err = CommandError::from(PipelineError::RateLimited("slow down".to_string()));
+        assert_eq!(err.kind, "NetworkError");
+        assert_eq!(CommandError::from("Invalid date: x".to_string()).kind, "InvalidInput");
+    }
+}
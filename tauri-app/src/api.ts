@@ -1,6 +1,36 @@
 // Tauri API wrapper
 
-import { invoke } from '@tauri-apps/api/core';
+import { invoke as tauriInvoke } from '@tauri-apps/api/core';
+
+// Error categories returned by every command
+export type CommandErrorKind =
+    | 'NotFound'
+    | 'NetworkError'
+    | 'UpstreamError'
+    | 'InvalidInput'
+    | 'IoError'
+    | 'DatabaseError';
+
+export class CommandError extends Error {
+    constructor(public kind: CommandErrorKind, message: string) {
+        super(message);
+        this.name = 'CommandError';
+    }
+
+    toString(): string {
+        return this.message;
+    }
+}
+
+// Rethrow command failures as CommandError so callers can check `kind`
+async function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
+    try {
+        return await tauriInvoke<T>(cmd, args);
+    } catch (error) {
+        const { kind, message } = error as { kind: CommandErrorKind; message: string };
+        throw new CommandError(kind, message);
+    }
+}
 
 // Types matching Rust structs
 export interface SymbolPrice {
@@ -44,6 +74,10 @@ export interface Alert {
     target_price: number;
     condition: string;
     triggered: boolean;
+    base_price: number | null;
+    percent_change: number | null;
+    recurring: boolean;
+    last_triggered_at: string | null;
 }
 
 export interface Position {
@@ -57,6 +91,10 @@ export interface Position {
     current_value: number;
     profit_loss: number;
     profit_loss_percent: number;
+    realized_profit_loss: number;
+    dividend_income: number;
+    strike: number | null;
+    expiration: string | null;
 }
 
 export interface Portfolio {
@@ -64,6 +102,58 @@ export interface Portfolio {
     total_value: number;
     total_profit_loss: number;
     total_profit_loss_percent: number;
+    long_profit_loss: number;
+    short_profit_loss: number;
+    gross_value: number;
+    realized_profit_loss: number;
+    total_dividend_income: number;
+    long_exposure: number;
+    short_exposure: number;
+    net_exposure: number;
+    beta_weighted_exposure: number;
+    exposure_benchmark: string;
+    missing_beta: string[];
+}
+
+export interface SymbolInfo {
+    symbol: string;
+    name: string | null;
+    sector: string | null;
+    industry: string | null;
+    market_cap: number | null;
+    country: string | null;
+    exchange: string | null;
+    currency: string | null;
+    asset_class: string | null;
+}
+
+export interface PriceSource {
+    source: string;
+    bars: number;
+    first_date: string;
+    last_date: string;
+}
+
+export interface BacktestMetrics {
+    total_return: number;
+    total_return_dollars: number;
+    max_drawdown: number;
+    sharpe_ratio: number;
+    win_rate: number;
+    total_trades: number;
+    winning_trades: number;
+    losing_trades: number;
+    avg_win_percent: number;
+    avg_loss_percent: number;
+    // null when infinite: no losing trades
+    profit_factor: number | null;
+    avg_trade_duration_days: number;
+    trades_per_year: number;
+    time_in_market_percent: number;
+    information_ratio: number;
+    // null when infinite: no losing days / no drawdown
+    sortino_ratio: number | null;
+    calmar_ratio: number | null;
 }
 
 // API functions
@@ -79,8 +169,12 @@ export async function getFavoritedSymbols(): Promise<string[]> {
     return invoke('get_favorited_symbols');
 }
 
-export async function fetchPrices(symbols: string, period: string): Promise<CommandResult> {
-    return invoke('fetch_prices', { symbols, period });
+export async function getSymbolInfo(symbol: string): Promise<SymbolInfo> {
+    return invoke('get_symbol_info', { symbol });
+}
+
+export async function fetchPrices(symbols: string, period: string, adjusted?: boolean, interval?: string): Promise<CommandResult> {
+    return invoke('fetch_prices', { symbols, period, adjusted, interval });
 }
 
 export async function fetchFred(indicators: string): Promise<CommandResult> {
@@ -99,12 +193,28 @@ export async function getIndicators(symbol: string): Promise<IndicatorData[]> {
     return invoke('get_indicators', { symbol });
 }
 
-export async function getIndicatorHistory(symbol: string, indicatorName: string): Promise<{ date: string; value: number }[]> {
-    return invoke('get_indicator_history', { symbol, indicatorName });
+export async function getIndicatorHistory(symbol: string, indicatorName: string, limit?: number, offset?: number): Promise<{ date: string; value: number }[]> {
+    return invoke('get_indicator_history', { symbol, indicatorName, limit, offset });
+}
+
+export async function getPriceHistory(symbol: string, maxPoints?: number, limit?: number, offset?: number): Promise<PriceData[]> {
+    return invoke('get_price_history', { symbol, maxPoints, limit, offset });
+}
+
+export async function listPriceSources(symbol: string): Promise<PriceSource[]> {
+    return invoke('list_price_sources', { symbol });
+}
+
+export async function getPricesBySource(symbol: string, source: string): Promise<PriceData[]> {
+    return invoke('get_prices_by_source', { symbol, source });
 }
 
-export async function getPriceHistory(symbol: string): Promise<PriceData[]> {
-    return invoke('get_price_history', { symbol });
+export async function getCorrelationMatrix(symbols: string, days?: number): Promise<{ symbol_a: string; symbol_b: string; correlation: number | null }[]> {
+    return invoke('get_correlation_matrix', { symbols, days });
+}
+
+export async function getRelativeStrength(symbols: string, days?: number): Promise<{ symbol: string; relative_strength: number }[]> {
+    return invoke('get_relative_strength', { symbols, days });
 }
 
 export async function searchSymbol(query: string): Promise<string[]> {
@@ -115,9 +225,17 @@ export async function exportCsv(symbol: string): Promise<CommandResult> {
     return invoke('export_csv', { symbol });
 }
 
+export async function importCsv(symbol: string, path: string, source?: string): Promise<CommandResult> {
+    return invoke('import_csv', { symbol, path, source });
+}
+
 // Alerts
-export async function addAlert(symbol: string, targetPrice: number, condition: string): Promise<CommandResult> {
-    return invoke('add_alert', { symbol, targetPrice, condition });
+export async function addAlert(symbol: string, targetPrice: number, condition: string, recurring?: boolean): Promise<CommandResult> {
+    return invoke('add_alert', { symbol, targetPrice, condition, recurring });
+}
+
+export async function addPercentAlert(symbol: string, percent: number, basePrice?: number): Promise<CommandResult> {
+    return invoke('add_percent_alert', { symbol, percent, basePrice });
 }
 
 export async function getAlerts(onlyActive: boolean): Promise<Alert[]> {
@@ -144,6 +262,21 @@ export async function addPosition(
     return invoke('add_position', { symbol, quantity, price, positionType, date, notes });
 }
 
+export async function addOptionPosition(
+    symbol: string,
+    strategy: 'covered_call' | 'cash_secured_put',
+    quantity: number,
+    premium: number,
+    strike: number,
+    expiration: string,
+    date: string,
+    notes: string | null
+): Promise<CommandResult> {
+    return invoke('add_option_position', {
+        symbol, strategy, quantity, premium, strike, expiration, date, notes,
+    });
+}
+
 export async function getPortfolio(): Promise<Portfolio> {
     return invoke('get_portfolio');
 }
@@ -153,12 +286,20 @@ export async function deletePosition(positionId: number): Promise<CommandResult>
 }
 
 // Google Trends
-export async function fetchTrends(keyword: string): Promise<CommandResult> {
-    return invoke('fetch_trends', { keyword });
+export async function fetchCorporateActions(symbols: string, period?: string): Promise<CommandResult> {
+    return invoke('fetch_corporate_actions', { symbols, period });
 }
 
-export async function getTrends(keyword: string): Promise<{ date: string; value: number }[]> {
-    return invoke('get_trends', { keyword });
+export async function fetchTrends(keyword: string, timeframe?: string, geo?: string): Promise<CommandResult> {
+    return invoke('fetch_trends', { keyword, timeframe, geo });
+}
+
+export async function getTrends(keyword: string, geo?: string): Promise<{ date: string; value: number }[]> {
+    return invoke('get_trends', { keyword, geo });
+}
+
+export async function getApiUsage(source: string, since: string): Promise<{ date: string; calls: number }[]> {
+    return invoke('get_api_usage', { source, since });
 }
 
 // Watchlists / Symbol Groups
@@ -189,6 +330,14 @@ export async function getWatchlistDetail(name: string): Promise<WatchlistDetail
     return invoke('get_watchlist_detail', { name });
 }
 
+export async function getWatchlists(): Promise<string[]> {
+    return invoke('get_watchlists');
+}
+
+export async function getWatchlistSymbols(name: string): Promise<string[]> {
+    return invoke('get_watchlist_symbols', { name });
+}
+
 export async function deleteWatchlist(name: string): Promise<CommandResult> {
     return invoke('delete_watchlist', { name });
 }
@@ -208,3 +357,64 @@ export async function updateWatchlistDescription(name: string, description: stri
 export async function renameWatchlist(oldName: string, newName: string): Promise<CommandResult> {
     return invoke('rename_watchlist', { oldName, newName });
 }
+
+export async function fetchWatchlist(name: string, period: string, adjusted?: boolean): Promise<CommandResult> {
+    return invoke('fetch_watchlist', { name, period, adjusted });
+}
+
+export async function refetchStale(maxAgeDays?: number): Promise<CommandResult> {
+    return invoke('refetch_stale', { maxAgeDays });
+}
+
+// Settings
+export async function getSettings(): Promise<Record<string, string>> {
+    return invoke('get_settings');
+}
+
+export async function getSetting(key: string): Promise<string | null> {
+    return invoke('get_setting', { key });
+}
+
+export async function setSetting(key: string, value: string): Promise<CommandResult> {
+    return invoke('set_setting', { key, value });
+}
+
+export async function setWebhookUrl(url: string | null): Promise<CommandResult> {
+    return invoke('set_webhook_url', { url });
+}
+
+// Signal thresholds
+export interface SignalConfig {
+    rsi_overbought: number;
+    rsi_oversold: number;
+    adx_strong_trend: number;
+    adx_weak_trend: number;
+    stoch_overbought: number;
+    stoch_oversold: number;
+    willr_overbought: number;
+    willr_oversold: number;
+    cci_overbought: number;
+    cci_oversold: number;
+    mfi_overbought: number;
+    mfi_oversold: number;
+    doji_body_ratio: number;
+    hammer_wick_ratio: number;
+    conflict_resolution: 'KeepAll' | 'Strongest' | 'Net';
+    auto_acknowledge_opposing: boolean;
+    backfill: boolean;
+    divergence_lookback: number;
+    pivot_bars: number;
+    ma_fast_period: number;
+    ma_slow_period: number;
+    macd_fast_period: number;
+    macd_slow_period: number;
+    macd_signal_period: number;
+}
+
+export async function getSignalConfig(): Promise<SignalConfig> {
+    return invoke('get_signal_config');
+}
+
+export async function setSignalConfig(config: SignalConfig): Promise<CommandResult> {
+    return invoke('set_signal_config', { config });
+}
@@ -0,0 +1,176 @@
+//! End-to-end tests that drive the CLI binary against a seeded database.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use chrono::NaiveDate;
+use financial_pipeline::{DailyPrice, Database};
+
+/// Fresh database path under the system temp dir, unique per test
+fn temp_db(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "financial_pipeline_cli_{}_{}.db",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Seed `symbol` with a slow sine wave of closes, so RSI swings through both extremes
+fn seed_prices(db: &mut Database, symbol: &str, days: usize) {
+    let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let prices: Vec<DailyPrice> = (0..days)
+        .map(|i| {
+            let close = 100.0 + 15.0 * (i as f64 / 8.0).sin();
+            DailyPrice {
+                symbol: symbol.to_string(),
+                date: start + chrono::Duration::days(i as i64),
+                open: close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: 1000,
+                adjusted_close: None,
+                source: "test".to_string(),
+            }
+        })
+        .collect();
+    db.upsert_daily_prices(&prices).unwrap();
+}
+
+fn run_cli(db: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_financial_pipeline"))
+        .arg("--database")
+        .arg(db)
+        .args(args)
+        .output()
+        .expect("failed to run CLI")
+}
+
+#[test]
+fn test_cli_backtest_runs_saved_strategy_and_saves_result() {
+    let path = temp_db("backtest");
+    {
+        let mut db = Database::open(&path).unwrap();
+        db.init_schema().unwrap();
+        seed_prices(&mut db, "OSC", 200);
+        db.refresh_indicators("OSC").unwrap();
+    }
+
+    let out = run_cli(
+        &path,
+        &[
+            "strategy",
+            "RSI swing",
+            "--entry",
+            "rsi_oversold",
+            "--exit",
+            "rsi_overbought",
+        ],
+    );
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let out = run_cli(
+        &path,
+        &[
+            "backtest",
+            "RSI swing",
+            "osc",
+            "--capital",
+            "5000",
+            "--save",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(stdout.contains("Backtest: RSI swing on OSC"), "{}", stdout);
+    assert!(stdout.contains("Initial capital: $5000.00"), "{}", stdout);
+    assert!(stdout.contains("Saved backtest"), "{}", stdout);
+
+    let db = Database::open(&path).unwrap();
+    let saved = db
+        .get_backtest_results(Some("RSI swing"), Some("OSC"), 10)
+        .unwrap();
+    assert_eq!(saved.len(), 1);
+    assert!(saved[0].metrics.total_trades > 0);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_cli_backtest_without_indicators_suggests_calculate() {
+    let path = temp_db("missing_indicators");
+    {
+        let mut db = Database::open(&path).unwrap();
+        db.init_schema().unwrap();
+        seed_prices(&mut db, "RAW", 200);
+    }
+
+    let out = run_cli(
+        &path,
+        &[
+            "strategy",
+            "Dip",
+            "--entry",
+            "rsi_oversold",
+            "--exit",
+            "rsi_overbought",
+        ],
+    );
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let out = run_cli(&path, &["backtest", "Dip", "RAW"]);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!out.status.success());
+    assert!(
+        stderr.contains("RSI_14") && stderr.contains("--calculate"),
+        "{}",
+        stderr
+    );
+
+    let out = run_cli(&path, &["backtest", "Dip", "RAW", "--calculate"]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_cli_signals_prints_latest_signals_for_seeded_symbol() {
+    let path = temp_db("signals");
+    {
+        let mut db = Database::open(&path).unwrap();
+        db.init_schema().unwrap();
+        seed_prices(&mut db, "OSC", 200);
+    }
+
+    let out = run_cli(&path, &["signals", "osc", "--limit", "3"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(stdout.contains("Generated"), "{}", stdout);
+    assert!(stdout.contains("Direction"), "{}", stdout);
+    let rows = stdout.lines().filter(|l| l.starts_with("  2024-")).count();
+    assert_eq!(rows, 3, "{}", stdout);
+
+    let db = Database::open(&path).unwrap();
+    assert!(!db.get_signals("OSC", false).unwrap().is_empty());
+    let _ = std::fs::remove_file(&path);
+}